@@ -1,22 +1,55 @@
-use crate::config::Config;
-use crate::feed::{Feed, Item};
-use crate::repo::{Repository, RepositoryEvent};
+use crate::config::{
+    ColorMode, Config, FeedSections, PaneLayout, RenderMode, ShareKind, BUILTIN_THEMES,
+    PANE_LAYOUTS,
+};
+use crate::control::{self, ControlCommand, ControlRequest, ControlSocket};
+use crate::feed::{Feed, Item, Page};
+use crate::repo::lock::{InstanceLock, LockError};
+use crate::repo::{
+    backup, DeadFeed, EventExport, FeedHealth, ItemRevision, ReadingStats, Repository, RepositoryEvent,
+    SearchResult, Shared, TagSummary,
+};
+use crate::logging;
+use crate::report;
+use crate::ui::batch::{BatchEntry, BatchOutcome};
+use crate::ui::body::BodyCache;
+use crate::ui::code::CodeCache;
+use crate::ui::graphics::{self, Icon, IconSlot};
+use crate::ui::monitor::{MonitorEntry, Severity};
+use crate::util;
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::error;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::ops::Range;
+use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
 use std::str::FromStr;
-use std::task::Poll;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tui::text::Text;
 use tui::widgets::{ListState, ScrollbarState};
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Default)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+
     /// Set a custom config file
     #[arg(short, long)]
     pub config: Option<String>,
 
+    /// Use a separate profile, with its own config, feeds and database
+    #[arg(short, long)]
+    pub profile: Option<String>,
+
     /// Set a custom theme, either built-in or a path to a theme file
     #[arg(short = 's', long)]
     pub color_scheme: Option<String>,
@@ -32,14 +65,388 @@ pub struct Args {
     /// Do not cache feeds in local file-backed database
     #[arg(short, long)]
     pub no_cache: bool,
+
+    /// Lay out the interface for screen readers
+    #[arg(long)]
+    pub screen_reader: bool,
+
+    /// Show feeds without letting subscriptions or the config file be changed
+    #[arg(long)]
+    pub read_only: bool,
+
+    /// Add the feeds whose URLs are piped in, one per line
+    #[arg(long)]
+    pub add_stdin: bool,
+
+    /// Show feeds on a wall display, read-only and without tabs, moving on to
+    /// the next item every SECONDS
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "15")]
+    pub dashboard: Option<u64>,
+
+    /// Set the log verbosity, one of off, error, warn, info, debug or trace
+    #[arg(short, long)]
+    pub log_level: Option<log::LevelFilter>,
+}
+
+/// Commands run from the shell instead of starting the app.
+#[derive(Subcommand, Debug)]
+pub enum CliCommand {
+    /// Export or import the read and starred state of items
+    State {
+        #[command(subcommand)]
+        action: StateAction,
+    },
+    /// Work with the raw feed responses cached on disk
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Write a snapshot of the database and config file to a directory,
+    /// by default a new one under `backups/` in the data directory
+    Backup { path: Option<PathBuf> },
+    /// Replace the database and config file with those of a backup, after
+    /// backing up the current ones
+    Restore { path: PathBuf },
+    /// Serve feeds and items over a small local HTTP JSON API, for companion
+    /// tools to read and mark the same items as the app
+    Serve {
+        /// The address to listen on
+        #[arg(long, default_value = "127.0.0.1:7575")]
+        addr: SocketAddr,
+    },
+    /// Send a command to the running app: `refresh`, `add <URL>`,
+    /// `goto <feed>` or `notify <message>`
+    Ctl {
+        #[arg(required = true)]
+        command: Vec<String>,
+    },
+}
+
+impl CliCommand {
+    /// Whether the command writes the database or config file, which it may
+    /// only do while no app has them.
+    pub fn writes(&self) -> bool {
+        matches!(
+            self,
+            Self::State {
+                action: StateAction::Import { .. }
+            } | Self::Cache { .. }
+                | Self::Restore { .. }
+        )
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StateAction {
+    /// Write the read and starred state of all items to a JSON file
+    Export { path: PathBuf },
+    /// Merge read and starred state from a JSON file written by `state export`
+    Import { path: PathBuf },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum CacheAction {
+    /// Parse every subscribed feed again from its cached response, without downloading it
+    Reparse,
+}
+
+/// How a console command is used, for completion, hints when it's given bad
+/// arguments, and `:help <cmd>`.
+#[derive(Debug)]
+pub struct CommandUsage {
+    pub name: &'static str,
+    pub alias: Option<&'static str>,
+    pub args: &'static str,
+    /// What a missing or bad argument should have been.
+    pub needs: &'static str,
+    pub summary: &'static str,
+}
+
+impl CommandUsage {
+    /// Finds a command by its name or alias.
+    pub fn find(name: &str) -> Option<&'static CommandUsage> {
+        CONSOLE_USAGE
+            .iter()
+            .find(|usage| usage.name == name || usage.alias == Some(name))
+    }
+}
+
+impl fmt::Display for CommandUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.args.is_empty() {
+            write!(f, " {}", self.args)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every console command, in the order they're offered for completion.
+const CONSOLE_USAGE: [CommandUsage; 34] = [
+    CommandUsage {
+        name: ":add",
+        alias: Some(":a"),
+        args: "<URL>",
+        needs: "a URL",
+        summary: "Preview a feed, and add it once confirmed",
+    },
+    CommandUsage {
+        name: ":add-many",
+        alias: None,
+        args: "[PATH]",
+        needs: "",
+        summary: "Add the feeds listed one per line in a file, or on the clipboard",
+    },
+    CommandUsage {
+        name: ":archive",
+        alias: None,
+        args: "[URL]",
+        needs: "",
+        summary: "Archive a feed, or the current one, or bring it back",
+    },
+    CommandUsage {
+        name: ":author",
+        alias: None,
+        args: "[NAME]",
+        needs: "",
+        summary: "List the items by an author, or go to the Authors tab",
+    },
+    CommandUsage {
+        name: ":back",
+        alias: None,
+        args: "",
+        needs: "",
+        summary: "Deselect the current item or feed",
+    },
+    CommandUsage {
+        name: ":background",
+        alias: None,
+        args: "",
+        needs: "",
+        summary: "Open the current feed or item in the background",
+    },
+    CommandUsage {
+        name: ":backup",
+        alias: None,
+        args: "[PATH]",
+        needs: "",
+        summary: "Back up the database and config file, to PATH if given",
+    },
+    CommandUsage {
+        name: ":category",
+        alias: Some(":tag"),
+        args: "<NAME>",
+        needs: "a category",
+        summary: "List the items in a category, or with a tag",
+    },
+    CommandUsage {
+        name: ":compare",
+        alias: None,
+        args: "",
+        needs: "",
+        summary: "Compare the current item with its previous version",
+    },
+    CommandUsage {
+        name: ":config",
+        alias: None,
+        args: "",
+        needs: "",
+        summary: "Open the config file",
+    },
+    CommandUsage {
+        name: ":delete",
+        alias: Some(":d"),
+        args: "[URL]",
+        needs: "",
+        summary: "Delete a feed, or the current one",
+    },
+    CommandUsage {
+        name: ":edit-url",
+        alias: None,
+        args: "<URL>",
+        needs: "a URL",
+        summary: "Point the selected feed at a new URL",
+    },
+    CommandUsage {
+        name: ":feed",
+        alias: Some(":f"),
+        args: "<NAME>",
+        needs: "a feed name",
+        summary: "Jump to the feed whose title best matches NAME",
+    },
+    CommandUsage {
+        name: ":find",
+        alias: None,
+        args: "<TEXT>",
+        needs: "something to find",
+        summary: "Find text in the Detail panel, then n/N to jump between matches",
+    },
+    CommandUsage {
+        name: ":goto",
+        alias: Some(":g"),
+        args: "<N>",
+        needs: "a row number",
+        summary: "Select the Nth feed or item in the active list",
+    },
+    CommandUsage {
+        name: ":help",
+        alias: None,
+        args: "[COMMAND]",
+        needs: "",
+        summary: "Show the keybinds, or how to use a command",
+    },
+    CommandUsage {
+        name: ":ical",
+        alias: None,
+        args: "[PATH|khal]",
+        needs: "",
+        summary: "Export the event in the current item to an .ics file, or to khal",
+    },
+    CommandUsage {
+        name: ":inspect",
+        alias: Some(":i"),
+        args: "",
+        needs: "",
+        summary: "Show details of the current feed",
+    },
+    CommandUsage {
+        name: ":jump",
+        alias: None,
+        args: "<MARK|back|forward>",
+        needs: "a mark's letter, back or forward",
+        summary: "Jump to a mark, or back or forward through the feeds you've read",
+    },
+    CommandUsage {
+        name: ":layout",
+        alias: Some(":l"),
+        args: "<NAME>",
+        needs: "one of three-pane, two-pane or horizontal",
+        summary: "Switch the pane layout for this session",
+    },
+    CommandUsage {
+        name: ":log",
+        alias: None,
+        args: "",
+        needs: "",
+        summary: "Show the most recent lines of the log file",
+    },
+    CommandUsage {
+        name: ":mark",
+        alias: None,
+        args: "<MARK>",
+        needs: "a letter to mark with",
+        summary: "Mark the current feed and item to jump back to",
+    },
+    CommandUsage {
+        name: ":monitor",
+        alias: None,
+        args: "",
+        needs: "",
+        summary: "Show the newest update of each status page feed",
+    },
+    CommandUsage {
+        name: ":open",
+        alias: Some(":o"),
+        args: "[N|URL]",
+        needs: "",
+        summary: "Open a link in the current item, a URL, or the current feed or item",
+    },
+    CommandUsage {
+        name: ":profile",
+        alias: Some(":p"),
+        args: "<NAME>",
+        needs: "a profile name",
+        summary: "Switch to another profile",
+    },
+    CommandUsage {
+        name: ":quit",
+        alias: Some(":q"),
+        args: "",
+        needs: "",
+        summary: "Quit",
+    },
+    CommandUsage {
+        name: ":refresh",
+        alias: Some(":r"),
+        args: "",
+        needs: "",
+        summary: "Refresh all feeds",
+    },
+    CommandUsage {
+        name: ":search",
+        alias: Some(":s"),
+        args: "<TEXT>",
+        needs: "something to search for",
+        summary: "Search for a feed, item, or text content",
+    },
+    CommandUsage {
+        name: ":speak",
+        alias: None,
+        args: "[stop]",
+        needs: "",
+        summary: "Read the current item aloud, or stop reading it",
+    },
+    CommandUsage {
+        name: ":stats",
+        alias: None,
+        args: "[reading]",
+        needs: "",
+        summary: "Show statistics of your reading habits",
+    },
+    CommandUsage {
+        name: ":summarize",
+        alias: None,
+        args: "",
+        needs: "",
+        summary: "Show a summary of the current item or page, or hide it",
+    },
+    CommandUsage {
+        name: ":tab",
+        alias: None,
+        args: "<NAME>",
+        needs: "one of browse, favorites, tags or authors",
+        summary: "Go to another tab",
+    },
+    CommandUsage {
+        name: ":theme",
+        alias: Some(":t"),
+        args: "<NAME>",
+        needs: "a theme name",
+        summary: "Switch to another theme for this session",
+    },
+    CommandUsage {
+        name: ":translate",
+        alias: None,
+        args: "",
+        needs: "",
+        summary: "Translate the current item or page, or go back to the original",
+    },
+];
+
+/// How many of the most recent log lines the log view shows.
+const LOG_TAIL_LINES: usize = 200;
+
+/// How many days of activity the statistics view covers.
+const STATS_DAYS: usize = 14;
+
+/// How long to wait after the last keystroke before running a live search.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// The most results shown in the live search overlay.
+const SEARCH_RESULTS_LIMIT: usize = 20;
+
+/// How many of each feed's newest items a dashboard shows before moving on to
+/// the next feed.
+const DASHBOARD_ITEMS: usize = 5;
+
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 
 #[derive(Debug)]
 pub enum Status {
     Loading(usize, usize),
+    /// A message in answer to a command, like `:help <cmd>`.
+    Info(String),
     Errored(String),
     Done,
 }
@@ -47,14 +454,117 @@ pub enum Status {
 #[derive(Debug)]
 pub enum ConsoleCommand {
     AddFeed(String),
+    AddMany(Option<PathBuf>),
+    ArchiveFeed(Option<String>),
+    Author(Option<String>),
+    Back,
+    Backup(Option<PathBuf>),
+    Category(String),
+    Compare,
     DeleteFeed(Option<String>),
+    EditFeedUrl(String),
+    FindFeed(String),
+    FindText(String),
+    GoTo(usize),
+    Help(Option<String>),
+    Ical(Option<String>),
+    Inspect,
+    Jump(JumpTarget),
+    Log,
+    Mark(char),
+    Monitor,
+    OpenConfig,
+    SetLayout(PaneLayout),
+    Open(OpenTarget),
+    Quit,
+    Refresh,
     Search(String),
+    Speak(bool),
+    SwitchProfile(String),
+    SetTab(Tab),
+    SetTheme(String),
+    Stats(StatsView),
+    Summarize,
+    Translate,
+}
+
+impl ConsoleCommand {
+    /// Whether the command changes what's subscribed to or the config file,
+    /// which read-only mode doesn't allow.
+    fn writes(&self) -> bool {
+        matches!(
+            self,
+            Self::AddFeed(_)
+                | Self::AddMany(_)
+                | Self::DeleteFeed(_)
+                | Self::ArchiveFeed(_)
+                | Self::EditFeedUrl(_)
+                | Self::OpenConfig
+                | Self::SwitchProfile(_)
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum StatsView {
+    Reading,
+}
+
+#[derive(Debug)]
+pub enum OpenTarget {
+    /// The current feed or item, in the foreground or not.
+    Current(bool),
+    /// The nth (1-based) link in the current item.
+    Link(usize),
+    Url(String),
+}
+
+#[derive(Debug)]
+pub enum JumpTarget {
+    Back,
+    Forward,
+    Mark(char),
+}
+
+/// The letter naming a mark, such as `a` in `:mark a`.
+fn mark_letter(arg: &str) -> Option<char> {
+    let mut chars = arg.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_alphabetic() => Some(letter),
+        _ => None,
+    }
 }
 
 #[derive(Debug)]
 pub enum ConsoleCommandError {
-    BadCommand,
-    BadArgument,
+    /// No command goes by this name.
+    BadCommand(String),
+    /// The command needs an argument it wasn't given.
+    MissingArgument(&'static CommandUsage),
+    /// The command was given an argument it can't use.
+    BadArgument(&'static CommandUsage, String),
+}
+
+impl fmt::Display for ConsoleCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadCommand(name) if name.is_empty() => write!(f, "no command given"),
+            Self::BadCommand(name) => write!(f, "unrecognized command {}", name),
+            Self::MissingArgument(usage) => write!(
+                f,
+                "{} requires {} (usage: {})",
+                usage.name, usage.needs, usage
+            ),
+            Self::BadArgument(usage, token) if usage.needs.is_empty() => {
+                write!(f, "{} can't use '{}' (usage: {})", usage.name, token, usage)
+            }
+            Self::BadArgument(usage, token) => write!(
+                f,
+                "{} can't use '{}', it needs {}",
+                usage.name, token, usage.needs
+            ),
+        }
+    }
 }
 
 impl FromStr for ConsoleCommand {
@@ -62,29 +572,154 @@ impl FromStr for ConsoleCommand {
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         let parts = s.split_whitespace().collect::<Vec<_>>();
-
-        if let Some(cmd) = parts.get(0) {
-            match *cmd {
-                ":a" | ":add" => match parts.get(1) {
-                    Some(url) => Ok(ConsoleCommand::AddFeed(url.to_string())),
-                    None => Err(ConsoleCommandError::BadArgument),
+        let Some(cmd) = parts.first() else {
+            return Err(ConsoleCommandError::BadCommand(String::new()));
+        };
+        let Some(usage) = CommandUsage::find(cmd) else {
+            return Err(ConsoleCommandError::BadCommand(cmd.to_string()));
+        };
+        let missing = ConsoleCommandError::MissingArgument(usage);
+        let bad = |token: &str| ConsoleCommandError::BadArgument(usage, token.to_owned());
+
+        match usage.name {
+            // The command of an `exec:` source may have arguments of its own.
+            ":add" => match parts.get(1..).map(|rest| rest.join(" ")) {
+                Some(url) if !url.is_empty() => Ok(ConsoleCommand::AddFeed(url)),
+                _ => Err(missing),
+            },
+            ":add-many" => Ok(ConsoleCommand::AddMany(parts.get(1).map(PathBuf::from))),
+            ":search" => {
+                let query = parts[1..].join(" ");
+                if query.is_empty() {
+                    Err(missing)
+                } else {
+                    Ok(ConsoleCommand::Search(query))
+                }
+            }
+            ":delete" => match parts.get(1) {
+                Some(url) => Ok(ConsoleCommand::DeleteFeed(Some(url.to_string()))),
+                None => Ok(ConsoleCommand::DeleteFeed(None)),
+            },
+            ":archive" => Ok(ConsoleCommand::ArchiveFeed(
+                parts.get(1).map(|url| url.to_string()),
+            )),
+            ":author" => {
+                let name = parts[1..].join(" ");
+                Ok(ConsoleCommand::Author((!name.is_empty()).then_some(name)))
+            }
+            ":back" => Ok(ConsoleCommand::Back),
+            ":backup" => Ok(ConsoleCommand::Backup(parts.get(1).map(PathBuf::from))),
+            ":background" => Ok(ConsoleCommand::Open(OpenTarget::Current(true))),
+            ":category" => {
+                let name = parts[1..].join(" ");
+                if name.is_empty() {
+                    Err(missing)
+                } else {
+                    Ok(ConsoleCommand::Category(name))
+                }
+            }
+            ":compare" => Ok(ConsoleCommand::Compare),
+            ":config" => Ok(ConsoleCommand::OpenConfig),
+            ":edit-url" => match parts.get(1) {
+                Some(url) => Ok(ConsoleCommand::EditFeedUrl(url.to_string())),
+                None => Err(missing),
+            },
+            ":feed" => {
+                let query = parts[1..].join(" ");
+                if query.is_empty() {
+                    Err(missing)
+                } else {
+                    Ok(ConsoleCommand::FindFeed(query))
+                }
+            }
+            ":find" => {
+                let query = parts[1..].join(" ");
+                if query.is_empty() {
+                    Err(missing)
+                } else {
+                    Ok(ConsoleCommand::FindText(query))
+                }
+            }
+            ":goto" => match parts.get(1) {
+                Some(n) => match n.parse::<usize>() {
+                    Ok(n) if n > 0 => Ok(ConsoleCommand::GoTo(n)),
+                    _ => Err(bad(n)),
                 },
-                ":s" | ":search" => {
-                    let query = parts.iter().skip(1).copied().collect::<String>();
-                    if query.is_empty() {
-                        Err(ConsoleCommandError::BadArgument)
-                    } else {
-                        Ok(ConsoleCommand::Search(query))
-                    }
+                None => Err(missing),
+            },
+            ":help" => Ok(ConsoleCommand::Help(parts.get(1).map(|cmd| {
+                if cmd.starts_with(':') {
+                    cmd.to_string()
+                } else {
+                    format!(":{}", cmd)
                 }
-                ":d" | ":delete" => match parts.get(1) {
-                    Some(url) => Ok(ConsoleCommand::DeleteFeed(Some(url.to_string()))),
-                    None => Ok(ConsoleCommand::DeleteFeed(None)),
+            }))),
+            ":ical" => Ok(ConsoleCommand::Ical(
+                parts.get(1).map(|arg| arg.to_string()),
+            )),
+            ":inspect" => Ok(ConsoleCommand::Inspect),
+            ":jump" => match parts.get(1).copied() {
+                Some("back") => Ok(ConsoleCommand::Jump(JumpTarget::Back)),
+                Some("forward") => Ok(ConsoleCommand::Jump(JumpTarget::Forward)),
+                Some(arg) => match mark_letter(arg) {
+                    Some(letter) => Ok(ConsoleCommand::Jump(JumpTarget::Mark(letter))),
+                    None => Err(bad(arg)),
                 },
-                _ => Err(ConsoleCommandError::BadCommand),
-            }
-        } else {
-            Err(ConsoleCommandError::BadCommand)
+                None => Err(missing),
+            },
+            ":layout" => match parts.get(1) {
+                Some(name) => match PaneLayout::from_str(name) {
+                    Ok(layout) => Ok(ConsoleCommand::SetLayout(layout)),
+                    Err(_) => Err(bad(name)),
+                },
+                None => Err(missing),
+            },
+            ":log" => Ok(ConsoleCommand::Log),
+            ":mark" => match parts.get(1) {
+                Some(arg) => match mark_letter(arg) {
+                    Some(letter) => Ok(ConsoleCommand::Mark(letter)),
+                    None => Err(bad(arg)),
+                },
+                None => Err(missing),
+            },
+            ":monitor" => Ok(ConsoleCommand::Monitor),
+            ":open" => match parts.get(1) {
+                Some(arg) => match arg.parse::<usize>() {
+                    Ok(n) => Ok(ConsoleCommand::Open(OpenTarget::Link(n))),
+                    Err(_) => Ok(ConsoleCommand::Open(OpenTarget::Url(arg.to_string()))),
+                },
+                None => Ok(ConsoleCommand::Open(OpenTarget::Current(false))),
+            },
+            ":profile" => match parts.get(1) {
+                Some(name) => Ok(ConsoleCommand::SwitchProfile(name.to_string())),
+                None => Err(missing),
+            },
+            ":quit" => Ok(ConsoleCommand::Quit),
+            ":refresh" => Ok(ConsoleCommand::Refresh),
+            ":speak" => match parts.get(1) {
+                None => Ok(ConsoleCommand::Speak(true)),
+                Some(&"stop") => Ok(ConsoleCommand::Speak(false)),
+                Some(arg) => Err(bad(arg)),
+            },
+            ":stats" => match parts.get(1) {
+                None | Some(&"reading") => Ok(ConsoleCommand::Stats(StatsView::Reading)),
+                Some(arg) => Err(bad(arg)),
+            },
+            ":summarize" => Ok(ConsoleCommand::Summarize),
+            ":translate" => Ok(ConsoleCommand::Translate),
+            ":tab" => match parts.get(1).copied() {
+                Some("browse") => Ok(ConsoleCommand::SetTab(Tab::Browse)),
+                Some("favorites") => Ok(ConsoleCommand::SetTab(Tab::Favorites)),
+                Some("tags") => Ok(ConsoleCommand::SetTab(Tab::Tags)),
+                Some("authors") => Ok(ConsoleCommand::SetTab(Tab::Authors)),
+                Some(arg) => Err(bad(arg)),
+                None => Err(missing),
+            },
+            ":theme" => match parts.get(1) {
+                Some(name) => Ok(ConsoleCommand::SetTheme(name.to_string())),
+                None => Err(missing),
+            },
+            _ => Err(ConsoleCommandError::BadCommand(cmd.to_string())),
         }
     }
 }
@@ -99,29 +734,150 @@ pub struct App {
     pub active_tab: Tab,
     pub feeds: StatefulList<Feed>,
     pub feeds_scroll: ScrollbarState,
+    /// The selected row of the feeds list and how far it's scrolled, counting
+    /// section headers as rows.
+    pub feed_rows_state: ListState,
+    /// The section each feed was sorted into, by id, which it stays in until
+    /// the list is sorted again rather than moving as it's read.
+    feed_sections: HashMap<String, (u32, String)>,
     pub items: StatefulList<Item>,
     pub items_scroll: ScrollbarState,
+    /// The tags given to items, or their authors, listed in place of feeds in
+    /// the Tags and Authors tabs.
+    pub labels: StatefulList<TagSummary>,
+    pub item_rows: RowCache,
+    pub jumps: Jumps,
+    pub code_blocks: CodeCache,
+    pub body_rows: BodyCache,
     pub detail_scroll: ScrollbarState,
     pub detail_scroll_index: u16,
+    pub pages: Vec<Page>,
     pub show_keybinds: bool,
+    pub show_inspector: bool,
+    pub stats: Option<ReadingStats>,
+    /// The latest update of each status page feed, while the monitor is open.
+    pub monitor: Option<Vec<MonitorEntry>>,
+    /// The URLs of a batch being added and what came of each, while the
+    /// batch overlay is open.
+    pub batch: Option<Vec<BatchEntry>>,
+    /// A feed fetched with `:add`, shown to be confirmed before it's
+    /// subscribed to.
+    pub preview: Option<Feed>,
+    /// URLs of a batch still being fetched, which are subscribed to once they
+    /// are, whether or not the overlay is still open.
+    batch_pending: HashSet<String>,
+    /// The earlier version of an updated item it's being compared with, and
+    /// the item's id.
+    pub revision: Option<(String, ItemRevision)>,
+    /// The translation of the text in the Detail panel, and the id of the item
+    /// or URL of the page it's of.
+    pub translation: Option<(String, String)>,
+    /// The summary shown above the text in the Detail panel, and the id of the
+    /// item or URL of the page it's of.
+    pub summary: Option<(String, String)>,
+    pub log_lines: Option<Vec<String>>,
+    /// The target picked in the share menu, while it's open.
+    pub share_menu: Option<ListState>,
+    /// A post being written to a share target, while the compose overlay is open.
+    pub compose: Option<ComposeState>,
+    /// Feeds gone on several refreshes in a row, offered one at a time for
+    /// unsubscribing.
+    pub dead_feeds: Vec<DeadFeed>,
+    /// Dead feeds kept anyway, which aren't offered again this session.
+    kept_feeds: HashSet<String>,
     pub status: Status,
     pub command_state: InputState,
+    pub completion: Completion,
+    pub search: SearchState,
+    /// A search within the text in the Detail panel.
+    pub find: Option<FindState>,
+    pub filter: Option<String>,
+    /// The quick filter over the feeds list, if one is on.
+    pub feed_filter: Option<FeedFilter>,
+    /// Feeds that failed to fetch on their last refresh, by URL.
+    failing_feeds: HashSet<String>,
+    /// Feeds archived, by URL, which are hidden and no longer refreshed.
+    archived_feeds: HashSet<String>,
+    /// Feeds marked favorites, by id, which sort to the top of the list.
+    favorite_feeds: HashSet<String>,
+    pub offline: bool,
+    /// The text-to-speech command reading the current item aloud, if any.
+    speech: Option<Child>,
+    /// The item in the Detail panel and since when, to mark it read once it's
+    /// been there for `mark_read_after`.
+    shown_item: Option<(String, Instant)>,
+    /// When a dashboard last moved on, or someone last pressed a key.
+    dashboard_moved: Instant,
+    /// Whether site icons are shown as images, rather than as badges.
+    pub graphics: bool,
+    /// The icons of feeds' sites, by feed id.
+    pub icons: HashMap<String, Icon>,
+    /// Where icons were left room for in the last render.
+    pub icon_slots: Vec<IconSlot>,
+    dirty: bool,
     dimensions: (u16, u16),
     repo_rx: UnboundedReceiver<RepositoryEvent>,
+    /// Where scripts send commands, unless another app already listens there.
+    control: Option<ControlSocket>,
+    /// The hold on the database and config file, unless another app has it.
+    instance_lock: Option<InstanceLock>,
+    /// Whether another app held them at startup, leaving this one read-only.
+    pub secondary: bool,
 }
 
 impl App {
-    pub fn init(dimensions: (u16, u16)) -> Result<Self> {
-        let args = Args::parse();
-        let config = Config::new(args)?;
+    pub fn init(args: Args, dimensions: (u16, u16)) -> Result<Self> {
+        let mut config = Config::new(args)?;
+        // Only one app may change the database and config at a time; any
+        // other started alongside it is read-only.
+        let (mut instance_lock, mut secondary) = (None, false);
+        if !config.read_only() {
+            match InstanceLock::acquire(&config) {
+                Ok(lock) => instance_lock = Some(lock),
+                Err(LockError::Held(pid)) => {
+                    match pid {
+                        Some(pid) => log::info!("moccasin is already running as {}", pid),
+                        None => log::info!("moccasin is already running"),
+                    }
+                    config.set_read_only(true);
+                    secondary = true;
+                }
+                Err(LockError::Failed(err)) => log::warn!("{:#}", err),
+            }
+        }
+
+        let mut app = Self::with_config(config, dimensions)?;
+        app.instance_lock = instance_lock;
+        app.secondary = secondary;
+        if secondary {
+            app.repo.hold_off();
+        }
+        app.control = match ControlSocket::bind(control::socket_path(&app.config)) {
+            Ok(socket) => Some(socket),
+            Err(err) => {
+                log::warn!("{:#}", err);
+                None
+            }
+        };
+        Ok(app)
+    }
 
+    pub fn with_config(config: Config, dimensions: (u16, u16)) -> Result<Self> {
         let (tx, rx) = mpsc::unbounded_channel::<RepositoryEvent>();
         let mut repo = Repository::init(&config, tx)?;
 
         let items = repo.read_all(&config).unwrap_or_default();
+        let favorite_feeds = repo.favorite_feeds();
+        let archived_feeds = repo.archived_feeds();
         let feeds_count = items.len() as u16;
-
-        Ok(Self {
+        // Icons are only decoration to a screen reader, and too colorful
+        // without colors.
+        let graphics = config.feed_icons()
+            && !config.screen_reader()
+            && config.color() != ColorMode::Never
+            && graphics::supports_images();
+
+        let mut app = Self {
             config,
             repo,
             running: true,
@@ -130,92 +886,380 @@ impl App {
             active_tab: Tab::Browse,
             feeds: StatefulList::<Feed>::with_items(items),
             feeds_scroll: ScrollbarState::default().content_length(feeds_count),
+            feed_rows_state: ListState::default(),
+            feed_sections: HashMap::new(),
             items: StatefulList::<Item>::default(),
             items_scroll: ScrollbarState::default(),
+            labels: StatefulList::<TagSummary>::default(),
+            item_rows: RowCache::default(),
+            jumps: Jumps::default(),
+            code_blocks: CodeCache::default(),
+            body_rows: BodyCache::default(),
             detail_scroll: ScrollbarState::default(),
             detail_scroll_index: 0,
+            pages: vec![],
             status: Status::Done,
             show_keybinds: false,
+            show_inspector: false,
+            stats: None,
+            monitor: None,
+            batch: None,
+            preview: None,
+            batch_pending: HashSet::new(),
+            revision: None,
+            translation: None,
+            summary: None,
+            log_lines: None,
+            share_menu: None,
+            compose: None,
+            dead_feeds: vec![],
+            kept_feeds: HashSet::new(),
             command_state: InputState::new(),
+            completion: Completion::default(),
+            search: SearchState::default(),
+            find: None,
+            filter: None,
+            feed_filter: None,
+            failing_feeds: HashSet::new(),
+            archived_feeds,
+            favorite_feeds,
+            offline: false,
+            speech: None,
+            shown_item: None,
+            dashboard_moved: Instant::now(),
+            graphics,
+            icons: HashMap::new(),
+            icon_slots: vec![],
+            dirty: true,
             repo_rx: rx,
-        })
+            control: None,
+            instance_lock: None,
+            secondary: false,
+        };
+        app.apply_feed_filter();
+        app.load_icons();
+        if app.config.dashboard_interval().is_some() {
+            app.cycle_dashboard();
+        }
+        Ok(app)
+    }
+
+    /// Reads the cached icons of all feeds, if they're shown at all.
+    fn load_icons(&mut self) {
+        if !self.graphics {
+            return;
+        }
+        self.icons = self
+            .feeds
+            .items()
+            .iter()
+            .filter_map(|feed| Some((feed.id().to_owned(), Icon::new(self.repo.icon(feed)?))))
+            .collect();
     }
 
     /// Handles the tick event of the terminal.
     pub fn tick(&mut self) {
-        self.repo.tick(&self.config);
-
-        let waker = futures::task::noop_waker();
-        let mut cx = std::task::Context::from_waker(&waker);
-
-        loop {
-            match self.repo_rx.poll_recv(&mut cx) {
-                Poll::Ready(m) => match m {
-                    Some(RepositoryEvent::Requesting(amount)) => {
-                        self.status = match self.status {
-                            Status::Loading(curr, total) => Status::Loading(curr, total + amount),
-                            _ => Status::Loading(0, amount),
-                        };
-                    }
-                    Some(RepositoryEvent::Requested(counts)) => {
-                        let counts = match self.status {
-                            Status::Loading(current, total) => ((current + 1).min(total), total),
-                            _ => counts,
-                        };
-                        self.status = Status::Loading(counts.0, counts.1);
-                    }
-                    Some(RepositoryEvent::RetrievedAll(feeds)) => {
-                        self.set_feeds(feeds);
-                        self.status = Status::Done;
-                        break;
-                    }
-                    Some(RepositoryEvent::RetrievedOne(feed)) => {
-                        match self
-                            .feeds
-                            .items
-                            .iter()
-                            .enumerate()
-                            .find(|(_, f)| f.link() == feed.link())
-                        {
-                            Some((i, f)) => {
-                                self.feeds.items[i] = f.clone();
-                            }
-                            None => {
-                                self.feeds.items.push(feed);
-                            }
-                        }
-
-                        match self.status {
-                            Status::Loading(_, _) => {
-                                self.status = Status::Done;
-                            }
-                            _ => {}
-                        }
-
-                        break;
-                    }
-                    Some(RepositoryEvent::Errored) => {
-                        self.status = Status::Errored("database transaction failed".into());
-                        break;
-                    }
-                    Some(RepositoryEvent::Refresh) => {}
-                    Some(RepositoryEvent::Aborted) => {
+        // Only time-based status segments can change without any other event,
+        // and aren't worth a redraw of their own over slow connections.
+        if self.config.status_bar().is_time_based()
+            && self.config.render_mode() == RenderMode::Full
+            && self.repo.time_until_refresh().is_some()
+        {
+            self.invalidate();
+        }
+        // Items on a dashboard are only on show, not read.
+        if let Some(interval) = self.config.dashboard_interval() {
+            if self.dashboard_moved.elapsed() >= interval {
+                self.cycle_dashboard();
+            }
+        } else if let Some(after) = self.config.mark_read_after() {
+            self.mark_shown_read(after);
+        }
+    }
+
+    /// Puts off a dashboard's next move while someone is at the keyboard.
+    pub fn hold_dashboard(&mut self) {
+        self.dashboard_moved = Instant::now();
+    }
+
+    /// Moves a dashboard on to the next of the newest items of the feed it's
+    /// showing, or to the next feed with items once it's shown them all.
+    fn cycle_dashboard(&mut self) {
+        self.dashboard_moved = Instant::now();
+        if self.active_tab.lists_labels() || self.should_render_console() {
+            return;
+        }
+        let next = self.items.state.selected().map_or(0, |i| i + 1);
+        let shown = self.items.items().len().min(DASHBOARD_ITEMS);
+        if self.selected_feed().is_some() && next < shown {
+            self.items.state.select(Some(next));
+            self.items_scroll = self.items_scroll.position(next as u16);
+        } else {
+            // Feeds without items are skipped, going at most once around.
+            for _ in 0..self.feeds.items().len() {
+                self.next_feed();
+                if !self.items.items().is_empty() {
+                    break;
+                }
+            }
+            self.reset_items_scroll();
+            if !self.items.items().is_empty() {
+                self.items.state.select(Some(0));
+            }
+        }
+        self.reset_detail_scroll();
+        self.active_view = View::SubList;
+        self.invalidate();
+    }
+
+    /// Marks the item in the Detail panel read once it's been there for
+    /// `after`, like web readers do.
+    fn mark_shown_read(&mut self, after: Duration) {
+        let shown = self
+            .current_item()
+            .filter(|_| self.current_page().is_none())
+            .map(|item| (item.id().to_owned(), item.read()));
+        match (shown, &self.shown_item) {
+            (Some((id, read)), Some((shown_id, since))) if id == *shown_id => {
+                if !read && since.elapsed() >= after {
+                    self.mark_current_read();
+                    self.invalidate();
+                }
+            }
+            (shown, _) => self.shown_item = shown.map(|(id, _)| (id, Instant::now())),
+        }
+    }
+
+    /// Marks the interface as needing to be redrawn.
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Returns whether the interface needs to be redrawn, and resets the flag.
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Waits for the next repository event, letting the repository write results
+    /// to storage and run scheduled refreshes in the meantime.
+    pub async fn update(&mut self) {
+        let search_deadline = self.search.deadline;
+        let search = async move {
+            match search_deadline {
+                Some(at) => tokio::time::sleep_until(at.into()).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        let control = self.control.as_mut();
+        let control = async move {
+            match control {
+                Some(control) => control.recv().await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            _ = self.repo.process(&self.config) => {}
+            Some(event) = self.repo_rx.recv() => self.handle_repository_event(event),
+            _ = search => self.run_search(),
+            Some(request) = control => self.handle_control(request),
+        }
+    }
+
+    /// Carries out a command sent over the control socket, and answers it.
+    fn handle_control(&mut self, request: ControlRequest) {
+        self.invalidate();
+        let result = match &request.command {
+            ControlCommand::Refresh => {
+                self.refresh_all();
+                Ok("refreshing".to_owned())
+            }
+            ControlCommand::Add(_) if self.config.read_only() => {
+                Err("feeds can't be changed in read-only mode".to_owned())
+            }
+            ControlCommand::Add(url) if self.config.feed_urls().contains(url) => {
+                Err(format!("already subscribed to {}", url))
+            }
+            ControlCommand::Add(url) => {
+                report!(self.config.add_feed_url(url), "Failed to write config");
+                self.repo.add_feed_url(url, &self.config);
+                Ok(format!("adding {}", url))
+            }
+            ControlCommand::GoTo(query) => match self.find_feeds(query).into_iter().next() {
+                Some(result) => {
+                    let title = result.feed_title.clone();
+                    self.open_result(result);
+                    Ok(format!("opened {}", title))
+                }
+                None => Err(format!("no feed matching {}", query)),
+            },
+            ControlCommand::Notify(message) => {
+                self.status = Status::Info(message.clone());
+                Ok("shown".to_owned())
+            }
+        };
+        request.answer(result);
+    }
+
+    fn handle_repository_event(&mut self, event: RepositoryEvent) {
+        self.invalidate();
+        match event {
+            RepositoryEvent::Requesting(amount) => {
+                self.status = match self.status {
+                    Status::Loading(curr, total) => Status::Loading(curr, total + amount),
+                    _ => Status::Loading(0, amount),
+                };
+            }
+            RepositoryEvent::Requested(counts) => {
+                let counts = match self.status {
+                    Status::Loading(current, total) => ((current + 1).min(total), total),
+                    _ => counts,
+                };
+                self.status = Status::Loading(counts.0, counts.1);
+            }
+            RepositoryEvent::RetrievedAll(feeds) => {
+                self.offline = feeds.is_empty() && !self.config.feed_urls().is_empty();
+                if self.graphics {
+                    self.repo.fetch_icons(&feeds, &self.config);
+                }
+                self.merge_feeds(feeds);
+                self.status = Status::Done;
+                if self.monitor.is_some() {
+                    self.open_monitor();
+                }
+            }
+            RepositoryEvent::RetrievedOne(feed) => {
+                self.offline = false;
+                if self.graphics {
+                    self.repo
+                        .fetch_icons(std::slice::from_ref(&feed), &self.config);
+                }
+                self.merge_feeds(vec![feed]);
+
+                match self.status {
+                    Status::Loading(_, _) => {
                         self.status = Status::Done;
-                        break;
                     }
-                    None => {
-                        break;
+                    _ => {}
+                }
+            }
+            RepositoryEvent::Previewed(feed) => {
+                self.offline = false;
+                self.status = Status::Done;
+                self.preview = Some(feed);
+            }
+            RepositoryEvent::RetrievedMonitored(feeds) => {
+                self.merge_feeds(feeds);
+                if self.monitor.is_some() {
+                    self.open_monitor();
+                }
+            }
+            RepositoryEvent::RetrievedPage(page) => {
+                self.status = Status::Done;
+                self.pages.push(page);
+                self.detail_scroll_index = 0;
+                self.detail_scroll = self.detail_scroll.position(0);
+                self.active_view = View::Detail;
+            }
+            RepositoryEvent::Failed(message) => {
+                self.status = Status::Errored(message);
+            }
+            RepositoryEvent::Errored => {
+                self.status = Status::Errored("database transaction failed".into());
+            }
+            RepositoryEvent::IconsUpdated => self.load_icons(),
+            RepositoryEvent::Summarized(id, summary) => {
+                self.status = Status::Done;
+                self.summary = Some((id, summary));
+                self.detail_scroll_index = 0;
+                self.detail_scroll = self.detail_scroll.position(0);
+            }
+            RepositoryEvent::Shared(name) => {
+                self.status = Status::Info(format!("shared to {}", name));
+            }
+            RepositoryEvent::ExportedEvent(exported) => self.status = Status::Info(exported),
+            RepositoryEvent::Translated(id, translation) => {
+                self.status = Status::Done;
+                self.translation = Some((id, translation));
+                self.detail_scroll_index = 0;
+                self.detail_scroll = self.detail_scroll.position(0);
+            }
+            RepositoryEvent::DeadFeeds(feeds) => {
+                for feed in feeds {
+                    if !self.config.read_only()
+                        && self.config.feed_urls().contains(&feed.url)
+                        && !self.kept_feeds.contains(&feed.url)
+                        && !self.dead_feeds.iter().any(|dead| dead.url == feed.url)
+                    {
+                        self.dead_feeds.push(feed);
                     }
-                },
-                Poll::Pending => {
-                    break;
                 }
             }
+            RepositoryEvent::Checked(health) => {
+                self.settle_batch(&health);
+                for url in &health.fetched {
+                    self.failing_feeds.remove(url);
+                }
+                let gone = health.gone.into_iter().map(|dead| dead.url);
+                self.failing_feeds
+                    .extend(health.failed.into_iter().chain(gone));
+                if self.feed_filter == Some(FeedFilter::Failing) {
+                    self.apply_feed_filter();
+                }
+            }
+            RepositoryEvent::RefreshSummary {
+                new_items,
+                updated,
+                failed,
+            } => {
+                let message = refresh_summary(new_items, updated, failed);
+                if new_items > 0 && self.config.should_notify_refresh() {
+                    util::notify("moccasin", &message);
+                }
+                if !matches!(self.status, Status::Loading(_, _) | Status::Errored(_)) {
+                    self.status = Status::Info(message);
+                }
+            }
+            RepositoryEvent::Refresh | RepositoryEvent::Stored(_) => {}
+            RepositoryEvent::Aborted => {
+                self.status = Status::Done;
+            }
+        }
+    }
+
+    /// What to ask about the first dead feed, if there is one.
+    pub fn dead_feed_prompt(&self) -> Option<String> {
+        let dead = self.dead_feeds.first()?;
+        let title = self
+            .feeds
+            .items()
+            .iter()
+            .find(|feed| feed.url() == dead.url)
+            .map_or(dead.url.as_str(), |feed| feed.title());
+        Some(format!(
+            "{} has been gone ({}) for a while. Unsubscribe? [y/n]",
+            title, dead.status
+        ))
+    }
+
+    /// Unsubscribes from the first dead feed, or keeps it and stops asking
+    /// about it.
+    pub fn answer_dead_feed(&mut self, unsubscribe: bool) {
+        if self.dead_feeds.is_empty() {
+            return;
+        }
+        let dead = self.dead_feeds.remove(0);
+        if unsubscribe {
+            self.delete_feed(&dead.url);
+        } else {
+            self.kept_feeds.insert(dead.url);
         }
     }
 
     /// Set running to false to quit the application.
     pub fn quit(&mut self) {
+        self.stop_speaking();
         self.running = false;
     }
 
@@ -224,11 +1268,15 @@ impl App {
     }
 
     pub fn should_render_feeds_scroll(&self) -> bool {
-        self.feeds.items().len() as u16 > self.dimensions.1 - 8
+        self.draws_scrollbars() && self.feeds.items().len() as u16 > self.dimensions.1 - 8
     }
 
     pub fn should_render_items_scroll(&self) -> bool {
-        self.items.items().len() as u16 > self.dimensions.1 - 8
+        self.draws_scrollbars() && self.items.items().len() as u16 > self.dimensions.1 - 8
+    }
+
+    fn draws_scrollbars(&self) -> bool {
+        !self.config.screen_reader() && self.config.render_mode() == RenderMode::Full
     }
 
     pub fn should_render_detail_scroll(&self) -> bool {
@@ -240,13 +1288,43 @@ impl App {
         self.command_state.show_input
     }
 
+    /// The selected feed, or in the Tags tab, where items come from any feed,
+    /// the feed of the selected item.
     pub fn current_feed(&self) -> Option<&Feed> {
+        match self.active_tab {
+            Tab::Tags | Tab::Authors => self.current_item().and_then(|item| {
+                self.feeds
+                    .items()
+                    .iter()
+                    .find(|feed| feed.id() == item.feed_id())
+            }),
+            _ => self.selected_feed(),
+        }
+    }
+
+    fn selected_feed(&self) -> Option<&Feed> {
         self.feeds
             .state
             .selected()
             .and_then(|i| self.feeds.items().get(i))
     }
 
+    pub fn current_label(&self) -> Option<&TagSummary> {
+        self.labels
+            .state
+            .selected()
+            .and_then(|i| self.labels.items().get(i))
+    }
+
+    /// Whether a feed, or in the Tags and Authors tabs a tag or author, is
+    /// selected to list items of.
+    fn has_current_list(&self) -> bool {
+        match self.active_tab {
+            Tab::Tags | Tab::Authors => self.current_label().is_some(),
+            _ => self.selected_feed().is_some(),
+        }
+    }
+
     pub fn current_item(&self) -> Option<&Item> {
         self.items
             .state
@@ -254,6 +1332,106 @@ impl App {
             .and_then(|i| self.items.items().get(i))
     }
 
+    /// The page being read with the internal browser, if any.
+    pub fn current_page(&self) -> Option<&Page> {
+        self.pages.last()
+    }
+
+    /// Goes back from the page being read to the previous one, or to the item
+    /// it was followed from.
+    fn close_page(&mut self) -> bool {
+        if self.pages.pop().is_some() {
+            self.detail_scroll_index = 0;
+            self.detail_scroll = self.detail_scroll.position(0);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn unread_count(&self) -> usize {
+        self.feeds.items().iter().map(Feed::unread_count).sum()
+    }
+
+    fn select_feed(&mut self, index: usize) {
+        self.switch_tab(Tab::Browse);
+        self.show_feed(index);
+    }
+
+    /// Selects the feed at `index` of the feeds list, in whichever tab lists
+    /// it, and reads its items.
+    fn show_feed(&mut self, index: usize) {
+        self.reset_items_scroll();
+        self.reset_detail_scroll();
+        self.active_view = View::MainList;
+        self.feeds.state.select(Some(index));
+        self.feeds_scroll = self.feeds_scroll.position(index as u16);
+
+        self.load_current_items();
+    }
+
+    fn load_current_items(&mut self) {
+        if let Some(items) = self.current_items() {
+            self.items.items = items;
+            self.items_scroll = self
+                .items_scroll
+                .content_length(self.items.items.len() as u16);
+        }
+    }
+
+    /// Reads the items of the selected feed, or of the selected tag or author
+    /// in the Tags and Authors tabs.
+    fn current_items(&mut self) -> Option<Vec<Item>> {
+        match self.active_tab {
+            Tab::Tags => {
+                let tag = self.current_label()?.name.clone();
+                let items = self.repo.items_for_tag(&tag);
+                Some(self.in_read_languages(items))
+            }
+            Tab::Authors => {
+                let author = self.current_label()?.name.clone();
+                let items = self.repo.items_by_author(&author);
+                Some(self.in_read_languages(items))
+            }
+            _ => {
+                let feed_id = self.selected_feed()?.id().to_owned();
+                Some(self.repo.items_for_feed(&feed_id))
+            }
+        }
+    }
+
+    /// Leaves out the items of feeds in languages that aren't read, for the
+    /// views gathering items from every feed.
+    fn in_read_languages(&self, mut items: Vec<Item>) -> Vec<Item> {
+        let foreign = self
+            .feeds
+            .all_items()
+            .filter(|feed| !self.config.reads_language(feed.language()))
+            .map(|feed| feed.id())
+            .collect::<HashSet<_>>();
+        items.retain(|item| !foreign.contains(item.feed_id()));
+        items
+    }
+
+    fn select_label(&mut self, index: usize) {
+        self.reset_items_scroll();
+        self.reset_detail_scroll();
+        self.active_view = View::MainList;
+        self.labels.state.select(Some(index));
+
+        self.load_current_items();
+    }
+
+    fn next_label(&mut self) {
+        self.labels.next();
+        self.load_current_items();
+    }
+
+    fn prev_label(&mut self) {
+        self.labels.previous();
+        self.load_current_items();
+    }
+
     pub fn next_feed(&mut self) {
         self.feeds.next();
         self.feeds_scroll = self.feeds_scroll.position(
@@ -263,12 +1441,7 @@ impl App {
                 .unwrap_or(self.feeds.state.offset()) as u16,
         );
 
-        if let Some(channel) = self.current_feed() {
-            self.items.items = channel.items().into();
-            self.items_scroll = self
-                .items_scroll
-                .content_length(self.items.items.len() as u16);
-        }
+        self.load_current_items();
     }
 
     pub fn prev_feed(&mut self) {
@@ -280,16 +1453,18 @@ impl App {
                 .unwrap_or(self.feeds.state.offset()) as u16,
         );
 
-        if let Some(channel) = self.current_feed() {
-            self.items.items = channel.items().into();
-            self.items_scroll = self
-                .items_scroll
-                .content_length(self.items.items.len() as u16);
-        }
+        self.load_current_items();
     }
 
     pub fn next_item(&mut self) {
+        let passed = self.items.state.selected();
         self.items.next();
+        // Wrapping around to the top doesn't scroll past the last item.
+        if let Some(passed) = passed.filter(|&i| self.items.state.selected() > Some(i)) {
+            if self.config.should_mark_read_on_scroll() {
+                self.mark_read(passed);
+            }
+        }
         self.items_scroll = self.items_scroll.position(
             self.items
                 .state
@@ -308,11 +1483,39 @@ impl App {
         );
     }
 
+    /// Moves the Detail panel on to the next item, or back to the previous
+    /// one, without going back to the items list. The item left is marked
+    /// read, as is the one moved to.
+    pub fn step_item(&mut self, forward: bool) {
+        let Some(selected) = self.items.state.selected() else {
+            return;
+        };
+        let target = if forward {
+            selected + 1
+        } else {
+            selected.wrapping_sub(1)
+        };
+        if target >= self.items.items().len() {
+            self.status = Status::Info(if forward {
+                "no more items".to_owned()
+            } else {
+                "no earlier items".to_owned()
+            });
+            return;
+        }
+
+        self.mark_read(selected);
+        self.reset_detail_scroll();
+        self.items.state.select(Some(target));
+        self.items_scroll = self.items_scroll.position(target as u16);
+        self.mark_read(target);
+    }
+
     pub fn next_view(&mut self, wrap: bool) {
-        let has_current_feed = self.current_feed().is_some();
+        let has_current_list = self.has_current_list();
         let has_current_item = self.current_item().is_some();
 
-        if !has_current_feed {
+        if !has_current_list {
             self.active_view = View::MainList;
             return;
         }
@@ -341,15 +1544,22 @@ impl App {
                 }
             }
         } {
+            if next_view == View::Detail {
+                self.mark_current_read();
+            }
             self.active_view = next_view;
         }
     }
 
     pub fn prev_view(&mut self, wrap: bool) {
-        let has_current_feed = self.current_feed().is_some();
+        if self.active_view == View::Detail && self.close_page() {
+            return;
+        }
+
+        let has_current_list = self.has_current_list();
         let has_current_item = self.current_item().is_some();
 
-        if !has_current_feed {
+        if !has_current_list {
             self.active_view = View::MainList;
             return;
         }
@@ -376,7 +1586,10 @@ impl App {
             View::MainList => {
                 self.reset_items_scroll();
                 self.reset_detail_scroll();
-                self.next_feed();
+                match self.active_tab {
+                    Tab::Tags | Tab::Authors => self.next_label(),
+                    _ => self.next_feed(),
+                }
             }
             View::SubList => {
                 self.reset_detail_scroll();
@@ -394,7 +1607,10 @@ impl App {
             View::MainList => {
                 self.reset_items_scroll();
                 self.reset_detail_scroll();
-                self.prev_feed();
+                match self.active_tab {
+                    Tab::Tags | Tab::Authors => self.prev_label(),
+                    _ => self.prev_feed(),
+                }
             }
             View::SubList => {
                 self.reset_detail_scroll();
@@ -411,59 +1627,241 @@ impl App {
         let next_tab = match self.active_tab {
             Tab::Browse => Tab::Favorites,
             Tab::Favorites => Tab::Tags,
-            Tab::Tags => Tab::Browse,
+            Tab::Tags => Tab::Authors,
+            Tab::Authors => Tab::Browse,
         };
 
-        self.active_tab = next_tab;
+        self.switch_tab(next_tab);
     }
 
     pub fn prev_tab(&mut self) {
         let prev_tab = match self.active_tab {
-            Tab::Browse => Tab::Tags,
+            Tab::Browse => Tab::Authors,
             Tab::Favorites => Tab::Browse,
             Tab::Tags => Tab::Favorites,
+            Tab::Authors => Tab::Tags,
         };
 
-        self.active_tab = prev_tab;
+        self.switch_tab(prev_tab);
     }
 
     pub fn set_tab(&mut self, index: usize) {
-        self.active_tab = Tab::from(index);
+        self.switch_tab(Tab::from(index));
     }
 
-    pub fn unselect(&mut self) {
-        if self.current_item().is_some() {
-            self.items.state.select(None);
-        } else {
-            self.feeds.state.select(None);
+    /// Shows another tab, going back to its list. The Tags and Authors tabs
+    /// read their lists afresh, since items are tagged as feeds are fetched.
+    fn switch_tab(&mut self, tab: Tab) {
+        if tab == self.active_tab {
+            return;
         }
-        self.prev_view(false);
+
+        self.active_tab = tab;
+        self.active_view = View::MainList;
+        self.pages.clear();
+        self.reset_items_scroll();
+        self.reset_detail_scroll();
+        self.items.items = vec![];
+        self.item_rows.invalidate();
+        self.labels.items = vec![];
+        self.labels.state.select(None);
+        self.reload_labels();
+        self.apply_feed_filter();
+        self.load_current_items();
+    }
+
+    /// Lists the items with a category, or tag, in the Tags tab.
+    fn show_category(&mut self, name: &str) {
+        self.switch_tab(Tab::Tags);
+        self.reload_labels();
+        if !self.select_label_named(name) {
+            self.status = Status::Errored(format!("no items in category {}", name));
+        }
+    }
+
+    /// Lists the items by an author in the Authors tab, or just the authors
+    /// when no name is given. Names match regardless of case, or in part if
+    /// none match fully.
+    fn show_author(&mut self, name: Option<&str>) {
+        self.switch_tab(Tab::Authors);
+        self.reload_labels();
+        let Some(name) = name else {
+            return;
+        };
+        if !self.select_label_named(name) {
+            self.status = Status::Errored(format!("no items by {}", name));
+        }
+    }
+
+    fn select_label_named(&mut self, name: &str) -> bool {
+        let needle = name.to_lowercase();
+        let labels = self.labels.items();
+        let found = labels
+            .iter()
+            .position(|label| label.name.to_lowercase() == needle)
+            .or_else(|| {
+                labels
+                    .iter()
+                    .position(|label| label.name.to_lowercase().contains(&needle))
+            });
+        if let Some(index) = found {
+            self.select_label(index);
+        }
+        found.is_some()
+    }
+
+    /// Reads the tags given to items, or their authors, keeping the selected
+    /// one if it still exists.
+    fn reload_labels(&mut self) {
+        let labels = match self.active_tab {
+            Tab::Tags => self.repo.tags(),
+            Tab::Authors => self.repo.authors(),
+            _ => return,
+        };
+        let selected = self.current_label().map(|label| label.name.clone());
+        self.labels.items = labels;
+        let selected = selected.and_then(|name| {
+            self.labels
+                .items()
+                .iter()
+                .position(|label| label.name == name)
+        });
+        self.labels.state.select(selected);
+    }
+
+    pub fn unselect(&mut self) {
+        if self.close_page() {
+            return;
+        }
+        if self.current_feed().is_none() {
+            self.filter = None;
+        }
+        if self.current_item().is_some() {
+            self.items.state.select(None);
+        } else if self.active_tab.lists_labels() {
+            self.labels.state.select(None);
+        } else {
+            self.feeds.state.select(None);
+        }
+        self.prev_view(false);
     }
 
     pub fn open(&mut self) {
+        self.open_current(false);
+    }
+
+    /// Moves on to the next match of the search in the Detail panel, or back
+    /// to the previous one, wrapping around at either end.
+    pub fn next_match(&mut self, forward: bool) {
+        let Some(find) = self.find.as_mut() else {
+            return;
+        };
+        let count = find.rows.len();
+        if count > 0 {
+            find.current = if forward {
+                (find.current + 1) % count
+            } else {
+                (find.current + count - 1) % count
+            };
+        }
+        find.follow = true;
+    }
+
+    pub fn clear_find(&mut self) {
+        self.find = None;
+    }
+
+    /// Opens the current feed or item without taking focus away from the terminal.
+    pub fn open_in_background(&mut self) {
+        self.open_current(true);
+    }
+
+    fn open_current(&mut self, background: bool) {
+        let open = |app: &Self, link: &str| {
+            if background {
+                app.open_link_in_background(link)
+            } else {
+                app.open_link(link)
+            }
+        };
+
         match self.active_view {
             View::MainList => {
                 if let Some(feed) = self.current_feed() {
                     let link = feed.link();
-                    let _ = App::open_link(link);
+                    let _ = open(self, link);
                 }
             }
             View::SubList => {
                 if let Some(item) = self.current_item() {
+                    self.repo.record_open(item);
                     if let Some(link) = item.link() {
-                        let _ = App::open_link(link);
+                        let _ = open(self, link);
                     }
                 }
+                self.mark_current_read();
             }
             _ => {}
         }
     }
 
+    fn mark_current_read(&mut self) {
+        if let Some(i) = self.items.state.selected() {
+            self.mark_read(i);
+        }
+    }
+
+    /// Marks the item at `index` in the items list read, counting it off its
+    /// feed and tag or author.
+    fn mark_read(&mut self, index: usize) {
+        if let Some(item) = self.items.items.get_mut(index) {
+            if !item.read {
+                item.read = true;
+                self.repo.mark_read(item);
+                self.item_rows.invalidate();
+                let feed_id = item.feed_id();
+                if let Some(feed) = self.feeds.items.iter_mut().find(|f| f.id() == feed_id) {
+                    feed.unread_count = feed.unread_count.saturating_sub(1);
+                }
+                let label = match self.active_tab {
+                    Tab::Tags | Tab::Authors => self.labels.state.selected(),
+                    _ => None,
+                };
+                if let Some(label) = label.and_then(|l| self.labels.items.get_mut(l)) {
+                    label.unread_count = label.unread_count.saturating_sub(1);
+                }
+            }
+        }
+    }
+
+    /// Tells whoever is at the keyboard that feeds and settings can't be
+    /// changed here, if moccasin was started with `--read-only`, returning
+    /// whether it was.
+    pub fn refuse_if_read_only(&mut self) -> bool {
+        if self.secondary {
+            self.status = Status::Errored(
+                "sorry, another moccasin is running, so feeds and settings can't be changed here"
+                    .into(),
+            );
+        } else if self.config.read_only() {
+            self.status = Status::Errored(
+                "sorry, feeds and settings can't be changed in read-only mode".into(),
+            );
+        }
+        self.config.read_only()
+    }
+
     pub fn open_config(&self) -> Option<Child> {
-        if let Some(cfg_path) = self.config.config_file_path().as_path().to_str() {
-            Self::open_link(cfg_path)
+        let cfg_path = self.config.config_file_path();
+        if cfg!(target_os = "windows") {
+            // `.toml` files rarely have a default app on Windows, so use one that's always there.
+            Command::new("notepad")
+                .arg(&cfg_path)
+                .stdout(Stdio::null())
+                .spawn()
+                .ok()
         } else {
-            None
+            Self::open_with_system(&cfg_path)
         }
     }
 
@@ -475,6 +1873,387 @@ impl App {
         self.show_keybinds = !self.show_keybinds;
     }
 
+    /// Shows or hides what changed in the current item since its previous
+    /// version, if it was updated.
+    pub fn toggle_revision(&mut self) {
+        if self.current_revision().is_some() {
+            self.revision = None;
+            return;
+        }
+        self.revision = self
+            .current_item()
+            .filter(|item| item.updated())
+            .and_then(|item| Some((item.id().to_owned(), self.repo.previous_revision(item)?)));
+        self.detail_scroll_index = 0;
+        self.detail_scroll = self.detail_scroll.position(0);
+    }
+
+    /// The previous version of the current item, if it's being compared with.
+    pub fn current_revision(&self) -> Option<&ItemRevision> {
+        let item = self
+            .current_item()
+            .filter(|_| self.current_page().is_none())?;
+        match &self.revision {
+            Some((id, revision)) if id == item.id() => Some(revision),
+            _ => None,
+        }
+    }
+
+    /// Translates the current item or page, or goes back to the original if
+    /// it's already translated.
+    pub fn toggle_translation(&mut self) {
+        if self.current_translation().is_some() {
+            self.translation = None;
+            self.detail_scroll_index = 0;
+            self.detail_scroll = self.detail_scroll.position(0);
+            return;
+        }
+        match self.current_detail_text() {
+            Some((id, text)) => self.repo.translate(&id, text, &self.config),
+            None => self.status = Status::Errored("no item selected".into()),
+        }
+    }
+
+    /// The translation of the current item or page, while it's shown.
+    pub fn current_translation(&self) -> Option<&str> {
+        let (id, translation) = self.translation.as_ref()?;
+        (*id == self.current_detail_id()?).then_some(translation.as_str())
+    }
+
+    /// Summarizes the current item or page, or hides its summary if it's
+    /// already shown.
+    pub fn toggle_summary(&mut self) {
+        if self.current_summary().is_some() {
+            self.summary = None;
+            return;
+        }
+        match self.current_detail_text() {
+            Some((id, text)) => self.repo.summarize(&id, text, &self.config),
+            None => self.status = Status::Errored("no item selected".into()),
+        }
+    }
+
+    /// The summary of the current item or page, while it's shown.
+    pub fn current_summary(&self) -> Option<&str> {
+        let (id, summary) = self.summary.as_ref()?;
+        (*id == self.current_detail_id()?).then_some(summary.as_str())
+    }
+
+    /// The id of the item, or URL of the page, in the Detail panel.
+    fn current_detail_id(&self) -> Option<&str> {
+        match self.current_page() {
+            Some(page) => Some(page.url()),
+            None => self.current_item().map(Item::id),
+        }
+    }
+
+    /// The text in the Detail panel, along with the id of the item or URL of
+    /// the page it's from.
+    fn current_detail_text(&self) -> Option<(String, String)> {
+        match (self.current_page(), self.current_item()) {
+            (Some(page), _) => Some((page.url().to_owned(), page.text().to_owned())),
+            (None, Some(item)) => Some((
+                item.id().to_owned(),
+                item.description().unwrap_or_default().to_owned(),
+            )),
+            (None, None) => None,
+        }
+    }
+
+    /// The item or page in the Detail panel, as it's sent to share targets.
+    fn current_shared(&self) -> Option<Shared> {
+        match (self.current_page(), self.current_item()) {
+            (Some(page), _) => Some(Shared {
+                title: page.title().unwrap_or_default().to_owned(),
+                link: util::clean_url(page.url(), self.config.tracking_params()),
+                text: page.text().to_owned(),
+            }),
+            (None, Some(item)) => Some(Shared {
+                title: item.title().unwrap_or_default().to_owned(),
+                link: util::clean_url(
+                    item.link().unwrap_or_default(),
+                    self.config.tracking_params(),
+                ),
+                text: item.description().unwrap_or_default().to_owned(),
+            }),
+            (None, None) => None,
+        }
+    }
+
+    /// Offers the configured share targets for the item in the Detail panel.
+    pub fn open_share_menu(&mut self) {
+        if self.current_shared().is_none() {
+            self.status = Status::Errored("no item selected".into());
+        } else if self.config.share_targets().is_empty() {
+            self.status = Status::Errored("no [share] targets configured".into());
+        } else {
+            let mut menu = ListState::default();
+            menu.select(Some(0));
+            self.share_menu = Some(menu);
+        }
+    }
+
+    pub fn close_share_menu(&mut self) {
+        self.share_menu = None;
+    }
+
+    /// Picks the next share target in the menu, or the previous one, wrapping
+    /// around at either end.
+    pub fn step_share_target(&mut self, forward: bool) {
+        let count = self.config.share_targets().len();
+        let Some(menu) = self.share_menu.as_mut() else {
+            return;
+        };
+        let current = menu.selected().unwrap_or_default();
+        menu.select(Some(if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        }));
+    }
+
+    /// Shares the item in the Detail panel with the target picked in the share
+    /// menu, closing it.
+    pub fn share_selected(&mut self) {
+        if let Some(index) = self.share_menu.as_ref().and_then(ListState::selected) {
+            self.share(index);
+        }
+    }
+
+    /// Shares the item in the Detail panel with the nth share target, closing
+    /// the share menu.
+    pub fn share(&mut self, index: usize) {
+        self.share_menu = None;
+        let (Some(target), Some(shared)) = (
+            self.config.share_targets().get(index).cloned(),
+            self.current_shared(),
+        ) else {
+            return;
+        };
+
+        match target.kind() {
+            ShareKind::Clipboard if shared.link.is_empty() => {
+                self.status = Status::Errored("no link to copy".into());
+            }
+            ShareKind::Clipboard => {
+                self.status = match util::copy_to_clipboard(&shared.link) {
+                    Ok(()) => Status::Info("copied link".into()),
+                    Err(_) => Status::Errored("could not copy link".into()),
+                };
+            }
+            ShareKind::Mastodon { .. } | ShareKind::Bluesky { .. } => {
+                let text = if shared.title.is_empty() {
+                    shared.link
+                } else {
+                    format!("{}\n\n{}", shared.title, shared.link)
+                };
+                self.compose = Some(ComposeState {
+                    target: index,
+                    cursor: text.chars().count(),
+                    text,
+                });
+            }
+            ShareKind::Email { to } => {
+                let mailto = format!(
+                    "mailto:{}?subject={}&body={}",
+                    to.as_deref().map(util::percent_encode).unwrap_or_default(),
+                    util::percent_encode(&shared.title),
+                    util::percent_encode(&shared.link)
+                );
+                if Self::open_with_system(mailto).is_none() {
+                    self.status = Status::Errored("could not open mail app".into());
+                }
+            }
+            _ => self.repo.share(&target, shared, &self.config),
+        }
+    }
+
+    /// Posts what was written in the compose overlay, unless it's too long,
+    /// closing it.
+    pub fn send_post(&mut self) {
+        let Some(compose) = self.compose.as_ref() else {
+            return;
+        };
+        let Some(target) = self.config.share_targets().get(compose.target) else {
+            return;
+        };
+        let over = target
+            .kind()
+            .post_limit()
+            .map_or(0, |limit| compose.char_count().saturating_sub(limit));
+        if over > 0 {
+            self.status = Status::Errored(format!("post is {} characters too long", over));
+            return;
+        }
+        if let Some(compose) = self.compose.take() {
+            self.repo.post(target, compose.text, &self.config);
+        }
+    }
+
+    pub fn close_compose(&mut self) {
+        self.compose = None;
+    }
+
+    /// Shows or hides details of the current feed, if there is one.
+    pub fn toggle_inspector(&mut self) {
+        self.show_inspector = !self.show_inspector && self.current_feed().is_some();
+    }
+
+    pub fn close_stats(&mut self) {
+        self.stats = None;
+    }
+
+    /// Shows the latest update of each status page feed, worst first.
+    pub fn open_monitor(&mut self) {
+        let monitored = self.config.monitored_urls();
+        let feeds: Vec<_> = self
+            .feeds
+            .items()
+            .iter()
+            .filter(|feed| monitored.iter().any(|url| url == feed.url()))
+            .map(|feed| (feed.id().to_owned(), feed.title().to_owned()))
+            .collect();
+
+        let mut entries: Vec<_> = feeds
+            .into_iter()
+            .map(|(id, feed_title)| {
+                let latest = self
+                    .repo
+                    .items_for_feed(&id)
+                    .into_iter()
+                    .max_by_key(|item| item.pub_date().and_then(util::parse_date));
+                let title = latest
+                    .as_ref()
+                    .and_then(|item| item.title())
+                    .map(str::to_owned);
+                MonitorEntry {
+                    feed_title,
+                    severity: title
+                        .as_deref()
+                        .map_or(Severity::Unknown, Severity::from_title),
+                    title,
+                    date: latest.and_then(|item| item.pub_date().map(str::to_owned)),
+                }
+            })
+            .collect();
+        entries.sort_by_key(|entry| Reverse(entry.severity));
+        self.monitor = Some(entries);
+    }
+
+    pub fn close_monitor(&mut self) {
+        self.monitor = None;
+    }
+
+    /// Subscribes to the URLs in `text`, one per line, skipping blank lines
+    /// and `#` comments, and opens the batch overlay to show how each went.
+    /// They're fetched together, and only those that fetch are subscribed to.
+    pub fn add_many(&mut self, text: &str) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+
+        let mut entries: Vec<BatchEntry> = vec![];
+        let lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        for url in lines {
+            if entries.iter().any(|entry| entry.url == url) {
+                continue;
+            }
+            let outcome = if !util::is_feed_url(url, true) {
+                BatchOutcome::Invalid
+            } else if self.config.feed_urls().contains(url) {
+                BatchOutcome::Subscribed
+            } else {
+                BatchOutcome::Pending
+            };
+            entries.push(BatchEntry {
+                url: url.to_owned(),
+                outcome,
+            });
+        }
+        if entries.is_empty() {
+            self.status = Status::Errored("no URLs to add".into());
+            return;
+        }
+
+        let pending: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.outcome == BatchOutcome::Pending)
+            .map(|entry| entry.url.clone())
+            .collect();
+        if !pending.is_empty() {
+            self.batch_pending.extend(pending.iter().cloned());
+            self.repo.add_feed_urls(pending, &self.config);
+        }
+        self.batch = Some(entries);
+    }
+
+    /// Subscribes to the URLs of a batch that fetched, and marks down what
+    /// came of each in the overlay.
+    fn settle_batch(&mut self, health: &FeedHealth) {
+        if self.batch_pending.is_empty() {
+            return;
+        }
+        let outcome = |url: &String| {
+            if health.fetched.contains(url) {
+                Some(BatchOutcome::Added)
+            } else if let Some(dead) = health.gone.iter().find(|dead| &dead.url == url) {
+                Some(BatchOutcome::Gone(dead.status))
+            } else if health.failed.contains(url) {
+                Some(BatchOutcome::Failed)
+            } else {
+                None
+            }
+        };
+
+        let settled: Vec<_> = self
+            .batch_pending
+            .iter()
+            .filter_map(|url| Some((url.clone(), outcome(url)?)))
+            .collect();
+        for (url, outcome) in settled {
+            self.batch_pending.remove(&url);
+            if outcome == BatchOutcome::Added {
+                report!(self.config.add_feed_url(&url), "Failed to write config");
+            }
+            let entry = self
+                .batch
+                .iter_mut()
+                .flatten()
+                .find(|entry| entry.url == url);
+            if let Some(entry) = entry {
+                entry.outcome = outcome;
+            }
+        }
+    }
+
+    /// Subscribes to the feed being previewed, writing it to the config and
+    /// database.
+    pub fn confirm_preview(&mut self) {
+        let Some(feed) = self.preview.take() else {
+            return;
+        };
+        report!(
+            self.config.add_feed_url(feed.url()),
+            "Failed to write config"
+        );
+        self.repo.add_previewed_feed(feed, &self.config);
+    }
+
+    pub fn cancel_preview(&mut self) {
+        self.preview = None;
+    }
+
+    pub fn close_batch(&mut self) {
+        self.batch = None;
+    }
+
+    pub fn close_log(&mut self) {
+        self.log_lines = None;
+    }
+
     pub fn toggle_console(&mut self, cmd: Option<&str>) {
         if let Some(cmd) = cmd {
             self.command_state.input = cmd.into();
@@ -484,6 +2263,8 @@ impl App {
             self.reset_cursor();
         }
         self.command_state.show_input = !self.command_state.show_input;
+        self.search = SearchState::default();
+        self.completion = Completion::default();
     }
 
     pub fn move_cursor_left(&mut self) {
@@ -501,6 +2282,8 @@ impl App {
             .input
             .insert(self.command_state.cursor_position, new_char);
         self.move_cursor_right();
+        self.completion = Completion::default();
+        self.schedule_search();
     }
 
     pub fn delete_char(&mut self) {
@@ -526,6 +2309,262 @@ impl App {
             // By leaving the selected one out, it is forgotten and therefore deleted.
             self.command_state.input = before_char_to_delete.chain(after_char_to_delete).collect();
             self.move_cursor_left();
+            self.completion = Completion::default();
+            self.schedule_search();
+        }
+    }
+
+    /// Candidates for the word under the cursor, along with where that word starts.
+    fn completion_candidates(&self) -> (usize, Vec<String>) {
+        let input = &self.command_state.input;
+        let command = input.split_whitespace().next();
+        // Tags and authors often have spaces in them, so they're completed whole.
+        let start = match command {
+            Some(":category" | ":tag" | ":author") => input.find(' '),
+            _ => input.rfind(' '),
+        }
+        .map(|i| i + 1)
+        .unwrap_or(0);
+        let word = &input[start..];
+
+        let candidates: Vec<String> = if start == 0 {
+            CONSOLE_USAGE
+                .iter()
+                .map(|usage| usage.name.to_string())
+                .collect()
+        } else {
+            match command {
+                Some(":d" | ":delete" | ":archive") => {
+                    self.config.feed_urls().iter().cloned().collect()
+                }
+                Some(":category" | ":tag") => {
+                    self.repo.tags().into_iter().map(|tag| tag.name).collect()
+                }
+                Some(":author") => self
+                    .repo
+                    .authors()
+                    .into_iter()
+                    .map(|author| author.name)
+                    .collect(),
+                Some(":p" | ":profile") => self.config.profiles(),
+                Some(":speak") => vec!["stop".to_owned()],
+                Some(":tab") => ["browse", "favorites", "tags", "authors"]
+                    .map(str::to_owned)
+                    .to_vec(),
+                Some(":help") => CONSOLE_USAGE
+                    .iter()
+                    .map(|usage| usage.name.to_string())
+                    .collect(),
+                Some(":stats") => vec!["reading".to_owned()],
+                Some(":l" | ":layout") => PANE_LAYOUTS.iter().map(|l| l.to_string()).collect(),
+                Some(":t" | ":theme") => BUILTIN_THEMES
+                    .iter()
+                    .map(|t| t.to_string())
+                    .chain(self.config.theme_files())
+                    .collect(),
+                _ => vec![],
+            }
+        };
+
+        let mut candidates = candidates
+            .into_iter()
+            .filter(|c| c.starts_with(word) && c != word)
+            .collect::<Vec<_>>();
+        candidates.sort();
+        (start, candidates)
+    }
+
+    /// The rest of the first completion candidate, shown after the cursor.
+    pub fn completion_hint(&self) -> Option<String> {
+        if self.completion.is_active() {
+            return None;
+        }
+
+        let (start, candidates) = self.completion_candidates();
+        let word_len = self.command_state.input.len() - start;
+        candidates.first().map(|c| c[word_len..].to_owned())
+    }
+
+    /// Completes the word under the cursor, cycling through candidates on
+    /// repeated presses.
+    pub fn complete(&mut self, forward: bool) {
+        if !self.completion.is_active() {
+            let (start, candidates) = self.completion_candidates();
+            if candidates.is_empty() {
+                return;
+            }
+            self.completion = Completion {
+                start,
+                candidates,
+                index: None,
+            };
+        }
+
+        let count = self.completion.candidates.len();
+        let index = match (self.completion.index, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+        };
+        self.completion.index = Some(index);
+
+        let completed = &self.completion.candidates[index];
+        self.command_state.input.truncate(self.completion.start);
+        self.command_state.input.push_str(completed);
+        if count == 1 && self.completion.start == 0 {
+            self.command_state.input.push(' ');
+        }
+        self.command_state.cursor_position = self.command_state.input.len();
+        self.schedule_search();
+    }
+
+    /// The query being typed, if the console holds a `:search` command.
+    fn search_query(&self) -> Option<&str> {
+        let input = self.command_state.input.as_str();
+        input
+            .strip_prefix(":search ")
+            .or_else(|| input.strip_prefix(":s "))
+            .map(str::trim)
+    }
+
+    /// The feed name being typed, if the console holds a `:feed` command.
+    fn feed_query(&self) -> Option<&str> {
+        let input = self.command_state.input.as_str();
+        input
+            .strip_prefix(":feed ")
+            .or_else(|| input.strip_prefix(":f "))
+            .map(str::trim)
+    }
+
+    /// Debounces a live search for the console input, so that only the last of
+    /// a burst of keystrokes hits the database. Feeds are matched in memory,
+    /// so a `:feed` query runs right away.
+    fn schedule_search(&mut self) {
+        if let Some(query) = self.feed_query().map(str::to_owned) {
+            self.run_feed_search(query);
+            return;
+        }
+
+        match self.search_query() {
+            Some(query) if !query.is_empty() => {
+                if query != self.search.query {
+                    self.search.deadline = Some(Instant::now() + SEARCH_DEBOUNCE);
+                }
+            }
+            _ => self.search = SearchState::default(),
+        }
+    }
+
+    fn run_search(&mut self) {
+        self.search.deadline = None;
+        let Some(query) = self.search_query().map(str::to_owned) else {
+            return;
+        };
+
+        let results = self.repo.search_items(&query, SEARCH_RESULTS_LIMIT);
+        self.search.results = StatefulList::with_items(results);
+        if !self.search.results.items.is_empty() {
+            self.search.results.state.select(Some(0));
+        }
+        self.search.query = query;
+        self.invalidate();
+    }
+
+    /// Feeds whose titles fuzzily match `query`, best matches first.
+    fn find_feeds(&self, query: &str) -> Vec<SearchResult> {
+        let mut matches = self
+            .feeds
+            .all_items()
+            .filter_map(|feed| util::fuzzy_score(query, feed.title()).map(|score| (score, feed)))
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(score, _)| Reverse(*score));
+
+        matches
+            .into_iter()
+            .take(SEARCH_RESULTS_LIMIT)
+            .map(|(_, feed)| SearchResult {
+                feed_id: feed.id().to_owned(),
+                item_id: None,
+                title: feed.title().to_owned(),
+                feed_title: feed.title().to_owned(),
+            })
+            .collect()
+    }
+
+    fn run_feed_search(&mut self, query: String) {
+        if query.is_empty() {
+            self.search = SearchState::default();
+            return;
+        }
+
+        self.search.results = StatefulList::with_items(self.find_feeds(&query));
+        if !self.search.results.items.is_empty() {
+            self.search.results.state.select(Some(0));
+        }
+        self.search.query = query;
+    }
+
+    pub fn next_search_result(&mut self) {
+        self.search.results.next();
+    }
+
+    pub fn prev_search_result(&mut self) {
+        self.search.results.previous();
+    }
+
+    pub fn has_search_results(&self) -> bool {
+        !self.search.results.items.is_empty()
+    }
+
+    /// Closes the console and selects the feed or item under the live search cursor.
+    pub fn open_search_result(&mut self) {
+        let Some(result) = self
+            .search
+            .results
+            .state
+            .selected()
+            .and_then(|i| self.search.results.items.get(i))
+            .cloned()
+        else {
+            return;
+        };
+
+        if result.item_id.is_some() {
+            self.filter = Some(self.search.query.clone());
+        }
+        self.toggle_console(None);
+        self.open_result(result);
+    }
+
+    fn open_result(&mut self, result: SearchResult) {
+        // Results in feeds hidden by a filter are shown among all feeds, or
+        // among the archived ones.
+        if !self.feeds.items().iter().any(|f| f.id() == result.feed_id) {
+            let archived = self
+                .feeds
+                .all_items()
+                .find(|f| f.id() == result.feed_id)
+                .is_some_and(|f| self.archived_feeds.contains(f.url()));
+            self.switch_tab(Tab::Browse);
+            self.feed_filter = archived.then_some(FeedFilter::Archived);
+            self.apply_feed_filter();
+        }
+        let Some(feed_index) = self.feeds.items().iter().position(|f| f.id() == result.feed_id)
+        else {
+            return;
+        };
+        self.select_feed(feed_index);
+
+        let Some(item_id) = result.item_id else {
+            self.next_view(false);
+            return;
+        };
+
+        if let Some(item_index) = self.items.items().iter().position(|i| i.id() == item_id) {
+            self.items.state.select(Some(item_index));
+            self.items_scroll = self.items_scroll.position(item_index as u16);
+            self.active_view = View::SubList;
         }
     }
 
@@ -538,80 +2577,852 @@ impl App {
     }
 
     pub fn submit_command(&mut self) {
+        let mut switch_to = None;
         match self.command_state.input.parse::<ConsoleCommand>() {
+            Ok(command) if command.writes() && self.refuse_if_read_only() => {}
+            Ok(ConsoleCommand::AddFeed(url)) if self.config.feed_urls().contains(&url) => {
+                self.status = Status::Errored(format!("already subscribed to {}", url));
+            }
             Ok(ConsoleCommand::AddFeed(url)) => {
-                self.config.add_feed_url(&url);
-                self.repo.add_feed_url(&url, &self.config);
+                self.repo.preview_feed_url(&url, &self.config);
+            }
+            Ok(ConsoleCommand::AddMany(path)) => {
+                let text = match &path {
+                    Some(path) => fs::read_to_string(path)
+                        .map_err(|err| format!("could not read {}: {}", path.display(), err)),
+                    None => util::read_clipboard()
+                        .map_err(|err| format!("could not read the clipboard: {}", err)),
+                };
+                match text {
+                    Ok(text) => self.add_many(&text),
+                    Err(err) => self.status = Status::Errored(err),
+                }
             }
             Ok(ConsoleCommand::DeleteFeed(maybe_url)) => {
                 if let Some(url) =
                     maybe_url.or(self.current_feed().and_then(|f| Some(f.url().into())))
                 {
-                    self.config.remove_feed_url(&url);
-                    self.repo.remove_feed_url(&url);
-
-                    // TODO: refactor, this is so bad
-                    self.feeds.items.retain(|u| u.url() != url);
-                    self.feeds.state.select(None);
-                    self.reset_items_scroll();
-                    self.reset_detail_scroll();
+                    self.delete_feed(&url);
+                }
+            }
+            Ok(ConsoleCommand::ArchiveFeed(maybe_url)) => {
+                if let Some(url) = maybe_url.or(self.current_feed().map(|f| f.url().into())) {
+                    self.toggle_archived(&url);
                 }
             }
-            Ok(ConsoleCommand::Search(_)) => todo!(),
-            _ => self.status = Status::Errored("unrecognized command".into()),
+            Ok(ConsoleCommand::EditFeedUrl(url)) => match self.current_feed() {
+                None => self.status = Status::Errored("no feed selected".into()),
+                Some(_) if self.config.feed_urls().contains(&url) => {
+                    self.status = Status::Errored(format!("already subscribed to {}", url));
+                }
+                Some(feed) => {
+                    let (id, old_url) = (feed.id().to_owned(), feed.url().to_owned());
+                    report!(
+                        self.config.replace_feed_url(&old_url, &url),
+                        "Failed to write config"
+                    );
+                    if let Some(feed) = self.feeds.items.iter_mut().find(|f| f.url() == old_url) {
+                        feed.set_url(&url);
+                    }
+                    self.repo.edit_feed_url(&id, &url, &self.config);
+                }
+            },
+            Ok(ConsoleCommand::FindFeed(query)) => {
+                match self.find_feeds(&query).into_iter().next() {
+                    Some(result) => self.open_result(result),
+                    None => self.status = Status::Errored(format!("no feed matching {}", query)),
+                }
+            }
+            Ok(ConsoleCommand::Back) => self.unselect(),
+            Ok(ConsoleCommand::Backup(path)) => {
+                let dir = path.unwrap_or_else(|| backup::default_dir(&self.config));
+                self.status = match self.repo.backup(&dir, &self.config) {
+                    Ok(()) => Status::Info(format!("backed up to {}", dir.display())),
+                    Err(err) => Status::Errored(format!("{:#}", err)),
+                };
+            }
+            Ok(ConsoleCommand::Compare) => self.toggle_revision(),
+            Ok(ConsoleCommand::GoTo(n)) => self.go_to(n),
+            Ok(ConsoleCommand::Jump(JumpTarget::Back)) => self.jump_back(),
+            Ok(ConsoleCommand::Jump(JumpTarget::Forward)) => self.jump_forward(),
+            Ok(ConsoleCommand::Jump(JumpTarget::Mark(letter))) => {
+                self.use_mark(MarkAction::Jump, letter)
+            }
+            Ok(ConsoleCommand::Mark(letter)) => self.use_mark(MarkAction::Set, letter),
+            Ok(ConsoleCommand::Help(None)) => self.show_keybinds = true,
+            Ok(ConsoleCommand::Help(Some(name))) => match CommandUsage::find(&name) {
+                Some(usage) => self.status = Status::Info(format!("{}: {}", usage, usage.summary)),
+                None => {
+                    self.status = Status::Errored(ConsoleCommandError::BadCommand(name).to_string())
+                }
+            },
+            Ok(ConsoleCommand::Ical(to)) => self.export_event(to.as_deref()),
+            Ok(ConsoleCommand::Inspect) => self.toggle_inspector(),
+            Ok(ConsoleCommand::OpenConfig) => {
+                if !self.refuse_if_read_only() {
+                    let _ = self.open_config();
+                }
+            }
+            Ok(ConsoleCommand::Quit) => self.quit(),
+            Ok(ConsoleCommand::Summarize) => self.toggle_summary(),
+            Ok(ConsoleCommand::Translate) => self.toggle_translation(),
+            Ok(ConsoleCommand::Refresh) => self.refresh_all(),
+            Ok(ConsoleCommand::SetTab(tab)) => self.switch_tab(tab),
+            Ok(ConsoleCommand::Open(OpenTarget::Current(background))) => {
+                self.open_current(background)
+            }
+            Ok(ConsoleCommand::Open(OpenTarget::Url(url))) => {
+                let _ = self.open_link(&url);
+            }
+            Ok(ConsoleCommand::Open(OpenTarget::Link(n))) => {
+                let links = match self.current_page() {
+                    Some(page) => page.links().to_vec(),
+                    None => self.current_item().map(Item::links).unwrap_or_default(),
+                };
+                let link = n.checked_sub(1).and_then(|i| links.get(i).cloned());
+                match link {
+                    Some(link) => {
+                        let _ = self.open_link(&link);
+                    }
+                    None => self.status = Status::Errored(format!("no link {}", n)),
+                }
+            }
+            Ok(ConsoleCommand::Log) => {
+                self.log_lines = Some(logging::tail(&self.config.log_path(), LOG_TAIL_LINES));
+            }
+            Ok(ConsoleCommand::Monitor) => self.open_monitor(),
+            Ok(ConsoleCommand::Speak(true)) => self.speak(),
+            Ok(ConsoleCommand::Speak(false)) => {
+                self.stop_speaking();
+            }
+            Ok(ConsoleCommand::Stats(StatsView::Reading)) => {
+                self.stats = Some(self.repo.reading_stats(STATS_DAYS));
+            }
+            Ok(ConsoleCommand::SetLayout(layout)) => {
+                self.config.set_layout(layout);
+            }
+            Ok(ConsoleCommand::SetTheme(name)) => {
+                if self.config.set_theme(&name).is_err() {
+                    self.status = Status::Errored(format!("unknown theme {}", name));
+                }
+                self.item_rows.invalidate();
+                self.code_blocks.invalidate();
+                self.body_rows.invalidate();
+            }
+            Ok(ConsoleCommand::Search(query)) => {
+                let (terms, language) = util::split_language_scope(&query);
+                let needle = terms.to_lowercase();
+                let matching_items = self.repo.search_item_feed_ids(&terms);
+                let found = self.feeds.items().iter().position(|f| {
+                    let in_language = language.map_or(true, |language| {
+                        f.language()
+                            .map_or(false, |l| util::language_matches(l, language))
+                    });
+                    in_language
+                        && (f.title().to_lowercase().contains(&needle)
+                            || matching_items.contains(f.id()))
+                });
+                self.filter = Some(query);
+                if let Some(index) = found {
+                    self.select_feed(index);
+                }
+            }
+            Ok(ConsoleCommand::FindText(query)) => {
+                self.find = Some(FindState {
+                    query,
+                    follow: true,
+                    ..Default::default()
+                });
+            }
+            Ok(ConsoleCommand::Author(name)) => self.show_author(name.as_deref()),
+            Ok(ConsoleCommand::Category(name)) => self.show_category(&name),
+            Ok(ConsoleCommand::SwitchProfile(name)) => switch_to = Some(name),
+            Err(err) => self.status = Status::Errored(err.to_string()),
         }
 
         self.command_state.input.clear();
         self.reset_cursor();
         self.toggle_console(None);
+
+        if let Some(name) = switch_to {
+            self.switch_profile(&name);
+        }
+    }
+
+    /// Starts over with the config and database of another profile, which is
+    /// created if it doesn't exist yet. Options given on the command line only
+    /// applied to the profile started with, and are not carried over, except
+    /// for screen reader mode, which is needed whatever the profile.
+    fn switch_profile(&mut self, name: &str) {
+        // Let go of the control socket and lock, should the profile's app want
+        // them.
+        self.control = None;
+        let locked = self.instance_lock.take().is_some();
+        let args = Args {
+            profile: Some(name.to_owned()),
+            screen_reader: self.config.screen_reader(),
+            ..Default::default()
+        };
+        match App::init(args, self.dimensions) {
+            Ok(app) => *self = app,
+            Err(err) => {
+                self.status = Status::Errored(format!("{:#}", err));
+                self.control = ControlSocket::bind(control::socket_path(&self.config)).ok();
+                if locked {
+                    self.instance_lock = InstanceLock::acquire(&self.config).ok();
+                }
+            }
+        }
+    }
+
+    /// Merges freshly fetched feeds into the current list by URL, keeping the
+    /// selected feed and item in place. Feeds that failed to fetch keep their
+    /// cached contents.
+    fn merge_feeds(&mut self, feeds: Vec<Feed>) {
+        self.feeds.unfilter();
+        for feed in feeds {
+            match self.feeds.items.iter_mut().find(|f| f.url() == feed.url()) {
+                Some(existing) => *existing = feed,
+                None => self.feeds.items.push(feed),
+            }
+        }
+        // Feeds whose id changed take their favorite with them.
+        self.favorite_feeds = self.repo.favorite_feeds();
+        self.apply_feed_filter();
+
+        self.refresh_current_items();
+    }
+
+    /// The rows of the feeds list, with a header above each of its sections
+    /// if it's divided into them.
+    pub fn feed_rows(&self) -> Vec<FeedRow<'_>> {
+        let mut rows = vec![];
+        let mut current = None;
+        for (i, feed) in self.feeds.items().iter().enumerate() {
+            let section = self
+                .feed_sections
+                .get(feed.id())
+                .cloned()
+                .or_else(|| feed_section(self.config.feed_sections(), feed));
+            if let Some((key, header)) = section {
+                if current != Some(key) {
+                    rows.push(FeedRow::Header(header));
+                    current = Some(key);
+                }
+            }
+            rows.push(FeedRow::Feed(i, feed));
+        }
+        rows
+    }
+
+    /// Whether the feeds list is the active pane, rather than items or labels.
+    pub fn lists_feeds(&self) -> bool {
+        self.active_view == View::MainList && !self.active_tab.lists_labels()
+    }
+
+    /// Turns a quick filter over the feeds list on, or off if it already is.
+    pub fn toggle_feed_filter(&mut self, filter: FeedFilter) {
+        self.feed_filter = (self.feed_filter != Some(filter)).then_some(filter);
+        self.apply_feed_filter();
+    }
+
+    /// Whether a feed is marked a favorite.
+    pub fn is_favorite(&self, feed: &Feed) -> bool {
+        self.favorite_feeds.contains(feed.id())
+    }
+
+    /// Marks the selected feed a favorite, or no longer one if it already is.
+    pub fn toggle_favorite(&mut self) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+        let Some(feed) = self.selected_feed() else {
+            return;
+        };
+        let (id, favorite) = (feed.id().to_owned(), !self.is_favorite(feed));
+        self.repo.set_favorite(feed, favorite);
+        if favorite {
+            self.favorite_feeds.insert(id);
+        } else {
+            self.favorite_feeds.remove(&id);
+        }
+        self.apply_feed_filter();
+    }
+
+    /// Archives the feed at `url`, hiding it and no longer refreshing it, or
+    /// brings it back if it already is. Its items are kept, and still turn up
+    /// in searches.
+    fn toggle_archived(&mut self, url: &str) {
+        let Some(feed) = self.feeds.all_items().find(|f| f.url() == url) else {
+            self.status = Status::Errored(format!("not subscribed to {}", url));
+            return;
+        };
+        let archived = !self.archived_feeds.contains(url);
+        self.repo.set_archived(feed, archived);
+        if archived {
+            self.archived_feeds.insert(url.to_owned());
+            self.status = Status::Info(format!("archived {}", feed.title()));
+        } else {
+            self.archived_feeds.remove(url);
+            self.status = Status::Info(format!("unarchived {}", feed.title()));
+        }
+        self.apply_feed_filter();
+    }
+
+    /// Sorts the feeds list, favorites first, and hides the feeds the quick
+    /// filter leaves out, keeping the selected feed selected if it's still
+    /// shown. The Favorites tab only ever shows favorites, and archived feeds
+    /// are only shown by their own filter.
+    fn apply_feed_filter(&mut self) {
+        let selected_url = self.selected_feed().map(|f| f.url().to_owned());
+
+        self.feeds.unfilter();
+        util::sort_feeds(&mut self.feeds.items, &self.config);
+        let favorites = &self.favorite_feeds;
+        self.feeds
+            .items
+            .sort_by_key(|feed| !favorites.contains(feed.id()));
+        let sections = self.config.feed_sections();
+        self.feed_sections = self
+            .feeds
+            .items
+            .iter()
+            .filter_map(|feed| Some((feed.id().to_owned(), feed_section(sections, feed)?)))
+            .collect();
+        let feed_sections = &self.feed_sections;
+        self.feeds
+            .items
+            .sort_by_key(|feed| feed_sections.get(feed.id()).map(|(key, _)| *key));
+        if self.active_tab == Tab::Favorites {
+            self.feeds.filter(|feed| favorites.contains(feed.id()));
+        }
+        let (failing, archived) = (&self.failing_feeds, &self.archived_feeds);
+        let filter = self.feed_filter;
+        self.feeds.filter(|feed| match filter {
+            Some(FeedFilter::Archived) => archived.contains(feed.url()),
+            _ if archived.contains(feed.url()) => false,
+            Some(FeedFilter::Unread) => feed.unread_count() > 0,
+            Some(FeedFilter::Failing) => failing.contains(feed.url()),
+            Some(FeedFilter::Favorite) => favorites.contains(feed.id()),
+            None => true,
+        });
+
+        let selected =
+            selected_url.and_then(|url| self.feeds.items().iter().position(|f| f.url() == url));
+        if selected.is_none()
+            && self.feeds.state.selected().is_some()
+            && !self.active_tab.lists_labels()
+        {
+            self.items.items.clear();
+            self.items.state.select(None);
+            self.reset_detail_scroll();
+            self.active_view = View::MainList;
+        }
+        self.feeds.state.select(selected);
+        self.feeds_scroll = self
+            .feeds_scroll
+            .content_length(self.feeds.items.len() as u16)
+            .position(selected.unwrap_or_default() as u16);
     }
 
-    fn set_feeds(&mut self, feeds: Vec<Feed>) {
-        self.feeds.items = feeds;
-        // self.items.state.select(None);
-        // self.active_view = ActiveView::Feeds;
+    /// Unsubscribes from the feed at `url`, removing it from the config file
+    /// and its items from the database.
+    fn delete_feed(&mut self, url: &str) {
+        report!(self.config.remove_feed_url(url), "Failed to write config");
+        self.repo.remove_feed_url(url, &self.config);
+
+        // TODO: refactor, this is so bad
+        self.archived_feeds.remove(url);
+        self.feeds.unfilter();
+        self.feeds.items.retain(|u| u.url() != url);
+        self.feeds.state.select(None);
+        self.apply_feed_filter();
+        self.dead_feeds.retain(|dead| dead.url != url);
+        self.items.items.clear();
+        self.reset_items_scroll();
+        self.reset_detail_scroll();
+        self.active_view = View::MainList;
+    }
+
+    /// Reloads the current feed's, tag's or author's items, keeping the
+    /// selected item if it still exists. Cached rows are only rebuilt if the
+    /// items actually changed.
+    fn refresh_current_items(&mut self) {
+        self.reload_labels();
+        let Some(items) = self.current_items() else {
+            return;
+        };
+
+        if items == self.items.items {
+            return;
+        }
+
+        let selected_id = self.current_item().map(|i| i.id().to_owned());
+        self.items.items = items;
+        let selected =
+            selected_id.and_then(|id| self.items.items().iter().position(|i| i.id() == id));
+        self.items.state.select(selected);
+        self.items_scroll = self
+            .items_scroll
+            .content_length(self.items.items.len() as u16)
+            .position(selected.unwrap_or_default() as u16);
+        self.item_rows.invalidate();
+
+        if selected.is_none() && self.active_view == View::Detail {
+            self.active_view = View::SubList;
+        }
+    }
+
+    /// Selects the `n`th (1-based) feed or item, depending on which list is
+    /// active.
+    fn go_to(&mut self, n: usize) {
+        let index = n - 1;
+        match self.active_view {
+            View::MainList if self.active_tab.lists_labels() => {
+                if index < self.labels.items().len() {
+                    self.select_label(index)
+                } else {
+                    self.status = Status::Errored(format!("no row {}", n))
+                }
+            }
+            View::MainList if index < self.feeds.items().len() => self.select_feed(index),
+            View::SubList | View::Detail if index < self.items.items().len() => {
+                self.reset_detail_scroll();
+                self.active_view = View::SubList;
+                self.items.state.select(Some(index));
+                self.items_scroll = self.items_scroll.position(index as u16);
+            }
+            _ => self.status = Status::Errored(format!("no row {}", n)),
+        }
+    }
+
+    /// Where the user is, if a feed, tag or author is selected.
+    fn here(&self) -> Option<Location> {
+        let list = match self.active_tab {
+            Tab::Tags | Tab::Authors => self.current_label()?.name.clone(),
+            _ => self.selected_feed()?.id().to_owned(),
+        };
+        Some(Location {
+            tab: self.active_tab,
+            list,
+            item: self.current_item().map(|item| item.id().to_owned()),
+            view: self.active_view,
+        })
+    }
+
+    /// Keeps track of where the user goes, for `Ctrl-O` to go back to.
+    pub fn note_location(&mut self) {
+        let here = self.here();
+        self.jumps.note(here);
+    }
+
+    pub fn jump_back(&mut self) {
+        let here = self.here();
+        match self.jumps.go_back(here) {
+            Some(location) => self.visit(&location),
+            None => self.status = Status::Errored("no older place to jump to".into()),
+        }
+    }
+
+    pub fn jump_forward(&mut self) {
+        let here = self.here();
+        match self.jumps.go_forward(here) {
+            Some(location) => self.visit(&location),
+            None => self.status = Status::Errored("no newer place to jump to".into()),
+        }
+    }
+
+    /// Sets or jumps to the mark named by `letter`, once it's typed after `m`
+    /// or `'`.
+    pub fn use_mark(&mut self, action: MarkAction, letter: char) {
+        if !letter.is_ascii_alphabetic() {
+            self.status = Status::Errored(format!("marks are letters, not {}", letter));
+            return;
+        }
+        match action {
+            MarkAction::Set => match self.here() {
+                Some(here) => {
+                    self.jumps.marks.insert(letter, here);
+                    self.status = Status::Info(format!("mark {} set", letter));
+                }
+                None => self.status = Status::Errored("nothing selected to mark".into()),
+            },
+            MarkAction::Jump => match self.jumps.marks.get(&letter).cloned() {
+                Some(location) => {
+                    if let Some(here) = self.here() {
+                        self.jumps.push(here);
+                    }
+                    self.visit(&location);
+                }
+                None => self.status = Status::Errored(format!("mark {} is not set", letter)),
+            },
+        }
+    }
+
+    /// Goes back to a place the user has been, as far as it's still there.
+    fn visit(&mut self, location: &Location) {
+        self.switch_tab(location.tab);
+        if location.tab.lists_labels() {
+            match self
+                .labels
+                .items()
+                .iter()
+                .position(|label| label.name == location.list)
+            {
+                Some(index) => self.select_label(index),
+                None => {
+                    self.status = Status::Errored(format!("{} has no items now", location.list));
+                    return;
+                }
+            }
+        } else {
+            // A feed hidden by a filter is shown among all feeds.
+            let listed = |app: &Self| {
+                app.feeds
+                    .items()
+                    .iter()
+                    .position(|feed| feed.id() == location.list)
+            };
+            if listed(self).is_none() && self.feed_filter.is_some() {
+                self.feed_filter = None;
+                self.apply_feed_filter();
+            }
+            match listed(self) {
+                Some(index) => self.show_feed(index),
+                None => {
+                    self.status = Status::Errored("that feed is no longer listed".into());
+                    return;
+                }
+            }
+        }
+
+        let item = location
+            .item
+            .as_ref()
+            .and_then(|id| self.items.items().iter().position(|item| item.id() == id));
+        if let Some(index) = item {
+            self.items.state.select(Some(index));
+            self.items_scroll = self.items_scroll.position(index as u16);
+        }
+        self.active_view = match (location.view, item) {
+            (View::Detail, None) => View::SubList,
+            (view, _) => view,
+        };
+        self.jumps.last = self.here();
     }
 
     fn reset_items_scroll(&mut self) {
         self.items.state.select(None);
+        self.item_rows.reset_window();
         self.items_scroll = self.items_scroll.position(0);
     }
 
     fn reset_detail_scroll(&mut self) {
+        self.pages.clear();
         self.detail_scroll_index = 0;
         self.detail_scroll = self.detail_scroll.position(0);
     }
 
-    fn open_link(link: &str) -> Option<Child> {
-        let null = Stdio::null();
-        if cfg!(target_os = "windows") {
-            Command::new("rundll32")
-                .args(["url.dll,FileProtocolHandler", link])
-                .stdout(null)
-                .spawn()
-                .ok()
-        } else if cfg!(target_os = "macos") {
-            Command::new("open").arg(link).stdout(null).spawn().ok()
-        } else if cfg!(target_os = "linux") {
-            Command::new("xdg-open").arg(link).stdout(null).spawn().ok()
+    /// Exports the event in the current item to `khal`, or to an .ics file at
+    /// a path, which is named after the item in the current directory if it's
+    /// not given, or inside the directory it names.
+    fn export_event(&mut self, to: Option<&str>) {
+        let Some(item) = self.current_item() else {
+            self.status = Status::Errored("no item selected".into());
+            return;
+        };
+        let Some(event) = item.event() else {
+            self.status = Status::Errored("no event in this item".into());
+            return;
+        };
+
+        let file_name = ics_file_name(item.title().unwrap_or_default());
+        let to = match to {
+            Some("khal") => EventExport::Khal,
+            Some(path) if PathBuf::from(path).is_dir() => {
+                EventExport::File(PathBuf::from(path).join(file_name))
+            }
+            Some(path) => EventExport::File(PathBuf::from(path)),
+            None => EventExport::File(PathBuf::from(file_name)),
+        };
+        self.repo.export_event(event, to, &self.config);
+    }
+
+    /// Reads the current page or item aloud with the speech command, or stops
+    /// if it's already being read.
+    fn speak(&mut self) {
+        if self.stop_speaking() {
+            return;
+        }
+
+        let text = match (self.current_page(), self.current_item()) {
+            (Some(page), _) => format!("{}\n\n{}", page.title().unwrap_or_default(), page.text()),
+            (None, Some(item)) => format!(
+                "{}\n\n{}",
+                item.title().unwrap_or_default(),
+                item.description().unwrap_or_default()
+            ),
+            (None, None) => {
+                self.status = Status::Errored("no item selected".into());
+                return;
+            }
+        };
+
+        let command = self.config.speech_command();
+        let mut shell = if cfg!(target_os = "windows") {
+            let mut shell = Command::new("cmd");
+            shell.args(["/C", command]);
+            shell
         } else {
+            let mut shell = Command::new("sh");
+            shell.args(["-c", command]);
+            shell
+        };
+        let child = shell
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match child {
+            Ok(mut child) => {
+                // Written from another thread, since the command may only read
+                // as fast as it speaks.
+                if let Some(mut stdin) = child.stdin.take() {
+                    std::thread::spawn(move || {
+                        let _ = stdin.write_all(text.as_bytes());
+                    });
+                }
+                self.speech = Some(child);
+            }
+            Err(err) => {
+                log::warn!("Failed to run speech command '{}': {}", command, err);
+                self.status = Status::Errored(format!("could not run {}", command));
+            }
+        }
+    }
+
+    /// Stops reading aloud, returning whether anything was still being read.
+    fn stop_speaking(&mut self) -> bool {
+        let Some(mut speech) = self.speech.take() else {
+            return false;
+        };
+        let speaking = matches!(speech.try_wait(), Ok(None));
+        if speaking {
+            let _ = speech.kill();
+        }
+        let _ = speech.wait();
+        speaking
+    }
+
+    fn open_link(&self, link: &str) -> Option<Child> {
+        let link = &util::clean_url(link, self.config.tracking_params());
+        if self.config.internal_browser() {
+            self.repo.fetch_page(link, &self.config);
             None
+        } else {
+            self.open_externally(link)
+        }
+    }
+
+    fn open_externally(&self, link: &str) -> Option<Child> {
+        match self.config.browser() {
+            Some(browser) => Self::open_with(browser, link),
+            None => Self::open_with_system(link),
+        }
+    }
+
+    fn open_link_in_background(&self, link: &str) -> Option<Child> {
+        let link = &util::clean_url(link, self.config.tracking_params());
+        match self.config.background_browser() {
+            Some(browser) => Self::open_with(browser, link),
+            None if cfg!(target_os = "macos") => Command::new("open")
+                .args(["-g", link])
+                .stdout(Stdio::null())
+                .spawn()
+                .ok(),
+            None => self.open_externally(link),
         }
     }
+
+    /// Runs a browser command line, substituting `{url}` or `%s` with the link,
+    /// or appending the link if there is no placeholder.
+    fn open_with(browser: &str, link: &str) -> Option<Child> {
+        let mut substituted = false;
+        let mut args = browser
+            .split_whitespace()
+            .map(|arg| {
+                if arg.contains("{url}") || arg.contains("%s") {
+                    substituted = true;
+                    arg.replace("{url}", link).replace("%s", link)
+                } else {
+                    arg.to_owned()
+                }
+            })
+            .collect::<Vec<_>>();
+        if !substituted {
+            args.push(link.to_owned());
+        }
+
+        let (program, args) = args.split_first()?;
+        Command::new(program)
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()
+    }
+
+    fn open_with_system(link: impl AsRef<OsStr>) -> Option<Child> {
+        util::system_open_command(link.as_ref())?
+            .stdout(Stdio::null())
+            .spawn()
+            .ok()
+    }
 }
 
-#[derive(Debug, PartialEq)]
+/// Sums up a refresh, like "12 new items, 3 updated (2 feeds failed)".
+fn refresh_summary(new_items: usize, updated: usize, failed: usize) -> String {
+    let mut message = match new_items {
+        0 => "no new items".to_owned(),
+        1 => "1 new item".to_owned(),
+        count => format!("{} new items", count),
+    };
+    if updated > 0 {
+        message.push_str(&format!(", {} updated", updated));
+    }
+    match failed {
+        0 => {}
+        1 => message.push_str(" (1 feed failed)"),
+        count => message.push_str(&format!(" ({} feeds failed)", count)),
+    }
+    message
+}
+
+/// Names an .ics file after the title of the item its event is from, like
+/// "rust-meetup-june.ics".
+fn ics_file_name(title: &str) -> String {
+    let slug = title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "event.ics".to_owned()
+    } else {
+        format!("{}.ics", slug)
+    }
+}
+
+/// A post being written in the compose overlay before it's sent.
+#[derive(Debug)]
+pub struct ComposeState {
+    /// The share target it's posted to, by index.
+    pub target: usize,
+    pub text: String,
+    /// Where text is typed, in chars from the start.
+    pub cursor: usize,
+}
+
+impl ComposeState {
+    /// How long the post is, in chars.
+    pub fn char_count(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn byte_index(&self) -> usize {
+        self.text
+            .char_indices()
+            .nth(self.cursor)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let i = self.byte_index();
+        self.text.insert(i, c);
+        self.cursor += 1;
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let i = self.byte_index();
+            self.text.remove(i);
+        }
+    }
+
+    pub fn move_cursor(&mut self, forward: bool) {
+        self.cursor = if forward {
+            (self.cursor + 1).min(self.char_count())
+        } else {
+            self.cursor.saturating_sub(1)
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum View {
     MainList,
     SubList,
     Detail,
 }
 
-#[derive(Debug, PartialEq)]
+/// A row of the feeds list: a feed, by its index in the list, or the header
+/// of a section of them, which can't be selected.
+#[derive(Debug, Clone)]
+pub enum FeedRow<'a> {
+    Header(String),
+    Feed(usize, &'a Feed),
+}
+
+/// The section a feed goes in, as a key the sections sort in order by and
+/// the header shown above them.
+fn feed_section(sections: FeedSections, feed: &Feed) -> Option<(u32, String)> {
+    match sections {
+        FeedSections::None => None,
+        FeedSections::Unread if feed.unread_count() > 0 => Some((0, "Unread".to_owned())),
+        FeedSections::Unread => Some((1, "Everything else".to_owned())),
+        FeedSections::Alphabet => {
+            let initial = feed
+                .title()
+                .chars()
+                .find(|c| c.is_alphanumeric())
+                .filter(|c| c.is_alphabetic())
+                .and_then(|c| c.to_uppercase().next());
+            Some(match initial {
+                Some(c) => (c as u32, c.to_string()),
+                None => (0, "#".to_owned()),
+            })
+        }
+    }
+}
+
+/// A quick filter over the feeds list, each turned on and off with a key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeedFilter {
+    Unread,
+    Failing,
+    Favorite,
+    Archived,
+}
+
+impl fmt::Display for FeedFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unread => write!(f, "unread"),
+            Self::Failing => write!(f, "failing"),
+            Self::Favorite => write!(f, "favorites"),
+            Self::Archived => write!(f, "archived"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Tab {
     Browse,
     Favorites,
     Tags,
+    Authors,
 }
 
 impl ToString for Tab {
@@ -620,6 +3431,7 @@ impl ToString for Tab {
             Self::Browse => "Browse".into(),
             Self::Favorites => "Favorites".into(),
             Self::Tags => "Tags".into(),
+            Self::Authors => "Authors".into(),
         }
     }
 }
@@ -630,8 +3442,14 @@ impl Tab {
             Self::Browse => 0,
             Self::Favorites => 1,
             Self::Tags => 2,
+            Self::Authors => 3,
         }
     }
+
+    /// Whether the tab lists tags or authors in place of feeds.
+    pub fn lists_labels(&self) -> bool {
+        matches!(self, Self::Tags | Self::Authors)
+    }
 }
 
 impl From<usize> for Tab {
@@ -639,6 +3457,7 @@ impl From<usize> for Tab {
         match value {
             1 => Tab::Favorites,
             2 => Tab::Tags,
+            3 => Tab::Authors,
             _ => Tab::Browse,
         }
     }
@@ -648,6 +3467,8 @@ impl From<usize> for Tab {
 pub struct StatefulList<T> {
     pub state: ListState,
     pub items: Vec<T>,
+    /// Items hidden by a filter, put back once it's lifted.
+    hidden: Vec<T>,
 }
 
 impl<T> StatefulList<T> {
@@ -655,9 +3476,27 @@ impl<T> StatefulList<T> {
         StatefulList {
             state: ListState::default(),
             items,
+            hidden: vec![],
         }
     }
 
+    /// Hides the items `keep` is false for, along with those already hidden.
+    fn filter(&mut self, keep: impl Fn(&T) -> bool) {
+        let (shown, hidden): (Vec<_>, Vec<_>) = self.items.drain(..).partition(|item| keep(item));
+        self.items = shown;
+        self.hidden.extend(hidden);
+    }
+
+    /// Puts back the items hidden by a filter, after those shown.
+    fn unfilter(&mut self) {
+        self.items.append(&mut self.hidden);
+    }
+
+    /// Every item, whether shown or hidden by a filter.
+    fn all_items(&self) -> impl Iterator<Item = &T> {
+        self.items.iter().chain(&self.hidden)
+    }
+
     fn next(&mut self) {
         if self.items.len() == 0 {
             return;
@@ -704,6 +3543,169 @@ impl<T> StatefulList<T> {
     }
 }
 
+/// Formatted list rows for one feed, rebuilt only when the feed or the width
+/// they're laid out in changes, along with the window of rows currently visible.
+#[derive(Debug, Default)]
+pub struct RowCache {
+    feed_id: Option<String>,
+    width: u16,
+    stale: bool,
+    rows: Vec<Text<'static>>,
+    offset: usize,
+}
+
+impl RowCache {
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    pub fn reset_window(&mut self) {
+        self.offset = 0;
+    }
+
+    pub fn rows<F>(&mut self, feed_id: &str, width: u16, build: F) -> &[Text<'static>]
+    where
+        F: FnOnce() -> Vec<Text<'static>>,
+    {
+        if self.stale || self.feed_id.as_deref() != Some(feed_id) || self.width != width {
+            self.rows = build();
+            self.feed_id = Some(feed_id.to_owned());
+            self.width = width;
+            self.stale = false;
+        }
+        &self.rows
+    }
+
+    /// Returns the range of rows to display in a viewport of `height` rows,
+    /// scrolling only as far as needed to keep `selected` in view.
+    pub fn window(&mut self, len: usize, selected: Option<usize>, height: usize) -> Range<usize> {
+        if let Some(selected) = selected {
+            if selected < self.offset {
+                self.offset = selected;
+            } else if selected >= self.offset + height {
+                self.offset = selected + 1 - height.max(1);
+            }
+        }
+        self.offset = self.offset.min(len.saturating_sub(height));
+        self.offset..(self.offset + height).min(len)
+    }
+}
+
+/// How many places `Ctrl-O` can go back through.
+const MAX_JUMPS: usize = 100;
+
+/// A place in the app to come back to: a feed, or a tag or author, in a tab,
+/// and the item selected in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    tab: Tab,
+    /// The id of the feed, or the name of the tag or author.
+    list: String,
+    item: Option<String>,
+    view: View,
+}
+
+impl Location {
+    fn is_in(&self, other: &Location) -> bool {
+        self.tab == other.tab && self.list == other.list
+    }
+}
+
+/// What the key after `m` or `'` names a mark for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkAction {
+    Set,
+    Jump,
+}
+
+/// The lists read before the current one, to go back and forth through like
+/// Vim's jump list, and the places marked to jump back to by letter.
+#[derive(Debug, Default)]
+pub struct Jumps {
+    back: Vec<Location>,
+    forward: Vec<Location>,
+    /// Where the user was last seen, which becomes a place to go back to
+    /// once they open another list.
+    last: Option<Location>,
+    marks: HashMap<char, Location>,
+    /// Set by `m` or `'` until the letter of the mark is typed.
+    pub pending: Option<MarkAction>,
+}
+
+impl Jumps {
+    /// Notes where the user is, remembering where they were if they've since
+    /// opened another list. Moving through the feeds list doesn't count until
+    /// a feed is opened.
+    fn note(&mut self, here: Option<Location>) {
+        let Some(here) = here.filter(|here| here.view != View::MainList) else {
+            return;
+        };
+        match self.last.take() {
+            Some(last) if !last.is_in(&here) => self.push(last),
+            _ => {}
+        }
+        self.last = Some(here);
+    }
+
+    /// Remembers `location` to go back to, forgetting any places gone back
+    /// from.
+    fn push(&mut self, location: Location) {
+        self.forward.clear();
+        if self.back.last() != Some(&location) {
+            self.back.push(location);
+        }
+        if self.back.len() > MAX_JUMPS {
+            self.back.remove(0);
+        }
+    }
+
+    fn go_back(&mut self, here: Option<Location>) -> Option<Location> {
+        let to = self.back.pop()?;
+        self.forward.extend(here);
+        Some(to)
+    }
+
+    fn go_forward(&mut self, here: Option<Location>) -> Option<Location> {
+        let to = self.forward.pop()?;
+        self.back.extend(here);
+        Some(to)
+    }
+}
+
+/// Tab-completion state of the console, while cycling through candidates.
+#[derive(Debug, Default)]
+pub struct Completion {
+    start: usize,
+    candidates: Vec<String>,
+    index: Option<usize>,
+}
+
+impl Completion {
+    fn is_active(&self) -> bool {
+        !self.candidates.is_empty()
+    }
+}
+
+/// Results of the live search shown while typing a `:search` command.
+#[derive(Debug, Default)]
+pub struct SearchState {
+    pub results: StatefulList<SearchResult>,
+    query: String,
+    deadline: Option<Instant>,
+}
+
+/// A search within the text in the Detail panel, started with `/` there.
+#[derive(Debug, Default)]
+pub struct FindState {
+    pub query: String,
+    /// The match last jumped to, counting from the first.
+    pub current: usize,
+    /// The row each match is on, as of the last render.
+    pub rows: Vec<usize>,
+    /// Whether the next render should scroll to the current match.
+    pub follow: bool,
+}
+
 #[derive(Debug)]
 pub struct InputState {
     pub input: String,
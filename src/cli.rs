@@ -0,0 +1,171 @@
+use crate::app::{CacheAction, CliCommand, StateAction};
+use crate::config::Config;
+use crate::control;
+use crate::feed::Feed;
+use crate::repo::backup;
+use crate::repo::lock::{InstanceLock, LockError};
+use crate::repo::responses::ResponseCache;
+use crate::repo::server;
+use crate::repo::storage::sqlite::SQLiteStorage;
+use crate::repo::ItemState;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Version of the state file format, bumped on incompatible changes.
+const STATE_FILE_VERSION: u32 = 1;
+
+/// The read and starred state of items, as written by `moccasin state export`.
+#[derive(Serialize, Deserialize)]
+struct StateFile {
+    version: u32,
+    items: Vec<ItemState>,
+}
+
+/// Runs a command given on the command line, without starting the app.
+pub async fn run(command: CliCommand, config: Config) -> Result<()> {
+    let config = &config;
+    // A running app would write over whatever is changed under it.
+    let _lock = command.writes().then(|| lock(config)).transpose()?;
+    match command {
+        CliCommand::State { action } => match action {
+            StateAction::Export { path } => export_state(&path, config),
+            StateAction::Import { path } => import_state(&path, config),
+        },
+        CliCommand::Cache { action } => match action {
+            CacheAction::Reparse => reparse_cache(config),
+        },
+        CliCommand::Backup { path } => create_backup(path, config),
+        CliCommand::Restore { path } => restore_backup(&path, config),
+        CliCommand::Serve { addr } => server::serve(config.clone(), addr).await,
+        CliCommand::Ctl { command } => {
+            let answer = control::send(&control::socket_path(config), &command.join(" ")).await?;
+            println!("{}", answer);
+            Ok(())
+        }
+    }
+}
+
+/// Takes the lock on the database and config file, refusing if an app holds it.
+fn lock(config: &Config) -> Result<InstanceLock> {
+    match InstanceLock::acquire(config) {
+        Ok(lock) => Ok(lock),
+        Err(LockError::Held(Some(pid))) => {
+            bail!("moccasin is running as process {}, quit it first", pid)
+        }
+        Err(LockError::Held(None)) => bail!("moccasin is running, quit it first"),
+        Err(LockError::Failed(err)) => Err(err),
+    }
+}
+
+fn create_backup(path: Option<PathBuf>, config: &Config) -> Result<()> {
+    let dir = path.unwrap_or_else(|| backup::default_dir(config));
+    let storage = SQLiteStorage::init(config);
+    backup::create(&storage, config, &dir)?;
+
+    println!("Backed up database and config to {}", dir.display());
+    Ok(())
+}
+
+fn restore_backup(path: &Path, config: &Config) -> Result<()> {
+    if !config.should_cache() {
+        bail!("feeds are not being cached");
+    }
+    if !backup::holds_backup(path) {
+        bail!("{} does not hold a moccasin backup", path.display());
+    }
+
+    // What's restored over is backed up first, in case it's wanted back.
+    let mut storage = SQLiteStorage::init(config);
+    let previous = backup::default_dir(config);
+    backup::create(&storage, config, &previous)?;
+    backup::restore(&mut storage, config, path)?;
+
+    println!(
+        "Restored database and config from {}, keeping the previous ones in {}",
+        path.display(),
+        previous.display()
+    );
+    Ok(())
+}
+
+fn reparse_cache(config: &Config) -> Result<()> {
+    let Some(responses) = ResponseCache::new(config) else {
+        bail!("feeds are not being cached");
+    };
+
+    let mut feeds = vec![];
+    for url in config.feed_urls() {
+        let bridged = config.bridge_url(url);
+        let Some((body, final_url)) = responses.read(bridged.as_deref().unwrap_or(url)) else {
+            eprintln!("No cached response for {}", url);
+            continue;
+        };
+        match Feed::read_from(&body[..], final_url) {
+            Ok(mut feed) => {
+                if bridged.is_some() {
+                    feed.set_url(url);
+                }
+                feeds.push(feed);
+            }
+            Err(err) => eprintln!("Could not parse cached response for {}: {}", url, err),
+        }
+    }
+
+    let mut storage = SQLiteStorage::init(config);
+    storage
+        .write_feeds(&feeds)
+        .map_err(|_| anyhow!("could not write feeds to the database"))?;
+
+    println!(
+        "Reparsed {} of {} feeds from cache",
+        feeds.len(),
+        config.feed_urls().len()
+    );
+    Ok(())
+}
+
+fn export_state(path: &Path, config: &Config) -> Result<()> {
+    let storage = SQLiteStorage::init(config);
+    let items = storage
+        .read_item_states()
+        .map_err(|_| anyhow!("could not read item state from the database"))?;
+    let count = items.len();
+
+    let file = StateFile {
+        version: STATE_FILE_VERSION,
+        items,
+    };
+    let json = serde_json::to_string_pretty(&file)?;
+    fs::write(path, json).with_context(|| format!("could not write {}", path.display()))?;
+
+    println!("Exported state of {} items to {}", count, path.display());
+    Ok(())
+}
+
+fn import_state(path: &Path, config: &Config) -> Result<()> {
+    let json =
+        fs::read_to_string(path).with_context(|| format!("could not read {}", path.display()))?;
+    let file: StateFile = serde_json::from_str(&json)
+        .with_context(|| format!("{} is not a moccasin state file", path.display()))?;
+    if file.version > STATE_FILE_VERSION {
+        bail!(
+            "{} was written by a newer moccasin (state file version {})",
+            path.display(),
+            file.version
+        );
+    }
+
+    let mut storage = SQLiteStorage::init(config);
+    storage
+        .merge_item_states(&file.items)
+        .map_err(|_| anyhow!("could not write item state to the database"))?;
+
+    println!(
+        "Imported state of {} items from {}",
+        file.items.len(),
+        path.display()
+    );
+    Ok(())
+}
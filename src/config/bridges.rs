@@ -0,0 +1,81 @@
+use reqwest::Url;
+use std::fmt;
+use toml::Value;
+
+#[derive(Debug)]
+pub struct ParseBridgeError(String);
+
+impl fmt::Display for ParseBridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid bridge: {}", self.0)
+    }
+}
+
+/// Fetches pages of some site through a feed generator, configured under
+/// `[bridges]` as `"<host>" = "<target>"`. The target is either the address of
+/// an RSS-Bridge instance, which detects the right bridge for a page itself, or
+/// a URL template in which `{url}` is the page's URL and `{path}` its path.
+#[derive(Debug, Clone)]
+pub struct Bridge {
+    host: String,
+    target: String,
+}
+
+impl Bridge {
+    /// Whether pages at `url` are fetched through this bridge, including any
+    /// subdomain of its host.
+    pub fn matches(&self, url: &Url) -> bool {
+        url.host_str().map_or(false, |host| {
+            let host = host.trim_start_matches("www.");
+            host == self.host || host.ends_with(&format!(".{}", self.host))
+        })
+    }
+
+    /// The feed URL for the page at `url`.
+    pub fn feed_url(&self, url: &Url) -> Option<String> {
+        if self.target.contains("{url}") || self.target.contains("{path}") {
+            return Some(
+                self.target
+                    .replace("{url}", &encode(url.as_str()))
+                    .replace("{path}", url.path().trim_end_matches('/')),
+            );
+        }
+
+        // RSS-Bridge can only be read here as RSS, not its default Atom.
+        let mut feed_url = Url::parse(&self.target).ok()?;
+        feed_url
+            .query_pairs_mut()
+            .append_pair("action", "detect")
+            .append_pair("format", "Mrss")
+            .append_pair("url", url.as_str());
+        Some(feed_url.into())
+    }
+}
+
+/// Percent-encodes a string for use as a query parameter value.
+fn encode(s: &str) -> String {
+    let mut url = Url::parse("http://localhost").expect("parse placeholder url");
+    url.query_pairs_mut().append_pair("", s);
+    url.query().unwrap_or_default()[1..].to_owned()
+}
+
+impl TryFrom<(&String, &Value)> for Bridge {
+    type Error = ParseBridgeError;
+
+    fn try_from((host, target): (&String, &Value)) -> Result<Self, Self::Error> {
+        let Value::String(target) = target else {
+            return Err(ParseBridgeError(format!("{} must be a string", host)));
+        };
+        if !target.contains("{url}")
+            && !target.contains("{path}")
+            && Url::parse(target).is_err()
+        {
+            return Err(ParseBridgeError(format!("'{}' is not a URL", target)));
+        }
+
+        Ok(Self {
+            host: host.trim_start_matches("www.").to_owned(),
+            target: target.to_owned(),
+        })
+    }
+}
@@ -0,0 +1,148 @@
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use tui::style::Color;
+
+/// Names of the color modes, from fewest colors to most.
+pub const COLOR_MODES: [&str; 4] = ["never", "16", "256", "truecolor"];
+
+/// The 16 ANSI colors, with their usual xterm values to measure against.
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// The levels of each channel in the 6×6×6 cube of the 256 color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// How many colors the terminal can show, which theme colors are brought down
+/// to the nearest of.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ColorMode {
+    /// No colors at all, only bold, dim and reversed text.
+    Never,
+    Ansi16,
+    Ansi256,
+    #[default]
+    TrueColor,
+}
+
+impl ColorMode {
+    /// No colors if `NO_COLOR` is set to anything, or all of them otherwise.
+    pub fn from_env() -> Self {
+        match env::var_os("NO_COLOR") {
+            Some(value) if !value.is_empty() => Self::Never,
+            _ => Self::TrueColor,
+        }
+    }
+
+    /// The closest color to `color` this mode can show.
+    pub fn downgrade(&self, color: Color) -> Color {
+        match (self, color) {
+            (Self::TrueColor, _) | (_, Color::Reset) => color,
+            (Self::Never, _) => Color::Reset,
+            (Self::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_indexed((r, g, b))),
+            (Self::Ansi256, _) => color,
+            (Self::Ansi16, Color::Rgb(r, g, b)) => nearest_ansi((r, g, b)),
+            (Self::Ansi16, Color::Indexed(i)) => match ANSI_COLORS.get(i as usize) {
+                Some((ansi, _)) => *ansi,
+                None => nearest_ansi(indexed_rgb(i)),
+            },
+            (Self::Ansi16, _) => color,
+        }
+    }
+}
+
+impl FromStr for ColorMode {
+    type Err = ParseColorModeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "never" => Ok(Self::Never),
+            "16" => Ok(Self::Ansi16),
+            "256" => Ok(Self::Ansi256),
+            "truecolor" => Ok(Self::TrueColor),
+            other => Err(ParseColorModeError(other.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseColorModeError(String);
+
+impl fmt::Display for ParseColorModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown color mode '{}', expected one of {}",
+            self.0,
+            COLOR_MODES.join(", ")
+        )
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+fn nearest_ansi(rgb: (u8, u8, u8)) -> Color {
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .map(|(color, _)| *color)
+        .expect("ANSI colors are not empty")
+}
+
+/// The index of the closest color in the cube or gray ramp of the 256 color
+/// palette, leaving out the first 16, whose values vary between terminals.
+fn nearest_indexed(rgb: (u8, u8, u8)) -> u8 {
+    let level = |c: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - c as i32).abs())
+            .expect("cube levels are not empty") as u8
+    };
+    let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray = 232 + ((average.saturating_sub(3) / 10).min(23) as u8);
+
+    if distance(rgb, indexed_rgb(gray)) < distance(rgb, indexed_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// The usual value of a color of the 256 color palette.
+fn indexed_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI_COLORS[i as usize].1,
+        16..=231 => {
+            let i = i - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (i - 232);
+            (level, level, level)
+        }
+    }
+}
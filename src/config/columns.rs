@@ -0,0 +1,163 @@
+use std::fmt;
+use std::str::FromStr;
+
+const DEFAULT_ITEM_COLUMNS: &'static str = "title";
+
+/// Narrowest the title may get before other columns are dropped to make room.
+const MIN_TITLE_WIDTH: u16 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnKind {
+    Unread,
+    Date,
+    Author,
+    Title,
+    Feed,
+    /// The item's first category, or failing that its first tag, as a badge.
+    Tag,
+}
+
+impl ColumnKind {
+    fn default_width(&self) -> u16 {
+        match self {
+            Self::Unread => 1,
+            Self::Date | Self::Tag => 10,
+            Self::Author | Self::Feed => 16,
+            Self::Title => MIN_TITLE_WIDTH,
+        }
+    }
+
+    /// Columns are dropped from narrow panes lowest priority first.
+    fn priority(&self) -> u8 {
+        match self {
+            Self::Title => 5,
+            Self::Unread => 4,
+            Self::Date => 3,
+            Self::Feed => 2,
+            Self::Tag => 1,
+            Self::Author => 0,
+        }
+    }
+}
+
+impl FromStr for ColumnKind {
+    type Err = ParseColumnsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unread" => Ok(Self::Unread),
+            "date" => Ok(Self::Date),
+            "author" => Ok(Self::Author),
+            "title" => Ok(Self::Title),
+            "feed" => Ok(Self::Feed),
+            "tag" => Ok(Self::Tag),
+            other => Err(ParseColumnsError(format!("unknown column '{}'", other))),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseColumnsError(String);
+
+impl fmt::Display for ParseColumnsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A column of the items list, and how wide it is. The title's width is the
+/// least it may have, since it takes up whatever room the others leave.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Column {
+    pub kind: ColumnKind,
+    pub width: u16,
+}
+
+/// The columns of the items list in order, such as `"unread date:10 title"`,
+/// each a name optionally followed by `:` and a width.
+#[derive(Debug, Clone)]
+pub struct ItemColumns {
+    columns: Vec<Column>,
+}
+
+impl ItemColumns {
+    /// The columns that fit in `width` cells, each separated by a space,
+    /// dropping the least important until they do. The title, or failing
+    /// that the last column, is stretched to fill the rest. Columns not
+    /// in `shown` are left out entirely.
+    pub fn layout(&self, width: u16, shown: impl Fn(ColumnKind) -> bool) -> Vec<Column> {
+        let mut columns: Vec<_> = self
+            .columns
+            .iter()
+            .filter(|column| shown(column.kind))
+            .copied()
+            .collect();
+
+        let needed = |columns: &[Column]| -> u16 {
+            columns
+                .iter()
+                .fold(0u16, |sum, column| sum.saturating_add(column.width))
+                .saturating_add(columns.len().saturating_sub(1) as u16)
+        };
+        while columns.len() > 1 && needed(&columns) > width {
+            let (drop, _) = columns
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, column)| column.kind.priority())
+                .expect("columns are not empty");
+            columns.remove(drop);
+        }
+
+        let spare = width.saturating_sub(needed(&columns));
+        let stretch = columns
+            .iter()
+            .position(|column| column.kind == ColumnKind::Title)
+            .or(columns.len().checked_sub(1));
+        if let Some(i) = stretch {
+            columns[i].width = columns[i].width.saturating_add(spare).min(width);
+        }
+        columns
+    }
+}
+
+impl Default for ItemColumns {
+    fn default() -> Self {
+        Self::from_str(DEFAULT_ITEM_COLUMNS).expect("parse default item columns")
+    }
+}
+
+impl FromStr for ItemColumns {
+    type Err = ParseColumnsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let columns = s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|column| !column.is_empty())
+            .map(|column| {
+                let (name, width) = match column.split_once(':') {
+                    Some((name, width)) => (name, Some(width)),
+                    None => (column, None),
+                };
+                let kind = ColumnKind::from_str(name)?;
+                let width = match width {
+                    Some(width) => {
+                        width
+                            .parse::<u16>()
+                            .ok()
+                            .filter(|w| *w > 0)
+                            .ok_or_else(|| {
+                                ParseColumnsError(format!("invalid width '{}' for {}", width, name))
+                            })?
+                    }
+                    None => kind.default_width(),
+                };
+                Ok(Column { kind, width })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if columns.is_empty() {
+            return Err(ParseColumnsError("no columns given".to_owned()));
+        }
+        Ok(Self { columns })
+    }
+}
@@ -1,32 +1,116 @@
 use crate::app::Args;
-use anyhow::Result;
+use crate::feed::Item;
+use crate::logging;
+use crate::util;
+use anyhow::{bail, Context, Result};
 use directories::ProjectDirs;
-use std::collections::HashSet;
-use std::fs::OpenOptions;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 use std::{fs, fs::File};
 use toml::{Table, Value};
 use toml_edit::{value, Array, Document};
 
+mod bridges;
+mod color;
+mod columns;
+mod rules;
+mod share;
+mod status;
+mod summarize;
+mod tags;
+mod template;
 mod theme;
+mod translate;
+mod validate;
+
+pub use bridges::Bridge;
+pub use color::ColorMode;
+pub use columns::{Column, ColumnKind, ItemColumns};
+pub use rules::FeedRule;
+pub use share::{ShareKind, ShareTarget};
+pub use status::{StatusBar, StatusSegment};
+pub use summarize::Summarizer;
+pub use tags::TagRule;
+pub use template::RowTemplate;
+pub use theme::{Theme, BUILTIN_THEMES};
+pub use translate::Translator;
+pub use validate::ConfigProblem;
 
 const DEFAULT_CONFIG_FILE: &'static str = "moccasin.toml";
 const DEFAULT_DB_FILE: &'static str = "moccasin.db";
+const DEFAULT_LOG_FILE: &'static str = "moccasin.log";
 const DEFAULT_REFRESH_INTERVAL: u64 = 300;
 const DEFAULT_REFRESH_TIMEOUT: u64 = 5;
+const DEFAULT_MONITOR_INTERVAL: u64 = 60;
+const DEFAULT_MAX_FEED_SIZE: u64 = 20;
+const PROFILES_DIR: &'static str = "profiles";
+/// Query parameters taken out of links unless `tracking_params` says otherwise.
+const DEFAULT_TRACKING_PARAMS: [&str; 14] = [
+    "utm_*", "fbclid", "gclid", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid",
+    "_hsenc", "_hsmi", "mkt_tok", "ref_src", "oly_*",
+];
+
+/// The name by which the profile used without `--profile` is switched back to.
+pub const DEFAULT_PROFILE: &'static str = "default";
 
 #[derive(Debug, Default, Clone)]
 pub struct Config {
+    profile: Option<String>,
     file_path: PathBuf,
     dir_path: PathBuf,
+    data_dir_path: PathBuf,
     feed_urls: HashSet<String>,
+    feed_rules: HashMap<String, FeedRule>,
+    bridges: Vec<Bridge>,
+    tag_rules: Vec<TagRule>,
+    ca_file: Option<PathBuf>,
+    connect_timeout: Option<u64>,
+    fever_api_key: Option<String>,
+    server_token: Option<String>,
+    translator: Option<Translator>,
+    summarizer: Option<Summarizer>,
+    share_targets: Vec<ShareTarget>,
     sort_order: SortOrder,
+    layout: PaneLayout,
+    problems: Vec<ConfigProblem>,
     cache_control: CacheControl,
     refresh_interval: u64,
     refresh_timeout: u64,
+    monitor_interval: u64,
+    max_feed_size: u64,
+    archive_pages: usize,
+    mark_read_after: u64,
+    mark_read_on_scroll: bool,
+    max_content_width: u16,
+    text_align: TextAlign,
+    render_mode: RenderMode,
+    hyphenate: bool,
     theme: theme::Theme,
+    color: ColorMode,
+    status_bar: StatusBar,
+    item_columns: ItemColumns,
+    item_template: Option<RowTemplate>,
+    item_preview: bool,
+    feed_template: RowTemplate,
+    feed_sections: FeedSections,
+    terminal_title: bool,
+    notify_refresh: bool,
+    browser: Option<String>,
+    background_browser: Option<String>,
+    internal_browser: bool,
+    tracking_params: Vec<String>,
+    languages: Vec<String>,
+    speech_command: Option<String>,
+    feed_icons: bool,
+    screen_reader: bool,
+    read_only: bool,
+    dashboard: Option<u64>,
+    ascii: bool,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -57,6 +141,151 @@ impl From<bool> for CacheControl {
     }
 }
 
+/// How the Browse tab arranges its panels.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum PaneLayout {
+    /// Feeds, items and detail side by side.
+    #[default]
+    ThreePane,
+    /// Items and detail only, with feeds chosen from a full-width list or `:feed`.
+    TwoPane,
+    /// Feeds beside the items list, with detail below the items.
+    Horizontal,
+}
+
+pub const PANE_LAYOUTS: [&str; 3] = ["three-pane", "two-pane", "horizontal"];
+
+#[derive(Debug)]
+pub struct PaneLayoutError(String);
+
+impl fmt::Display for PaneLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown layout '{}'", self.0)
+    }
+}
+
+impl FromStr for PaneLayout {
+    type Err = PaneLayoutError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "three-pane" => Ok(PaneLayout::ThreePane),
+            "two-pane" => Ok(PaneLayout::TwoPane),
+            "horizontal" => Ok(PaneLayout::Horizontal),
+            other => Err(PaneLayoutError(other.to_owned())),
+        }
+    }
+}
+
+/// How the lines of paragraphs in the Detail panel are set.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TextAlign {
+    /// Lines end where their last word does.
+    #[default]
+    Ragged,
+    /// Lines are spaced out to the full width, but for the last of each
+    /// paragraph.
+    Justified,
+}
+
+#[derive(Debug)]
+pub struct TextAlignError(String);
+
+impl fmt::Display for TextAlignError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown text alignment '{}', expected ragged or justified",
+            self.0
+        )
+    }
+}
+
+impl FromStr for TextAlign {
+    type Err = TextAlignError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "ragged" => Ok(TextAlign::Ragged),
+            "justified" => Ok(TextAlign::Justified),
+            other => Err(TextAlignError(other.to_owned())),
+        }
+    }
+}
+
+/// How the feeds list is divided into sections, each under a header.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum FeedSections {
+    #[default]
+    None,
+    /// Feeds with unread items, then everything else.
+    Unread,
+    /// A section for each letter feeds' titles start with.
+    Alphabet,
+}
+
+#[derive(Debug)]
+pub struct FeedSectionsError(String);
+
+impl fmt::Display for FeedSectionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown feed sections '{}', expected none, unread or alphabet",
+            self.0
+        )
+    }
+}
+
+impl FromStr for FeedSections {
+    type Err = FeedSectionsError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(FeedSections::None),
+            "unread" => Ok(FeedSections::Unread),
+            "alphabet" => Ok(FeedSections::Alphabet),
+            other => Err(FeedSectionsError(other.to_owned())),
+        }
+    }
+}
+
+/// How much the interface draws, and how often.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum RenderMode {
+    #[default]
+    Full,
+    /// As little as will do over slow SSH or mosh connections: no scrollbars
+    /// or Unicode borders, no redraws just for the passage of time, and one
+    /// redraw for a burst of updates.
+    Minimal,
+}
+
+#[derive(Debug)]
+pub struct RenderModeError(String);
+
+impl fmt::Display for RenderModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown render mode '{}', expected full or minimal",
+            self.0
+        )
+    }
+}
+
+impl FromStr for RenderMode {
+    type Err = RenderModeError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(RenderMode::Full),
+            "minimal" => Ok(RenderMode::Minimal),
+            other => Err(RenderModeError(other.to_owned())),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct SortOrderError;
 
@@ -69,6 +298,7 @@ impl FromStr for SortOrder {
             "z-a" => Ok(SortOrder::Za),
             "newest" => Ok(SortOrder::Newest),
             "oldest" => Ok(SortOrder::Oldest),
+            "unread" => Ok(SortOrder::Unread),
             "custom" => Ok(SortOrder::Custom),
             _ => Ok(SortOrder::Az),
         }
@@ -77,50 +307,94 @@ impl FromStr for SortOrder {
 
 impl Config {
     pub fn new(args: Args) -> Result<Self> {
-        let (dir_path, file_path): (PathBuf, PathBuf) = if let Some(path) = &args.config {
-            let file_path = Path::new(&path);
-            if !file_path.exists() {
-                panic!(
-                    "no config file found at '{}'",
-                    file_path.to_owned().to_str().unwrap()
-                )
-            }
+        let profile = args
+            .profile
+            .clone()
+            .filter(|name| name != DEFAULT_PROFILE);
+        if let Some(name) = &profile {
+            check_profile_name(name)?;
+        }
+        let (dir_path, file_path) = locate_config(&args, profile.as_deref())?;
+        fs::create_dir_all(&dir_path)?;
+        let log_level = logging::level(args.log_level);
 
-            let dir_path = file_path.parent().expect("could not find config directory");
-            (dir_path.into(), file_path.into())
+        let config = if file_path.exists() {
+            Self::read_from_toml(args, dir_path, file_path, profile.as_deref())?
         } else {
-            let dir_path = ProjectDirs::from("com", "rektsoft", "moccasin")
-                .unwrap()
-                .config_local_dir()
-                .to_owned();
-            let file_path = dir_path.join(DEFAULT_CONFIG_FILE).to_owned();
-            fs::create_dir_all(&dir_path)?;
-            (dir_path, file_path)
+            Self::create_initialized(args, dir_path, file_path, profile.as_deref())?
         };
+        fs::create_dir_all(config.data_dir_path())?;
+        logging::init(&config.log_path(), log_level);
 
-        if cfg!(debug_assertions) {
-            dbg!(&dir_path.join("moccasin.log"));
-            let file = OpenOptions::new()
-                .create(true)
-                .write(true)
-                .append(true)
-                .open(dir_path.join("moccasin.log"))
-                .expect("could not open file for witing");
-            simplelog::WriteLogger::init(
-                simplelog::LevelFilter::Info,
-                simplelog::Config::default(),
-                file,
-            )
-            .expect("could not initialize logger");
+        for problem in config.problems() {
+            log::warn!("{}", problem);
         }
+        config.migrate_legacy_data();
 
-        if file_path.exists() {
-            Self::read_from_toml(args, dir_path, file_path)
-        } else {
-            Self::create_initialized(args, dir_path, file_path)
+        Ok(config)
+    }
+
+    /// A config with the defaults that touches nothing on disk, keeping feeds
+    /// in memory only, for running the app without a config directory.
+    pub fn in_memory() -> Self {
+        Self {
+            cache_control: CacheControl::Never,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            refresh_timeout: DEFAULT_REFRESH_TIMEOUT,
+            monitor_interval: DEFAULT_MONITOR_INTERVAL,
+            max_feed_size: DEFAULT_MAX_FEED_SIZE,
+            share_targets: vec![ShareTarget::clipboard()],
+            tracking_params: default_tracking_params(),
+            ..Default::default()
         }
     }
 
+    /// Moves a database left beside the config file by older versions over to
+    /// the data directory, unless one is already there.
+    fn migrate_legacy_data(&self) {
+        let legacy_path = self.config_dir_path().join(DEFAULT_DB_FILE);
+        if legacy_path == self.db_path() || !legacy_path.exists() || self.db_path().exists() {
+            return;
+        }
+
+        log::info!("Moving database to {}", self.db_path().display());
+        // SQLite may keep recent writes in these files alongside the database.
+        for suffix in ["", "-wal", "-shm"] {
+            let from = PathBuf::from(format!("{}{}", legacy_path.display(), suffix));
+            let to = PathBuf::from(format!("{}{}", self.db_path().display(), suffix));
+            if from.exists() {
+                let moved = fs::rename(&from, &to)
+                    .or_else(|_| fs::copy(&from, &to).and_then(|_| fs::remove_file(&from)));
+                if let Err(err) = moved {
+                    log::error!("Failed to move {}: {}", from.display(), err);
+                }
+            }
+        }
+    }
+
+    /// The name of the profile in use, if not the default one.
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Names of the profiles that can be switched to, including the default.
+    pub fn profiles(&self) -> Vec<String> {
+        let mut profiles = default_config_dir()
+            .map(|dir| dir.join(PROFILES_DIR))
+            .and_then(|dir| Ok(fs::read_dir(dir)?))
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.path().is_dir())
+                    .filter_map(|entry| entry.file_name().into_string().ok())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        profiles.push(DEFAULT_PROFILE.to_owned());
+        profiles.sort();
+        profiles
+    }
+
     pub fn config_dir_path(&self) -> PathBuf {
         Path::new(&self.dir_path).to_owned()
     }
@@ -129,38 +403,348 @@ impl Config {
         Path::new(&self.file_path).to_owned()
     }
 
+    /// Where mutable data like the database and logs are kept, apart from config.
+    pub fn data_dir_path(&self) -> PathBuf {
+        Path::new(&self.data_dir_path).to_owned()
+    }
+
     pub fn db_path(&self) -> PathBuf {
-        self.config_dir_path().join(DEFAULT_DB_FILE)
+        self.data_dir_path().join(DEFAULT_DB_FILE)
+    }
+
+    /// Where backups taken without a path of their own are kept.
+    pub fn backups_path(&self) -> PathBuf {
+        self.data_dir_path().join("backups")
+    }
+
+    pub fn log_path(&self) -> PathBuf {
+        self.data_dir_path().join(DEFAULT_LOG_FILE)
     }
 
     pub fn themes_path(&self) -> PathBuf {
         self.config_dir_path().join("themes")
     }
 
+    /// The command used to open links, if not the system default.
+    pub fn browser(&self) -> Option<&str> {
+        self.browser.as_deref()
+    }
+
+    /// The command used to open links without stealing focus, if configured.
+    pub fn background_browser(&self) -> Option<&str> {
+        self.background_browser.as_deref()
+    }
+
+    /// Whether followed links are read inside the app rather than a browser.
+    pub fn internal_browser(&self) -> bool {
+        self.internal_browser
+    }
+
+    /// Query parameters to take out of links, where a trailing `*` matches
+    /// any parameter starting with what comes before it.
+    pub fn tracking_params(&self) -> &[String] {
+        &self.tracking_params
+    }
+
+    /// Whether items in `language` are read, going by the `languages` listed,
+    /// which take in their regional variants. Feeds that don't say what
+    /// language they're in are always read, as is everything with no list.
+    pub fn reads_language(&self, language: Option<&str>) -> bool {
+        match language {
+            Some(language) if !self.languages.is_empty() => self
+                .languages
+                .iter()
+                .any(|read| util::language_matches(language, read)),
+            _ => true,
+        }
+    }
+
+    pub fn set_languages(&mut self, languages: Vec<String>) {
+        self.languages = languages;
+    }
+
+    /// The shell command that reads text aloud from its standard input.
+    pub fn speech_command(&self) -> &str {
+        match &self.speech_command {
+            Some(command) => command,
+            None if cfg!(target_os = "macos") => "say",
+            None => "espeak-ng --stdin",
+        }
+    }
+
+    /// Whether to fetch site icons and show them beside feeds, where the
+    /// terminal can display images.
+    pub fn feed_icons(&self) -> bool {
+        self.feed_icons
+    }
+
+    /// Whether to lay the interface out for screen readers, without borders,
+    /// scrollbars or other decoration, and with each row saying where it is.
+    pub fn screen_reader(&self) -> bool {
+        self.screen_reader
+    }
+
+    /// Whether subscriptions and the config file are kept from being changed,
+    /// for showing feeds on a shared terminal.
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// How long a dashboard shows each item for, if moccasin was started as
+    /// one with `--dashboard`.
+    pub fn dashboard_interval(&self) -> Option<Duration> {
+        self.dashboard.map(|secs| Duration::from_secs(secs.max(1)))
+    }
+
+    /// Whether to draw borders, scrollbars and symbols with ASCII only, for
+    /// terminals without Unicode.
+    pub fn ascii(&self) -> bool {
+        self.ascii
+    }
+
     pub fn theme(&self) -> &theme::Theme {
         &self.theme
     }
 
+    /// Switches the color scheme for this session, by built-in name or path to a theme file.
+    pub fn set_theme(&mut self, name: &str) -> Result<(), theme::ParseThemeError> {
+        self.theme = theme::Theme::from_str(name)?.for_color(self.color);
+        Ok(())
+    }
+
+    /// How many colors to draw with, to which the theme's are brought down.
+    pub fn color(&self) -> ColorMode {
+        self.color
+    }
+
+    /// Paths of the theme files in the themes directory.
+    pub fn theme_files(&self) -> Vec<String> {
+        fs::read_dir(self.themes_path())
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter_map(|entry| entry.path().to_str().map(str::to_owned))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn status_bar(&self) -> &StatusBar {
+        &self.status_bar
+    }
+
+    pub fn item_columns(&self) -> &ItemColumns {
+        &self.item_columns
+    }
+
+    pub fn set_item_columns(&mut self, columns: ItemColumns) {
+        self.item_columns = columns;
+    }
+
+    /// Whether the selected item shows the start of its description under it.
+    pub fn item_preview(&self) -> bool {
+        self.item_preview
+    }
+
+    pub fn set_item_preview(&mut self, preview: bool) {
+        self.item_preview = preview;
+    }
+
+    /// The template for rows of the items list, which takes the place of its
+    /// columns if set.
+    pub fn item_template(&self) -> Option<&RowTemplate> {
+        self.item_template.as_ref()
+    }
+
+    pub fn feed_template(&self) -> &RowTemplate {
+        &self.feed_template
+    }
+
+    pub fn should_set_terminal_title(&self) -> bool {
+        self.terminal_title
+    }
+
+    /// Whether to show a desktop notification after a refresh finds new items.
+    pub fn should_notify_refresh(&self) -> bool {
+        self.notify_refresh
+    }
+
     pub fn feed_urls(&self) -> &HashSet<String> {
         &self.feed_urls
     }
 
+    pub fn feed_rule(&self, url: &str) -> Option<&FeedRule> {
+        self.feed_rules.get(url)
+    }
+
+    /// The URL to fetch the feed for `url` from, if it goes through a bridge.
+    pub fn bridge_url(&self, url: &str) -> Option<String> {
+        let url = reqwest::Url::parse(url).ok()?;
+        self.bridges
+            .iter()
+            .find(|bridge| bridge.matches(&url))?
+            .feed_url(&url)
+    }
+
+    /// The tags of every `[tags]` rule `item` matches.
+    pub fn item_tags(&self, item: &Item) -> Vec<String> {
+        self.tag_rules
+            .iter()
+            .filter(|rule| rule.matches(item))
+            .map(|rule| rule.tag().to_owned())
+            .collect()
+    }
+
+    /// A PEM file of extra certificate authorities to trust, such as an internal CA.
+    pub fn ca_file(&self) -> Option<&Path> {
+        self.ca_file.as_deref()
+    }
+
     pub fn sort_order(&self) -> &SortOrder {
         &self.sort_order
     }
 
+    pub fn set_sort_order(&mut self, order: SortOrder) {
+        self.sort_order = order;
+    }
+
+    pub fn layout(&self) -> PaneLayout {
+        self.layout
+    }
+
+    pub fn set_layout(&mut self, layout: PaneLayout) {
+        self.layout = layout;
+    }
+
+    /// Entries of the config file that were ignored because they were invalid.
+    pub fn problems(&self) -> &[ConfigProblem] {
+        &self.problems
+    }
+
     pub fn should_cache(&self) -> bool {
         self.cache_control == CacheControl::Always
     }
 
+    /// The largest feed to download, in bytes, or `None` for no limit.
+    pub fn max_feed_size(&self) -> Option<u64> {
+        (self.max_feed_size > 0).then(|| self.max_feed_size * 1024 * 1024)
+    }
+
+    /// How many archive pages to backfill from when subscribing to a paged feed.
+    pub fn archive_pages(&self) -> usize {
+        self.archive_pages
+    }
+
+    pub fn set_archive_pages(&mut self, pages: usize) {
+        self.archive_pages = pages;
+    }
+
+    /// How long an item is shown in the Detail panel before it's marked read,
+    /// if it ever is.
+    pub fn mark_read_after(&self) -> Option<Duration> {
+        (self.mark_read_after > 0).then(|| Duration::from_secs(self.mark_read_after))
+    }
+
+    /// Whether items scrolled past in the items list are marked read.
+    pub fn should_mark_read_on_scroll(&self) -> bool {
+        self.mark_read_on_scroll
+    }
+
+    /// The widest the text in the Detail panel is wrapped to, in columns, or
+    /// `None` to fill the panel.
+    pub fn max_content_width(&self) -> Option<u16> {
+        (self.max_content_width > 0).then_some(self.max_content_width)
+    }
+
+    /// Whether paragraphs in the Detail panel are ragged or justified.
+    pub fn text_align(&self) -> TextAlign {
+        self.text_align
+    }
+
+    pub fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    pub fn feed_sections(&self) -> FeedSections {
+        self.feed_sections
+    }
+
+    pub fn set_feed_sections(&mut self, sections: FeedSections) {
+        self.feed_sections = sections;
+    }
+
+    /// Whether long words are hyphenated at the ends of lines in the Detail
+    /// panel, rather than moved down whole.
+    pub fn hyphenate(&self) -> bool {
+        self.hyphenate
+    }
+
     pub fn refresh_interval(&self) -> u64 {
         self.refresh_interval
     }
 
+    /// How often to refetch status page feeds, in seconds, or 0 to only
+    /// refetch them with the rest.
+    pub fn monitor_interval(&self) -> u64 {
+        self.monitor_interval
+    }
+
+    /// The URLs of status page feeds, which are marked with a `monitor` rule.
+    pub fn monitored_urls(&self) -> Vec<String> {
+        self.feed_urls
+            .iter()
+            .filter(|url| self.feed_rule(url).map_or(false, FeedRule::is_monitored))
+            .cloned()
+            .collect()
+    }
+
     pub fn refresh_timeout(&self) -> u64 {
         self.refresh_timeout
     }
 
+    /// How long to wait for a feed, which its rule may set apart from the rest.
+    pub fn feed_timeout(&self, url: &str) -> u64 {
+        self.feed_rule(url)
+            .and_then(FeedRule::refresh_timeout)
+            .unwrap_or(self.refresh_timeout)
+    }
+
+    /// Where `:translate` sends the text of items, if anywhere.
+    pub fn translator(&self) -> Option<&Translator> {
+        self.translator.as_ref()
+    }
+
+    /// Where `:summarize` sends the text of items, if anywhere.
+    pub fn summarizer(&self) -> Option<&Summarizer> {
+        self.summarizer.as_ref()
+    }
+
+    /// Where the share menu can send the current item, by name.
+    pub fn share_targets(&self) -> &[ShareTarget] {
+        &self.share_targets
+    }
+
+    /// How long to wait to resolve and connect to a host, which falls back to
+    /// the whole `refresh_timeout`.
+    pub fn connect_timeout(&self) -> u64 {
+        self.connect_timeout.unwrap_or(self.refresh_timeout)
+    }
+
+    /// The key Fever clients sign in to `moccasin serve` with, if it should
+    /// speak Fever at all.
+    pub fn fever_api_key(&self) -> Option<&str> {
+        self.fever_api_key.as_deref()
+    }
+
+    /// The token clients of `moccasin serve`'s JSON API must send.
+    pub fn server_token(&self) -> Option<&str> {
+        self.server_token.as_deref()
+    }
+
     pub fn write_config(&self) -> Result<()> {
         let toml = fs::read_to_string(&self.file_path)?;
         let mut toml = toml.parse::<Document>()?;
@@ -185,6 +769,28 @@ impl Config {
         Ok(())
     }
 
+    /// Swaps a feed's URL for another, carrying over any rule configured for it.
+    pub fn replace_feed_url(&mut self, old_url: &str, new_url: &str) -> Result<()> {
+        log::info!("Replacing feed {} with {}", old_url, new_url);
+        self.feed_urls.remove(old_url);
+        self.feed_urls.insert(new_url.into());
+
+        if let Some(rule) = self.feed_rules.remove(old_url) {
+            self.feed_rules.insert(new_url.into(), rule);
+
+            let toml = fs::read_to_string(&self.file_path)?;
+            let mut toml = toml.parse::<Document>()?;
+            if let Some(rules) = toml["sources"]["rules"].as_table_like_mut() {
+                if let Some(rule) = rules.remove(old_url) {
+                    rules.insert(new_url, rule);
+                }
+            }
+            fs::write(&self.file_path, toml.to_string())?;
+        }
+
+        self.write_config()
+    }
+
     pub fn remove_feed_url(&mut self, url: &str) -> Result<()> {
         if self.feed_urls().contains(url) {
             log::info!("Deleting feed for {}", url);
@@ -194,27 +800,108 @@ impl Config {
         Ok(())
     }
 
-    fn read_from_toml(args: Args, dir_path: PathBuf, file_path: PathBuf) -> Result<Self> {
+    fn read_from_toml(
+        args: Args,
+        dir_path: PathBuf,
+        file_path: PathBuf,
+        profile: Option<&str>,
+    ) -> Result<Self> {
         let toml = fs::read_to_string(&file_path)?;
-        let table = toml.parse::<Table>()?;
+        let table = toml
+            .parse::<Table>()
+            .with_context(|| format!("could not parse {}", file_path.display()))?;
+
+        // Anything invalid is reported and otherwise ignored below.
+        let problems = validate::validate(&toml, &table);
+
         let feeds: HashSet<String> = match table.get("sources") {
             Some(Value::Table(sources)) => match sources.get("feeds") {
                 Some(Value::Array(els)) => els
                     .iter()
                     .filter_map(|v| v.as_str().and_then(|v| Some(v.to_owned())))
                     .collect(),
-                Some(_) => {
-                    panic!("unexpected config entry for [sources].feeds")
-                }
                 _ => HashSet::new(),
             },
-            _ => panic!("unexpected config entry for [sources]"),
+            _ => HashSet::new(),
+        };
+
+        let feed_rules: HashMap<String, FeedRule> = match table.get("sources") {
+            Some(Value::Table(sources)) => match sources.get("rules") {
+                Some(Value::Table(rules)) => rules
+                    .iter()
+                    .filter_map(|(url, rule)| {
+                        FeedRule::try_from(rule)
+                            .ok()
+                            .map(|rule| (url.to_owned(), rule))
+                    })
+                    .collect(),
+                _ => HashMap::new(),
+            },
+            _ => HashMap::new(),
+        };
+
+        let bridges = match table.get("bridges") {
+            Some(Value::Table(bridges)) => bridges
+                .iter()
+                .filter_map(|bridge| Bridge::try_from(bridge).ok())
+                .collect(),
+            _ => vec![],
+        };
+
+        let tag_rules = match table.get("tags") {
+            Some(Value::Table(tags)) => tags
+                .iter()
+                .filter_map(|rule| TagRule::try_from(rule).ok())
+                .collect(),
+            _ => vec![],
+        };
+
+        let ca_file = match table.get("network") {
+            Some(Value::Table(network)) => network.get("ca_file").and_then(|f| match f {
+                Value::String(f) => Some(PathBuf::from(f)),
+                _ => None,
+            }),
+            _ => None,
+        };
+        let connect_timeout = match table.get("network") {
+            Some(Value::Table(network)) => network.get("connect_timeout").and_then(|t| match t {
+                Value::Integer(t) if *t >= 0 => Some(*t as u64),
+                _ => None,
+            }),
+            _ => None,
+        };
+        let fever_api_key = match table.get("fever") {
+            Some(Value::Table(fever)) => fever.get("api_key").and_then(|k| match k {
+                Value::String(k) if !k.is_empty() => Some(k.to_lowercase()),
+                _ => None,
+            }),
+            _ => None,
+        };
+        let server_token = match table.get("server") {
+            Some(Value::Table(server)) => server.get("token").and_then(|t| match t {
+                Value::String(t) if !t.is_empty() => Some(t.clone()),
+                _ => None,
+            }),
+            _ => None,
+        };
+
+        let translator = table
+            .get("translate")
+            .and_then(|translate| Translator::try_from(translate).ok());
+        let summarizer = table
+            .get("summarize")
+            .and_then(|summarize| Summarizer::try_from(summarize).ok());
+        let share_targets = match table.get("share") {
+            Some(Value::Table(targets)) => targets
+                .iter()
+                .filter_map(|target| ShareTarget::try_from(target).ok())
+                .collect(),
+            _ => vec![ShareTarget::clipboard()],
         };
 
         let preferences = match table.get("preferences") {
             Some(Value::Table(prefs)) => Some(prefs),
-            Some(_) => panic!("invalid config entry for [preferences]"),
-            None => None,
+            _ => None,
         };
 
         // TODO: load from args if present
@@ -228,6 +915,17 @@ impl Config {
             }))
             .unwrap_or_default();
 
+        // The preference wins over NO_COLOR, so that it can be set just for moccasin.
+        let color = preferences
+            .and_then(|prefs| {
+                prefs.get("color").and_then(|c| match c {
+                    Value::String(c) => ColorMode::from_str(c).ok(),
+                    _ => None,
+                })
+            })
+            .unwrap_or_else(ColorMode::from_env);
+        let theme = theme.for_color(color);
+
         let sort_order: SortOrder = preferences
             .and_then(|prefs| {
                 prefs.get("sort_feeds").and_then(|ord| match ord {
@@ -237,12 +935,21 @@ impl Config {
             })
             .unwrap_or_default();
 
+        let layout = preferences
+            .and_then(|prefs| {
+                prefs.get("layout").and_then(|l| match l {
+                    Value::String(l) => PaneLayout::from_str(l).ok(),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
         let refresh_interval = args
             .interval
             .or({
                 preferences.and_then(|prefs| {
                     prefs.get("refresh_interval").and_then(|i| match i {
-                        Value::Integer(i) => Some(*i as u64),
+                        Value::Integer(i) if *i >= 0 => Some(*i as u64),
                         _ => None,
                     })
                 })
@@ -254,13 +961,258 @@ impl Config {
             .or({
                 preferences.and_then(|prefs| {
                     prefs.get("refresh_timeout").and_then(|i| match i {
-                        Value::Integer(i) => Some(*i as u64),
+                        Value::Integer(i) if *i >= 0 => Some(*i as u64),
                         _ => None,
                     })
                 })
             })
             .unwrap_or(DEFAULT_REFRESH_TIMEOUT);
 
+        let max_feed_size = preferences
+            .and_then(|prefs| {
+                prefs.get("max_feed_size").and_then(|i| match i {
+                    Value::Integer(i) if *i >= 0 => Some(*i as u64),
+                    _ => None,
+                })
+            })
+            .unwrap_or(DEFAULT_MAX_FEED_SIZE);
+
+        let monitor_interval = preferences
+            .and_then(|prefs| {
+                prefs.get("monitor_interval").and_then(|i| match i {
+                    Value::Integer(i) if *i >= 0 => Some(*i as u64),
+                    _ => None,
+                })
+            })
+            .unwrap_or(DEFAULT_MONITOR_INTERVAL);
+
+        let archive_pages = preferences
+            .and_then(|prefs| {
+                prefs.get("archive_pages").and_then(|i| match i {
+                    Value::Integer(i) if *i >= 0 => Some(*i as usize),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let mark_read_after = preferences
+            .and_then(|prefs| {
+                prefs.get("mark_read_after").and_then(|i| match i {
+                    Value::Integer(i) if *i >= 0 => Some(*i as u64),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let mark_read_on_scroll = preferences
+            .and_then(|prefs| {
+                prefs.get("mark_read_on_scroll").and_then(|b| match b {
+                    Value::Boolean(b) => Some(*b),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let max_content_width = preferences
+            .and_then(|prefs| {
+                prefs.get("max_content_width").and_then(|i| match i {
+                    Value::Integer(i) if *i >= 0 => Some((*i).min(u16::MAX as i64) as u16),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let text_align = preferences
+            .and_then(|prefs| {
+                prefs.get("text_align").and_then(|a| match a {
+                    Value::String(a) => TextAlign::from_str(a).ok(),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let feed_sections = preferences
+            .and_then(|prefs| {
+                prefs.get("feed_sections").and_then(|s| match s {
+                    Value::String(s) => FeedSections::from_str(s).ok(),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let render_mode = preferences
+            .and_then(|prefs| {
+                prefs.get("render_mode").and_then(|m| match m {
+                    Value::String(m) => RenderMode::from_str(m).ok(),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let hyphenate = preferences
+            .and_then(|prefs| {
+                prefs.get("hyphenate").and_then(|b| match b {
+                    Value::Boolean(b) => Some(*b),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let status_bar = preferences
+            .and_then(|prefs| {
+                prefs.get("status_bar").and_then(|s| match s {
+                    Value::String(s) => StatusBar::from_str(s).ok(),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let item_columns = preferences
+            .and_then(|prefs| {
+                prefs.get("item_columns").and_then(|s| match s {
+                    Value::String(s) => ItemColumns::from_str(s).ok(),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let item_preview = preferences
+            .and_then(|prefs| {
+                prefs.get("item_preview").and_then(|b| match b {
+                    Value::Boolean(b) => Some(*b),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let item_template = preferences.and_then(|prefs| {
+            prefs.get("item_template").and_then(|s| match s {
+                Value::String(s) => RowTemplate::items(s).ok(),
+                _ => None,
+            })
+        });
+
+        let feed_template = preferences
+            .and_then(|prefs| {
+                prefs.get("feed_template").and_then(|s| match s {
+                    Value::String(s) => RowTemplate::feeds(s).ok(),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let terminal_title = preferences
+            .and_then(|prefs| {
+                prefs.get("terminal_title").and_then(|b| match b {
+                    Value::Boolean(b) => Some(*b),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let notify_refresh = preferences
+            .and_then(|prefs| {
+                prefs.get("notify_refresh").and_then(|b| match b {
+                    Value::Boolean(b) => Some(*b),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let browser = preferences
+            .and_then(|prefs| {
+                prefs.get("browser").and_then(|b| match b {
+                    Value::String(b) => Some(b.to_owned()),
+                    _ => None,
+                })
+            })
+            .or_else(browser_from_env);
+
+        let data_dir_path = locate_data_dir(
+            preferences.and_then(|prefs| {
+                prefs.get("data_dir").and_then(|d| match d {
+                    Value::String(d) => Some(PathBuf::from(d)),
+                    _ => None,
+                })
+            }),
+            profile,
+        )?;
+
+        let background_browser = preferences.and_then(|prefs| {
+            prefs.get("background_browser").and_then(|b| match b {
+                Value::String(b) => Some(b.to_owned()),
+                _ => None,
+            })
+        });
+
+        let speech_command = preferences.and_then(|prefs| {
+            prefs.get("speech_command").and_then(|c| match c {
+                Value::String(c) => Some(c.to_owned()),
+                _ => None,
+            })
+        });
+
+        let internal_browser = preferences
+            .and_then(|prefs| {
+                prefs.get("internal_browser").and_then(|b| match b {
+                    Value::Boolean(b) => Some(*b),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+
+        let tracking_params = preferences
+            .and_then(|prefs| match prefs.get("tracking_params") {
+                Some(Value::Array(params)) => Some(
+                    params
+                        .iter()
+                        .filter_map(|param| param.as_str().map(str::to_owned))
+                        .collect(),
+                ),
+                _ => None,
+            })
+            .unwrap_or_else(default_tracking_params);
+
+        let languages = preferences
+            .and_then(|prefs| match prefs.get("languages") {
+                Some(Value::Array(languages)) => Some(
+                    languages
+                        .iter()
+                        .filter_map(|language| language.as_str().map(str::to_owned))
+                        .collect(),
+                ),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        let feed_icons = preferences
+            .and_then(|prefs| {
+                prefs.get("feed_icons").and_then(|b| match b {
+                    Value::Boolean(b) => Some(*b),
+                    _ => None,
+                })
+            })
+            .unwrap_or(true);
+
+        let screen_reader = args.screen_reader
+            || preferences
+                .and_then(|prefs| {
+                    prefs.get("screen_reader").and_then(|b| match b {
+                        Value::Boolean(b) => Some(*b),
+                        _ => None,
+                    })
+                })
+                .unwrap_or_default();
+
+        let ascii = preferences
+            .and_then(|prefs| {
+                prefs.get("ascii").and_then(|b| match b {
+                    Value::Boolean(b) => Some(*b),
+                    _ => None,
+                })
+            })
+            .unwrap_or_else(ascii_from_env);
+
         let cache_control = if args.no_cache {
             CacheControl::Never
         } else {
@@ -275,21 +1227,68 @@ impl Config {
         };
 
         Ok(Self {
+            profile: profile.map(str::to_owned),
             file_path,
             dir_path,
+            data_dir_path,
             feed_urls: feeds,
+            feed_rules,
+            bridges,
+            tag_rules,
+            ca_file,
+            connect_timeout,
+            fever_api_key,
+            server_token,
+            translator,
+            summarizer,
+            share_targets,
             sort_order,
+            layout,
+            problems,
             cache_control,
             refresh_interval,
             refresh_timeout,
+            monitor_interval,
+            max_feed_size,
+            archive_pages,
+            mark_read_after,
+            mark_read_on_scroll,
+            max_content_width,
+            text_align,
+            render_mode,
+            hyphenate,
             theme,
+            color,
+            status_bar,
+            item_columns,
+            item_template,
+            item_preview,
+            feed_template,
+            feed_sections,
+            terminal_title,
+            notify_refresh,
+            browser,
+            background_browser,
+            internal_browser,
+            tracking_params,
+            languages,
+            speech_command,
+            feed_icons,
+            screen_reader,
+            read_only: args.read_only || args.dashboard.is_some(),
+            dashboard: args.dashboard,
+            ascii,
         })
     }
 
-    fn create_initialized(args: Args, dir_path: PathBuf, file_path: PathBuf) -> Result<Self> {
+    fn create_initialized(
+        args: Args,
+        dir_path: PathBuf,
+        file_path: PathBuf,
+        profile: Option<&str>,
+    ) -> Result<Self> {
         fs::create_dir_all(&dir_path)?;
-        let cfg_path = Path::new(dir_path.as_path()).join(DEFAULT_CONFIG_FILE);
-        let mut file = File::create(&cfg_path)?;
+        let mut file = File::create(&file_path)?;
         let toml = include_str!("moccasin.toml");
         let stub = toml.parse::<Table>()?;
         let feed_urls = stub["sources"]["feeds"]
@@ -303,11 +1302,135 @@ impl Config {
 
         // TODO: load theme from args if present
         Ok(Self {
+            profile: profile.map(str::to_owned),
             dir_path: dir_path.to_owned(),
             file_path: file_path.to_owned(),
+            data_dir_path: locate_data_dir(None, profile)?,
             feed_urls,
             refresh_interval: args.interval.unwrap_or(DEFAULT_REFRESH_INTERVAL),
+            monitor_interval: DEFAULT_MONITOR_INTERVAL,
+            max_feed_size: DEFAULT_MAX_FEED_SIZE,
+            browser: browser_from_env(),
+            feed_icons: true,
+            color: ColorMode::from_env(),
+            theme: theme::Theme::default().for_color(ColorMode::from_env()),
+            screen_reader: args.screen_reader,
+            read_only: args.read_only || args.dashboard.is_some(),
+            dashboard: args.dashboard,
+            ascii: ascii_from_env(),
             ..Default::default()
         })
     }
 }
+
+/// Reads a path from an environment variable, treating an empty one as unset.
+fn path_from_env(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Profile names become directory names, so they must not reach outside the
+/// profiles directory.
+fn check_profile_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        bail!("invalid profile name '{}'", name);
+    }
+    Ok(())
+}
+
+/// Finds the config directory and file, preferring `--config`, then the
+/// `MOCCASIN_CONFIG` and `MOCCASIN_CONFIG_DIR` environment variables, then
+/// `$XDG_CONFIG_HOME` on Linux, and finally the platform default. A profile
+/// keeps its config in a directory of its own under the config directory.
+fn locate_config(args: &Args, profile: Option<&str>) -> Result<(PathBuf, PathBuf)> {
+    let file_path = match &args.config {
+        Some(path) => {
+            let file_path = PathBuf::from(path);
+            if !file_path.exists() {
+                bail!("no config file found at '{}'", file_path.display());
+            }
+            Some(file_path)
+        }
+        None => path_from_env("MOCCASIN_CONFIG"),
+    };
+    if let Some(file_path) = file_path {
+        let dir_path = file_path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_owned();
+        return Ok((dir_path, file_path));
+    }
+
+    let dir_path = match profile {
+        Some(name) => default_config_dir()?.join(PROFILES_DIR).join(name),
+        None => default_config_dir()?,
+    };
+    let file_path = dir_path.join(DEFAULT_CONFIG_FILE);
+    Ok((dir_path, file_path))
+}
+
+fn default_config_dir() -> Result<PathBuf> {
+    match path_from_env("MOCCASIN_CONFIG_DIR") {
+        Some(dir_path) => Ok(dir_path),
+        None => match path_from_env("XDG_CONFIG_HOME") {
+            Some(xdg) if cfg!(target_os = "linux") && xdg.is_absolute() => Ok(xdg.join("moccasin")),
+            _ => Ok(ProjectDirs::from("com", "rektsoft", "moccasin")
+                .context("could not find a config directory")?
+                .config_local_dir()
+                .to_owned()),
+        },
+    }
+}
+
+/// Finds the data directory, preferring the `MOCCASIN_DATA_DIR` environment
+/// variable, then the `data_dir` preference, then `$XDG_DATA_HOME` on Linux,
+/// and finally the platform default. Unless set by the profile's own
+/// `data_dir`, a profile keeps its data in a directory of its own.
+fn locate_data_dir(preferred: Option<PathBuf>, profile: Option<&str>) -> Result<PathBuf> {
+    let dir_path = match (path_from_env("MOCCASIN_DATA_DIR"), preferred) {
+        (Some(dir_path), _) => dir_path,
+        (None, Some(dir_path)) => return Ok(dir_path),
+        (None, None) => match path_from_env("XDG_DATA_HOME") {
+            Some(xdg) if cfg!(target_os = "linux") && xdg.is_absolute() => xdg.join("moccasin"),
+            _ => ProjectDirs::from("com", "rektsoft", "moccasin")
+                .context("could not find a data directory")?
+                .data_local_dir()
+                .to_owned(),
+        },
+    };
+
+    match profile {
+        Some(name) => Ok(dir_path.join(PROFILES_DIR).join(name)),
+        None => Ok(dir_path),
+    }
+}
+
+/// Whether the locale says the terminal can't show Unicode. If none is set,
+/// as is usual on Windows, it's assumed that it can.
+fn ascii_from_env() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|locale| !locale.is_empty())
+        .map_or(false, |locale| {
+            let locale = locale.to_lowercase();
+            !locale.contains("utf-8") && !locale.contains("utf8")
+        })
+}
+
+/// The first command in `$BROWSER`, which may hold a colon-separated list.
+fn browser_from_env() -> Option<String> {
+    env::var("BROWSER").ok().and_then(|browsers| {
+        browsers
+            .split(':')
+            .map(str::trim)
+            .find(|b| !b.is_empty())
+            .map(str::to_owned)
+    })
+}
+
+fn default_tracking_params() -> Vec<String> {
+    DEFAULT_TRACKING_PARAMS.map(str::to_owned).to_vec()
+}
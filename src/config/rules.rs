@@ -0,0 +1,147 @@
+use crate::feed::StripRules;
+use std::fmt;
+use std::str::FromStr;
+use toml::Value;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Priority {
+    #[default]
+    Normal,
+    High,
+}
+
+impl FromStr for Priority {
+    type Err = ParseFeedRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            other => Err(ParseFeedRuleError(format!("unknown priority '{}'", other))),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseFeedRuleError(String);
+
+impl fmt::Display for ParseFeedRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid feed rule: {}", self.0)
+    }
+}
+
+/// Per-feed behavior, configured under `[sources.rules."<url>"]`. High priority
+/// feeds notify about and star their new items unless told otherwise.
+#[derive(Debug, Default, Clone)]
+pub struct FeedRule {
+    priority: Priority,
+    notify: bool,
+    star: bool,
+    insecure: bool,
+    monitor: bool,
+    refresh_timeout: Option<u64>,
+    strip: Option<StripRules>,
+}
+
+impl FeedRule {
+    pub fn is_high_priority(&self) -> bool {
+        self.priority == Priority::High
+    }
+
+    /// Whether to show a desktop notification for new items.
+    pub fn should_notify(&self) -> bool {
+        self.notify
+    }
+
+    /// Whether to star new items as they arrive.
+    pub fn should_star(&self) -> bool {
+        self.star
+    }
+
+    /// How long to wait for the feed, if not the usual `refresh_timeout`.
+    pub fn refresh_timeout(&self) -> Option<u64> {
+        self.refresh_timeout
+    }
+
+    /// Whether to fetch the feed without verifying its TLS certificate.
+    pub fn is_insecure(&self) -> bool {
+        self.insecure
+    }
+
+    /// Whether this is a status page feed, shown in the monitor view and
+    /// refreshed every `monitor_interval`.
+    pub fn is_monitored(&self) -> bool {
+        self.monitor
+    }
+
+    /// What to take out of the feed's items before their HTML is flattened.
+    pub fn strip_rules(&self) -> Option<&StripRules> {
+        self.strip.as_ref()
+    }
+}
+
+impl TryFrom<&Value> for FeedRule {
+    type Error = ParseFeedRuleError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let Value::Table(rule) = value else {
+            return Err(ParseFeedRuleError("expected a table".into()));
+        };
+
+        let priority = match rule.get("priority") {
+            Some(Value::String(p)) => Priority::from_str(p)?,
+            Some(_) => return Err(ParseFeedRuleError("priority must be a string".into())),
+            None => Priority::default(),
+        };
+        let is_high = priority == Priority::High;
+
+        let flag = |name: &str| match rule.get(name) {
+            Some(Value::Boolean(b)) => Ok(*b),
+            Some(_) => Err(ParseFeedRuleError(format!("{} must be a boolean", name))),
+            None => Ok(is_high),
+        };
+
+        let insecure = match rule.get("insecure") {
+            Some(Value::Boolean(b)) => *b,
+            Some(_) => return Err(ParseFeedRuleError("insecure must be a boolean".into())),
+            None => false,
+        };
+
+        let monitor = match rule.get("monitor") {
+            Some(Value::Boolean(b)) => *b,
+            Some(_) => return Err(ParseFeedRuleError("monitor must be a boolean".into())),
+            None => false,
+        };
+
+        let refresh_timeout = match rule.get("refresh_timeout") {
+            Some(Value::Integer(t)) if *t >= 0 => Some(*t as u64),
+            Some(_) => return Err(ParseFeedRuleError("refresh_timeout must be seconds".into())),
+            None => None,
+        };
+
+        let strings = |name: &str| match rule.get(name) {
+            Some(Value::Array(values)) => values
+                .iter()
+                .map(|value| match value {
+                    Value::String(s) => Ok(s.to_owned()),
+                    _ => Err(ParseFeedRuleError(format!("{} must be strings", name))),
+                })
+                .collect(),
+            Some(_) => Err(ParseFeedRuleError(format!("{} must be an array", name))),
+            None => Ok(vec![]),
+        };
+        let strip = StripRules::new(&strings("strip")?, &strings("strip_text")?)
+            .map_err(|err| ParseFeedRuleError(err.to_string()))?;
+
+        Ok(Self {
+            priority,
+            notify: flag("notify")?,
+            star: flag("star")?,
+            insecure,
+            monitor,
+            refresh_timeout,
+            strip: (!strip.is_empty()).then_some(strip),
+        })
+    }
+}
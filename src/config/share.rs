@@ -0,0 +1,169 @@
+use reqwest::Url;
+use std::fmt;
+use toml::Value;
+
+const DEFAULT_BLUESKY_URL: &str = "https://bsky.social";
+
+const KEYS: [&str; 11] = [
+    "type",
+    "to",
+    "command",
+    "url",
+    "client_id",
+    "client_secret",
+    "username",
+    "password",
+    "token",
+    "visibility",
+    "handle",
+];
+
+#[derive(Debug)]
+pub struct ParseShareTargetError(String);
+
+impl fmt::Display for ParseShareTargetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid share target: {}", self.0)
+    }
+}
+
+/// Somewhere the item in the Detail panel can be sent from the share menu,
+/// configured under `[share]` as `<name> = { type = "<type>", ... }`.
+#[derive(Debug, Clone)]
+pub struct ShareTarget {
+    name: String,
+    kind: ShareKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum ShareKind {
+    /// Copies the link to the clipboard through the terminal, which works
+    /// over SSH too.
+    Clipboard,
+    /// Starts an email with the title as its subject and the link as its body.
+    Email { to: Option<String> },
+    /// Runs a shell command with the text on stdin, and the title and link in
+    /// `$MOCCASIN_TITLE` and `$MOCCASIN_LINK`.
+    Command(String),
+    /// Saves the link to read later on a Wallabag instance.
+    Wallabag {
+        url: String,
+        client_id: String,
+        client_secret: String,
+        username: String,
+        password: String,
+    },
+    /// Posts a status written in the compose overlay to a Mastodon instance,
+    /// with a `token` kept in the system keyring unless it's given here.
+    Mastodon {
+        url: String,
+        token: Option<String>,
+        visibility: Option<String>,
+    },
+    /// Posts a status written in the compose overlay to Bluesky, signing in
+    /// with an app password kept in the system keyring unless it's given here.
+    Bluesky {
+        url: String,
+        handle: String,
+        password: Option<String>,
+    },
+}
+
+impl ShareKind {
+    /// The most characters a post can have, for targets that are posted to
+    /// from the compose overlay.
+    pub fn post_limit(&self) -> Option<usize> {
+        match self {
+            Self::Mastodon { .. } => Some(500),
+            Self::Bluesky { .. } => Some(300),
+            _ => None,
+        }
+    }
+}
+
+impl ShareTarget {
+    /// What's offered when no `[share]` targets are configured.
+    pub fn clipboard() -> Self {
+        Self {
+            name: "clipboard".to_owned(),
+            kind: ShareKind::Clipboard,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> &ShareKind {
+        &self.kind
+    }
+}
+
+impl TryFrom<(&String, &Value)> for ShareTarget {
+    type Error = ParseShareTargetError;
+
+    fn try_from((name, value): (&String, &Value)) -> Result<Self, Self::Error> {
+        let Value::Table(table) = value else {
+            return Err(ParseShareTargetError(format!("{} must be a table", name)));
+        };
+        if let Some(key) = table.keys().find(|key| !KEYS.contains(&key.as_str())) {
+            return Err(ParseShareTargetError(format!("unknown key {}", key)));
+        }
+        let string = |key: &str| match table.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.to_owned())),
+            Some(other) => Err(ParseShareTargetError(format!(
+                "{} must be a string, found {}",
+                key,
+                other.type_str()
+            ))),
+        };
+        let required = |key: &str| {
+            string(key)?.ok_or_else(|| ParseShareTargetError(format!("{} needs a {}", name, key)))
+        };
+        let url = || {
+            let url = required("url")?;
+            Url::parse(&url)
+                .map_err(|_| ParseShareTargetError(format!("'{}' is not a URL", url)))?;
+            Ok(url.trim_end_matches('/').to_owned())
+        };
+
+        let kind = match string("type")?.as_deref() {
+            Some("clipboard") => ShareKind::Clipboard,
+            Some("email") => ShareKind::Email { to: string("to")? },
+            Some("command") => ShareKind::Command(required("command")?),
+            Some("wallabag") => ShareKind::Wallabag {
+                url: url()?,
+                client_id: required("client_id")?,
+                client_secret: required("client_secret")?,
+                username: required("username")?,
+                password: required("password")?,
+            },
+            Some("mastodon") => ShareKind::Mastodon {
+                url: url()?,
+                token: string("token")?,
+                visibility: string("visibility")?,
+            },
+            Some("bluesky") => ShareKind::Bluesky {
+                url: match string("url")? {
+                    Some(_) => url()?,
+                    None => DEFAULT_BLUESKY_URL.to_owned(),
+                },
+                handle: required("handle")?,
+                password: string("password")?,
+            },
+            Some(other) => {
+                return Err(ParseShareTargetError(format!(
+                    "unknown type '{}', expected clipboard, email, command, wallabag, mastodon or bluesky",
+                    other
+                )))
+            }
+            None => return Err(ParseShareTargetError(format!("{} needs a type", name))),
+        };
+
+        Ok(Self {
+            name: name.to_owned(),
+            kind,
+        })
+    }
+}
@@ -0,0 +1,88 @@
+use std::fmt;
+use std::str::FromStr;
+
+const DEFAULT_STATUS_BAR: &'static str = "{last_fetched}   {countdown}";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatusSegment {
+    Text(String),
+    LastFetched,
+    Unread,
+    Filter,
+    Offline,
+    Countdown,
+}
+
+impl FromStr for StatusSegment {
+    type Err = ParseStatusBarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "last_fetched" => Ok(Self::LastFetched),
+            "unread" => Ok(Self::Unread),
+            "filter" => Ok(Self::Filter),
+            "offline" => Ok(Self::Offline),
+            "countdown" => Ok(Self::Countdown),
+            other => Err(ParseStatusBarError(other.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseStatusBarError(String);
+
+impl fmt::Display for ParseStatusBarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown status bar segment '{}'", self.0)
+    }
+}
+
+/// A status bar template such as `"{last_fetched} | {unread}"`, where each
+/// `{name}` is replaced by a live segment and everything else is literal text.
+#[derive(Debug, Clone)]
+pub struct StatusBar {
+    segments: Vec<StatusSegment>,
+}
+
+impl StatusBar {
+    pub fn segments(&self) -> &[StatusSegment] {
+        &self.segments
+    }
+
+    /// Whether any segment changes with the passage of time alone.
+    pub fn is_time_based(&self) -> bool {
+        self.segments.contains(&StatusSegment::Countdown)
+    }
+}
+
+impl Default for StatusBar {
+    fn default() -> Self {
+        Self::from_str(DEFAULT_STATUS_BAR).expect("parse default status bar")
+    }
+}
+
+impl FromStr for StatusBar {
+    type Err = ParseStatusBarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = vec![];
+        let mut rest = s;
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(StatusSegment::Text(rest[..start].to_owned()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or(ParseStatusBarError(rest[start..].to_owned()))?;
+            segments.push(rest[start + 1..start + end].parse()?);
+            rest = &rest[start + end + 1..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(StatusSegment::Text(rest.to_owned()));
+        }
+
+        Ok(Self { segments })
+    }
+}
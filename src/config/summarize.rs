@@ -0,0 +1,70 @@
+use reqwest::Url;
+use std::fmt;
+use toml::Value;
+
+const DEFAULT_PROMPT: &str =
+    "Summarize the following article in a few sentences, in the language it is written in.";
+
+#[derive(Debug)]
+pub struct ParseSummarizerError(String);
+
+impl fmt::Display for ParseSummarizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// What `:summarize` sends the text of items to, configured under
+/// `[summarize]`. Either a `command` that reads text on stdin and writes a
+/// summary to stdout, or the `url` of an OpenAI-compatible chat completions
+/// endpoint, with the `model` to ask, any `api_key`, and the `prompt` to ask
+/// it with.
+#[derive(Debug, Clone)]
+pub enum Summarizer {
+    Command(String),
+    ChatCompletions {
+        url: String,
+        api_key: Option<String>,
+        model: String,
+        prompt: String,
+    },
+}
+
+impl TryFrom<&Value> for Summarizer {
+    type Error = ParseSummarizerError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let Value::Table(table) = value else {
+            return Err(ParseSummarizerError("expected a table".into()));
+        };
+        let string = |key: &str| match table.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.to_owned())),
+            Some(other) => Err(ParseSummarizerError(format!(
+                "{} must be a string, found {}",
+                key,
+                other.type_str()
+            ))),
+        };
+
+        match (string("command")?, string("url")?) {
+            (Some(command), None) => Ok(Self::Command(command)),
+            (None, Some(url)) => {
+                Url::parse(&url)
+                    .map_err(|_| ParseSummarizerError(format!("'{}' is not a URL", url)))?;
+                let model = string("model")?
+                    .ok_or_else(|| ParseSummarizerError("a url needs a model".into()))?;
+                Ok(Self::ChatCompletions {
+                    url,
+                    api_key: string("api_key")?,
+                    model,
+                    prompt: string("prompt")?.unwrap_or(DEFAULT_PROMPT.to_owned()),
+                })
+            }
+            (Some(_), Some(_)) => Err(ParseSummarizerError(
+                "expected either a command or a url, not both".into(),
+            )),
+            (None, None) => Err(ParseSummarizerError("expected a command or a url".into())),
+        }
+    }
+}
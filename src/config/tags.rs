@@ -0,0 +1,56 @@
+use crate::feed::Item;
+use regex::Regex;
+use std::fmt;
+use toml::Value;
+
+#[derive(Debug)]
+pub struct ParseTagRuleError(String);
+
+impl fmt::Display for ParseTagRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid tag rule: {}", self.0)
+    }
+}
+
+/// Tags items whose title or text matches a pattern, configured under
+/// `[tags]` as `<tag> = "<regex>"`. Patterns are case-sensitive unless they
+/// start with `(?i)`.
+#[derive(Debug, Clone)]
+pub struct TagRule {
+    tag: String,
+    pattern: Regex,
+}
+
+impl TagRule {
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn matches(&self, item: &Item) -> bool {
+        [item.title(), item.description()]
+            .into_iter()
+            .flatten()
+            .any(|text| self.pattern.is_match(text))
+    }
+}
+
+impl TryFrom<(&String, &Value)> for TagRule {
+    type Error = ParseTagRuleError;
+
+    fn try_from((tag, pattern): (&String, &Value)) -> Result<Self, Self::Error> {
+        let Value::String(pattern) = pattern else {
+            return Err(ParseTagRuleError(format!("{} must be a string", tag)));
+        };
+        let pattern = Regex::new(pattern).map_err(|err| match err {
+            regex::Error::Syntax(_) => {
+                ParseTagRuleError(format!("'{}' is not a valid pattern", pattern))
+            }
+            _ => ParseTagRuleError(format!("'{}' is too large a pattern", pattern)),
+        })?;
+
+        Ok(Self {
+            tag: tag.to_owned(),
+            pattern,
+        })
+    }
+}
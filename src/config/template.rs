@@ -0,0 +1,112 @@
+use chrono::format::{Item, StrftimeItems};
+use std::fmt;
+
+const DEFAULT_FEED_TEMPLATE: &'static str = "{title} ({unread}/{count})";
+
+/// Fields an item row template may use.
+const ITEM_FIELDS: [&str; 6] = ["date", "flag", "title", "author", "feed", "link"];
+
+/// Fields a feed row template may use.
+const FEED_FIELDS: [&str; 5] = ["title", "unread", "count", "url", "date"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateSegment {
+    Text(String),
+    /// A field by name, and for dates a `strftime` format to write it in.
+    Field(String, Option<String>),
+}
+
+#[derive(Debug)]
+pub struct ParseTemplateError(String);
+
+impl fmt::Display for ParseTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A template for the rows of the items or feeds list, such as
+/// `"{date:%m-%d} {flag} {title} — {author}"`, where each `{field}` is filled
+/// in from the item or feed and everything else is literal text.
+#[derive(Debug, Clone)]
+pub struct RowTemplate {
+    segments: Vec<TemplateSegment>,
+}
+
+impl RowTemplate {
+    pub fn items(s: &str) -> Result<Self, ParseTemplateError> {
+        Self::parse(s, &ITEM_FIELDS)
+    }
+
+    pub fn feeds(s: &str) -> Result<Self, ParseTemplateError> {
+        Self::parse(s, &FEED_FIELDS)
+    }
+
+    /// Fills in the template, asking `field` for the value of each field by
+    /// its name and format.
+    pub fn render(&self, field: impl Fn(&str, Option<&str>) -> String) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                TemplateSegment::Text(text) => text.to_owned(),
+                TemplateSegment::Field(name, format) => field(name, format.as_deref()),
+            })
+            .collect()
+    }
+
+    fn parse(s: &str, fields: &[&str]) -> Result<Self, ParseTemplateError> {
+        let mut segments = vec![];
+        let mut rest = s;
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(TemplateSegment::Text(rest[..start].to_owned()));
+            }
+            let end = rest[start..].find('}').ok_or_else(|| {
+                ParseTemplateError(format!("unclosed field '{}'", &rest[start..]))
+            })?;
+            let field = &rest[start + 1..start + end];
+            let (name, format) = match field.split_once(':') {
+                Some((name, format)) => (name.trim(), Some(format)),
+                None => (field.trim(), None),
+            };
+            if !fields.contains(&name) {
+                return Err(ParseTemplateError(format!(
+                    "unknown field '{}', expected one of {}",
+                    name,
+                    fields.join(", ")
+                )));
+            }
+            if let Some(format) = format {
+                if name != "date" {
+                    return Err(ParseTemplateError(format!("{} takes no format", name)));
+                }
+                if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                    return Err(ParseTemplateError(format!(
+                        "invalid date format '{}'",
+                        format
+                    )));
+                }
+            }
+            segments.push(TemplateSegment::Field(
+                name.to_owned(),
+                format.map(str::to_owned),
+            ));
+            rest = &rest[start + end + 1..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(TemplateSegment::Text(rest.to_owned()));
+        }
+
+        Ok(Self { segments })
+    }
+}
+
+impl Default for RowTemplate {
+    /// The feeds list's rows as they've always been, with the title and the
+    /// unread and total counts.
+    fn default() -> Self {
+        Self::feeds(DEFAULT_FEED_TEMPLATE).expect("parse default feed template")
+    }
+}
@@ -1,3 +1,4 @@
+use super::ColorMode;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::{error::Error, str::FromStr};
@@ -13,6 +14,11 @@ enum ParseColorError {
     Empty,
 }
 
+/// Names of the built-in color schemes.
+pub const BUILTIN_THEMES: [&str; 8] = [
+    "default", "borland", "darcula", "focus", "jungle", "matrix", "redshift", "wyse",
+];
+
 #[derive(Debug)]
 pub struct ParseThemeError;
 
@@ -44,6 +50,16 @@ fn make_color(c: &str) -> Color {
     }
 }
 
+/// Colors badges may have unless a theme says otherwise.
+const BADGE_COLORS: [Color; 6] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+];
+
 #[derive(Debug, Clone)]
 pub struct Theme {
     base: Style,
@@ -54,6 +70,8 @@ pub struct Theme {
     border: Option<Style>,
     border_active: Option<Style>,
     scrollbar: Option<Style>,
+    highlight: Option<Style>,
+    badges: Option<Vec<Color>>,
 }
 
 impl Theme {
@@ -101,6 +119,27 @@ impl Theme {
         }
     }
 
+    /// Unread items of high priority feeds.
+    pub fn highlight(&self) -> Style {
+        if let Some(s) = self.highlight {
+            s.to_owned()
+        } else {
+            self.base.add_modifier(Modifier::BOLD)
+        }
+    }
+
+    /// A badge labelled `name`, in one of the badge colors picked by the name
+    /// so that it's the same every time. With no colors to pick from, it's
+    /// reversed instead.
+    pub fn badge(&self, name: &str) -> Style {
+        let colors = self.badges.as_deref().unwrap_or(&BADGE_COLORS);
+        if colors.is_empty() {
+            return self.base.add_modifier(Modifier::REVERSED);
+        }
+        let color = colors[name.bytes().map(usize::from).sum::<usize>() % colors.len()];
+        Style::default().bg(color).fg(Color::Black)
+    }
+
     pub fn scrollbar_thumb(&self) -> Style {
         if let Some(s) = self.scrollbar {
             if let Some(fg) = s.fg {
@@ -125,6 +164,16 @@ impl Theme {
         }
     }
 
+    /// This theme as drawn with the given colors. Without any, the `focus`
+    /// theme stands in, since it tells things apart only by bold, dim and
+    /// reversed text.
+    pub fn for_color(self, color: ColorMode) -> Self {
+        match color {
+            ColorMode::Never => Self::focus(),
+            _ => self,
+        }
+    }
+
     pub fn borland() -> Self {
         let white = make_color("#FFFFFF");
         let gray = make_color("#bbbbbb");
@@ -141,6 +190,8 @@ impl Theme {
             selection: Some(Style::default().fg(midnight).bg(gray)),
             selection_active: Some(Style::default().fg(midnight).bg(yellow)),
             scrollbar: Some(Style::default().fg(white).bg(gray)),
+            highlight: None,
+            badges: None,
         }
     }
 
@@ -161,6 +212,8 @@ impl Theme {
             selection: Some(Style::default().fg(background).bg(bright_yellow)),
             selection_active: Some(Style::default().fg(background).bg(yellow)),
             scrollbar: Some(Style::default().fg(bright_black)),
+            highlight: None,
+            badges: None,
         }
     }
 
@@ -174,6 +227,8 @@ impl Theme {
             selection: Some(Style::default().reversed().dim()),
             selection_active: Some(Style::default().reversed().bold()),
             scrollbar: Some(Style::default()),
+            highlight: None,
+            badges: Some(vec![]),
         }
     }
 
@@ -187,6 +242,8 @@ impl Theme {
             selection: Some(Style::default().dim().reversed()),
             selection_active: Some(Style::default().green().reversed()),
             scrollbar: Some(Style::default().dim()),
+            highlight: None,
+            badges: None,
         }
     }
 
@@ -204,6 +261,8 @@ impl Theme {
             selection: Some(Style::default().fg(dark_green).bg(mid_green)),
             selection_active: Some(Style::default().fg(dark_green).bg(bright_green)),
             scrollbar: Some(Style::default()),
+            highlight: None,
+            badges: None,
         }
     }
 
@@ -220,6 +279,8 @@ impl Theme {
             selection: Some(Style::default().dim().reversed()),
             border: Some(Style::default().dim()),
             scrollbar: Some(Style::default().dim()),
+            highlight: None,
+            badges: None,
         }
     }
 
@@ -237,6 +298,8 @@ impl Theme {
             selection: Some(Style::default().fg(black).bg(dark_amber)),
             selection_active: Some(Style::default().fg(black).bg(bright_amber)),
             scrollbar: Some(Style::default()),
+            highlight: None,
+            badges: None,
         }
     }
 }
@@ -252,6 +315,8 @@ impl Default for Theme {
             border_active: None,
             border: None,
             scrollbar: Some(Style::default().dim()),
+            highlight: None,
+            badges: None,
         }
     }
 }
@@ -332,6 +397,18 @@ impl TryFrom<&toml::Value> for Theme {
                 scrollbar: scheme
                     .get("scrollbar")
                     .and_then(|v| try_style_from_toml(v).ok()),
+                highlight: scheme
+                    .get("highlight")
+                    .and_then(|v| try_style_from_toml(v).ok()),
+                badges: scheme
+                    .get("badges")
+                    .and_then(|v| v.as_array())
+                    .map(|colors| {
+                        colors
+                            .iter()
+                            .filter_map(|v| try_style_from_toml(v).ok()?.fg)
+                            .collect()
+                    }),
             }),
             _ => Err(ParseThemeError),
         }
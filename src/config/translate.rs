@@ -0,0 +1,82 @@
+use reqwest::Url;
+use std::fmt;
+use toml::Value;
+
+const DEFAULT_TARGET_LANGUAGE: &str = "en";
+
+#[derive(Debug)]
+pub struct ParseTranslatorError(String);
+
+impl fmt::Display for ParseTranslatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Where the text of items is sent by `:translate`, configured under
+/// `[translate]`. Either a `command` that reads text on stdin and writes its
+/// translation to stdout, or the `url` of a LibreTranslate or DeepL API,
+/// along with any `api_key`. Text is translated into the `target` language.
+#[derive(Debug, Clone)]
+pub enum Translator {
+    Command(String),
+    LibreTranslate {
+        url: String,
+        api_key: Option<String>,
+        target: String,
+    },
+    DeepL {
+        url: String,
+        api_key: Option<String>,
+        target: String,
+    },
+}
+
+impl TryFrom<&Value> for Translator {
+    type Error = ParseTranslatorError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let Value::Table(table) = value else {
+            return Err(ParseTranslatorError("expected a table".into()));
+        };
+        let string = |key: &str| match table.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.to_owned())),
+            Some(other) => Err(ParseTranslatorError(format!(
+                "{} must be a string, found {}",
+                key,
+                other.type_str()
+            ))),
+        };
+
+        let target = string("target")?.unwrap_or(DEFAULT_TARGET_LANGUAGE.to_owned());
+        let api_key = string("api_key")?;
+        match (string("command")?, string("url")?) {
+            (Some(command), None) => Ok(Self::Command(command)),
+            (None, Some(url)) => {
+                let parsed = Url::parse(&url)
+                    .map_err(|_| ParseTranslatorError(format!("'{}' is not a URL", url)))?;
+                let deepl = parsed
+                    .host_str()
+                    .map_or(false, |host| host.ends_with("deepl.com"));
+                if deepl {
+                    Ok(Self::DeepL {
+                        url,
+                        api_key,
+                        target,
+                    })
+                } else {
+                    Ok(Self::LibreTranslate {
+                        url,
+                        api_key,
+                        target,
+                    })
+                }
+            }
+            (Some(_), Some(_)) => Err(ParseTranslatorError(
+                "expected either a command or a url, not both".into(),
+            )),
+            (None, None) => Err(ParseTranslatorError("expected a command or a url".into())),
+        }
+    }
+}
@@ -0,0 +1,393 @@
+use super::bridges::Bridge;
+use super::columns::ItemColumns;
+use super::rules::FeedRule;
+use super::share::ShareTarget;
+use super::status::StatusBar;
+use super::summarize::Summarizer;
+use super::tags::TagRule;
+use super::template::RowTemplate;
+use super::theme::Theme;
+use super::translate::Translator;
+use super::{ColorMode, FeedSections, PaneLayout, RenderMode, TextAlign};
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+use toml::{Spanned, Table, Value};
+
+const SORT_ORDERS: [&str; 6] = ["a-z", "z-a", "newest", "oldest", "unread", "custom"];
+
+/// The keys of a table along with where they appear in the file. Values are
+/// looked up in the parsed [`Table`] instead.
+type Keys = BTreeMap<Spanned<String>, IgnoredAny>;
+
+/// The rule tables under `[sources.rules]`, which sit one level deeper than
+/// everything else we check.
+#[derive(Deserialize)]
+struct RuleKeys {
+    sources: Option<RuleSourceKeys>,
+}
+
+#[derive(Deserialize)]
+struct RuleSourceKeys {
+    #[serde(default)]
+    rules: Keys,
+}
+
+/// An entry in the config file that could not be used, and was ignored.
+#[derive(Debug, Clone)]
+pub struct ConfigProblem {
+    line: usize,
+    key: String,
+    message: String,
+}
+
+impl ConfigProblem {
+    fn new(source: &str, span: Range<usize>, key: String, message: String) -> Self {
+        let line = source[..span.start.min(source.len())].matches('\n').count() + 1;
+        Self { line, key, message }
+    }
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}: {}", self.line, self.key, self.message)
+    }
+}
+
+fn expected(what: &str, value: &Value) -> String {
+    format!("expected {}, found {}", what, value.type_str())
+}
+
+/// Checks a known preference, returning `None` for keys we don't recognize.
+fn check_preference(key: &str, value: &Value) -> Option<Result<(), String>> {
+    let result = match (key, value) {
+        ("color_scheme", Value::String(_) | Value::Table(_)) => Theme::try_from(value)
+            .map(drop)
+            .map_err(|_| "not a built-in theme, theme file or theme table".into()),
+        ("color_scheme", _) => Err(expected("a theme name or table", value)),
+        ("sort_feeds", Value::String(s)) if SORT_ORDERS.contains(&s.as_str()) => Ok(()),
+        ("sort_feeds", Value::String(s)) => Err(format!(
+            "unknown sort order '{}', expected one of {}",
+            s,
+            SORT_ORDERS.join(", ")
+        )),
+        ("layout", Value::String(s)) => {
+            PaneLayout::from_str(s).map(drop).map_err(|e| e.to_string())
+        }
+        ("status_bar", Value::String(s)) => {
+            StatusBar::from_str(s).map(drop).map_err(|e| e.to_string())
+        }
+        ("color", Value::String(s)) => ColorMode::from_str(s).map(drop).map_err(|e| e.to_string()),
+        ("item_columns", Value::String(s)) => ItemColumns::from_str(s)
+            .map(drop)
+            .map_err(|e| e.to_string()),
+        ("item_template", Value::String(s)) => {
+            RowTemplate::items(s).map(drop).map_err(|e| e.to_string())
+        }
+        ("feed_template", Value::String(s)) => {
+            RowTemplate::feeds(s).map(drop).map_err(|e| e.to_string())
+        }
+        ("text_align", Value::String(s)) => {
+            TextAlign::from_str(s).map(drop).map_err(|e| e.to_string())
+        }
+        ("feed_sections", Value::String(s)) => FeedSections::from_str(s)
+            .map(drop)
+            .map_err(|e| e.to_string()),
+        ("render_mode", Value::String(s)) => {
+            RenderMode::from_str(s).map(drop).map_err(|e| e.to_string())
+        }
+        (
+            "refresh_interval" | "refresh_timeout" | "monitor_interval" | "max_feed_size"
+            | "archive_pages" | "mark_read_after" | "max_content_width",
+            Value::Integer(i),
+        ) if *i >= 0 => Ok(()),
+        (
+            "refresh_interval" | "refresh_timeout" | "monitor_interval" | "max_feed_size"
+            | "archive_pages" | "mark_read_after" | "max_content_width",
+            Value::Integer(_),
+        ) => Err("must not be negative".into()),
+        (
+            "refresh_interval" | "refresh_timeout" | "monitor_interval" | "max_feed_size"
+            | "archive_pages" | "mark_read_after" | "max_content_width",
+            _,
+        ) => Err(expected("an integer", value)),
+        (
+            "terminal_title"
+            | "notify_refresh"
+            | "internal_browser"
+            | "feed_icons"
+            | "screen_reader"
+            | "item_preview"
+            | "ascii"
+            | "cache_feeds"
+            | "mark_read_on_scroll"
+            | "hyphenate",
+            Value::Boolean(_),
+        ) => Ok(()),
+        (
+            "terminal_title"
+            | "notify_refresh"
+            | "internal_browser"
+            | "feed_icons"
+            | "screen_reader"
+            | "item_preview"
+            | "ascii"
+            | "cache_feeds"
+            | "mark_read_on_scroll"
+            | "hyphenate",
+            _,
+        ) => Err(expected("a boolean", value)),
+        ("browser" | "background_browser" | "speech_command" | "data_dir", Value::String(_)) => {
+            Ok(())
+        }
+        (
+            "sort_feeds" | "layout" | "status_bar" | "color" | "item_columns" | "item_template"
+            | "feed_template" | "feed_sections" | "text_align" | "render_mode" | "browser"
+            | "background_browser" | "speech_command" | "data_dir",
+            _,
+        ) => Err(expected("a string", value)),
+        ("tracking_params", Value::Array(params)) => match params.iter().find(|p| !p.is_str()) {
+            Some(param) => Err(format!(
+                "expected parameter names, found {}",
+                param.type_str()
+            )),
+            None => Ok(()),
+        },
+        ("tracking_params", _) => Err(expected("an array of parameter names", value)),
+        ("languages", Value::Array(languages)) => match languages.iter().find(|l| !l.is_str()) {
+            Some(language) => Err(format!(
+                "expected language codes, found {}",
+                language.type_str()
+            )),
+            None => Ok(()),
+        },
+        ("languages", _) => Err(expected("an array of language codes", value)),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn check_source(key: &str, value: &Value) -> Option<Result<(), String>> {
+    let result = match value {
+        Value::Array(urls) if key == "feeds" => match urls.iter().find(|url| !url.is_str()) {
+            Some(url) => Err(format!("expected feed URLs, found {}", url.type_str())),
+            None => Ok(()),
+        },
+        _ if key == "feeds" => Err(expected("an array of feed URLs", value)),
+        Value::Table(_) if key == "rules" => Ok(()),
+        _ if key == "rules" => Err(expected("a table of feed rules", value)),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn check_network(key: &str, value: &Value) -> Option<Result<(), String>> {
+    let result = match (key, value) {
+        ("ca_file", Value::String(_)) => Ok(()),
+        ("ca_file", _) => Err(expected("a path", value)),
+        ("connect_timeout", Value::Integer(i)) if *i >= 0 => Ok(()),
+        ("connect_timeout", Value::Integer(_)) => Err("must not be negative".into()),
+        ("connect_timeout", _) => Err(expected("an integer", value)),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn check_fever(key: &str, value: &Value) -> Option<Result<(), String>> {
+    let result = match (key, value) {
+        ("api_key", Value::String(k)) if k.is_empty() => Err("must not be empty".into()),
+        ("api_key", Value::String(_)) => Ok(()),
+        ("api_key", _) => Err(expected("a string", value)),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn check_server(key: &str, value: &Value) -> Option<Result<(), String>> {
+    let result = match (key, value) {
+        ("token", Value::String(t)) if t.is_empty() => Err("must not be empty".into()),
+        ("token", Value::String(_)) => Ok(()),
+        ("token", _) => Err(expected("a string", value)),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn check_translate(key: &str, value: &Value) -> Option<Result<(), String>> {
+    let result = match (key, value) {
+        ("command" | "url" | "api_key" | "target", Value::String(_)) => Ok(()),
+        ("command" | "url" | "api_key" | "target", _) => Err(expected("a string", value)),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn check_summarize(key: &str, value: &Value) -> Option<Result<(), String>> {
+    let result = match (key, value) {
+        ("command" | "url" | "api_key" | "model" | "prompt", Value::String(_)) => Ok(()),
+        ("command" | "url" | "api_key" | "model" | "prompt", _) => Err(expected("a string", value)),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn check_tag(tag: &str, value: &Value) -> Option<Result<(), String>> {
+    Some(
+        TagRule::try_from((&tag.to_owned(), value))
+            .map(drop)
+            .map_err(|e| e.to_string()),
+    )
+}
+
+fn check_share(name: &str, value: &Value) -> Option<Result<(), String>> {
+    Some(
+        ShareTarget::try_from((&name.to_owned(), value))
+            .map(drop)
+            .map_err(|e| e.to_string()),
+    )
+}
+
+fn check_bridge(host: &str, value: &Value) -> Option<Result<(), String>> {
+    Some(
+        Bridge::try_from((&host.to_owned(), value))
+            .map(drop)
+            .map_err(|e| e.to_string()),
+    )
+}
+
+/// Checks the contents of a config file against the entries moccasin knows
+/// about, so that mistakes can be reported by line rather than silently
+/// ignored. Syntax errors are left to the parser, which already reports them.
+pub fn validate(source: &str, table: &Table) -> Vec<ConfigProblem> {
+    let mut problems = vec![];
+
+    let Ok(sections) = toml::from_str::<Keys>(source) else {
+        return problems;
+    };
+    let mut tables_ok = true;
+    let mut translate_span = None;
+    let mut summarize_span = None;
+    for name in sections.keys() {
+        match name.get_ref().as_str() {
+            "translate" => translate_span = Some(name.span()),
+            "summarize" => summarize_span = Some(name.span()),
+            _ => {}
+        }
+        let known = matches!(
+            name.get_ref().as_str(),
+            "sources"
+                | "bridges"
+                | "tags"
+                | "network"
+                | "fever"
+                | "server"
+                | "translate"
+                | "summarize"
+                | "share"
+                | "preferences"
+        );
+        let section = &table[name.get_ref()];
+        let message = if !known {
+            "unknown table".to_owned()
+        } else if !section.is_table() {
+            tables_ok = false;
+            expected("a table", section)
+        } else {
+            continue;
+        };
+        problems.push(ConfigProblem::new(
+            source,
+            name.span(),
+            format!("[{}]", name.get_ref()),
+            message,
+        ));
+    }
+
+    if !tables_ok {
+        return problems;
+    }
+    let Ok(sections) = toml::from_str::<BTreeMap<String, Keys>>(source) else {
+        return problems;
+    };
+    let mut rules_ok = true;
+    for (section, keys) in &sections {
+        let check = match section.as_str() {
+            "sources" => check_source,
+            "bridges" => check_bridge,
+            "tags" => check_tag,
+            "network" => check_network,
+            "fever" => check_fever,
+            "server" => check_server,
+            "translate" => check_translate,
+            "summarize" => check_summarize,
+            "share" => check_share,
+            "preferences" => check_preference,
+            _ => continue,
+        };
+        for key in keys.keys() {
+            let message = match check(key.get_ref(), &table[section][key.get_ref()]) {
+                Some(Ok(())) => continue,
+                Some(Err(message)) => {
+                    rules_ok &= key.get_ref() != "rules";
+                    message
+                }
+                None => "unknown key".to_owned(),
+            };
+            problems.push(ConfigProblem::new(
+                source,
+                key.span(),
+                format!("[{}].{}", section, key.get_ref()),
+                message,
+            ));
+        }
+    }
+
+    // Keys that are fine on their own may still not make a translator or
+    // summarizer.
+    if let (Some(translate), Some(span)) = (table.get("translate"), translate_span) {
+        if let Err(err) = Translator::try_from(translate) {
+            problems.push(ConfigProblem::new(
+                source,
+                span,
+                "[translate]".to_owned(),
+                err.to_string(),
+            ));
+        }
+    }
+    if let (Some(summarize), Some(span)) = (table.get("summarize"), summarize_span) {
+        if let Err(err) = Summarizer::try_from(summarize) {
+            problems.push(ConfigProblem::new(
+                source,
+                span,
+                "[summarize]".to_owned(),
+                err.to_string(),
+            ));
+        }
+    }
+
+    if !rules_ok {
+        return problems;
+    }
+    if let Ok(RuleKeys {
+        sources: Some(sources),
+    }) = toml::from_str::<RuleKeys>(source)
+    {
+        for url in sources.rules.keys() {
+            let rule = &table["sources"]["rules"][url.get_ref()];
+            if let Err(err) = FeedRule::try_from(rule) {
+                problems.push(ConfigProblem::new(
+                    source,
+                    url.span(),
+                    format!("[sources.rules.\"{}\"]", url.get_ref()),
+                    err.to_string(),
+                ));
+            }
+        }
+    }
+
+    problems.sort_by_key(|problem| problem.line);
+    problems
+}
@@ -0,0 +1,263 @@
+use crate::config::Config;
+use anyhow::{anyhow, bail, Context, Result};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// The longest command a client may send.
+#[cfg(unix)]
+const MAX_COMMAND_SIZE: u64 = 4096;
+
+/// How long a client has to send its command.
+#[cfg(unix)]
+const COMMAND_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// A command sent to a running app over its control socket.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    Refresh,
+    Add(String),
+    /// Opens the feed that best matches a name.
+    GoTo(String),
+    /// Shows a message in the status bar.
+    Notify(String),
+}
+
+#[derive(Debug)]
+pub struct ParseControlCommandError(String);
+
+impl fmt::Display for ParseControlCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for ControlCommand {
+    type Err = ParseControlCommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, arg) = match s.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (s, ""),
+        };
+        let needs = |what: &str| ParseControlCommandError(format!("{} needs {}", name, what));
+        match (name, arg) {
+            ("refresh", "") => Ok(Self::Refresh),
+            ("refresh", _) => Err(ParseControlCommandError("refresh takes no argument".into())),
+            ("add", "") => Err(needs("a URL")),
+            ("add", url) => Ok(Self::Add(url.to_owned())),
+            ("goto", "") => Err(needs("a feed name")),
+            ("goto", feed) => Ok(Self::GoTo(feed.to_owned())),
+            ("notify", "") => Err(needs("a message")),
+            ("notify", message) => Ok(Self::Notify(message.to_owned())),
+            _ => Err(ParseControlCommandError(format!(
+                "unknown command '{}', expected refresh, add, goto or notify",
+                name
+            ))),
+        }
+    }
+}
+
+/// A command from a client, along with the way back to it.
+#[derive(Debug)]
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    reply: oneshot::Sender<Result<String, String>>,
+}
+
+impl ControlRequest {
+    /// Tells the client how the command went.
+    pub fn answer(self, result: Result<String, String>) {
+        let _ = self.reply.send(result);
+    }
+}
+
+/// Where the app listens for commands: `moccasin.sock` in `$XDG_RUNTIME_DIR`,
+/// or in a directory of the user's own under the temporary directory without
+/// one, with the profile in its name for any profile but the default.
+pub fn socket_path(config: &Config) -> PathBuf {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(user_temp_dir);
+    match config.profile() {
+        Some(profile) => dir.join(format!("moccasin-{}.sock", profile)),
+        None => dir.join("moccasin.sock"),
+    }
+}
+
+/// A directory under the temporary directory named for the user, so that
+/// users sharing a machine don't share a socket.
+#[cfg(unix)]
+fn user_temp_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("moccasin-{}", current_uid()))
+}
+
+#[cfg(not(unix))]
+fn user_temp_dir() -> PathBuf {
+    std::env::temp_dir()
+}
+
+#[cfg(unix)]
+fn current_uid() -> u32 {
+    // SAFETY: getuid always succeeds and touches no memory of ours.
+    unsafe { libc::getuid() }
+}
+
+/// Creates `dir` if needed, making sure it belongs to the user and that no
+/// one else may reach into it, since the socket inside it is only as private
+/// as the directory it's in.
+#[cfg(unix)]
+fn ensure_private_dir(dir: &Path) -> Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+
+    if let Err(err) = fs::DirBuilder::new().mode(0o700).create(dir) {
+        if err.kind() != std::io::ErrorKind::AlreadyExists {
+            return Err(err).with_context(|| format!("could not create {}", dir.display()));
+        }
+    }
+    let metadata =
+        fs::symlink_metadata(dir).with_context(|| format!("could not read {}", dir.display()))?;
+    if !metadata.is_dir() || metadata.uid() != current_uid() {
+        bail!("{} is not a directory of your own", dir.display());
+    }
+    if metadata.mode() & 0o077 != 0 {
+        bail!("{} may be reached by other users", dir.display());
+    }
+    Ok(())
+}
+
+/// The socket a running app takes commands on, so that scripts and window
+/// manager keybinds can drive it. Each connection sends one command on a
+/// line, and gets back a line saying what came of it, starting `error: ` if
+/// it failed.
+#[derive(Debug)]
+pub struct ControlSocket {
+    path: PathBuf,
+    rx: mpsc::UnboundedReceiver<ControlRequest>,
+    listener: JoinHandle<()>,
+}
+
+impl ControlSocket {
+    /// Listens on `path`, replacing a socket left behind by an app that
+    /// didn't exit cleanly, but not one that's still answering or that
+    /// belongs to another user.
+    #[cfg(unix)]
+    pub fn bind(path: PathBuf) -> Result<Self> {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+        use tokio::net::UnixListener;
+
+        if let Some(dir) = path.parent() {
+            ensure_private_dir(dir)?;
+        }
+        if let Ok(metadata) = fs::symlink_metadata(&path) {
+            if metadata.uid() != current_uid() {
+                bail!("{} belongs to another user", path.display());
+            }
+            if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+                bail!("moccasin is already listening on {}", path.display());
+            }
+            fs::remove_file(&path)
+                .with_context(|| format!("could not remove stale socket {}", path.display()))?;
+        }
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("could not listen on {}", path.display()))?;
+        // Only the user running the app may drive it. The directory already
+        // keeps others out while the socket still has the umask's permissions.
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let listener = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(serve(stream, tx.clone()));
+                    }
+                    Err(err) => log::warn!("Failed to accept a control connection: {}", err),
+                }
+            }
+        });
+        Ok(Self { path, rx, listener })
+    }
+
+    #[cfg(not(unix))]
+    pub fn bind(_path: PathBuf) -> Result<Self> {
+        bail!("control sockets are only available on Unix")
+    }
+
+    pub async fn recv(&mut self) -> Option<ControlRequest> {
+        self.rx.recv().await
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        self.listener.abort();
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Reads a client's command, hands it to the app, and writes back its answer.
+#[cfg(unix)]
+async fn serve(stream: tokio::net::UnixStream, tx: mpsc::UnboundedSender<ControlRequest>) {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    let mut reader = BufReader::new(reader.take(MAX_COMMAND_SIZE));
+    let answer = match tokio::time::timeout(COMMAND_TIMEOUT, reader.read_line(&mut line)).await {
+        Ok(Ok(_)) => match line.parse::<ControlCommand>() {
+            Ok(command) => {
+                let (reply, answer) = oneshot::channel();
+                let _ = tx.send(ControlRequest { command, reply });
+                answer
+                    .await
+                    .unwrap_or_else(|_| Err("moccasin is exiting".into()))
+            }
+            Err(err) => Err(err.to_string()),
+        },
+        Ok(Err(err)) => Err(err.to_string()),
+        Err(_) => Err("timed out waiting for a command".into()),
+    };
+
+    let answer = match answer {
+        Ok(message) => format!("{}\n", message),
+        Err(message) => format!("error: {}\n", message),
+    };
+    if let Err(err) = writer.write_all(answer.as_bytes()).await {
+        log::warn!("Failed to answer a control connection: {}", err);
+    }
+}
+
+/// Sends a command to the app listening on `path`, returning its answer.
+#[cfg(unix)]
+pub async fn send(path: &Path, command: &str) -> Result<String> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixStream;
+
+    command
+        .parse::<ControlCommand>()
+        .map_err(|err| anyhow!("{}", err))?;
+    let mut stream = UnixStream::connect(path)
+        .await
+        .with_context(|| format!("no moccasin is listening on {}", path.display()))?;
+    stream
+        .write_all(format!("{}\n", command).as_bytes())
+        .await?;
+
+    let mut answer = String::new();
+    stream.read_to_string(&mut answer).await?;
+    match answer.trim_end().strip_prefix("error: ") {
+        Some(err) => bail!("{}", err),
+        None => Ok(answer.trim_end().to_owned()),
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn send(_path: &Path, _command: &str) -> Result<String> {
+    bail!("control sockets are only available on Unix")
+}
@@ -1,7 +1,7 @@
 use crate::app::AppResult;
 use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
-use std::sync::mpsc;
 use std::thread;
+use tokio::sync::mpsc;
 use std::time::{Duration, Instant};
 
 /// Terminal events.
@@ -22,9 +22,9 @@ pub enum Event {
 #[derive(Debug)]
 pub struct EventHandler {
     /// Event sender channel.
-    sender: mpsc::Sender<Event>,
+    sender: mpsc::UnboundedSender<Event>,
     /// Event receiver channel.
-    receiver: mpsc::Receiver<Event>,
+    receiver: mpsc::UnboundedReceiver<Event>,
     /// Event handler thread.
     handler: thread::JoinHandle<()>,
 }
@@ -33,7 +33,7 @@ impl EventHandler {
     /// Constructs a new instance of [`EventHandler`].
     pub fn new(tick_rate: u64) -> Self {
         let tick_rate = Duration::from_millis(tick_rate);
-        let (sender, receiver) = mpsc::channel::<Event>();
+        let (sender, receiver) = mpsc::unbounded_channel::<Event>();
         let handler = {
             let sender = sender.clone();
             thread::spawn(move || {
@@ -71,9 +71,9 @@ impl EventHandler {
 
     /// Receive the next event from the handler thread.
     ///
-    /// This function will always block the current thread if
-    /// there is no data available and it's possible for more data to be sent.
-    pub fn next(&self) -> AppResult<Event> {
-        Ok(self.receiver.recv()?)
+    /// This function will wait until there is data available,
+    /// or return an error if the handler thread has stopped.
+    pub async fn next(&mut self) -> AppResult<Event> {
+        Ok(self.receiver.recv().await.ok_or("event handler stopped")?)
     }
 }
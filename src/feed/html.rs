@@ -1,6 +1,8 @@
+use super::StripRules;
 use anyhow::Result;
 use html_escape::decode_html_entities as decode;
-use html_parser::{Dom, DomVariant, Node};
+use html_parser::{Dom, DomVariant, Element, Node};
+use reqwest::Url;
 
 pub enum HTMLParseError {
     NotParseable,
@@ -43,16 +45,16 @@ fn flatten_html(node: &Node) -> Result<Option<String>, HTMLParseError> {
                 parts.push_str("\n\n");
                 Ok(Some(parts))
             }
-            "b" | "i" | "strong" | "em" | "small" | "span" | "pre" | "code" => {
+            "pre" => Ok(Some(flatten_code(el))),
+            "b" | "i" | "strong" | "em" | "small" | "span" | "code" => {
                 let parts = flatten_nodes(&el.children, true);
                 Ok(Some(parts))
             }
             "ul" | "ol" => {
                 let mut text = String::from("\n");
-                let parts = flatten_nodes(&el.children, true);
-                text.push_str(&parts);
-                text.push_str("\n");
-                Ok(Some(parts))
+                text.push_str(&flatten_list(el, 0));
+                text.push('\n');
+                Ok(Some(text))
             }
             "li" => {
                 let mut text = String::from("- ");
@@ -61,6 +63,7 @@ fn flatten_html(node: &Node) -> Result<Option<String>, HTMLParseError> {
                 text.push_str("\n");
                 Ok(Some(text))
             }
+            "table" => Ok(flatten_table(el)),
             "a" => {
                 let parts = flatten_nodes(&el.children, true);
                 if let Some(href) = el.attributes.get("href") {
@@ -87,10 +90,267 @@ fn flatten_html(node: &Node) -> Result<Option<String>, HTMLParseError> {
     }
 }
 
-pub fn parse_html(content: &str) -> Result<String, HTMLParseError> {
+pub(super) fn collect_text(nodes: &[Node], text: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(s) => text.push_str(&decode(s)),
+            Node::Element(el) => collect_text(&el.children, text),
+            Node::Comment(_) => {}
+        }
+    }
+}
+
+/// Writes a preformatted block out fenced by backticks, as in Markdown, with
+/// the language its `<code>` is marked as, as in `class="language-rust"`.
+fn flatten_code(pre: &Element) -> String {
+    let code = pre.children.iter().find_map(|node| match node {
+        Node::Element(el) if el.name == "code" => Some(el),
+        _ => None,
+    });
+    let language = code
+        .into_iter()
+        .chain([pre])
+        .flat_map(|el| &el.classes)
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+        .unwrap_or_default();
+
+    let mut text = String::new();
+    collect_text(&pre.children, &mut text);
+    format!("```{}\n{}\n```\n\n", language, text.trim_matches('\n'))
+}
+
+/// Writes a list out an item to a line, indented by how deeply it's nested,
+/// with ordered lists numbered from their `start`. Indents are non-breaking
+/// spaces, which the detail pane doesn't trim away.
+fn flatten_list(list: &Element, depth: usize) -> String {
+    let indent = "\u{a0}\u{a0}".repeat(depth);
+    let mut number = list
+        .attributes
+        .get("start")
+        .and_then(|start| start.as_deref()?.trim().parse::<i64>().ok())
+        .unwrap_or(1);
+
+    let mut text = String::new();
+    for node in &list.children {
+        let Node::Element(el) = node else {
+            continue;
+        };
+        // A list right inside another, rather than inside one of its items.
+        if el.name == "ul" || el.name == "ol" {
+            text.push_str(&flatten_list(el, depth + 1));
+            continue;
+        }
+        if el.name != "li" {
+            continue;
+        }
+
+        let marker = match list.name.as_str() {
+            "ol" => format!("{}.", number),
+            _ => "-".to_owned(),
+        };
+        number += 1;
+        let mut body = String::new();
+        let mut nested = String::new();
+        for child in &el.children {
+            match child {
+                Node::Element(sub) if sub.name == "ul" || sub.name == "ol" => {
+                    nested.push_str(&flatten_list(sub, depth + 1))
+                }
+                _ => {
+                    if let Ok(Some(s)) = flatten_html(child) {
+                        body.push_str(&s);
+                    }
+                }
+            }
+        }
+        let body = body.split_whitespace().collect::<Vec<_>>().join(" ");
+        text.push_str(&format!("{}{} {}\n", indent, marker, body));
+        text.push_str(&nested);
+    }
+    text
+}
+
+fn collect_rows<'a>(nodes: &'a [Node], rows: &mut Vec<&'a Element>) {
+    for node in nodes {
+        if let Node::Element(el) = node {
+            match el.name.as_str() {
+                "tr" => rows.push(el),
+                "thead" | "tbody" | "tfoot" => collect_rows(&el.children, rows),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Writes a table out as rows of cells between pipes, with a rule of dashes
+/// under a header row, for the detail pane to lay out as a grid that fits.
+/// Pipes in cells are swapped for broken bars so they can't split them.
+fn flatten_table(table: &Element) -> Option<String> {
+    let mut rows = vec![];
+    collect_rows(&table.children, &mut rows);
+
+    let mut cells = rows
+        .iter()
+        .map(|row| {
+            let cells = row
+                .children
+                .iter()
+                .filter_map(|node| match node {
+                    Node::Element(el) if el.name == "td" || el.name == "th" => Some(el),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let header = !cells.is_empty() && cells.iter().all(|cell| cell.name == "th");
+            let text = cells
+                .iter()
+                .map(|cell| {
+                    flatten_nodes(&cell.children, true)
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        .replace('|', "¦")
+                })
+                .collect::<Vec<_>>();
+            (header, text)
+        })
+        .filter(|(_, text)| !text.is_empty())
+        .collect::<Vec<_>>();
+
+    let columns = cells.iter().map(|(_, text)| text.len()).max()?;
+    let mut flat = String::new();
+    for (i, (header, text)) in cells.iter_mut().enumerate() {
+        text.resize(columns, String::new());
+        flat.push_str(&format!("| {} |\n", text.join(" | ")));
+        if i == 0 && *header {
+            flat.push_str(&format!("|{}\n", "---|".repeat(columns)));
+        }
+    }
+    flat.push('\n');
+    Some(flat)
+}
+
+fn collect_links(nodes: &Vec<Node>, links: &mut Vec<String>) {
+    for node in nodes {
+        if let Node::Element(el) = node {
+            if el.name == "a" {
+                if let Some(Some(href)) = el.attributes.get("href") {
+                    links.push(decode(href).into_owned());
+                }
+            }
+            collect_links(&el.children, links);
+        }
+    }
+}
+
+/// Makes the relative `href` and `src` of every element absolute, resolved
+/// against `base`. Links that already are absolute are left as they were.
+fn resolve_links(nodes: &mut [Node], base: &Url) {
+    for node in nodes {
+        if let Node::Element(el) = node {
+            for name in ["href", "src"] {
+                if let Some(Some(link)) = el.attributes.get_mut(name) {
+                    if let Some(resolved) = resolve_link(base, &decode(link)) {
+                        *link = resolved;
+                    }
+                }
+            }
+            resolve_links(&mut el.children, base);
+        }
+    }
+}
+
+/// A link resolved against `base`, if it's relative.
+pub fn resolve_link(base: &Url, link: &str) -> Option<String> {
+    if Url::parse(link).is_ok() {
+        return None;
+    }
+    base.join(link).ok().map(String::from)
+}
+
+/// Returns the targets of all links in an HTML fragment, in document order.
+pub fn extract_links(content: &str) -> Vec<String> {
+    let mut links = vec![];
+    if let Ok(dom) = Dom::parse(content) {
+        collect_links(&dom.children, &mut links);
+    }
+    links
+}
+
+fn find_element<'a>(nodes: &'a [Node], name: &str) -> Option<&'a Element> {
+    nodes.iter().find_map(|node| match node {
+        Node::Element(el) if el.name == name => Some(el),
+        Node::Element(el) => find_element(&el.children, name),
+        _ => None,
+    })
+}
+
+fn collect_readable(nodes: &Vec<Node>, text: &mut String) {
+    for node in nodes {
+        if let Node::Element(el) = node {
+            match el.name.as_str() {
+                "script" | "style" | "noscript" | "nav" | "header" | "footer" | "aside"
+                | "form" => {}
+                "p" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "ul" | "ol" | "pre" | "table" => {
+                    if let Ok(Some(s)) = flatten_html(node) {
+                        text.push_str(&s);
+                    }
+                }
+                _ => collect_readable(&el.children, text),
+            }
+        }
+    }
+}
+
+/// Extracts the title, readable text and links of a full web page. Text is taken
+/// from the page's `<article>` or `<main>` element if it has one, skipping
+/// navigation and other page chrome.
+pub fn parse_page(
+    content: &str,
+) -> Result<(Option<String>, String, Vec<String>), HTMLParseError> {
+    let dom = Dom::parse(content).map_err(|_| HTMLParseError::NotParseable)?;
+
+    let title = find_element(&dom.children, "title")
+        .map(|el| flatten_nodes(&el.children, true).trim().to_owned())
+        .filter(|title| !title.is_empty());
+
+    let root = ["article", "main", "body"]
+        .iter()
+        .find_map(|name| find_element(&dom.children, name))
+        .map(|el| &el.children)
+        .unwrap_or(&dom.children);
+
+    let mut text = String::new();
+    collect_readable(root, &mut text);
+    if text.trim().is_empty() {
+        return Err(HTMLParseError::NotStringifiable);
+    }
+
+    let mut links = vec![];
+    collect_links(root, &mut links);
+
+    Ok((title, text, links))
+}
+
+/// Flattens an HTML fragment to text, once anything `strip` matches is taken
+/// out of it, with relative links resolved against `base`.
+pub fn parse_html(
+    content: &str,
+    strip: Option<&StripRules>,
+    base: Option<&Url>,
+) -> Result<String, HTMLParseError> {
     match Dom::parse(content) {
-        Ok(dom) => match dom.tree_type {
+        Ok(mut dom) => match dom.tree_type {
             DomVariant::DocumentFragment => {
+                if let Some(strip) = strip {
+                    strip.strip(&mut dom.children);
+                }
+                if let Some(base) = base {
+                    resolve_links(&mut dom.children, base);
+                }
                 let text = dom
                     .children
                     .iter()
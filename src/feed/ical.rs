@@ -0,0 +1,208 @@
+use super::Item;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+
+/// The longest a line of an iCalendar file may be, in bytes, before it has
+/// to be folded onto the next.
+const LINE_LIMIT: usize = 75;
+
+/// An event announced by an item.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CalendarEvent {
+    /// A calendar file attached to or linked from the item, by its URL.
+    Attached(String),
+    /// An event put together from when the item says it takes place.
+    Mentioned(Event),
+}
+
+/// An event read from an item, starting at a time in no particular time zone,
+/// or lasting the day if no time was given.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub uid: String,
+    pub summary: String,
+    pub start: NaiveDateTime,
+    pub all_day: bool,
+    pub url: Option<String>,
+    pub description: Option<String>,
+}
+
+impl Event {
+    /// Writes the event out as an iCalendar file, an hour long unless it
+    /// lasts the day.
+    pub fn to_ics(&self) -> String {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_owned(),
+            "VERSION:2.0".to_owned(),
+            "PRODID:-//moccasin//moccasin//EN".to_owned(),
+            "BEGIN:VEVENT".to_owned(),
+            format!("UID:{}", escape(&self.uid)),
+            format!("DTSTAMP:{}", Utc::now().format("%Y%m%dT%H%M%SZ")),
+        ];
+        if self.all_day {
+            let end = self.start.date() + Duration::days(1);
+            lines.push(format!(
+                "DTSTART;VALUE=DATE:{}",
+                self.start.format("%Y%m%d")
+            ));
+            lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+        } else {
+            let end = self.start + Duration::hours(1);
+            lines.push(format!("DTSTART:{}", self.start.format("%Y%m%dT%H%M%S")));
+            lines.push(format!("DTEND:{}", end.format("%Y%m%dT%H%M%S")));
+        }
+        lines.push(format!("SUMMARY:{}", escape(&self.summary)));
+        if let Some(url) = &self.url {
+            lines.push(format!("URL:{}", url));
+        }
+        if let Some(description) = &self.description {
+            lines.push(format!("DESCRIPTION:{}", escape(description)));
+        }
+        lines.push("END:VEVENT".to_owned());
+        lines.push("END:VCALENDAR".to_owned());
+
+        lines.iter().map(|line| fold(line) + "\r\n").collect()
+    }
+}
+
+/// Finds the event an item announces: a calendar file it comes with, or else
+/// a date it mentions. Dates are everywhere, so one only counts in the text
+/// if it has a time of day too, though one in the title will do without.
+pub(super) fn find_event(item: &Item) -> Option<CalendarEvent> {
+    if let Some(url) = calendar_link(item) {
+        return Some(CalendarEvent::Attached(url));
+    }
+
+    let title = item.title().unwrap_or_default();
+    let (start, all_day) = find_start(title, true)
+        .or_else(|| item.description().and_then(|text| find_start(text, false)))?;
+    Some(CalendarEvent::Mentioned(Event {
+        uid: format!("{}@moccasin", item.id()),
+        summary: title.to_owned(),
+        start,
+        all_day,
+        url: item.link().map(str::to_owned),
+        description: item.description().map(|text| text.trim().to_owned()),
+    }))
+}
+
+/// The calendar file attached to the item, or the first one it links to.
+fn calendar_link(item: &Item) -> Option<String> {
+    let attached = item
+        .enclosure()
+        .filter(|e| e.mime_type == "text/calendar" || is_calendar_url(&e.url))
+        .map(|e| e.url.clone());
+    let url = attached
+        .or_else(|| {
+            item.link()
+                .filter(|link| is_calendar_url(link))
+                .map(str::to_owned)
+        })
+        .or_else(|| item.links().into_iter().find(|link| is_calendar_url(link)))?;
+    Some(match url.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => url,
+    })
+}
+
+fn is_calendar_url(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    url.starts_with("webcal://") || path.to_lowercase().ends_with(".ics")
+}
+
+fn patterns() -> &'static (Regex, Regex) {
+    static PATTERNS: OnceLock<(Regex, Regex)> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let month = "(jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\\.?";
+        (
+            Regex::new(&format!(
+                r"(?i)\b(?:(?P<y>\d{{4}})-(?P<m>\d{{2}})-(?P<d>\d{{2}})|(?P<mon>{month})\s+(?P<md>\d{{1,2}})(?:st|nd|rd|th)?,?\s+(?P<my>\d{{4}})|(?P<dm>\d{{1,2}})(?:st|nd|rd|th)?\s+(?P<dmon>{month}),?\s+(?P<dy>\d{{4}}))"
+            ))
+            .unwrap(),
+            Regex::new(
+                r"(?i)^(?:,?\s*(?:at|from|@)\s*|,?\s+|T)(?P<h>\d{1,2})(?:[:.](?P<min>\d{2}))?(?:\s*(?P<ap>[ap])\.?m\b\.?)?",
+            )
+            .unwrap(),
+        )
+    })
+}
+
+/// The first date in `text` that has a time of day after it, or that has none
+/// should `date_only` allow it, and whether it's only a date.
+fn find_start(text: &str, date_only: bool) -> Option<(NaiveDateTime, bool)> {
+    let (dates, times) = patterns();
+    dates.captures_iter(text).find_map(|caps| {
+        let date = parse_date(&caps)?;
+        let rest = &text[caps.get(0)?.end()..];
+        match times.captures(rest).and_then(|caps| parse_time(&caps)) {
+            Some(time) => Some((date.and_time(time), false)),
+            None if date_only => Some((date.and_time(NaiveTime::MIN), true)),
+            None => None,
+        }
+    })
+}
+
+fn parse_date(caps: &Captures) -> Option<NaiveDate> {
+    let number = |name: &str| caps.name(name)?.as_str().parse::<u32>().ok();
+    let month = |name: &str| {
+        let month = caps.name(name)?.as_str().to_lowercase();
+        let index = [
+            "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+        ]
+        .iter()
+        .position(|m| month.starts_with(m))?;
+        Some(index as u32 + 1)
+    };
+    let (year, month, day) = if caps.name("y").is_some() {
+        (number("y")?, number("m")?, number("d")?)
+    } else if caps.name("mon").is_some() {
+        (number("my")?, month("mon")?, number("md")?)
+    } else {
+        (number("dy")?, month("dmon")?, number("dm")?)
+    };
+    NaiveDate::from_ymd_opt(year as i32, month, day)
+}
+
+/// A time of day, which needs minutes or an "am" or "pm" to tell it apart
+/// from any other number.
+fn parse_time(caps: &Captures) -> Option<NaiveTime> {
+    let hour = caps.name("h")?.as_str().parse::<u32>().ok()?;
+    let minute = caps
+        .name("min")
+        .and_then(|m| m.as_str().parse::<u32>().ok());
+    let hour = match caps.name("ap").map(|ap| ap.as_str().to_ascii_lowercase()) {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(ap) if ap == "p" && hour < 12 => hour + 12,
+        Some(ap) if ap == "a" && hour == 12 => 0,
+        Some(_) => hour,
+        None if minute.is_some() => hour,
+        None => return None,
+    };
+    NaiveTime::from_hms_opt(hour, minute.unwrap_or_default(), 0)
+}
+
+/// Escapes text for an iCalendar property value.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Folds a line longer than the limit onto lines starting with a space,
+/// breaking it between characters rather than within one.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
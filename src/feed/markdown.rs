@@ -0,0 +1,244 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Patterns for the inline parts of a line, compiled once.
+struct Inline {
+    image: Regex,
+    link: Regex,
+    autolink: Regex,
+    code: Regex,
+    strong: Regex,
+    emphasis: Regex,
+    strike: Regex,
+    escape: Regex,
+}
+
+fn inline() -> &'static Inline {
+    static INLINE: OnceLock<Inline> = OnceLock::new();
+    INLINE.get_or_init(|| Inline {
+        image: Regex::new(r"!\[([^\]]*)\]\([^)]*\)").unwrap(),
+        link: Regex::new(r#"\[([^\]]+)\]\(([^)\s]+)(?:\s+"[^"]*")?\)"#).unwrap(),
+        autolink: Regex::new(r"<((?:https?|mailto):[^>\s]+)>").unwrap(),
+        code: Regex::new(r"`([^`]+)`").unwrap(),
+        strong: Regex::new(r"\*\*([^*]+)\*\*|__([^_]+)__").unwrap(),
+        emphasis: Regex::new(r"\*([^*\s][^*]*)\*|\b_([^_]+)_\b").unwrap(),
+        strike: Regex::new(r"~~([^~]+)~~").unwrap(),
+        escape: Regex::new(r"\\([\\`*_{}\[\]()#+\-.!|~>])").unwrap(),
+    })
+}
+
+fn block_patterns() -> &'static (Regex, Regex, Regex) {
+    static BLOCKS: OnceLock<(Regex, Regex, Regex)> = OnceLock::new();
+    BLOCKS.get_or_init(|| {
+        (
+            Regex::new(r"^(#{1,6})\s+(.*?)(?:\s+#+)?\s*$").unwrap(),
+            Regex::new(r"^(\s*)([-*+]|\d{1,9}[.)])\s+(.*)$").unwrap(),
+            Regex::new(r"<(?:[a-zA-Z][a-zA-Z0-9]*(?:\s[^>]*)?/?|/[a-zA-Z][a-zA-Z0-9]*)>").unwrap(),
+        )
+    })
+}
+
+/// Whether some text reads as Markdown rather than HTML or plain text: it has
+/// no HTML elements, but does have headings, lists, fences or links.
+pub fn looks_like_markdown(content: &str) -> bool {
+    let (heading, item, tag) = block_patterns();
+    let inline = inline();
+    if tag.is_match(content) {
+        return false;
+    }
+
+    content.lines().any(|line| {
+        let line = line.trim_end();
+        heading.is_match(line)
+            || item.is_match(line)
+            || line.starts_with("```")
+            || line.starts_with("~~~")
+            || inline.link.is_match(line)
+            || inline.strong.is_match(line)
+    })
+}
+
+/// Reduces the inline markup of a line to its text, giving links as
+/// `text (url)` the same as HTML anchors are.
+fn flatten_inline(line: &str) -> String {
+    let inline = inline();
+    let line = inline.image.replace_all(line, "$1");
+    let line = inline.link.replace_all(&line, "$1 ($2)");
+    let line = inline.autolink.replace_all(&line, "$1");
+    let line = inline.code.replace_all(&line, "$1");
+    let line = inline.strong.replace_all(&line, "$1$2");
+    let line = inline.emphasis.replace_all(&line, "$1$2");
+    let line = inline.strike.replace_all(&line, "$1");
+    inline.escape.replace_all(&line, "$1").into_owned()
+}
+
+/// Whether a line is a thematic break, like `---` or `* * *`.
+fn is_break(line: &str) -> bool {
+    let marks = line
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    marks.len() >= 3
+        && ["-", "*", "_"]
+            .iter()
+            .any(|mark| marks.chars().all(|c| c.to_string() == *mark))
+}
+
+/// Writes a table row out between pipes, with the alignment colons dropped
+/// from the rule under its header.
+fn flatten_row(line: &str) -> String {
+    let inner = line.trim().trim_start_matches('|').trim_end_matches('|');
+    let cells = inner.split('|').map(str::trim).collect::<Vec<_>>();
+    let is_rule = cells.iter().all(|cell| {
+        let dashes = cell.trim_matches(':');
+        !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+    });
+
+    let cells = cells
+        .iter()
+        .map(|cell| {
+            if is_rule {
+                "---".to_owned()
+            } else {
+                flatten_inline(cell).replace('|', "¦")
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("| {} |", cells.join(" | "))
+}
+
+fn flush_paragraph(paragraph: &mut Vec<String>, text: &mut String) {
+    if !paragraph.is_empty() {
+        text.push_str(&flatten_inline(&paragraph.join(" ")));
+        text.push_str("\n\n");
+        paragraph.clear();
+    }
+}
+
+/// Flattens Markdown into the same text as HTML is, so it's laid out and
+/// styled alike in the detail pane.
+pub fn parse_markdown(content: &str) -> String {
+    let (heading, item, _) = block_patterns();
+    let mut text = String::new();
+    let mut paragraph: Vec<String> = vec![];
+    let mut fence: Option<&str> = None;
+    // Lists, tables and quotes run line by line, and are closed off by a
+    // blank line.
+    let mut in_block = false;
+
+    for line in content.lines() {
+        if let Some(marker) = fence {
+            if line.trim_start().starts_with(marker) {
+                text.push_str("```\n\n");
+                fence = None;
+            } else {
+                text.push_str(line);
+                text.push('\n');
+            }
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            flush_paragraph(&mut paragraph, &mut text);
+            let lang = trimmed.trim_start_matches(marker).trim();
+            text.push_str("```");
+            text.push_str(lang.split_whitespace().next().unwrap_or_default());
+            text.push('\n');
+            fence = Some(marker);
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            flush_paragraph(&mut paragraph, &mut text);
+            if in_block {
+                text.push('\n');
+                in_block = false;
+            }
+            continue;
+        }
+
+        // Setext headings underline the paragraph before them.
+        if !paragraph.is_empty() && trimmed.chars().all(|c| c == '=' || c == '-') {
+            let level = if trimmed.starts_with('=') { "#" } else { "##" };
+            let title = flatten_inline(&paragraph.join(" "));
+            text.push_str(&format!("{} {}\n\n", level, title));
+            paragraph.clear();
+            continue;
+        }
+
+        if is_break(trimmed) {
+            flush_paragraph(&mut paragraph, &mut text);
+            continue;
+        }
+
+        if let Some(caps) = heading.captures(trimmed) {
+            flush_paragraph(&mut paragraph, &mut text);
+            text.push_str(&format!("{} {}\n\n", &caps[1], flatten_inline(&caps[2])));
+            continue;
+        }
+
+        if let Some(caps) = item.captures(line) {
+            flush_paragraph(&mut paragraph, &mut text);
+            if !in_block {
+                text.push('\n');
+                in_block = true;
+            }
+            let indent = caps[1].replace('\t', "    ").len() / 2;
+            let marker = match &caps[2] {
+                "-" | "*" | "+" => "-".to_owned(),
+                number => format!("{}.", &number[..number.len() - 1]),
+            };
+            text.push_str(&"\u{a0}\u{a0}".repeat(indent));
+            text.push_str(&format!("{} {}\n", marker, flatten_inline(&caps[3])));
+            continue;
+        }
+
+        if trimmed.starts_with('|') {
+            flush_paragraph(&mut paragraph, &mut text);
+            text.push_str(&flatten_row(trimmed));
+            text.push('\n');
+            in_block = true;
+            continue;
+        }
+
+        if let Some(quote) = trimmed.strip_prefix('>') {
+            flush_paragraph(&mut paragraph, &mut text);
+            text.push_str(&format!("> {}\n", flatten_inline(quote.trim())));
+            in_block = true;
+            continue;
+        }
+
+        paragraph.push(trimmed.to_owned());
+    }
+
+    flush_paragraph(&mut paragraph, &mut text);
+    if fence.is_some() {
+        text.push_str("```\n");
+    }
+    text
+}
+
+/// Links found in Markdown, in the order they appear, leaving out images.
+pub fn extract_links(content: &str) -> Vec<String> {
+    let inline = inline();
+    let content = inline.image.replace_all(content, "");
+    let mut links = inline
+        .link
+        .captures_iter(&content)
+        .chain(inline.autolink.captures_iter(&content))
+        .filter_map(|caps| caps.get(caps.len() - 1))
+        .map(|m| (m.start(), m.as_str().to_owned()))
+        .collect::<Vec<_>>();
+    links.sort_by_key(|(start, _)| *start);
+    links.into_iter().map(|(_, link)| link).collect()
+}
+
+/// The first top-level heading of a Markdown document, for its title.
+pub fn find_title(content: &str) -> Option<String> {
+    content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("# "))
+        .map(|title| flatten_inline(title.trim()))
+        .filter(|title| !title.is_empty())
+}
@@ -1,10 +1,21 @@
+use crate::util;
 use anyhow;
 use chrono::prelude::*;
+use reqwest::Url;
 use rss::{Channel, Item as ChannelItem};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::io::BufRead;
 
+pub use ical::{CalendarEvent, Event};
+pub use strip::{ParseStripRuleError, StripRules};
+
 mod html;
+mod ical;
+mod markdown;
+mod strip;
+#[cfg(test)]
+mod tests;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Feed {
@@ -15,10 +26,19 @@ pub struct Feed {
     pub(crate) url: String,
     pub(crate) link: String,
     pub(crate) ttl: Option<String>,
+    /// The language the feed says it's written in, like `en-us`.
+    pub(crate) language: Option<String>,
     #[serde(skip)]
     pub(crate) items: Vec<Item>,
     pub(crate) pub_date: Option<String>,
     pub(crate) last_fetched: Option<String>,
+    #[serde(skip)]
+    pub(crate) item_count: usize,
+    #[serde(skip)]
+    pub(crate) unread_count: usize,
+    /// The previous page of an RFC 5005 archived feed, if advertised.
+    #[serde(skip)]
+    pub(crate) prev_archive: Option<String>,
 }
 
 impl Feed {
@@ -42,6 +62,10 @@ impl Feed {
         &self.url
     }
 
+    pub(crate) fn set_url(&mut self, url: &str) {
+        self.url = url.to_owned();
+    }
+
     pub fn link(&self) -> &str {
         &self.link
     }
@@ -50,6 +74,12 @@ impl Feed {
         self.ttl.as_deref()
     }
 
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Items of this feed, if they are loaded. Feeds read from storage only
+    /// carry a summary, and their items are loaded on demand.
     pub fn items(&self) -> &[Item] {
         &self.items
     }
@@ -62,12 +92,68 @@ impl Feed {
         self.last_fetched.as_deref()
     }
 
+    pub fn item_count(&self) -> usize {
+        self.item_count
+    }
+
+    pub fn unread_count(&self) -> usize {
+        self.unread_count
+    }
+
     pub fn with_items(mut self, items: Vec<Item>) -> Self {
+        self.item_count = items.len();
+        self.unread_count = items.iter().filter(|i| !i.read()).count();
         self.items = items;
         self
     }
 
-    fn from_channel_with_url(value: Channel, url: String) -> Self {
+    /// The previous archive page of this feed, if it advertises one.
+    pub fn prev_archive(&self) -> Option<&str> {
+        self.prev_archive.as_deref()
+    }
+
+    /// Adds the items of an archive page of this feed that it doesn't already have.
+    pub(crate) fn absorb_archive(&mut self, archive: Feed) {
+        let ids = self
+            .items
+            .iter()
+            .map(|item| item.id().to_owned())
+            .collect::<HashSet<_>>();
+        for mut item in archive.items {
+            if !ids.contains(item.id()) {
+                item.feed_id = self.id.clone();
+                self.items.push(item);
+            }
+        }
+        self.item_count = self.items.len();
+        self.unread_count = self.items.iter().filter(|i| !i.read()).count();
+    }
+
+    /// Takes tracking parameters out of the links of its items, leaving their
+    /// ids as they were.
+    pub(crate) fn clean_links(&mut self, params: &[String]) {
+        for item in self.items.iter_mut() {
+            if let Some(link) = &item.link {
+                item.link = Some(util::clean_url(link, params));
+            }
+        }
+    }
+
+    /// Drops loaded items, keeping only the title and counts.
+    pub(crate) fn into_summary(mut self) -> Self {
+        self.items = vec![];
+        self
+    }
+
+    pub(crate) fn restore_read_state(&mut self, read_ids: &HashSet<String>) {
+        for item in self.items.iter_mut() {
+            item.read = read_ids.contains(item.id());
+        }
+        self.unread_count = self.items.iter().filter(|i| !i.read()).count();
+    }
+
+    fn from_channel_with_url(value: Channel, url: String, strip: Option<&StripRules>) -> Self {
+        let site = Url::parse(value.link()).ok();
         let id = value
             .dublin_core_ext()
             .and_then(|dc| {
@@ -79,12 +165,25 @@ impl Feed {
             })
             .unwrap_or(value.link().to_owned());
 
+        let prev_archive = value.atom_ext().and_then(|atom| {
+            atom.links()
+                .iter()
+                .find(|link| link.rel() == "prev-archive")
+                .map(|link| link.href().to_owned())
+        });
+
         Self {
             title: value.title.clone(),
             description: value.description.clone(),
             url: url,
             link: value.link.clone(),
             ttl: value.ttl.clone(),
+            language: value
+                .language
+                .as_deref()
+                .map(str::trim)
+                .filter(|language| !language.is_empty())
+                .map(str::to_owned),
             categories: value
                 .categories
                 .iter()
@@ -93,29 +192,93 @@ impl Feed {
                     domain: c.domain.clone(),
                 })
                 .collect(),
+            item_count: value.items.len(),
+            unread_count: value.items.len(),
             items: value
                 .items
                 .iter()
-                .map(|i| Item::with_parent(id.as_str(), i))
+                .map(|i| Item::with_parent(id.as_str(), i, strip, site.as_ref()))
                 .collect(),
             pub_date: value
                 .pub_date
                 .and_then(|s| DateTime::parse_from_rfc2822(&s).ok())
                 .and_then(|s| Some(DateTime::to_rfc2822(&s))),
             last_fetched: None,
+            prev_archive,
             id,
         }
     }
 
     pub fn read_from<R: BufRead>(reader: R, url: String) -> anyhow::Result<Feed> {
+        Feed::read_stripped(reader, url, None)
+    }
+
+    /// Reads a feed, taking what `strip` matches out of its items' HTML before
+    /// it's flattened to text.
+    pub fn read_stripped<R: BufRead>(
+        reader: R,
+        url: String,
+        strip: Option<&StripRules>,
+    ) -> anyhow::Result<Feed> {
         let channel = Channel::read_from(reader)?;
-        let mut feed = Feed::from_channel_with_url(channel, url);
+        let mut feed = Feed::from_channel_with_url(channel, url, strip);
         feed.last_fetched = Some(Local::now().to_rfc2822());
         Ok(feed)
     }
 }
 
-#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// A web page followed from an item, reduced to its readable text.
+#[derive(Clone, Debug)]
+pub struct Page {
+    pub(crate) url: String,
+    pub(crate) title: Option<String>,
+    pub(crate) text: String,
+    pub(crate) links: Vec<String>,
+}
+
+impl Page {
+    pub fn from_html(url: &str, content: &str) -> Option<Self> {
+        let (title, text, links) = html::parse_page(content).ok()?;
+        Some(Self {
+            url: url.to_owned(),
+            title,
+            text,
+            links,
+        })
+    }
+
+    /// Reads a page served as Markdown, titling it after its first heading.
+    pub fn from_markdown(url: &str, content: &str) -> Option<Self> {
+        let text = markdown::parse_markdown(content);
+        if text.trim().is_empty() {
+            return None;
+        }
+        Some(Self {
+            url: url.to_owned(),
+            title: markdown::find_title(content),
+            text,
+            links: markdown::extract_links(content),
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn links(&self) -> &[String] {
+        &self.links
+    }
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Item {
     pub(crate) id: String,
     pub(crate) feed_id: String,
@@ -127,6 +290,15 @@ pub struct Item {
     pub(crate) categories: Vec<Category>,
     pub(crate) link: Option<String>,
     pub(crate) pub_date: Option<String>,
+    /// A file attached to the item, like a podcast episode or calendar event.
+    #[serde(default)]
+    pub(crate) enclosure: Option<Enclosure>,
+    #[serde(default)]
+    pub(crate) read: bool,
+    /// Whether the feed has republished the item with changes since it was
+    /// first seen.
+    #[serde(default)]
+    pub(crate) updated: bool,
 }
 
 impl Item {
@@ -170,7 +342,56 @@ impl Item {
         self.pub_date.as_deref()
     }
 
-    fn with_parent(feed_id: &str, value: &ChannelItem) -> Self {
+    /// Links found in the item's content, or its description if it has none,
+    /// with relative links resolved against the item's own link.
+    pub fn links(&self) -> Vec<String> {
+        let links = self
+            .content
+            .as_deref()
+            .or(self.description.as_deref())
+            .map(|content| {
+                if markdown::looks_like_markdown(content) {
+                    markdown::extract_links(content)
+                } else {
+                    html::extract_links(content)
+                }
+            })
+            .unwrap_or_default();
+        match self.link.as_deref().and_then(|link| Url::parse(link).ok()) {
+            Some(base) => links
+                .into_iter()
+                .map(|link| html::resolve_link(&base, &link).unwrap_or(link))
+                .collect(),
+            None => links,
+        }
+    }
+
+    pub fn enclosure(&self) -> Option<&Enclosure> {
+        self.enclosure.as_ref()
+    }
+
+    /// The event the item announces, if it has a calendar file or mentions
+    /// when it takes place.
+    pub fn event(&self) -> Option<CalendarEvent> {
+        ical::find_event(self)
+    }
+
+    pub fn read(&self) -> bool {
+        self.read
+    }
+
+    pub fn updated(&self) -> bool {
+        self.updated
+    }
+
+    /// Maps an item of the feed `feed_id`, whose site is at `site`. Relative
+    /// links in its HTML are resolved against its own link if it has one.
+    fn with_parent(
+        feed_id: &str,
+        value: &ChannelItem,
+        strip: Option<&StripRules>,
+        site: Option<&Url>,
+    ) -> Self {
         let id = value
             .guid()
             .and_then(|g| {
@@ -212,8 +433,16 @@ impl Item {
                 }
             }));
 
+        let base = value
+            .link()
+            .and_then(|link| Url::parse(link).ok())
+            .or_else(|| site.cloned());
         let text_description = if let Some(d) = value.description() {
-            html::parse_html(&d).ok()
+            if markdown::looks_like_markdown(d) {
+                Some(markdown::parse_markdown(d))
+            } else {
+                html::parse_html(d, strip, base.as_ref()).ok()
+            }
         } else {
             None
         };
@@ -236,6 +465,12 @@ impl Item {
                 .collect(),
             link: value.link.clone(),
             pub_date: value.pub_date.clone(),
+            enclosure: value.enclosure.as_ref().map(|e| Enclosure {
+                url: e.url.clone(),
+                mime_type: e.mime_type.clone(),
+            }),
+            read: false,
+            updated: false,
         }
     }
 }
@@ -284,7 +519,13 @@ impl Item {
 //     }
 // }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Enclosure {
+    pub url: String,
+    pub mime_type: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Category {
     pub name: String,
     pub domain: Option<String>,
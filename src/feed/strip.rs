@@ -0,0 +1,215 @@
+use super::html::collect_text;
+use html_parser::{Element, Node};
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub struct ParseStripRuleError(String);
+
+impl fmt::Display for ParseStripRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Elements to take out of a feed's item HTML before it's flattened to text:
+/// those matching any of a few selectors, and those whose whole text matches
+/// a pattern, like the "The post X appeared first on Y" some blogs append.
+#[derive(Debug, Default, Clone)]
+pub struct StripRules {
+    selectors: Vec<Selector>,
+    patterns: Vec<Regex>,
+}
+
+impl StripRules {
+    pub fn new(selectors: &[String], patterns: &[String]) -> Result<Self, ParseStripRuleError> {
+        let selectors = selectors
+            .iter()
+            .flat_map(|list| list.split(','))
+            .map(|selector| selector.trim().parse())
+            .collect::<Result<_, _>>()?;
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(&format!("^(?:{})$", pattern)).map_err(|_| {
+                    ParseStripRuleError(format!("'{}' is not a valid pattern", pattern))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            selectors,
+            patterns,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selectors.is_empty() && self.patterns.is_empty()
+    }
+
+    /// Takes the nodes these rules match out of a tree, and out of what's
+    /// left of it below them.
+    pub(super) fn strip(&self, nodes: &mut Vec<Node>) {
+        nodes.retain(|node| !self.matches(node));
+        for node in nodes {
+            if let Node::Element(el) = node {
+                self.strip(&mut el.children);
+            }
+        }
+    }
+
+    fn matches(&self, node: &Node) -> bool {
+        if let Node::Element(el) = node {
+            if self.selectors.iter().any(|selector| selector.matches(el)) {
+                return true;
+            }
+        }
+        if self.patterns.is_empty() || matches!(node, Node::Comment(_)) {
+            return false;
+        }
+
+        let mut text = String::new();
+        collect_text(std::slice::from_ref(node), &mut text);
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        !text.is_empty() && self.patterns.iter().any(|pattern| pattern.is_match(&text))
+    }
+}
+
+/// A simple CSS selector: an optional element name, and any number of
+/// `.class`, `#id` and `[attribute]` conditions, which can test values with
+/// `=`, `*=`, `^=` or `$=`. There are no combinators.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Selector {
+    name: Option<String>,
+    conditions: Vec<Condition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Class(String),
+    Id(String),
+    Attribute(String, Option<(Operator, String)>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Equals,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+impl Selector {
+    fn matches(&self, el: &Element) -> bool {
+        if let Some(name) = &self.name {
+            if !el.name.eq_ignore_ascii_case(name) {
+                return false;
+            }
+        }
+        let id = el
+            .id
+            .clone()
+            .or_else(|| el.attributes.get("id").cloned().flatten());
+        self.conditions.iter().all(|condition| match condition {
+            Condition::Class(class) => el.classes.contains(class),
+            Condition::Id(expected) => id.as_ref() == Some(expected),
+            Condition::Attribute(name, test) => {
+                let value = match name.as_str() {
+                    "class" if !el.classes.is_empty() => Some(el.classes.join(" ")),
+                    "id" => id.clone(),
+                    _ => el
+                        .attributes
+                        .get(name)
+                        .map(|value| value.clone().unwrap_or_default()),
+                };
+                match (value, test) {
+                    (None, _) => false,
+                    (Some(_), None) => true,
+                    (Some(value), Some((operator, expected))) => match operator {
+                        Operator::Equals => &value == expected,
+                        Operator::Contains => value.contains(expected.as_str()),
+                        Operator::StartsWith => value.starts_with(expected.as_str()),
+                        Operator::EndsWith => value.ends_with(expected.as_str()),
+                    },
+                }
+            }
+        })
+    }
+}
+
+impl FromStr for Selector {
+    type Err = ParseStripRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = |reason: &str| ParseStripRuleError(format!("'{}' {}", s, reason));
+
+        let mut selector = Selector::default();
+        let mut rest = s;
+        if let Some(after) = rest.strip_prefix('*') {
+            rest = after;
+        } else if rest.starts_with(is_name_char) {
+            let (name, after) = take_name(rest);
+            selector.name = Some(name);
+            rest = after;
+        }
+
+        while let Some(c) = rest.chars().next() {
+            rest = &rest[c.len_utf8()..];
+            match c {
+                '.' | '#' => {
+                    let (name, after) = take_name(rest);
+                    if name.is_empty() {
+                        return Err(bad("has a class or id without a name"));
+                    }
+                    selector.conditions.push(match c {
+                        '.' => Condition::Class(name),
+                        _ => Condition::Id(name),
+                    });
+                    rest = after;
+                }
+                '[' => {
+                    let end = rest.find(']').ok_or_else(|| bad("has an unclosed ["))?;
+                    let inner = &rest[..end];
+                    rest = &rest[end + 1..];
+                    let condition = match inner.split_once('=') {
+                        None => Condition::Attribute(inner.trim().to_owned(), None),
+                        Some((name, value)) => {
+                            let name = name.trim();
+                            let (name, operator) = match name.chars().last() {
+                                Some('*') => (&name[..name.len() - 1], Operator::Contains),
+                                Some('^') => (&name[..name.len() - 1], Operator::StartsWith),
+                                Some('$') => (&name[..name.len() - 1], Operator::EndsWith),
+                                _ => (name, Operator::Equals),
+                            };
+                            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+                            Condition::Attribute(
+                                name.trim_end().to_owned(),
+                                Some((operator, value.to_owned())),
+                            )
+                        }
+                    };
+                    if matches!(&condition, Condition::Attribute(name, _) if name.is_empty()) {
+                        return Err(bad("has an attribute without a name"));
+                    }
+                    selector.conditions.push(condition);
+                }
+                _ => return Err(bad("is not a selector this understands")),
+            }
+        }
+
+        if selector.name.is_none() && selector.conditions.is_empty() && !s.starts_with('*') {
+            return Err(bad("is not a selector"));
+        }
+        Ok(selector)
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Splits a name off the start of the rest of a selector.
+fn take_name(rest: &str) -> (String, &str) {
+    let end = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+    (rest[..end].to_owned(), &rest[end..])
+}
@@ -0,0 +1,116 @@
+use super::html::{parse_html, HTMLParseError};
+use super::{Feed, StripRules};
+use crate::golden;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixtures(dir: &str) -> Vec<PathBuf> {
+    let mut paths = fs::read_dir(golden::fixtures(dir))
+        .expect("Could not read fixtures")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(true, |ext| ext != "golden"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+/// The golden file of a fixture, named after it.
+fn golden_path(fixture: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.golden", fixture.display()))
+}
+
+/// Lays out every field mapped from a feed, leaving out when it was fetched.
+fn describe(feed: &Feed) -> String {
+    let mut out = String::new();
+    writeln!(out, "id: {:?}", feed.id).unwrap();
+    writeln!(out, "title: {:?}", feed.title).unwrap();
+    writeln!(out, "description: {:?}", feed.description).unwrap();
+    writeln!(out, "url: {:?}", feed.url).unwrap();
+    writeln!(out, "link: {:?}", feed.link).unwrap();
+    writeln!(out, "ttl: {:?}", feed.ttl).unwrap();
+    writeln!(out, "language: {:?}", feed.language).unwrap();
+    writeln!(out, "pub_date: {:?}", feed.pub_date).unwrap();
+    writeln!(out, "categories: {:?}", feed.categories).unwrap();
+    writeln!(out, "prev_archive: {:?}", feed.prev_archive).unwrap();
+    writeln!(out, "item_count: {}", feed.item_count).unwrap();
+    for item in &feed.items {
+        writeln!(out).unwrap();
+        writeln!(out, "item: {:?}", item.id).unwrap();
+        writeln!(out, "  feed_id: {:?}", item.feed_id).unwrap();
+        writeln!(out, "  title: {:?}", item.title).unwrap();
+        writeln!(out, "  author: {:?}", item.author).unwrap();
+        writeln!(out, "  link: {:?}", item.link).unwrap();
+        writeln!(out, "  pub_date: {:?}", item.pub_date).unwrap();
+        writeln!(out, "  categories: {:?}", item.categories).unwrap();
+        writeln!(out, "  enclosure: {:?}", item.enclosure).unwrap();
+        writeln!(out, "  event: {:?}", item.event()).unwrap();
+        writeln!(out, "  description: {:?}", item.description).unwrap();
+        writeln!(out, "  content: {:?}", item.content).unwrap();
+        writeln!(out, "  text_description: {:?}", item.text_description).unwrap();
+    }
+    out
+}
+
+#[test]
+fn feeds_match_golden_files() {
+    let failures = fixtures("feeds")
+        .into_iter()
+        .filter_map(|path| {
+            let bytes = fs::read(&path).unwrap();
+            let name = path.file_name().unwrap().to_string_lossy();
+            let url = format!("https://fixtures.example/{}", name);
+            let actual = match Feed::read_from(&bytes[..], url) {
+                Ok(feed) => describe(&feed),
+                Err(_) => "not a feed\n".to_owned(),
+            };
+            golden::check(&golden_path(&path), &actual)
+        })
+        .collect();
+    golden::assert_all(failures);
+}
+
+#[test]
+fn html_matches_golden_files() {
+    let failures = fixtures("html")
+        .into_iter()
+        .filter_map(|path| {
+            let content = fs::read_to_string(&path).unwrap();
+            let actual = match parse_html(content.trim_end(), None, None) {
+                Ok(text) => text,
+                Err(HTMLParseError::NotParseable) => "not parseable\n".to_owned(),
+                Err(HTMLParseError::NotStringifiable) => "not stringifiable\n".to_owned(),
+            };
+            golden::check(&golden_path(&path), &actual)
+        })
+        .collect();
+    golden::assert_all(failures);
+}
+
+#[test]
+fn strip_rules_take_out_matching_elements() {
+    let rules = StripRules::new(
+        &[
+            ".share, img[width=1]".to_owned(),
+            "div[class*=promo]".to_owned(),
+        ],
+        &["The post .* appeared first on .*\\.".to_owned()],
+    )
+    .unwrap();
+    let html = concat!(
+        r#"<p>Actual text.</p>"#,
+        r#"<div class="share buttons"><a href="https://share.example">Share</a></div>"#,
+        r#"<div class="sidebar-promo">Subscribe!</div>"#,
+        r#"<p>The post <a href="https://blog.example/1">Hello</a> appeared first on <a href="https://blog.example">Blog</a>.</p>"#,
+    );
+
+    let text = parse_html(html, Some(&rules), None).ok().unwrap();
+    assert_eq!(text, "Actual text.\n\n");
+}
+
+#[test]
+fn strip_rules_reject_bad_selectors() {
+    assert!(StripRules::new(&["div > p".to_owned()], &[]).is_err());
+    assert!(StripRules::new(&["[class".to_owned()], &[]).is_err());
+    assert!(StripRules::new(&[], &["(unclosed".to_owned()]).is_err());
+}
@@ -0,0 +1,41 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Set to rewrite golden files from what is produced now, once any difference
+/// has been checked to be intended.
+const BLESS: &str = "MOCCASIN_BLESS";
+
+/// The directory of fixtures, and the golden files kept with them, by name.
+pub fn fixtures(dir: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(dir)
+}
+
+/// Compares output with the golden file at `path`, returning a description of
+/// the difference if there is one.
+pub fn check(path: &Path, actual: &str) -> Option<String> {
+    if std::env::var_os(BLESS).is_some() {
+        fs::write(path, actual).expect("Could not write golden file");
+        return None;
+    }
+
+    match fs::read_to_string(path) {
+        Ok(expected) if expected == actual => None,
+        Ok(expected) => Some(format!(
+            "{} differs\n--- expected\n{}\n--- actual\n{}",
+            path.display(),
+            expected,
+            actual
+        )),
+        Err(_) => Some(format!(
+            "{} is missing, run with {}=1 to write it",
+            path.display(),
+            BLESS
+        )),
+    }
+}
+
+pub fn assert_all(failures: Vec<String>) {
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
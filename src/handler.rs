@@ -1,4 +1,4 @@
-use crate::app::{App, AppResult};
+use crate::app::{App, AppResult, FeedFilter, MarkAction, View};
 use crossterm::event::{
     KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
 };
@@ -12,6 +12,10 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
         }
     }
 
+    app.invalidate();
+    app.hold_dashboard();
+    app.note_location();
+
     if app.should_render_console() {
         match key_event.code {
             KeyCode::Char('c') | KeyCode::Char('C')
@@ -19,7 +23,22 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
             {
                 app.quit();
             }
+            KeyCode::Enter if app.has_search_results() => app.open_search_result(),
             KeyCode::Enter => app.submit_command(),
+            KeyCode::Down => app.next_search_result(),
+            KeyCode::Up => app.prev_search_result(),
+            KeyCode::Char('j') | KeyCode::Char('n')
+                if key_event.modifiers == KeyModifiers::CONTROL =>
+            {
+                app.next_search_result();
+            }
+            KeyCode::Char('k') | KeyCode::Char('p')
+                if key_event.modifiers == KeyModifiers::CONTROL =>
+            {
+                app.prev_search_result();
+            }
+            KeyCode::Tab => app.complete(true),
+            KeyCode::BackTab => app.complete(false),
             KeyCode::Char(to_insert) => {
                 app.enter_char(to_insert);
             }
@@ -40,6 +59,90 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
         return Ok(());
     }
 
+    if app.stats.is_some() {
+        match key_event.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') => app.quit(),
+            _ => app.close_stats(),
+        }
+        return Ok(());
+    }
+
+    if app.monitor.is_some() {
+        match key_event.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') => app.quit(),
+            _ => app.close_monitor(),
+        }
+        return Ok(());
+    }
+
+    if app.preview.is_some() {
+        match key_event.code {
+            KeyCode::Char('c') | KeyCode::Char('C')
+                if key_event.modifiers == KeyModifiers::CONTROL =>
+            {
+                app.quit();
+            }
+            KeyCode::Enter | KeyCode::Char('y') => app.confirm_preview(),
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => app.cancel_preview(),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if app.batch.is_some() {
+        match key_event.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') => app.quit(),
+            _ => app.close_batch(),
+        }
+        return Ok(());
+    }
+
+    if app.show_inspector {
+        match key_event.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') => app.quit(),
+            _ => app.toggle_inspector(),
+        }
+        return Ok(());
+    }
+
+    if app.log_lines.is_some() {
+        match key_event.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') => app.quit(),
+            _ => app.close_log(),
+        }
+        return Ok(());
+    }
+
+    if let Some(compose) = app.compose.as_mut() {
+        match key_event.code {
+            KeyCode::Char('c') | KeyCode::Char('C')
+                if key_event.modifiers == KeyModifiers::CONTROL =>
+            {
+                app.quit();
+            }
+            KeyCode::Enter if key_event.modifiers == KeyModifiers::ALT => compose.insert('\n'),
+            KeyCode::Enter => app.send_post(),
+            KeyCode::Esc => app.close_compose(),
+            KeyCode::Backspace => compose.backspace(),
+            KeyCode::Left => compose.move_cursor(false),
+            KeyCode::Right => compose.move_cursor(true),
+            KeyCode::Char(c) => compose.insert(c),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if app.share_menu.is_some() {
+        match key_event.code {
+            KeyCode::Down | KeyCode::Char('j') => app.step_share_target(true),
+            KeyCode::Up | KeyCode::Char('k') => app.step_share_target(false),
+            KeyCode::Enter => app.share_selected(),
+            KeyCode::Char(n @ '1'..='9') => app.share(n as usize - '1' as usize),
+            _ => app.close_share_menu(),
+        }
+        return Ok(());
+    }
+
     if app.show_keybinds {
         match key_event.code {
             // Exit application on `q`
@@ -53,6 +156,30 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
         }
     }
 
+    // A dead feed being offered for unsubscribing takes an answer, while every
+    // other key works as usual.
+    if !app.dead_feeds.is_empty() {
+        match key_event.code {
+            KeyCode::Char('y') => {
+                app.answer_dead_feed(true);
+                return Ok(());
+            }
+            KeyCode::Char('n') => {
+                app.answer_dead_feed(false);
+                return Ok(());
+            }
+            _ => {}
+        }
+    }
+
+    // The key after `m` or `'` names the mark, and any other cancels it.
+    if let Some(action) = app.jumps.pending.take() {
+        if let KeyCode::Char(letter) = key_event.code {
+            app.use_mark(action, letter);
+        }
+        return Ok(());
+    }
+
     match key_event.code {
         // Exit application on `q`
         KeyCode::Char('q') | KeyCode::Char('Q') => {
@@ -62,6 +189,20 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
         KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
             app.quit();
         }
+        // Back and forth through the feeds read, as in Vim. Terminals that
+        // can't tell `Ctrl-I` from `Tab` leave `:jump forward` for that.
+        KeyCode::Char('o') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.jump_back();
+        }
+        KeyCode::Char('i') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.jump_forward();
+        }
+        KeyCode::Char('m') => {
+            app.jumps.pending = Some(MarkAction::Set);
+        }
+        KeyCode::Char('\'') => {
+            app.jumps.pending = Some(MarkAction::Jump);
+        }
         // Navigation handlers
         KeyCode::Down | KeyCode::Char('j') => {
             app.next();
@@ -84,32 +225,86 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
         KeyCode::Char('b') => app.set_tab(0),
         KeyCode::Char('f') => app.set_tab(1),
         KeyCode::Char('t') => app.set_tab(2),
+        KeyCode::Char('A') => app.set_tab(3),
         // Other handlers you could add here.
+        KeyCode::Esc if app.active_view == View::Detail && app.find.is_some() => {
+            app.clear_find();
+        }
         KeyCode::Esc => {
             app.unselect();
         }
+        // Feeds and settings can't be changed in read-only mode.
+        KeyCode::Char('a' | 'd') if app.refuse_if_read_only() => {}
         KeyCode::Char('a') => {
             app.toggle_console(Some(":add "));
         }
         KeyCode::Char('d') => {
             app.toggle_console(Some(":delete "));
         }
+        KeyCode::Char('u') if app.lists_feeds() => {
+            app.toggle_feed_filter(FeedFilter::Unread);
+        }
+        KeyCode::Char('e') if app.lists_feeds() => {
+            app.toggle_feed_filter(FeedFilter::Failing);
+        }
+        KeyCode::Char('F') if app.lists_feeds() => {
+            app.toggle_feed_filter(FeedFilter::Favorite);
+        }
+        KeyCode::Char('x') if app.lists_feeds() => {
+            app.toggle_feed_filter(FeedFilter::Archived);
+        }
+        KeyCode::Char('s') if app.lists_feeds() => {
+            app.toggle_favorite();
+        }
+        KeyCode::Char('s') if app.active_view == View::Detail => {
+            app.open_share_menu();
+        }
+        KeyCode::Char('F') => {
+            app.toggle_console(Some(":feed "));
+        }
+        KeyCode::Char('/') if app.active_view == View::Detail => {
+            app.toggle_console(Some(":find "));
+        }
         KeyCode::Char('/') => {
             app.toggle_console(Some(":search "));
         }
+        KeyCode::Char('n') if app.active_view == View::Detail => {
+            app.next_match(true);
+        }
+        KeyCode::Char('N') if app.active_view == View::Detail => {
+            app.next_match(false);
+        }
+        KeyCode::Char('J') if app.active_view == View::Detail => {
+            app.step_item(true);
+        }
+        KeyCode::Char('K') if app.active_view == View::Detail => {
+            app.step_item(false);
+        }
         KeyCode::Char(':') => {
             app.toggle_console(Some(":"));
         }
         KeyCode::Char('o') => {
             app.open();
         }
+        KeyCode::Char('O') => {
+            app.open_in_background();
+        }
         KeyCode::Char('r') => {
             app.refresh_all();
         }
+        KeyCode::Char('i') => {
+            app.toggle_inspector();
+        }
+        KeyCode::Char('D') => {
+            app.toggle_revision();
+        }
+        KeyCode::Char('M') => {
+            app.open_monitor();
+        }
         KeyCode::Char('?') => {
             app.toggle_keybinds();
         }
-        KeyCode::Char(',') => {
+        KeyCode::Char(',') if !app.refuse_if_read_only() => {
             app.open_config();
         }
         _ => {}
@@ -118,6 +313,11 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
 }
 
 pub fn handle_mouse_events(mouse_event: MouseEvent, app: &mut App) -> AppResult<()> {
+    if let MouseEventKind::Moved = mouse_event.kind {
+        return Ok(());
+    }
+
+    app.invalidate();
     match mouse_event.kind {
         MouseEventKind::ScrollDown => {
             app.next();
@@ -138,5 +338,6 @@ pub fn handle_mouse_events(mouse_event: MouseEvent, app: &mut App) -> AppResult<
 
 pub fn handle_resize_events(dimensions: (u16, u16), app: &mut App) -> AppResult<()> {
     app.set_dimensions(dimensions);
+    app.invalidate();
     Ok(())
 }
@@ -1,9 +1,14 @@
 pub mod app;
+pub mod cli;
 pub mod config;
+pub mod control;
 pub mod repo;
 pub mod event;
 pub mod feed;
+#[cfg(test)]
+mod golden;
 pub mod handler;
+pub mod logging;
 pub mod tui;
 pub mod ui;
 pub mod util;
@@ -0,0 +1,83 @@
+use log::LevelFilter;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests;
+
+/// Size past which the log file is rotated out on startup.
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+/// The level to log at, from `--log-level`, then `RUST_LOG`, falling back to
+/// `Info` in debug builds and `Warn` otherwise.
+pub fn level(arg: Option<LevelFilter>) -> LevelFilter {
+    arg.or_else(|| {
+        std::env::var("RUST_LOG")
+            .ok()
+            .and_then(|filter| env_level(&filter))
+    })
+    .unwrap_or(if cfg!(debug_assertions) {
+        LevelFilter::Info
+    } else {
+        LevelFilter::Warn
+    })
+}
+
+/// The level a `RUST_LOG` filter like `warn,moccasin=debug` sets: that of a
+/// `moccasin` directive if there is one, else the last bare level. Everything
+/// is logged to one file at one level, so directives for other crates or for
+/// modules within moccasin, and any `/regex` filter, are ignored.
+fn env_level(filter: &str) -> Option<LevelFilter> {
+    let directives = filter.split('/').next().unwrap_or_default();
+    let mut bare = None;
+    for directive in directives.split(',').map(str::trim) {
+        match directive.split_once('=') {
+            Some(("moccasin", level)) => {
+                if let Ok(level) = LevelFilter::from_str(level.trim()) {
+                    return Some(level);
+                }
+            }
+            // A target without a level turns everything on for it.
+            None if directive == "moccasin" => return Some(LevelFilter::Trace),
+            None => bare = LevelFilter::from_str(directive).ok().or(bare),
+            Some(_) => {}
+        }
+    }
+    bare
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.1", path.display()))
+}
+
+/// Starts logging to the file at `path`, first moving it aside if it has grown
+/// too large. Only the one previous log is kept.
+pub fn init(path: &Path, level: LevelFilter) {
+    if level == LevelFilter::Off {
+        return;
+    }
+
+    if fs::metadata(path).map_or(false, |meta| meta.len() > MAX_LOG_SIZE) {
+        let _ = fs::rename(path, rotated_path(path));
+    }
+
+    let file = match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(file) => file,
+        Err(err) => {
+            eprintln!("could not open log file {}: {}", path.display(), err);
+            return;
+        }
+    };
+    let _ = simplelog::WriteLogger::init(level, simplelog::Config::default(), file);
+}
+
+/// The last `count` lines of the log at `path`, oldest first.
+pub fn tail(path: &Path, count: usize) -> Vec<String> {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    let lines = contents.lines().collect::<Vec<_>>();
+    lines[lines.len().saturating_sub(count)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect()
+}
@@ -0,0 +1,24 @@
+use super::env_level;
+use log::LevelFilter;
+
+#[test]
+fn rust_log_sets_the_level_for_moccasin() {
+    let cases = [
+        ("debug", Some(LevelFilter::Debug)),
+        (" Warn ", Some(LevelFilter::Warn)),
+        ("moccasin=debug", Some(LevelFilter::Debug)),
+        ("moccasin=debug,warn", Some(LevelFilter::Debug)),
+        ("warn,moccasin=trace,hyper=off", Some(LevelFilter::Trace)),
+        ("moccasin", Some(LevelFilter::Trace)),
+        ("info,error", Some(LevelFilter::Error)),
+        ("hyper=off", None),
+        ("hyper=trace,info", Some(LevelFilter::Info)),
+        ("moccasin::repo=trace", None),
+        ("moccasin=loud,warn", Some(LevelFilter::Warn)),
+        ("info/fetch", Some(LevelFilter::Info)),
+        ("", None),
+    ];
+    for (filter, expected) in cases {
+        assert_eq!(env_level(filter), expected, "RUST_LOG={:?}", filter);
+    }
+}
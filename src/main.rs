@@ -1,38 +1,117 @@
+use clap::Parser;
 use crossterm::terminal;
-use moccasin::app::{App, AppResult};
+use moccasin::app::{App, AppResult, Args};
+use moccasin::cli;
+use moccasin::config::{Config, RenderMode};
 use moccasin::event::{Event, EventHandler};
 use moccasin::handler::{handle_key_events, handle_mouse_events, handle_resize_events};
 use moccasin::tui::Tui;
-use std::io;
-use tui::backend::CrosstermBackend;
+use std::io::{self, IsTerminal, Read};
+use std::time::{Duration, Instant};
+use tui::backend::{Backend, CrosstermBackend};
 use tui::Terminal;
 
+/// How long to wait for more updates before drawing, in the minimal render mode.
+const COALESCE_WINDOW: Duration = Duration::from_millis(30);
+
+/// The longest updates are held back from being drawn, in the minimal render
+/// mode, however quickly they keep coming.
+const COALESCE_LIMIT: Duration = Duration::from_millis(250);
+
 #[tokio::main]
 async fn main() -> AppResult<()> {
-    // Create an application.
-    let mut app = App::init(terminal::size().unwrap())?;
+    let mut args = Args::parse();
+
+    // Run any one-off command instead of the app.
+    if let Some(command) = args.command.take() {
+        let result = match Config::new(args) {
+            Ok(config) => cli::run(command, config).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            eprintln!("moccasin: {:#}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Read any URLs piped in to add before the terminal is taken over.
+    let mut piped_urls = None;
+    if args.add_stdin {
+        let mut stdin = io::stdin();
+        if stdin.is_terminal() {
+            eprintln!("moccasin: --add-stdin needs URLs piped in");
+            std::process::exit(1);
+        }
+        let mut urls = String::new();
+        if let Err(err) = stdin.read_to_string(&mut urls) {
+            eprintln!("moccasin: could not read URLs: {}", err);
+            std::process::exit(1);
+        }
+        piped_urls = Some(urls);
+    }
+
+    // Create an application, explaining what went wrong before any terminal setup.
+    let mut app = match App::init(args, terminal::size().unwrap()) {
+        Ok(app) => app,
+        Err(err) => {
+            eprintln!("moccasin: {:#}", err);
+            std::process::exit(1);
+        }
+    };
+    if let Some(urls) = piped_urls {
+        app.add_many(&urls);
+    }
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stderr());
     let terminal = Terminal::new(backend)?;
-    let events = EventHandler::new(250);
+    let events = EventHandler::new(1000);
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
 
-    // Start the main loop.
+    // Start the main loop, making sure the terminal is restored even if it fails.
+    let res = run(&mut app, &mut tui).await;
+
+    // Exit the user interface.
+    tui.exit()?;
+    res
+}
+
+async fn run<B: Backend>(app: &mut App, tui: &mut Tui<B>) -> AppResult<()> {
+    let coalesce = app.config.render_mode() == RenderMode::Minimal;
     while app.running {
-        // Render the user interface.
-        tui.draw(&mut app)?;
-        // Handle events.
-        match tui.events.next()? {
-            Event::Tick => app.tick(),
-            Event::Key(key_event) => handle_key_events(key_event, &mut app)?,
-            Event::Mouse(mouse_event) => handle_mouse_events(mouse_event, &mut app)?,
-            Event::Resize(w, h) => handle_resize_events((w, h), &mut app)?,
+        // Render the user interface, but only if something changed.
+        if app.take_dirty() {
+            tui.draw(app)?;
+        }
+        // Handle whichever of terminal input or repository data arrives first.
+        tokio::select! {
+            event = tui.events.next() => handle_event(event?, app)?,
+            _ = app.update() => {}
+        }
+        // Over slow connections, handle whatever follows closely on its heels
+        // too, and draw it all at once.
+        if coalesce {
+            let deadline = Instant::now() + COALESCE_LIMIT;
+            while app.running && Instant::now() < deadline {
+                tokio::select! {
+                    event = tui.events.next() => handle_event(event?, app)?,
+                    _ = app.update() => {}
+                    _ = tokio::time::sleep(COALESCE_WINDOW) => break,
+                }
+            }
         }
     }
+    Ok(())
+}
 
-    // Exit the user interface.
-    tui.exit()?;
+fn handle_event(event: Event, app: &mut App) -> AppResult<()> {
+    match event {
+        Event::Tick => app.tick(),
+        Event::Key(key_event) => handle_key_events(key_event, app)?,
+        Event::Mouse(mouse_event) => handle_mouse_events(mouse_event, app)?,
+        Event::Resize(w, h) => handle_resize_events((w, h), app)?,
+    }
     Ok(())
 }
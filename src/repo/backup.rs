@@ -0,0 +1,63 @@
+use super::storage::sqlite::SQLiteStorage;
+use crate::config::Config;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Local;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The names of the database and config file within a backup.
+const DB_FILE: &str = "moccasin.db";
+const CONFIG_FILE: &str = "moccasin.toml";
+
+/// A new directory under the data directory for a backup taken now.
+pub fn default_dir(config: &Config) -> PathBuf {
+    config
+        .backups_path()
+        .join(Local::now().format("%Y-%m-%dT%H-%M-%S").to_string())
+}
+
+/// Whether `dir` holds a backup to restore from.
+pub fn holds_backup(dir: &Path) -> bool {
+    dir.join(DB_FILE).is_file()
+}
+
+/// Writes a snapshot of the database and config file to `dir`, which is
+/// created if need be. The database is copied with SQLite's backup API, so
+/// the snapshot is consistent even while the app is writing to it.
+pub fn create(storage: &SQLiteStorage, config: &Config, dir: &Path) -> Result<()> {
+    let db = dir.join(DB_FILE);
+    if db.exists() {
+        bail!("{} already holds a backup", dir.display());
+    }
+    fs::create_dir_all(dir).with_context(|| format!("could not create {}", dir.display()))?;
+
+    storage
+        .backup_to(&db)
+        .map_err(|_| anyhow!("could not back up the database to {}", db.display()))?;
+    let config_file = config.config_file_path();
+    if config_file.exists() {
+        fs::copy(&config_file, dir.join(CONFIG_FILE))
+            .with_context(|| format!("could not copy {}", config_file.display()))?;
+    }
+    Ok(())
+}
+
+/// Replaces the database and config file with those of the backup in `dir`.
+/// Backups without a config file leave the current one in place.
+pub fn restore(storage: &mut SQLiteStorage, config: &Config, dir: &Path) -> Result<()> {
+    if !holds_backup(dir) {
+        bail!("{} does not hold a moccasin backup", dir.display());
+    }
+    let db = dir.join(DB_FILE);
+
+    storage
+        .restore_from(&db)
+        .map_err(|_| anyhow!("could not restore the database from {}", db.display()))?;
+    let backed_up = dir.join(CONFIG_FILE);
+    if backed_up.is_file() {
+        let config_file = config.config_file_path();
+        fs::copy(&backed_up, &config_file)
+            .with_context(|| format!("could not write {}", config_file.display()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,554 @@
+use crate::config::{Config, FeedRule};
+use crate::util;
+use futures::future::BoxFuture;
+use reqwest::header::{HeaderName, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use std::fs;
+use std::io::{IsTerminal, Read};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+use tokio::sync::OnceCell;
+
+/// The URL a feed piped into the app is subscribed to as.
+pub const STDIN_URL: &str = "-";
+
+/// What the source of a feed made by a command starts with, followed by the
+/// command, as in `exec:~/bin/scrape-forum`.
+pub const EXEC_PREFIX: &str = "exec:";
+
+/// What the source of a feed passed through a script starts with, followed by
+/// the script and the URL, as in `filter:~/bin/clean-up:https://example.com/feed`.
+pub const FILTER_PREFIX: &str = "filter:";
+
+#[derive(Debug)]
+pub enum FetchErr {
+    Request,
+    Deserialize,
+    Parse,
+    TooLarge,
+    /// The server answered 404 or 410.
+    Gone(u16),
+}
+
+/// A request for the body of a feed, made conditional on the response cached
+/// for it, if any.
+#[derive(Clone, Debug, Default)]
+pub struct FetchRequest {
+    /// The URL the feed is subscribed to, which its rules are kept under.
+    pub feed_url: String,
+    /// The URL to fetch, which differs for bridged feeds and archive pages.
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub enum FetchResponse {
+    /// The feed hasn't changed since the cached response.
+    NotModified,
+    Fetched {
+        body: Vec<u8>,
+        /// The URL the body came from, after any redirects.
+        final_url: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
+/// Gets the raw body of a feed from wherever it's kept, leaving it to the
+/// repository to parse and cache. Implemented over HTTP, for local files and
+/// for stdin, and by tests that serve feeds of their own.
+pub trait FeedFetcher: Send + Sync {
+    fn fetch<'a>(
+        &'a self,
+        request: &'a FetchRequest,
+        config: &'a Config,
+    ) -> BoxFuture<'a, Result<FetchResponse, FetchErr>>;
+}
+
+/// Fetches `file://` URLs from disk, the feed piped in on stdin as
+/// [`STDIN_URL`], feeds printed by `exec:` commands, and anything else over
+/// HTTP, passing those given as `filter:` sources through their scripts.
+#[derive(Default)]
+pub struct DefaultFetcher {
+    http: HttpFetcher,
+    stdin: StdinFetcher,
+}
+
+impl FeedFetcher for DefaultFetcher {
+    fn fetch<'a>(
+        &'a self,
+        request: &'a FetchRequest,
+        config: &'a Config,
+    ) -> BoxFuture<'a, Result<FetchResponse, FetchErr>> {
+        // Only a source set up in the config file may run a command or read
+        // from disk, never a URL that a feed links to, like an archive page.
+        self.fetch_source(request, config, request.url == request.feed_url)
+    }
+}
+
+impl DefaultFetcher {
+    /// Fetches `request` over HTTP, or, if it's one of the user's own
+    /// `configured` sources, from wherever it says.
+    fn fetch_source<'a>(
+        &'a self,
+        request: &'a FetchRequest,
+        config: &'a Config,
+        configured: bool,
+    ) -> BoxFuture<'a, Result<FetchResponse, FetchErr>> {
+        if !configured {
+            if !util::is_feed_url(&request.url, false) {
+                log::warn!("Refused to fetch {} for {}", request.url, request.feed_url);
+                return Box::pin(async { Err(FetchErr::Request) });
+            }
+            self.http.fetch(request, config)
+        } else if let Some((script, url)) = filter_source(&request.url) {
+            Box::pin(self.filter(script, url, request, config))
+        } else if request.url == STDIN_URL {
+            self.stdin.fetch(request, config)
+        } else if request.url.starts_with(EXEC_PREFIX) {
+            CommandFetcher.fetch(request, config)
+        } else if file_path(&request.url).is_some() {
+            FileFetcher.fetch(request, config)
+        } else {
+            self.http.fetch(request, config)
+        }
+    }
+
+    /// Fetches `url` and pipes what it got through `script`, like newsboat's
+    /// filter URLs. An unchanged feed is left to the cached output of the last
+    /// run.
+    async fn filter(
+        &self,
+        script: &str,
+        url: &str,
+        request: &FetchRequest,
+        config: &Config,
+    ) -> Result<FetchResponse, FetchErr> {
+        let inner = FetchRequest {
+            url: url.to_owned(),
+            ..request.clone()
+        };
+        let FetchResponse::Fetched {
+            body,
+            etag,
+            last_modified,
+            ..
+        } = self.fetch_source(&inner, config, true).await?
+        else {
+            return Ok(FetchResponse::NotModified);
+        };
+
+        let timeout = Duration::from_secs(config.feed_timeout(&request.feed_url));
+        let body = run_command(script, Some(body), timeout, config.max_feed_size()).await?;
+        Ok(FetchResponse::Fetched {
+            body,
+            final_url: request.url.clone(),
+            etag,
+            last_modified,
+        })
+    }
+}
+
+/// The script and URL of a `filter:` source. The script ends at the first
+/// colon, so it can't have one in its path.
+fn filter_source(url: &str) -> Option<(&str, &str)> {
+    url.strip_prefix(FILTER_PREFIX)?
+        .split_once(':')
+        .filter(|(script, url)| !script.is_empty() && !url.is_empty())
+}
+
+/// Fetches feeds over HTTP with clients built on first use and kept from
+/// then on, asking for compressed responses, and falling back to IPv4 for
+/// hosts that can't be reached over IPv6.
+#[derive(Default)]
+pub struct HttpFetcher {
+    client: OnceLock<reqwest::Client>,
+    insecure_client: OnceLock<reqwest::Client>,
+}
+
+impl HttpFetcher {
+    async fn get(
+        &self,
+        request: &FetchRequest,
+        config: &Config,
+    ) -> Result<FetchResponse, FetchErr> {
+        let insecure = config
+            .feed_rule(&request.feed_url)
+            .map_or(false, FeedRule::is_insecure);
+        let client = if insecure {
+            &self.insecure_client
+        } else {
+            &self.client
+        };
+        let client = client.get_or_init(|| http_client(config, insecure));
+        let send = |client: &reqwest::Client| {
+            let mut req = client
+                .get(&request.url)
+                .timeout(Duration::from_secs(config.feed_timeout(&request.feed_url)));
+            if let Some(etag) = &request.etag {
+                req = req.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &request.last_modified {
+                req = req.header(IF_MODIFIED_SINCE, last_modified);
+            }
+            req.send()
+        };
+        let res = match send(client).await {
+            Ok(res) => res,
+            // Hosts that publish IPv6 addresses they can't be reached at get a
+            // second try over IPv4.
+            Err(err) if err.is_connect() => {
+                match ipv4_client(config, &request.feed_url, &request.url).await {
+                    Some(client) => send(&client).await.map_err(|_| FetchErr::Request)?,
+                    None => return Err(FetchErr::Request),
+                }
+            }
+            Err(_) => return Err(FetchErr::Request),
+        };
+
+        // Feeds that no longer exist are told apart, so they can be unsubscribed from.
+        if matches!(res.status(), StatusCode::NOT_FOUND | StatusCode::GONE) {
+            return Err(FetchErr::Gone(res.status().as_u16()));
+        }
+        if res.status() == StatusCode::NOT_MODIFIED {
+            return Ok(FetchResponse::NotModified);
+        }
+
+        let header = |name: HeaderName| {
+            res.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+        };
+        let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
+        let final_url = res.url().to_string();
+        Ok(FetchResponse::Fetched {
+            body: read_body(res, config.max_feed_size()).await?,
+            final_url,
+            etag,
+            last_modified,
+        })
+    }
+}
+
+impl FeedFetcher for HttpFetcher {
+    fn fetch<'a>(
+        &'a self,
+        request: &'a FetchRequest,
+        config: &'a Config,
+    ) -> BoxFuture<'a, Result<FetchResponse, FetchErr>> {
+        Box::pin(self.get(request, config))
+    }
+}
+
+/// Reads feeds kept as local files, given as `file://` URLs.
+pub struct FileFetcher;
+
+impl FeedFetcher for FileFetcher {
+    fn fetch<'a>(
+        &'a self,
+        request: &'a FetchRequest,
+        config: &'a Config,
+    ) -> BoxFuture<'a, Result<FetchResponse, FetchErr>> {
+        let max_size = config.max_feed_size();
+        Box::pin(async move {
+            let path = file_path(&request.url).ok_or(FetchErr::Request)?;
+            let body = tokio::task::spawn_blocking(move || read_file(&path, max_size))
+                .await
+                .map_err(|_| FetchErr::Request)??;
+            Ok(FetchResponse::Fetched {
+                body,
+                final_url: request.url.clone(),
+                etag: None,
+                last_modified: None,
+            })
+        })
+    }
+}
+
+/// The path of a local feed given as a `file://` URL.
+pub fn file_path(url: &str) -> Option<PathBuf> {
+    if !url.starts_with("file:") {
+        return None;
+    }
+    reqwest::Url::parse(url).ok()?.to_file_path().ok()
+}
+
+fn read_file(path: &Path, max_size: Option<u64>) -> Result<Vec<u8>, FetchErr> {
+    let file = fs::File::open(path).map_err(|err| {
+        log::warn!("Failed to open {}: {}", path.display(), err);
+        match err.kind() {
+            std::io::ErrorKind::NotFound => FetchErr::Gone(404),
+            _ => FetchErr::Request,
+        }
+    })?;
+    let max_size = max_size.unwrap_or(u64::MAX);
+    let mut body = vec![];
+    file.take(max_size.saturating_add(1))
+        .read_to_end(&mut body)
+        .map_err(|_| FetchErr::Deserialize)?;
+    if body.len() as u64 > max_size {
+        log::warn!(
+            "Aborted {}, which is larger than {} bytes",
+            path.display(),
+            max_size
+        );
+        return Err(FetchErr::TooLarge);
+    }
+    Ok(body)
+}
+
+/// Runs the command of an `exec:` source through the shell, reading the feed
+/// it prints, like newsboat's exec URLs. It's given the feed's timeout, and is
+/// killed if it takes longer or prints more than `max_feed_size`.
+pub struct CommandFetcher;
+
+impl FeedFetcher for CommandFetcher {
+    fn fetch<'a>(
+        &'a self,
+        request: &'a FetchRequest,
+        config: &'a Config,
+    ) -> BoxFuture<'a, Result<FetchResponse, FetchErr>> {
+        let timeout = Duration::from_secs(config.feed_timeout(&request.feed_url));
+        let max_size = config.max_feed_size();
+        Box::pin(async move {
+            let command = request
+                .url
+                .strip_prefix(EXEC_PREFIX)
+                .ok_or(FetchErr::Request)?;
+            let body = run_command(command, None, timeout, max_size).await?;
+            Ok(FetchResponse::Fetched {
+                body,
+                final_url: request.url.clone(),
+                etag: None,
+                last_modified: None,
+            })
+        })
+    }
+}
+
+/// Runs `command` through the shell, writing `input` to it if given, and reads
+/// what it prints. It's killed if it takes longer than `timeout` or prints
+/// more than `max_size` bytes.
+async fn run_command(
+    command: &str,
+    input: Option<Vec<u8>>,
+    timeout: Duration,
+    max_size: Option<u64>,
+) -> Result<Vec<u8>, FetchErr> {
+    tokio::time::timeout(timeout, run_command_to_end(command, input, max_size))
+        .await
+        .map_err(|_| {
+            log::warn!("{} took longer than {:?}", command, timeout);
+            FetchErr::Request
+        })?
+}
+
+async fn run_command_to_end(
+    command: &str,
+    input: Option<Vec<u8>>,
+    max_size: Option<u64>,
+) -> Result<Vec<u8>, FetchErr> {
+    let mut shell = if cfg!(target_os = "windows") {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    let mut child = shell
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| {
+            log::error!("Could not run {}: {}", command, err);
+            FetchErr::Request
+        })?;
+
+    // Written alongside reading, since the command may print as it goes.
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        tokio::spawn(async move {
+            let _ = stdin.write_all(&input).await;
+        });
+    }
+
+    let max_size = max_size.unwrap_or(u64::MAX);
+    let mut body = vec![];
+    let stdout = child.stdout.take().ok_or(FetchErr::Request)?;
+    stdout
+        .take(max_size.saturating_add(1))
+        .read_to_end(&mut body)
+        .await
+        .map_err(|_| FetchErr::Deserialize)?;
+    if body.len() as u64 > max_size {
+        log::warn!(
+            "Aborted {}, which printed more than {} bytes",
+            command,
+            max_size
+        );
+        return Err(FetchErr::TooLarge);
+    }
+
+    let status = child.wait().await.map_err(|_| FetchErr::Request)?;
+    if !status.success() {
+        log::warn!("{} failed with {}", command, status);
+        return Err(FetchErr::Request);
+    }
+    Ok(body)
+}
+
+/// Reads the feed piped in on stdin. Stdin can only be read once, so what was
+/// read is kept and served again on every refresh.
+#[derive(Default)]
+pub struct StdinFetcher {
+    body: OnceCell<Option<Vec<u8>>>,
+}
+
+impl FeedFetcher for StdinFetcher {
+    fn fetch<'a>(
+        &'a self,
+        request: &'a FetchRequest,
+        _config: &'a Config,
+    ) -> BoxFuture<'a, Result<FetchResponse, FetchErr>> {
+        Box::pin(async move {
+            let body = self
+                .body
+                .get_or_init(|| async {
+                    tokio::task::spawn_blocking(read_stdin).await.ok().flatten()
+                })
+                .await;
+            match body {
+                Some(body) => Ok(FetchResponse::Fetched {
+                    body: body.clone(),
+                    final_url: request.url.clone(),
+                    etag: None,
+                    last_modified: None,
+                }),
+                None => Err(FetchErr::Request),
+            }
+        })
+    }
+}
+
+/// Reads all of stdin, unless it's the terminal, where the app's own input
+/// comes from.
+fn read_stdin() -> Option<Vec<u8>> {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        log::warn!("No feed was piped in on stdin");
+        return None;
+    }
+    let mut body = vec![];
+    match stdin.read_to_end(&mut body) {
+        Ok(_) => Some(body),
+        Err(err) => {
+            log::error!("Failed to read stdin: {}", err);
+            None
+        }
+    }
+}
+
+/// Builds the client that feeds and pages are fetched with, which asks for
+/// compressed responses and decompresses them as they arrive. It trusts the
+/// configured CA file, or with `insecure`, any certificate at all.
+pub(super) fn http_client(config: &Config, insecure: bool) -> reqwest::Client {
+    http_client_builder(config, insecure)
+        .build()
+        .expect("Failed to build client")
+}
+
+pub(super) fn http_client_builder(config: &Config, insecure: bool) -> reqwest::ClientBuilder {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(config.connect_timeout()))
+        .timeout(Duration::from_secs(config.refresh_timeout()))
+        .gzip(true)
+        .brotli(true)
+        .danger_accept_invalid_certs(insecure);
+
+    if let Some(path) = config.ca_file() {
+        let cert = fs::read(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|pem| Ok(reqwest::Certificate::from_pem(&pem)?));
+        match cert {
+            Ok(cert) => builder = builder.add_root_certificate(cert),
+            Err(err) => log::error!("Failed to load CA file {}: {}", path.display(), err),
+        }
+    }
+    builder
+}
+
+/// A client that reaches the host of `url` over IPv4 only, for hosts with IPv6
+/// addresses that can't be connected to. `None` if the host doesn't have both
+/// kinds of address, in which case there's nothing to fall back to.
+async fn ipv4_client(config: &Config, feed_url: &str, url: &str) -> Option<reqwest::Client> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    let lookup = tokio::net::lookup_host((host, url.port_or_known_default()?));
+    let addrs = tokio::time::timeout(Duration::from_secs(config.connect_timeout()), lookup)
+        .await
+        .ok()?
+        .ok()?
+        .collect::<Vec<_>>();
+    if !addrs.iter().any(SocketAddr::is_ipv6) {
+        return None;
+    }
+    let ipv4_addrs = addrs
+        .into_iter()
+        .filter(SocketAddr::is_ipv4)
+        .collect::<Vec<_>>();
+    if ipv4_addrs.is_empty() {
+        return None;
+    }
+
+    log::info!("Retrying {} over IPv4", url);
+    let insecure = config
+        .feed_rule(feed_url)
+        .map_or(false, FeedRule::is_insecure);
+    http_client_builder(config, insecure)
+        .resolve_to_addrs(host, &ipv4_addrs)
+        .build()
+        .ok()
+}
+
+/// Reads a response body, giving up as soon as it grows past `max_size` bytes
+/// instead of buffering all of it. Compressed bodies are measured decompressed.
+pub(super) async fn read_body(
+    mut res: reqwest::Response,
+    max_size: Option<u64>,
+) -> Result<Vec<u8>, FetchErr> {
+    let max_size = max_size.unwrap_or(u64::MAX);
+    let too_large = |res: &reqwest::Response| {
+        log::warn!(
+            "Aborted {}, which is larger than {} bytes",
+            res.url(),
+            max_size
+        );
+        FetchErr::TooLarge
+    };
+    if res.content_length().map_or(false, |len| len > max_size) {
+        return Err(too_large(&res));
+    }
+
+    let mut body = vec![];
+    while let Some(chunk) = res.chunk().await.map_err(|_| FetchErr::Deserialize)? {
+        body.extend_from_slice(&chunk);
+        if body.len() as u64 > max_size {
+            return Err(too_large(&res));
+        }
+    }
+    Ok(body)
+}
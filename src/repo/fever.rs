@@ -0,0 +1,284 @@
+use super::storage::sqlite::SQLiteStorage;
+use super::storage::StorageError;
+use crate::config::Config;
+use crate::feed::{Feed, Item};
+use crate::util;
+use reqwest::Url;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+
+/// The version of the Fever API this speaks.
+const API_VERSION: u32 = 3;
+
+/// How many items a Fever client gets at a time.
+const ITEM_PAGE_SIZE: usize = 50;
+
+/// The one group every feed is in, since Fever clients expect feeds to be
+/// grouped and moccasin doesn't group them.
+const GROUP_ID: i64 = 1;
+
+/// Answers a request to the Fever API, taking its parameters from both the
+/// query string and the form it posted. Feeds and items are numbered by
+/// their rows in the database, since Fever ids are integers.
+pub(super) fn respond(
+    storage: &mut SQLiteStorage,
+    config: &Config,
+    api_key: &str,
+    url: &Url,
+    form: &[u8],
+) -> Result<Value, StorageError> {
+    let params = url
+        .query_pairs()
+        .into_owned()
+        .chain(parse_form(form))
+        .collect::<HashMap<_, _>>();
+    let param = |name: &str| params.get(name).map(String::as_str);
+
+    let feeds = storage.read_all(config)?;
+    let mut response = Map::new();
+    response.insert("api_version".into(), json!(API_VERSION));
+    let authorized = param("api_key").map_or(false, |key| key.eq_ignore_ascii_case(api_key));
+    response.insert("auth".into(), json!(authorized as u8));
+    if !authorized {
+        return Ok(Value::Object(response));
+    }
+    let last_refreshed = feeds
+        .iter()
+        .filter_map(|feed| feed.last_fetched().and_then(util::parse_date))
+        .map(|date| date.timestamp())
+        .max()
+        .unwrap_or_default();
+    response.insert("last_refreshed_on_time".into(), json!(last_refreshed));
+
+    let feed_numbers = storage.read_feed_numbers()?;
+    let item_numbers = storage.read_item_numbers()?;
+    let mut items = numbered_items(storage, &feeds, &item_numbers)?;
+    if let Some(mark) = param("mark") {
+        if !config.read_only() {
+            mark_items(storage, mark, &params, &items, &feed_numbers)?;
+            // Send back the state the marks left behind.
+            items = numbered_items(storage, &feeds, &item_numbers)?;
+        }
+    }
+    let saved = storage
+        .read_item_states()?
+        .into_iter()
+        .filter(|state| state.starred)
+        .map(|state| state.guid)
+        .collect::<HashSet<_>>();
+
+    let feed_ids = feeds
+        .iter()
+        .filter_map(|feed| feed_numbers.get(feed.id()))
+        .map(i64::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    let feeds_groups = json!([{ "group_id": GROUP_ID, "feed_ids": feed_ids }]);
+    if param("groups").is_some() {
+        response.insert("groups".into(), json!([{ "id": GROUP_ID, "title": "All" }]));
+        response.insert("feeds_groups".into(), feeds_groups.clone());
+    }
+    if param("feeds").is_some() {
+        let feeds = feeds
+            .iter()
+            .filter_map(|feed| {
+                Some(json!({
+                    "id": feed_numbers.get(feed.id())?,
+                    "favicon_id": 0,
+                    "title": feed.title(),
+                    "url": feed.url(),
+                    "site_url": feed.link(),
+                    "is_spark": 0,
+                    "last_updated_on_time": feed
+                        .last_fetched()
+                        .and_then(util::parse_date)
+                        .map_or(0, |date| date.timestamp()),
+                }))
+            })
+            .collect();
+        response.insert("feeds".into(), Value::Array(feeds));
+        response.insert("feeds_groups".into(), feeds_groups);
+    }
+    if param("favicons").is_some() {
+        response.insert("favicons".into(), json!([]));
+    }
+    if param("links").is_some() {
+        response.insert("links".into(), json!([]));
+    }
+    if param("items").is_some() {
+        let page = item_page(&items, &params)
+            .into_iter()
+            .filter_map(|(number, item)| {
+                let feed = feed_numbers.get(item.feed_id())?;
+                Some(item_json(*number, *feed, item, saved.contains(item.id())))
+            })
+            .collect();
+        response.insert("items".into(), Value::Array(page));
+        response.insert("total_items".into(), json!(items.len()));
+    }
+    if param("unread_item_ids").is_some() {
+        let ids = items
+            .iter()
+            .filter(|(_, item)| !item.read())
+            .map(|(number, _)| number.to_string());
+        response.insert("unread_item_ids".into(), json!(join(ids)));
+    }
+    if param("saved_item_ids").is_some() {
+        let ids = items
+            .iter()
+            .filter(|(_, item)| saved.contains(item.id()))
+            .map(|(number, _)| number.to_string());
+        response.insert("saved_item_ids".into(), json!(join(ids)));
+    }
+
+    Ok(Value::Object(response))
+}
+
+/// Every item of every feed, with its number, in the order they were stored.
+fn numbered_items(
+    storage: &SQLiteStorage,
+    feeds: &[Feed],
+    numbers: &HashMap<String, i64>,
+) -> Result<Vec<(i64, Item)>, StorageError> {
+    let mut items = vec![];
+    for feed in feeds {
+        for item in storage.read_items_for_feed_id(feed.id())? {
+            if let Some(number) = numbers.get(item.id()) {
+                items.push((*number, item));
+            }
+        }
+    }
+    items.sort_by_key(|(number, _)| *number);
+    Ok(items)
+}
+
+/// The items a client asked for: those listed in `with_ids`, or else a page
+/// of those after `since_id` going up, or before `max_id` going down.
+fn item_page<'a>(
+    items: &'a [(i64, Item)],
+    params: &HashMap<String, String>,
+) -> Vec<&'a (i64, Item)> {
+    let number = |name: &str| params.get(name).and_then(|n| n.parse::<i64>().ok());
+    if let Some(ids) = params.get("with_ids") {
+        let ids = ids
+            .split(',')
+            .filter_map(|id| id.trim().parse::<i64>().ok())
+            .collect::<HashSet<_>>();
+        return items
+            .iter()
+            .filter(|(number, _)| ids.contains(number))
+            .take(ITEM_PAGE_SIZE)
+            .collect();
+    }
+    match (number("since_id"), number("max_id")) {
+        (_, Some(max)) => items
+            .iter()
+            .rev()
+            .filter(|(number, _)| *number < max)
+            .take(ITEM_PAGE_SIZE)
+            .collect(),
+        (since, None) => items
+            .iter()
+            .filter(|(number, _)| *number > since.unwrap_or_default())
+            .take(ITEM_PAGE_SIZE)
+            .collect(),
+    }
+}
+
+/// Marks an item read, unread, saved or unsaved, or everything in a feed or
+/// group read up to the time `before`. Group 0 is Fever's "Kindling", which
+/// holds every feed, as does the one group there is.
+fn mark_items(
+    storage: &SQLiteStorage,
+    mark: &str,
+    params: &HashMap<String, String>,
+    items: &[(i64, Item)],
+    feed_numbers: &HashMap<String, i64>,
+) -> Result<(), StorageError> {
+    let (Some(action), Some(id)) = (
+        params.get("as"),
+        params.get("id").and_then(|id| id.parse::<i64>().ok()),
+    ) else {
+        return Ok(());
+    };
+
+    if mark == "item" {
+        let Some((_, item)) = items.iter().find(|(number, _)| *number == id) else {
+            return Ok(());
+        };
+        match action.as_str() {
+            "read" if !item.read() => {
+                storage.mark_item_read(item.id())?;
+                let _ = storage.record_item_event(item, "read");
+            }
+            "unread" => {
+                storage.mark_item_unread(item.id())?;
+            }
+            "saved" => {
+                storage.star_item(item.id())?;
+            }
+            "unsaved" => {
+                storage.unstar_item(item.id())?;
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    let in_mark = |item: &Item| match mark {
+        "feed" => feed_numbers.get(item.feed_id()) == Some(&id),
+        "group" => id == 0 || id == GROUP_ID,
+        _ => false,
+    };
+    let before = params
+        .get("before")
+        .and_then(|before| before.parse::<i64>().ok())
+        .unwrap_or(i64::MAX);
+    if action != "read" {
+        return Ok(());
+    }
+    for (_, item) in items
+        .iter()
+        .filter(|(_, item)| in_mark(item) && !item.read())
+    {
+        let published = item
+            .pub_date()
+            .and_then(util::parse_date)
+            .map_or(0, |date| date.timestamp());
+        if published <= before {
+            storage.mark_item_read(item.id())?;
+            let _ = storage.record_item_event(item, "read");
+        }
+    }
+    Ok(())
+}
+
+fn item_json(number: i64, feed: i64, item: &Item, saved: bool) -> Value {
+    json!({
+        "id": number,
+        "feed_id": feed,
+        "title": item.title().unwrap_or_default(),
+        "author": item.author().unwrap_or_default(),
+        "html": item.content().or(item.description()).unwrap_or_default(),
+        "url": item.link().unwrap_or_default(),
+        "is_saved": saved as u8,
+        "is_read": item.read() as u8,
+        "created_on_time": item
+            .pub_date()
+            .and_then(util::parse_date)
+            .map_or(0, |date| date.timestamp()),
+    })
+}
+
+fn join(ids: impl Iterator<Item = String>) -> String {
+    ids.collect::<Vec<_>>().join(",")
+}
+
+/// Decodes an `application/x-www-form-urlencoded` body.
+fn parse_form(body: &[u8]) -> Vec<(String, String)> {
+    let body = String::from_utf8_lossy(body);
+    match Url::parse(&format!("http://localhost/?{}", body.trim())) {
+        Ok(url) => url.query_pairs().into_owned().collect(),
+        Err(_) => vec![],
+    }
+}
@@ -0,0 +1,83 @@
+use super::responses::ResponseCache;
+use crate::feed::Feed;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Icons larger than this aren't worth showing in two cells, so aren't kept.
+const MAX_ICON_SIZE: usize = 256 * 1024;
+
+/// Where to find the icon of the site a feed belongs to.
+pub fn icon_url(feed: &Feed) -> Option<String> {
+    let site = if feed.link().is_empty() {
+        feed.url()
+    } else {
+        feed.link()
+    };
+    let url = reqwest::Url::parse(site).ok()?.join("/favicon.ico").ok()?;
+    Some(url.into())
+}
+
+/// The icon as a PNG image, if it either is one, or is an ICO file holding
+/// one. The smallest of several images is picked. Bitmap icons aren't read.
+pub fn extract_png(icon: &[u8]) -> Option<Vec<u8>> {
+    if icon.starts_with(PNG_SIGNATURE) {
+        return Some(icon.to_vec());
+    }
+
+    // An ICO file starts with its type and image count, followed by a 16-byte
+    // entry for each image with its width, size and offset.
+    if icon.get(..4)? != [0, 0, 1, 0] {
+        return None;
+    }
+    let count = u16::from_le_bytes([icon[4], icon[5]]) as usize;
+    (0..count)
+        .filter_map(|i| {
+            let entry = icon.get(6 + i * 16..6 + (i + 1) * 16)?;
+            let width = if entry[0] == 0 { 256 } else { entry[0] as u16 };
+            let size = u32::from_le_bytes(entry[8..12].try_into().ok()?) as usize;
+            let offset = u32::from_le_bytes(entry[12..16].try_into().ok()?) as usize;
+            let image = icon.get(offset..offset.checked_add(size)?)?;
+            image.starts_with(PNG_SIGNATURE).then_some((width, image))
+        })
+        .min_by_key(|(width, _)| *width)
+        .map(|(_, image)| image.to_vec())
+}
+
+/// Fetches the icon at `url` into the cache, unless the cached one is current.
+pub async fn fetch_icon(client: &reqwest::Client, icons: &ResponseCache, url: &str) {
+    let mut req = client.get(url);
+    if let Some(etag) = icons.lookup(url).and_then(|cached| cached.etag) {
+        req = req.header(IF_NONE_MATCH, etag);
+    }
+
+    let res = match req.send().await {
+        Ok(res) if res.status() == StatusCode::NOT_MODIFIED => return,
+        Ok(res) if res.status().is_success() => res,
+        Ok(res) => {
+            log::debug!("No icon at {}: {}", url, res.status());
+            return;
+        }
+        Err(err) => {
+            log::debug!("Failed to fetch icon {}: {}", url, err);
+            return;
+        }
+    };
+
+    let etag = res
+        .headers()
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    let final_url = res.url().to_string();
+    match res.bytes().await {
+        Ok(bytes) if bytes.len() <= MAX_ICON_SIZE => {
+            if let Err(err) = icons.write(url, &final_url, etag, None, &bytes) {
+                log::warn!("Failed to cache icon {}: {}", url, err);
+            }
+        }
+        Ok(_) => log::debug!("Icon {} is too large to keep", url),
+        Err(err) => log::debug!("Failed to fetch icon {}: {}", url, err),
+    }
+}
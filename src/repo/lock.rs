@@ -0,0 +1,65 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use fs2::FileExt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, Write};
+
+const LOCK_FILE: &str = "moccasin.lock";
+
+/// A hold on a profile's database and config file, which only one app at a
+/// time gets, so that a second one started alongside knows to leave them be.
+/// The lock is the operating system's, so it goes with the app however it
+/// exits.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+/// Why the lock couldn't be had.
+#[derive(Debug)]
+pub enum LockError {
+    /// Another app holds it, with the process id it wrote down if any.
+    Held(Option<u32>),
+    Failed(anyhow::Error),
+}
+
+impl InstanceLock {
+    /// Takes the lock in the data directory, writing down this process's id
+    /// for whoever finds it held.
+    pub fn acquire(config: &Config) -> Result<Self, LockError> {
+        let open = || -> Result<File> {
+            let dir = config.data_dir_path();
+            fs::create_dir_all(&dir)?;
+            let path = dir.join(LOCK_FILE);
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+                .with_context(|| format!("could not open {}", path.display()))
+        };
+        let mut file = open().map_err(LockError::Failed)?;
+
+        if let Err(err) = file.try_lock_exclusive() {
+            if err.raw_os_error() == fs2::lock_contended_error().raw_os_error() {
+                let mut pid = String::new();
+                let _ = file.read_to_string(&mut pid);
+                return Err(LockError::Held(pid.trim().parse().ok()));
+            }
+            return Err(LockError::Failed(
+                anyhow::Error::new(err).context("could not lock the data directory"),
+            ));
+        }
+
+        let write_pid = |file: &mut File| -> std::io::Result<()> {
+            file.set_len(0)?;
+            file.rewind()?;
+            write!(file, "{}", std::process::id())
+        };
+        if let Err(err) = write_pid(&mut file) {
+            log::warn!("Could not write to the lock file: {}", err);
+        }
+        Ok(Self { _file: file })
+    }
+}
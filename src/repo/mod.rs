@@ -1,16 +1,119 @@
+pub mod backup;
+pub mod fetch;
+mod fever;
+mod icons;
+pub mod lock;
+pub mod responses;
 pub mod storage;
 mod repo;
+pub mod server;
+mod share;
+mod summarize;
+mod translate;
 
-use crate::feed::Feed;
+use crate::feed::{Feed, Page};
 pub use repo::Repository;
+pub use share::{EventExport, Shared};
+use serde::{Deserialize, Serialize};
+
+/// A feed or item matching a search, with enough context to jump to it.
+#[derive(Clone, Debug, Default)]
+pub struct SearchResult {
+    pub feed_id: String,
+    pub item_id: Option<String>,
+    pub title: String,
+    pub feed_title: String,
+}
+
+/// A tag given to items by `[tags]` rules or their feeds, or an author of
+/// items, and how many items have it.
+#[derive(Clone, Debug, Default)]
+pub struct TagSummary {
+    pub name: String,
+    pub item_count: usize,
+    pub unread_count: usize,
+}
+
+/// Summary of reading activity, for the statistics view.
+#[derive(Clone, Debug, Default)]
+pub struct ReadingStats {
+    /// Items read on each of the last few days, oldest first.
+    pub reads_per_day: Vec<(String, u64)>,
+    /// Feeds with the most items read, most first.
+    pub top_feeds: Vec<(String, u64)>,
+    /// Unread items at the end of each recorded day among those, oldest first.
+    pub backlog: Vec<u64>,
+}
+
+/// The read and starred flags of an item, keyed by its guid, as exported for
+/// syncing between machines.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ItemState {
+    pub guid: String,
+    pub read_at: Option<String>,
+    #[serde(default)]
+    pub starred: bool,
+}
+
+/// An earlier version of an item, kept when the feed republished it changed.
+#[derive(Clone, Debug)]
+pub struct ItemRevision {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub replaced_at: String,
+}
 
 #[derive(Clone, Debug)]
 pub enum RepositoryEvent {
     Refresh,
     RetrievedAll(Vec<Feed>),
     RetrievedOne(Feed),
+    /// A feed fetched to be looked over before it's subscribed to.
+    Previewed(Feed),
+    /// Status page feeds, refetched on their own between full refreshes.
+    RetrievedMonitored(Vec<Feed>),
+    RetrievedPage(Page),
     Requesting(usize),
     Requested((usize, usize)),
     Errored,
+    Failed(String),
     Aborted,
+    IconsUpdated,
+    /// The translation of an item or page, by its id or URL.
+    Translated(String, String),
+    /// The summary of an item or page, by its id or URL.
+    Summarized(String, String),
+    /// An item or page was shared, to the target of this name.
+    Shared(String),
+    /// The event in an item was exported, saying where to.
+    ExportedEvent(String),
+    /// Fetched feeds once they're written to the database.
+    Stored(Box<RepositoryEvent>),
+    /// Which feeds a refresh found and which were gone.
+    Checked(FeedHealth),
+    /// Feeds that have been gone on several refreshes in a row.
+    DeadFeeds(Vec<DeadFeed>),
+    /// What a full refresh found, once its feeds are written: how many items
+    /// were new, how many changed, and how many feeds could not be fetched.
+    RefreshSummary {
+        new_items: usize,
+        updated: usize,
+        failed: usize,
+    },
+}
+
+/// The feeds a refresh fetched, by the URLs they're subscribed to, those
+/// whose servers said they no longer exist, and those that failed otherwise.
+#[derive(Clone, Debug, Default)]
+pub struct FeedHealth {
+    pub fetched: Vec<String>,
+    pub gone: Vec<DeadFeed>,
+    pub failed: Vec<String>,
+}
+
+/// A feed that could not be found, with the status it was answered with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeadFeed {
+    pub url: String,
+    pub status: u16,
 }
@@ -1,34 +1,86 @@
-use super::RepositoryEvent;
+use super::backup;
+use super::fetch::{
+    self, http_client, http_client_builder, DefaultFetcher, FeedFetcher, FetchErr, FetchRequest,
+    FetchResponse,
+};
+use super::icons;
+use super::share::{self, EventExport, Shared};
+use super::summarize;
+use super::translate;
+use super::{
+    DeadFeed, FeedHealth, ItemRevision, ReadingStats, RepositoryEvent, SearchResult, TagSummary,
+};
+use super::responses::ResponseCache;
+use super::storage::actor::StorageActor;
 use super::storage::sqlite::SQLiteStorage;
-use crate::config::Config;
-use crate::feed::Feed;
-use crate::repo::storage::{StorageError, StorageEvent};
+use crate::config::{Config, FeedRule, ShareTarget};
+use crate::feed::{CalendarEvent, Feed, Item, Page};
+use crate::repo::storage::StorageError;
 use crate::report;
-use crate::util::sort_feeds;
+use crate::util::{self, sort_feeds};
 use anyhow::Result;
+use reqwest::header::CONTENT_TYPE;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
-use std::task::Poll;
-use std::thread;
-use std::time::Duration;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::{
     sync::mpsc::{self, UnboundedSender},
     task::JoinHandle,
 };
 
-#[derive(Debug)]
-enum FetchErr {
-    Request,
-    Deserialize,
-    Parse,
-}
+/// How long to wait for a summary, which language models can take a while to
+/// write.
+const SUMMARY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How many refreshes in a row a feed must be gone on to be offered for
+/// unsubscribing.
+const DEAD_FEED_REFRESHES: u32 = 3;
+
+/// How many feeds' items to keep in memory after they were last viewed.
+const ITEMS_CACHE_SIZE: usize = 8;
+
+/// How often local feed files are checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
 
 pub struct Repository {
+    /// The connection everything is read through, which never waits for writes.
     storage: SQLiteStorage,
+    /// The connection everything is written through, on a thread of its own.
+    writer: StorageActor,
     app_tx: mpsc::UnboundedSender<RepositoryEvent>,
     storage_tx: mpsc::UnboundedSender<RepositoryEvent>,
     storage_rx: mpsc::UnboundedReceiver<RepositoryEvent>,
     handle_one: Option<JoinHandle<()>>,
     handle_many: Option<JoinHandle<()>>,
+    handle_monitor: Option<JoinHandle<()>>,
+    refresh_interval: Option<Duration>,
+    next_refresh: Option<Instant>,
+    /// How many feeds the latest full refresh asked for.
+    refresh_count: usize,
+    /// How often status page feeds are refetched between full refreshes.
+    monitor_interval: Option<Duration>,
+    next_monitor: Option<Instant>,
+    next_watch: Instant,
+    /// When each local feed file was last modified, by its URL, as of the last
+    /// check.
+    watched_files: HashMap<String, SystemTime>,
+    items_cache: VecDeque<(String, Vec<Item>)>,
+    responses: Option<ResponseCache>,
+    icons: Option<ResponseCache>,
+    /// Icon URLs already checked this session, which are revalidated only on
+    /// the next start.
+    checked_icons: HashSet<String>,
+    /// The id of a feed whose URL was just edited, and that URL, until it's refetched.
+    edited_feed: Option<(String, String)>,
+    fetcher: Arc<dyn FeedFetcher>,
+    /// The client pages, icons and shares are fetched with, built on first use.
+    client: OnceLock<reqwest::Client>,
+    /// Whether another app holds the database, leaving fetching feeds and
+    /// writing to it to that one.
+    secondary: bool,
 }
 
 impl Debug for Repository {
@@ -39,60 +91,271 @@ impl Debug for Repository {
 
 impl Repository {
     pub fn init(config: &Config, app_tx: UnboundedSender<RepositoryEvent>) -> Result<Self> {
-        let storage = SQLiteStorage::init(config);
+        let (storage, writer) = SQLiteStorage::init_pair(config);
+        let writer = StorageActor::spawn(writer);
 
         let (storage_tx, storage_rx) = mpsc::unbounded_channel::<RepositoryEvent>();
 
-        if config.refresh_interval() > 0 {
-            let tick_rate = Duration::from_secs(config.refresh_interval());
-            let tx = storage_tx.clone();
-            thread::spawn(move || loop {
-                tx.send(RepositoryEvent::Refresh)
-                    .expect("Failed to send storage message");
-                thread::sleep(tick_rate);
-            });
-        }
+        let refresh_interval = if config.refresh_interval() > 0 {
+            Some(Duration::from_secs(config.refresh_interval()))
+        } else {
+            None
+        };
+
+        let monitor_interval = if config.monitor_interval() > 0 {
+            Some(Duration::from_secs(config.monitor_interval()))
+        } else {
+            None
+        };
 
         Ok(Self {
             storage,
+            writer,
             app_tx,
             storage_tx,
             storage_rx,
             handle_one: None,
             handle_many: None,
+            handle_monitor: None,
+            refresh_interval,
+            next_refresh: refresh_interval.map(|_| Instant::now()),
+            refresh_count: 0,
+            monitor_interval,
+            next_monitor: monitor_interval.map(|i| Instant::now() + i),
+            next_watch: Instant::now() + WATCH_INTERVAL,
+            watched_files: HashMap::new(),
+            items_cache: VecDeque::with_capacity(ITEMS_CACHE_SIZE),
+            responses: ResponseCache::new(config),
+            icons: ResponseCache::icons(config),
+            checked_icons: HashSet::new(),
+            edited_feed: None,
+            fetcher: Arc::new(DefaultFetcher::default()),
+            client: OnceLock::new(),
+            secondary: false,
         })
     }
 
-    pub fn tick(&mut self, config: &Config) {
-        let waker = futures::task::noop_waker();
-        let mut cx = std::task::Context::from_waker(&waker);
+    /// The client for everything but feeds, which is built once and shared.
+    fn client(&self, config: &Config) -> reqwest::Client {
+        self.client
+            .get_or_init(|| http_client(config, false))
+            .clone()
+    }
 
-        match self.storage_rx.poll_recv(&mut cx) {
-            Poll::Ready(m) => match m {
-                Some(RepositoryEvent::RetrievedAll(feeds)) => {
-                    report!(self.storage.write_feeds(&feeds), "Failed to write feeds");
-                    self.app_tx
-                        .send(RepositoryEvent::RetrievedAll(feeds))
-                        .expect("Failed to send app message");
-                    self.handle_many = None;
-                }
-                Some(RepositoryEvent::RetrievedOne(feed)) => {
-                    report!(self.storage.write_feed(&feed, None), "Failed to write feed");
-                    self.app_tx
-                        .send(RepositoryEvent::RetrievedOne(feed))
-                        .expect("Failed to send app message");
-                    self.handle_one = None;
-                }
-                Some(RepositoryEvent::Refresh) => {
-                    self.refresh_all(config);
+    /// Fetches feeds with `fetcher` from now on, in place of the default.
+    pub fn set_fetcher(&mut self, fetcher: Arc<dyn FeedFetcher>) {
+        self.fetcher = fetcher;
+    }
+
+    /// Leaves the database to the app holding it: nothing is refreshed on a
+    /// schedule or written from here on, and refreshing only reloads what
+    /// that app has stored.
+    pub fn hold_off(&mut self) {
+        self.secondary = true;
+        self.refresh_interval = None;
+        self.next_refresh = None;
+        self.monitor_interval = None;
+        self.next_monitor = None;
+    }
+
+    /// Time remaining until the next scheduled refresh, if automatic refresh is enabled.
+    pub fn time_until_refresh(&self) -> Option<Duration> {
+        self.next_refresh
+            .map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Waits for the next storage message or scheduled refresh, and handles it.
+    pub async fn process(&mut self, config: &Config) {
+        let next_refresh = self.next_refresh;
+        let refresh = async move {
+            match next_refresh {
+                Some(at) => tokio::time::sleep_until(at.into()).await,
+                None => std::future::pending().await,
+            }
+        };
+        let next_monitor = self.next_monitor;
+        let monitor = async move {
+            match next_monitor {
+                Some(at) => tokio::time::sleep_until(at.into()).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        let watch = tokio::time::sleep_until(self.next_watch.into());
+
+        tokio::select! {
+            Some(event) = self.storage_rx.recv() => self.handle_storage_event(event, config),
+            _ = refresh => self.refresh_all(config),
+            _ = monitor => self.refresh_monitored(config),
+            _ = watch => self.check_watched_files(config),
+        }
+    }
+
+    fn handle_storage_event(&mut self, event: RepositoryEvent, config: &Config) {
+        match event {
+            RepositoryEvent::RetrievedAll(_)
+            | RepositoryEvent::RetrievedOne(_)
+            | RepositoryEvent::RetrievedMonitored(_) => self.store(event, config),
+            RepositoryEvent::Stored(event) => self.pass_on(*event),
+            RepositoryEvent::Checked(health) => self.check_health(health),
+            RepositoryEvent::Refresh => {
+                self.refresh_all(config);
+            }
+            _ => {}
+        }
+    }
+
+    /// Writes fetched feeds to the database through the writer, so the app can
+    /// keep reading meanwhile, and passes them on once they're written.
+    fn store(&mut self, event: RepositoryEvent, config: &Config) {
+        let rekey = match (&event, self.edited_feed.take()) {
+            (RepositoryEvent::RetrievedOne(feed), Some((old_id, url))) if url == feed.url() => {
+                self.items_cache.retain(|(id, _)| id != &old_id);
+                (old_id != feed.id()).then_some(old_id)
+            }
+            (_, edited) => {
+                self.edited_feed = edited;
+                None
+            }
+        };
+
+        // Those of the feeds asked for that aren't here failed to be fetched.
+        let failed = match &event {
+            RepositoryEvent::RetrievedAll(feeds) => {
+                Some(self.refresh_count.saturating_sub(feeds.len()))
+            }
+            _ => None,
+        };
+
+        let storage_tx = self.storage_tx.clone();
+        let config = config.clone();
+        self.write(move |storage| {
+            let summary = match (&event, failed) {
+                (RepositoryEvent::RetrievedAll(feeds), Some(failed)) => {
+                    Some(summarize_refresh(storage, feeds, failed))
                 }
-                Some(_) => {}
-                None => {}
-            },
-            Poll::Pending => {}
+                _ => None,
+            };
+            let event = write_fetched(storage, event, rekey, &config);
+            storage_tx
+                .send(RepositoryEvent::Stored(Box::new(event)))
+                .expect("Failed to send storage message");
+            if let Some(summary) = summary {
+                storage_tx
+                    .send(RepositoryEvent::Stored(Box::new(summary)))
+                    .expect("Failed to send storage message");
+            }
+        });
+    }
+
+    /// Queues a write to the database, unless another app holds it.
+    fn write(&self, job: impl FnOnce(&mut SQLiteStorage) + Send + 'static) {
+        if !self.secondary {
+            self.writer.run(job);
         }
     }
 
+    /// Lets the app know of feeds once they're written.
+    fn pass_on(&mut self, event: RepositoryEvent) {
+        match event {
+            RepositoryEvent::RetrievedAll(feeds) => {
+                self.items_cache.clear();
+                let feeds = feeds.into_iter().map(Feed::into_summary).collect();
+                self.app_tx
+                    .send(RepositoryEvent::RetrievedAll(feeds))
+                    .expect("Failed to send app message");
+                self.handle_many = None;
+            }
+            RepositoryEvent::RetrievedOne(feed) => {
+                self.items_cache.retain(|(id, _)| id != feed.id());
+                self.app_tx
+                    .send(RepositoryEvent::RetrievedOne(feed.into_summary()))
+                    .expect("Failed to send app message");
+                self.handle_one = None;
+            }
+            RepositoryEvent::RetrievedMonitored(feeds) => {
+                self.items_cache
+                    .retain(|(id, _)| !feeds.iter().any(|feed| feed.id() == id));
+                let feeds = feeds.into_iter().map(Feed::into_summary).collect();
+                self.app_tx
+                    .send(RepositoryEvent::RetrievedMonitored(feeds))
+                    .expect("Failed to send app message");
+                self.handle_monitor = None;
+            }
+            RepositoryEvent::RefreshSummary { .. } => {
+                self.app_tx.send(event).expect("Failed to send app message");
+            }
+            _ => {}
+        }
+    }
+
+    /// Keeps count of how many refreshes in a row each feed was gone on, letting
+    /// the app know of those gone on enough of them to be dead, and of which
+    /// feeds failed this time.
+    fn check_health(&mut self, health: FeedHealth) {
+        self.app_tx
+            .send(RepositoryEvent::Checked(health.clone()))
+            .expect("Failed to send app message");
+        let app_tx = self.app_tx.clone();
+        self.write(move |storage| {
+            report!(
+                storage.clear_feeds_gone(&health.fetched),
+                "Failed to clear feed failures"
+            );
+            let dead = health
+                .gone
+                .into_iter()
+                .filter(
+                    |feed| match storage.record_feed_gone(&feed.url, feed.status) {
+                        Ok(count) => count >= DEAD_FEED_REFRESHES,
+                        Err(_) => {
+                            log::error!("Failed to record feed failure");
+                            false
+                        }
+                    },
+                )
+                .collect::<Vec<_>>();
+            if !dead.is_empty() {
+                app_tx
+                    .send(RepositoryEvent::DeadFeeds(dead))
+                    .expect("Failed to send app message");
+            }
+        });
+    }
+
+    /// Fetches the icons of the sites these feeds belong to, if they weren't
+    /// already this session, letting the app know once they're in the cache.
+    pub fn fetch_icons(&mut self, feeds: &[Feed], config: &Config) {
+        let Some(cache) = self.icons.clone() else {
+            return;
+        };
+        let urls: Vec<_> = feeds
+            .iter()
+            .filter_map(icons::icon_url)
+            .filter(|url| self.checked_icons.insert(url.clone()))
+            .collect();
+        if urls.is_empty() {
+            return;
+        }
+
+        let client = self.client(config);
+        let app_tx = self.app_tx.clone();
+        tokio::spawn(async move {
+            let fetches = urls
+                .iter()
+                .map(|url| icons::fetch_icon(&client, &cache, url));
+            futures::future::join_all(fetches).await;
+            let _ = app_tx.send(RepositoryEvent::IconsUpdated);
+        });
+    }
+
+    /// The cached icon of the site a feed belongs to, as a PNG image.
+    pub fn icon(&self, feed: &Feed) -> Option<Vec<u8>> {
+        let url = icons::icon_url(feed)?;
+        let (icon, _) = self.icons.as_ref()?.read(&url)?;
+        icons::extract_png(&icon)
+    }
+
     pub fn read_all(&mut self, config: &Config) -> Result<Vec<Feed>, StorageError> {
         let res = self.storage.read_all(config);
         report!(res, "Failed to read from DB");
@@ -100,6 +363,34 @@ impl Repository {
     }
 
     pub fn add_feed_url(&mut self, url: &str, config: &Config) {
+        self.fetch_one(url, config, false);
+    }
+
+    /// Fetches a feed to be looked over before it's subscribed to, handing it
+    /// to the app as `Previewed` without writing it anywhere.
+    pub fn preview_feed_url(&mut self, url: &str, config: &Config) {
+        self.fetch_one(url, config, true);
+    }
+
+    /// Writes a feed fetched by `preview_feed_url` to the database, once it's
+    /// been subscribed to, and passes it on like any other added feed. Its
+    /// archive pages are only fetched now, rather than while previewing it.
+    pub fn add_previewed_feed(&mut self, mut feed: Feed, config: &Config) {
+        let fetcher = self.fetcher.clone();
+        let config = config.clone();
+        let storage_tx = self.storage_tx.clone();
+
+        tokio::spawn(async move {
+            backfill_archives(&*fetcher, &config, &mut feed).await;
+            storage_tx
+                .send(RepositoryEvent::RetrievedOne(feed))
+                .expect("Failed to send storage message");
+        });
+    }
+
+    /// Fetches a single feed, aborting any other still being fetched, and
+    /// either stores it or hands it to the app to preview.
+    fn fetch_one(&mut self, url: &str, config: &Config, preview: bool) {
         let app_tx = self.app_tx.clone();
         if let Some(handle) = &self.handle_one {
             handle.abort();
@@ -110,7 +401,10 @@ impl Repository {
         }
 
         let url = url.to_owned();
-        let interval = config.refresh_timeout();
+        let bridged = config.bridge_url(&url);
+        let fetcher = self.fetcher.clone();
+        let config = config.clone();
+        let responses = self.responses.clone();
         let storage_tx = self.storage_tx.clone();
 
         app_tx
@@ -118,20 +412,28 @@ impl Repository {
             .expect("Failed to send app event");
 
         self.handle_one = Some(tokio::spawn(async move {
-            let client = reqwest::Client::builder()
-                .connect_timeout(Duration::from_secs(interval))
-                .timeout(Duration::from_secs(interval))
-                .build()
-                .expect("failed to build client");
-
-            match make_feed_request(client.get(url).send()).await {
-                Ok(feed) => {
+            let fetched_url = bridged.as_deref().unwrap_or(&url);
+            let req = make_feed_request(&*fetcher, &config, &url, fetched_url, responses.as_ref());
+            match req.await {
+                Ok(mut feed) => {
+                    if !preview {
+                        backfill_archives(&*fetcher, &config, &mut feed).await;
+                    }
+                    if bridged.is_some() {
+                        feed.set_url(&url);
+                    }
                     app_tx
                         .send(RepositoryEvent::Requested((1, 1)))
                         .expect("Failed to send app event");
-                    storage_tx
-                        .send(RepositoryEvent::RetrievedOne(feed))
-                        .expect("Failed to send app event");
+                    if preview {
+                        app_tx
+                            .send(RepositoryEvent::Previewed(feed))
+                            .expect("Failed to send app event");
+                    } else {
+                        storage_tx
+                            .send(RepositoryEvent::RetrievedOne(feed))
+                            .expect("Failed to send app event");
+                    }
                 }
                 Err(_) => {
                     app_tx
@@ -142,11 +444,371 @@ impl Repository {
         }));
     }
 
-    pub fn remove_feed_url(&mut self, url: &str) -> Result<StorageEvent, StorageError> {
-        self.storage.delete_feed_with_url(url)
+    /// Returns the items of a feed, from the recently viewed cache if possible.
+    pub fn items_for_feed(&mut self, feed_id: &str) -> Vec<Item> {
+        if let Some(index) = self.items_cache.iter().position(|(id, _)| id == feed_id) {
+            let entry = self.items_cache.remove(index).expect("cache entry exists");
+            let items = entry.1.clone();
+            self.items_cache.push_front(entry);
+            return items;
+        }
+
+        match self.storage.read_items_for_feed_id(feed_id) {
+            Ok(items) => {
+                self.items_cache.push_front((feed_id.to_owned(), items.clone()));
+                self.items_cache.truncate(ITEMS_CACHE_SIZE);
+                items
+            }
+            Err(_) => {
+                log::error!("Failed to read items for feed {}", feed_id);
+                vec![]
+            }
+        }
+    }
+
+    pub fn search_item_feed_ids(&self, query: &str) -> HashSet<String> {
+        let res = self.storage.search_item_feed_ids(query);
+        report!(res, "Failed to search items");
+        res.unwrap_or_default()
+    }
+
+    pub fn search_items(&self, query: &str, limit: usize) -> Vec<SearchResult> {
+        let res = self.storage.search_items(query, limit);
+        report!(res, "Failed to search items");
+        res.unwrap_or_default()
+    }
+
+    pub fn mark_read(&mut self, item: &Item) {
+        let read = item.clone();
+        self.write(move |storage| {
+            report!(storage.mark_item_read(read.id()), "Failed to mark item read");
+            report!(
+                storage.record_item_event(&read, "read"),
+                "Failed to record read"
+            );
+        });
+        for (_, items) in self.items_cache.iter_mut() {
+            if let Some(cached) = items.iter_mut().find(|i| i.id() == item.id()) {
+                cached.read = true;
+            }
+        }
+    }
+
+    /// Every tag given to items, with how many items have it.
+    pub fn tags(&self) -> Vec<TagSummary> {
+        let res = self.storage.read_tags();
+        report!(res, "Failed to read tags");
+        res.unwrap_or_default()
+    }
+
+    pub fn items_for_tag(&self, tag: &str) -> Vec<Item> {
+        let res = self.storage.read_items_for_tag(tag);
+        report!(res, "Failed to read items for tag");
+        res.unwrap_or_default()
+    }
+
+    /// Every author credited with items, with how many items they wrote.
+    pub fn authors(&self) -> Vec<TagSummary> {
+        let res = self.storage.read_authors();
+        report!(res, "Failed to read authors");
+        res.unwrap_or_default()
+    }
+
+    pub fn items_by_author(&self, author: &str) -> Vec<Item> {
+        let res = self.storage.read_items_by_author(author);
+        report!(res, "Failed to read items by author");
+        res.unwrap_or_default()
+    }
+
+    /// The ids of the feeds marked favorites.
+    pub fn favorite_feeds(&self) -> HashSet<String> {
+        let res = self.storage.read_favorite_feeds();
+        report!(res, "Failed to read favorite feeds");
+        res.unwrap_or_default()
+    }
+
+    pub fn set_favorite(&self, feed: &Feed, favorite: bool) {
+        let id = feed.id().to_owned();
+        self.write(move |storage| {
+            report!(
+                storage.set_feed_favorite(&id, favorite),
+                "Failed to mark feed favorite"
+            );
+        });
+    }
+
+    /// The URLs of the feeds archived, which are no longer refreshed.
+    pub fn archived_feeds(&self) -> HashSet<String> {
+        let res = self.storage.read_archived_feeds();
+        report!(res, "Failed to read archived feeds");
+        res.unwrap_or_default()
+    }
+
+    pub fn set_archived(&self, feed: &Feed, archived: bool) {
+        let id = feed.id().to_owned();
+        self.write(move |storage| {
+            report!(
+                storage.set_feed_archived(&id, archived),
+                "Failed to archive feed"
+            );
+        });
+    }
+
+    /// Writes a snapshot of the database and config file to `dir`.
+    pub fn backup(&self, dir: &Path, config: &Config) -> Result<()> {
+        backup::create(&self.storage, config, dir)
+    }
+
+    /// The version of an updated item before its latest change.
+    pub fn previous_revision(&self, item: &Item) -> Option<ItemRevision> {
+        let res = self.storage.read_previous_revision(item.id());
+        report!(res, "Failed to read item revision");
+        res.ok().flatten()
+    }
+
+    pub fn record_open(&self, item: &Item) {
+        let opened = item.clone();
+        self.write(move |storage| {
+            report!(
+                storage.record_item_event(&opened, "open"),
+                "Failed to record open"
+            );
+        });
+    }
+
+    /// Reading activity over the last `days` days.
+    pub fn reading_stats(&self, days: usize) -> ReadingStats {
+        let res = self.storage.read_reading_stats(days);
+        report!(res, "Failed to read reading stats");
+        res.unwrap_or_default()
+    }
+
+    /// Points a feed at a new URL and refetches it. Its items and read state are
+    /// kept, even if the feed identifies itself differently at the new URL.
+    pub fn edit_feed_url(&mut self, feed_id: &str, url: &str, config: &Config) {
+        let (id, new_url) = (feed_id.to_owned(), url.to_owned());
+        self.write(move |storage| {
+            report!(
+                storage.update_feed_url(&id, &new_url),
+                "Failed to update feed url"
+            );
+        });
+        self.edited_feed = Some((feed_id.to_owned(), url.to_owned()));
+        self.add_feed_url(url, config);
+    }
+
+    pub fn remove_feed_url(&mut self, url: &str, config: &Config) {
+        if let Some(responses) = &self.responses {
+            responses.remove(&config.bridge_url(url).unwrap_or_else(|| url.to_owned()));
+        }
+        let url = url.to_owned();
+        self.write(move |storage| {
+            report!(storage.delete_feed_with_url(&url), "Failed to delete feed");
+        });
+    }
+
+    /// Fetches a web page to be read inside the app, sending it straight to the
+    /// app since pages are not stored.
+    pub fn fetch_page(&self, url: &str, config: &Config) {
+        let app_tx = self.app_tx.clone();
+        let url = url.to_owned();
+        let client = self.client(config);
+        let max_size = config.max_feed_size();
+
+        app_tx
+            .send(RepositoryEvent::Requesting(1))
+            .expect("Failed to send app event");
+
+        tokio::spawn(async move {
+            let body = match client.get(&url).send().await {
+                Ok(res) => {
+                    // Markdown is often served as plain text, so a `.md` path
+                    // counts too, unless the page says it's HTML.
+                    let content_type = res
+                        .headers()
+                        .get(CONTENT_TYPE)
+                        .and_then(|value| value.to_str().ok())
+                        .unwrap_or_default();
+                    let markdown = content_type.starts_with("text/markdown")
+                        || (!content_type.starts_with("text/html")
+                            && res.url().path().ends_with(".md"));
+                    fetch::read_body(res, max_size)
+                        .await
+                        .ok()
+                        .map(|body| (String::from_utf8_lossy(&body).into_owned(), markdown))
+                }
+                Err(_) => None,
+            };
+            let page = match body {
+                Some((body, markdown)) => {
+                    let url = url.clone();
+                    tokio::task::spawn_blocking(move || {
+                        if markdown {
+                            Page::from_markdown(&url, &body)
+                        } else {
+                            Page::from_html(&url, &body)
+                        }
+                    })
+                    .await
+                    .ok()
+                    .flatten()
+                }
+                None => None,
+            };
+
+            let event = match page {
+                Some(page) => RepositoryEvent::RetrievedPage(page),
+                None => RepositoryEvent::Failed(format!("could not load {}", url)),
+            };
+            app_tx.send(event).expect("Failed to send app event");
+        });
+    }
+
+    /// Translates the text of an item or page, sending the result to the app
+    /// along with `id`, by which it's told apart.
+    pub fn translate(&self, id: &str, text: String, config: &Config) {
+        let Some(translator) = config.translator().cloned() else {
+            let _ = self
+                .app_tx
+                .send(RepositoryEvent::Failed("no [translate] configured".into()));
+            return;
+        };
+        let app_tx = self.app_tx.clone();
+        let id = id.to_owned();
+        let client = self.client(config);
+
+        app_tx
+            .send(RepositoryEvent::Requesting(1))
+            .expect("Failed to send app event");
+
+        tokio::spawn(async move {
+            let event = match translate::translate(&client, &translator, &text).await {
+                Ok(translation) => RepositoryEvent::Translated(id, translation),
+                Err(err) => {
+                    log::warn!("Failed to translate: {:#}", err);
+                    RepositoryEvent::Failed(format!("could not translate: {}", err))
+                }
+            };
+            app_tx.send(event).expect("Failed to send app event");
+        });
+    }
+
+    /// Summarizes the text of an item or page, sending the result to the app
+    /// along with `id`, by which it's told apart.
+    pub fn summarize(&self, id: &str, text: String, config: &Config) {
+        let Some(summarizer) = config.summarizer().cloned() else {
+            let _ = self
+                .app_tx
+                .send(RepositoryEvent::Failed("no [summarize] configured".into()));
+            return;
+        };
+        let app_tx = self.app_tx.clone();
+        let id = id.to_owned();
+        let client = http_client_builder(config, false)
+            .timeout(SUMMARY_TIMEOUT)
+            .build()
+            .expect("Failed to build client");
+
+        app_tx
+            .send(RepositoryEvent::Requesting(1))
+            .expect("Failed to send app event");
+
+        tokio::spawn(async move {
+            let event = match summarize::summarize(&client, &summarizer, &text).await {
+                Ok(summary) => RepositoryEvent::Summarized(id, summary),
+                Err(err) => {
+                    log::warn!("Failed to summarize: {:#}", err);
+                    RepositoryEvent::Failed(format!("could not summarize: {}", err))
+                }
+            };
+            app_tx.send(event).expect("Failed to send app event");
+        });
+    }
+
+    /// Shares an item or page with a target reached through a command or an
+    /// API, letting the app know once it's done.
+    pub fn share(&self, target: &ShareTarget, shared: Shared, config: &Config) {
+        let app_tx = self.app_tx.clone();
+        let target = target.clone();
+        let client = self.client(config);
+
+        app_tx
+            .send(RepositoryEvent::Requesting(1))
+            .expect("Failed to send app event");
+
+        tokio::spawn(async move {
+            let event = match share::share(&client, target.kind(), &shared).await {
+                Ok(()) => RepositoryEvent::Shared(target.name().to_owned()),
+                Err(err) => {
+                    log::warn!("Failed to share to {}: {:#}", target.name(), err);
+                    RepositoryEvent::Failed(format!(
+                        "could not share to {}: {}",
+                        target.name(),
+                        err
+                    ))
+                }
+            };
+            app_tx.send(event).expect("Failed to send app event");
+        });
+    }
+
+    /// Exports the event in an item to a file or calendar, letting the app
+    /// know where it went.
+    pub fn export_event(&self, event: CalendarEvent, to: EventExport, config: &Config) {
+        let app_tx = self.app_tx.clone();
+        let client = self.client(config);
+
+        app_tx
+            .send(RepositoryEvent::Requesting(1))
+            .expect("Failed to send app event");
+
+        tokio::spawn(async move {
+            let event = match share::export_event(&client, &event, &to).await {
+                Ok(exported) => RepositoryEvent::ExportedEvent(exported),
+                Err(err) => {
+                    log::warn!("Failed to export event: {:#}", err);
+                    RepositoryEvent::Failed(format!("could not export event: {}", err))
+                }
+            };
+            app_tx.send(event).expect("Failed to send app event");
+        });
+    }
+
+    /// Posts text written in the compose overlay to a Mastodon or Bluesky
+    /// target, letting the app know once it's done.
+    pub fn post(&self, target: &ShareTarget, text: String, config: &Config) {
+        let app_tx = self.app_tx.clone();
+        let target = target.clone();
+        let client = self.client(config);
+
+        app_tx
+            .send(RepositoryEvent::Requesting(1))
+            .expect("Failed to send app event");
+
+        tokio::spawn(async move {
+            let event = match share::post(&client, target.name(), target.kind(), &text).await {
+                Ok(()) => RepositoryEvent::Shared(target.name().to_owned()),
+                Err(err) => {
+                    log::warn!("Failed to post to {}: {:#}", target.name(), err);
+                    RepositoryEvent::Failed(format!("could not post to {}: {}", target.name(), err))
+                }
+            };
+            app_tx.send(event).expect("Failed to send app event");
+        });
     }
 
     pub fn refresh_all(&mut self, config: &Config) {
+        self.next_refresh = self.refresh_interval.map(|i| Instant::now() + i);
+        self.next_monitor = self.monitor_interval.map(|i| Instant::now() + i);
+        if self.secondary {
+            self.items_cache.clear();
+            let feeds = self.read_all(config).unwrap_or_default();
+            self.app_tx
+                .send(RepositoryEvent::RetrievedAll(feeds))
+                .expect("Failed to send app message");
+            return;
+        }
+
         let app_tx = self.app_tx.clone();
         if let Some(handle) = &self.handle_many {
             handle.abort();
@@ -157,69 +819,386 @@ impl Repository {
         }
 
         let storage_tx = self.storage_tx.clone();
+        let responses = self.responses.clone();
+        let fetcher = self.fetcher.clone();
         let config: Config = config.clone();
-        let urls = config.feed_urls().clone();
+        let archived = self.archived_feeds();
+        let urls: Vec<_> = config
+            .feed_urls()
+            .iter()
+            .filter(|url| !archived.contains(*url))
+            .cloned()
+            .collect();
         let count = urls.len();
+        self.refresh_count = count;
 
         app_tx
             .send(RepositoryEvent::Requesting(count))
             .expect("Could not send app message");
 
         self.handle_many = Some(tokio::spawn(async move {
-            let client = reqwest::Client::builder()
-                .connect_timeout(Duration::from_secs(config.refresh_timeout()))
-                .timeout(Duration::from_secs(config.refresh_timeout()))
-                .build()
-                .expect("Failed to build client");
-            let futures: Vec<_> = urls.into_iter().map(|url| client.get(url).send()).collect();
-            let handles: Vec<_> = futures
-                .into_iter()
-                .enumerate()
-                .map(|(n, req)| {
-                    let app_tx = app_tx.clone();
-                    tokio::task::spawn(async move {
-                        let res = make_feed_request(req).await;
-                        app_tx
-                            .send(RepositoryEvent::Requested((n, count)))
-                            .expect("Failed to send app message");
-                        res
-                    })
-                })
-                .collect();
-            let results = futures::future::join_all(handles).await;
-            let mut feeds: Vec<Feed> = results
-                .into_iter()
-                .filter_map(|handle| match handle {
-                    Ok(res) => match res {
-                        Ok(feed) => Some(feed),
-                        _ => None,
-                    },
-                    _ => None,
-                })
-                .collect();
-
+            let (mut feeds, health) =
+                fetch_feeds(fetcher, &config, urls, responses, Some(app_tx)).await;
             sort_feeds(&mut feeds, &config);
+            storage_tx
+                .send(RepositoryEvent::Checked(health))
+                .expect("Failed to send storage message");
             storage_tx
                 .send(RepositoryEvent::RetrievedAll(feeds))
                 .expect("Failed to send storage message");
         }));
     }
+
+    /// Fetches a batch of feeds being added together, in the background
+    /// alongside anything else, reporting how each went with `Checked`.
+    pub fn add_feed_urls(&mut self, urls: Vec<String>, config: &Config) {
+        log::info!("Adding {} feeds", urls.len());
+        self.refetch_in_background(urls, config);
+    }
+
+    /// Refetches status page feeds in the background, without reporting
+    /// progress, since they're refreshed far more often than the rest.
+    fn refresh_monitored(&mut self, config: &Config) {
+        self.next_monitor = self.monitor_interval.map(|i| Instant::now() + i);
+        if self.handle_monitor.is_some() {
+            return;
+        }
+
+        let urls = config.monitored_urls();
+        if urls.is_empty() {
+            return;
+        }
+        self.refetch_in_background(urls, config);
+    }
+
+    /// Refetches local feed files changed since they were last checked, so
+    /// that edits to them show up right away.
+    fn check_watched_files(&mut self, config: &Config) {
+        self.next_watch = Instant::now() + WATCH_INTERVAL;
+        if self.secondary || self.handle_monitor.is_some() {
+            return;
+        }
+
+        let mut changed = vec![];
+        for url in config.feed_urls() {
+            let Some(path) = fetch::file_path(url) else {
+                continue;
+            };
+            let Ok(modified) = fs::metadata(&path).and_then(|meta| meta.modified()) else {
+                continue;
+            };
+            // A file seen for the first time was just fetched with the rest.
+            match self.watched_files.insert(url.clone(), modified) {
+                Some(seen) if seen != modified => changed.push(url.clone()),
+                _ => {}
+            }
+        }
+        if !changed.is_empty() {
+            let archived = self.archived_feeds();
+            changed.retain(|url| !archived.contains(url));
+        }
+        if changed.is_empty() {
+            return;
+        }
+
+        log::info!("Refetching changed local feeds {:?}", changed);
+        self.refetch_in_background(changed, config);
+    }
+
+    /// Refetches some feeds alongside any full refresh, passing them on to the
+    /// app as they're written without reporting progress.
+    fn refetch_in_background(&mut self, urls: Vec<String>, config: &Config) {
+        let storage_tx = self.storage_tx.clone();
+        let responses = self.responses.clone();
+        let fetcher = self.fetcher.clone();
+        let config: Config = config.clone();
+
+        self.handle_monitor = Some(tokio::spawn(async move {
+            let (feeds, health) = fetch_feeds(fetcher, &config, urls, responses, None).await;
+            storage_tx
+                .send(RepositoryEvent::Checked(health))
+                .expect("Failed to send storage message");
+            storage_tx
+                .send(RepositoryEvent::RetrievedMonitored(feeds))
+                .expect("Failed to send storage message");
+        }));
+    }
+}
+
+/// Writes fetched feeds to the database, moving a feed whose URL was edited
+/// from its `old_id` first, and gives them back with their read state.
+fn write_fetched(
+    storage: &mut SQLiteStorage,
+    event: RepositoryEvent,
+    old_id: Option<String>,
+    config: &Config,
+) -> RepositoryEvent {
+    match event {
+        RepositoryEvent::RetrievedAll(mut feeds) => {
+            apply_feed_rules(storage, &feeds, config);
+            report!(storage.write_feeds(&feeds), "Failed to write feeds");
+            apply_tag_rules(storage, &feeds, config);
+            report!(storage.record_backlog(), "Failed to record backlog");
+            restore_read_state(storage, &mut feeds);
+            RepositoryEvent::RetrievedAll(feeds)
+        }
+        RepositoryEvent::RetrievedOne(mut feed) => {
+            if let Some(old_id) = old_id {
+                report!(
+                    storage.rekey_feed(&old_id, feed.id()),
+                    "Failed to rekey feed"
+                );
+            }
+            apply_feed_rules(storage, std::slice::from_ref(&feed), config);
+            report!(storage.write_feed(&feed), "Failed to write feed");
+            apply_tag_rules(storage, std::slice::from_ref(&feed), config);
+            report!(storage.record_backlog(), "Failed to record backlog");
+            restore_read_state(storage, std::slice::from_mut(&mut feed));
+            RepositoryEvent::RetrievedOne(feed)
+        }
+        RepositoryEvent::RetrievedMonitored(mut feeds) => {
+            apply_feed_rules(storage, &feeds, config);
+            report!(storage.write_feeds(&feeds), "Failed to write feeds");
+            apply_tag_rules(storage, &feeds, config);
+            restore_read_state(storage, &mut feeds);
+            RepositoryEvent::RetrievedMonitored(feeds)
+        }
+        event => event,
+    }
+}
+
+/// Counts the new and changed items of a full refresh, which must happen
+/// before its feeds are written.
+fn summarize_refresh(storage: &SQLiteStorage, feeds: &[Feed], failed: usize) -> RepositoryEvent {
+    let (mut new_items, mut updated) = (0, 0);
+    for feed in feeds {
+        match storage.count_item_changes(feed) {
+            Ok((new, changed)) => {
+                new_items += new;
+                updated += changed;
+            }
+            Err(_) => log::error!("Failed to count changes to feed {}", feed.id()),
+        }
+    }
+    RepositoryEvent::RefreshSummary {
+        new_items,
+        updated,
+        failed,
+    }
+}
+
+/// Notifies about and stars the new items of feeds with rules, which must
+/// happen before the feeds are written so that new items can be told apart.
+fn apply_feed_rules(storage: &SQLiteStorage, feeds: &[Feed], config: &Config) {
+    for feed in feeds {
+        let Some(rule) = config.feed_rule(feed.url()) else {
+            continue;
+        };
+        if !rule.should_notify() && !rule.should_star() {
+            continue;
+        }
+
+        let Ok(known) = storage.read_item_ids_for_feed_id(feed.id()) else {
+            log::error!("Failed to read items for feed {}", feed.id());
+            continue;
+        };
+        let new_items = feed
+            .items()
+            .iter()
+            .filter(|item| !known.contains(item.id()))
+            .collect::<Vec<_>>();
+        if new_items.is_empty() {
+            continue;
+        }
+
+        if rule.should_star() {
+            for item in &new_items {
+                report!(storage.star_item(item.id()), "Failed to star item");
+            }
+        }
+
+        // Every item is new the first time a feed is fetched, which is not news.
+        if rule.should_notify() && !known.is_empty() {
+            let body = match new_items.as_slice() {
+                [item] => item.title().unwrap_or("New item").to_owned(),
+                items => format!("{} new items", items.len()),
+            };
+            util::notify(feed.title(), &body);
+        }
+    }
 }
 
+/// Tags the items of feeds by the `[tags]` rules they match, replacing
+/// whatever tags they had so that changed rules apply on the next fetch.
+fn apply_tag_rules(storage: &mut SQLiteStorage, feeds: &[Feed], config: &Config) {
+    let tags = feeds
+        .iter()
+        .flat_map(Feed::items)
+        .map(|item| (item.id().to_owned(), config.item_tags(item)))
+        .collect::<Vec<_>>();
+    report!(storage.write_item_tags(&tags), "Failed to write item tags");
+}
+
+fn restore_read_state(storage: &SQLiteStorage, feeds: &mut [Feed]) {
+    match storage.read_item_ids() {
+        Ok(read_ids) => {
+            for feed in feeds.iter_mut() {
+                feed.restore_read_state(&read_ids);
+            }
+        }
+        Err(_) => log::error!("Failed to read item states"),
+    }
+}
+
+/// Fetches feeds concurrently, letting the app know as each one completes if
+/// given `progress`. Feeds that fail to fetch are left out, but those that are
+/// gone are noted along with those fetched.
+async fn fetch_feeds(
+    fetcher: Arc<dyn FeedFetcher>,
+    config: &Config,
+    urls: Vec<String>,
+    responses: Option<ResponseCache>,
+    progress: Option<UnboundedSender<RepositoryEvent>>,
+) -> (Vec<Feed>, FeedHealth) {
+    let count = urls.len();
+    let handles: Vec<_> = urls
+        .into_iter()
+        .enumerate()
+        .map(|(n, url)| {
+            let progress = progress.clone();
+            let fetcher = fetcher.clone();
+            let responses = responses.clone();
+            let bridged = config.bridge_url(&url);
+            let config = config.clone();
+            tokio::task::spawn(async move {
+                let fetched_url = bridged.as_deref().unwrap_or(&url);
+                let req =
+                    make_feed_request(&*fetcher, &config, &url, fetched_url, responses.as_ref());
+                let res = req.await.map(|mut feed| {
+                    // Keep bridged feeds under the URL they were added with.
+                    if bridged.is_some() {
+                        feed.set_url(&url);
+                    }
+                    feed
+                });
+                if let Some(progress) = progress {
+                    progress
+                        .send(RepositoryEvent::Requested((n, count)))
+                        .expect("Failed to send app message");
+                }
+                (url, res)
+            })
+        })
+        .collect();
+    let results = futures::future::join_all(handles).await;
+
+    let mut feeds = vec![];
+    let mut health = FeedHealth::default();
+    for (url, res) in results.into_iter().filter_map(Result::ok) {
+        match res {
+            Ok(feed) => {
+                health.fetched.push(url);
+                feeds.push(feed);
+            }
+            Err(FetchErr::Gone(status)) => health.gone.push(DeadFeed { url, status }),
+            Err(_) => health.failed.push(url),
+        }
+    }
+    (feeds, health)
+}
+
+/// Walks back through up to `archive_pages` RFC 5005 archive pages of a newly
+/// added feed, adding their items so that its history is available right away.
+async fn backfill_archives(fetcher: &dyn FeedFetcher, config: &Config, feed: &mut Feed) {
+    let feed_url = feed.url().to_owned();
+    let mut visited = HashSet::new();
+    let mut base = reqwest::Url::parse(feed.url()).ok();
+    let mut next = feed.prev_archive().map(str::to_owned);
+
+    for _ in 0..config.archive_pages() {
+        let Some(url) = next
+            .take()
+            .and_then(|href| match &base {
+                Some(base) => base.join(&href).ok(),
+                None => reqwest::Url::parse(&href).ok(),
+            })
+            // Archive pages come from the feed, so they're only ever fetched
+            // over the web, however the feed itself is.
+            .filter(|url| util::is_feed_url(url.as_str(), false))
+            .filter(|url| visited.insert(url.clone()))
+        else {
+            break;
+        };
+
+        match make_feed_request(fetcher, config, &feed_url, url.as_str(), None).await {
+            Ok(archive) => {
+                log::info!("Backfilled {} items from {}", archive.items().len(), url);
+                next = archive.prev_archive().map(str::to_owned);
+                base = Some(url);
+                feed.absorb_archive(archive);
+            }
+            Err(err) => {
+                log::warn!("Failed to fetch archive page {}: {:?}", url, err);
+                break;
+            }
+        }
+    }
+}
+
+/// Fetches and parses the feed subscribed to as `feed_url` from `url`, leaving
+/// timeouts and size limits to `fetcher`. With a response cache, the request
+/// is made conditional on the cached response, which is parsed again if the
+/// feed hasn't changed, and fresh responses are cached once they parse.
 async fn make_feed_request(
-    req: impl std::future::Future<Output = Result<reqwest::Response, reqwest::Error>>,
+    fetcher: &dyn FeedFetcher,
+    config: &Config,
+    feed_url: &str,
+    url: &str,
+    responses: Option<&ResponseCache>,
 ) -> Result<Feed, FetchErr> {
-    match req.await {
-        Ok(res) => {
-            let url = res.url().to_string();
-            match &res.bytes().await {
-                Ok(bytes) => match Feed::read_from(&bytes[..], url) {
-                    Ok(feed) => Ok(feed),
-                    Err(_) => Err(FetchErr::Parse),
-                },
-                Err(_) => Err(FetchErr::Deserialize),
+    let cached = responses.and_then(|responses| responses.lookup(url));
+    let request = FetchRequest {
+        feed_url: feed_url.to_owned(),
+        url: url.to_owned(),
+        etag: cached.as_ref().and_then(|cached| cached.etag.clone()),
+        last_modified: cached.and_then(|cached| cached.last_modified),
+    };
+
+    let (bytes, final_url, etag, last_modified, fresh) =
+        match fetcher.fetch(&request, config).await? {
+            FetchResponse::NotModified => {
+                let responses = responses.ok_or(FetchErr::Deserialize)?;
+                let (bytes, final_url) = responses.read(url).ok_or(FetchErr::Deserialize)?;
+                (bytes, final_url, None, None, false)
+            }
+            FetchResponse::Fetched {
+                body,
+                final_url,
+                etag,
+                last_modified,
+            } => (body, final_url, etag, last_modified, true),
+        };
+
+    // Parsing and flattening item HTML is CPU-bound and can take a while for
+    // large feeds, so keep it off the async workers driving the UI.
+    let url = url.to_owned();
+    let responses = responses.cloned();
+    let strip = config
+        .feed_rule(feed_url)
+        .and_then(FeedRule::strip_rules)
+        .cloned();
+    let tracking_params = config.tracking_params().to_vec();
+    tokio::task::spawn_blocking(move || {
+        let mut feed = Feed::read_stripped(&bytes[..], final_url.clone(), strip.as_ref())?;
+        feed.clean_links(&tracking_params);
+        if let Some(responses) = responses.filter(|_| fresh) {
+            if let Err(err) = responses.write(&url, &final_url, etag, last_modified, &bytes) {
+                log::warn!("Failed to cache response for {}: {}", url, err);
             }
         }
-        Err(_) => Err(FetchErr::Request),
-    }
+        Ok::<_, anyhow::Error>(feed)
+    })
+    .await
+    .map_err(|_| FetchErr::Parse)?
+    .map_err(|_| FetchErr::Parse)
 }
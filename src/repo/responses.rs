@@ -0,0 +1,118 @@
+use crate::config::Config;
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+const RESPONSES_DIR: &'static str = "responses";
+const ICONS_DIR: &'static str = "icons";
+
+/// What is known about a cached response, kept beside its body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// The URL that was requested.
+    pub url: String,
+    /// The URL the response came from, after any redirects.
+    pub final_url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub fetched_at: String,
+}
+
+/// Raw responses kept compressed in the data directory, so that feeds can be
+/// parsed again without downloading them, and feeds and icons refetched only
+/// if changed.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+impl ResponseCache {
+    /// The cache of feed responses, unless feeds aren't being cached at all.
+    pub fn new(config: &Config) -> Option<Self> {
+        Self::in_dir(config, RESPONSES_DIR)
+    }
+
+    /// The cache of the icons of the sites feeds belong to.
+    pub fn icons(config: &Config) -> Option<Self> {
+        Self::in_dir(config, ICONS_DIR)
+    }
+
+    fn in_dir(config: &Config, name: &str) -> Option<Self> {
+        if !config.should_cache() {
+            return None;
+        }
+        Some(Self {
+            dir: config.data_dir_path().join(name),
+        })
+    }
+
+    fn key(url: &str) -> String {
+        // FNV-1a, which unlike the std hasher is stable between Rust releases.
+        let hash = url.bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x100000001b3)
+        });
+        format!("{:016x}", hash)
+    }
+
+    fn meta_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", Self::key(url)))
+    }
+
+    fn body_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.gz", Self::key(url)))
+    }
+
+    /// The metadata of the response cached for `url`, if its body is there too.
+    pub fn lookup(&self, url: &str) -> Option<CachedResponse> {
+        let meta = fs::read_to_string(self.meta_path(url)).ok()?;
+        let meta = serde_json::from_str::<CachedResponse>(&meta).ok()?;
+        (meta.url == url && self.body_path(url).exists()).then_some(meta)
+    }
+
+    /// The body cached for `url`, along with the URL it came from.
+    pub fn read(&self, url: &str) -> Option<(Vec<u8>, String)> {
+        let meta = self.lookup(url)?;
+        let mut body = vec![];
+        GzDecoder::new(File::open(self.body_path(url)).ok()?)
+            .read_to_end(&mut body)
+            .ok()?;
+        Some((body, meta.final_url))
+    }
+
+    /// Caches the body fetched for `url`, replacing anything cached before.
+    pub fn write(
+        &self,
+        url: &str,
+        final_url: &str,
+        etag: Option<String>,
+        last_modified: Option<String>,
+        body: &[u8],
+    ) -> std::io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        let mut encoder =
+            GzEncoder::new(File::create(self.body_path(url))?, Compression::default());
+        encoder.write_all(body)?;
+        encoder.finish()?;
+
+        let meta = CachedResponse {
+            url: url.to_owned(),
+            final_url: final_url.to_owned(),
+            etag,
+            last_modified,
+            fetched_at: Utc::now().to_rfc3339(),
+        };
+        fs::write(self.meta_path(url), serde_json::to_string(&meta)?)
+    }
+
+    /// Forgets the response cached for `url`.
+    pub fn remove(&self, url: &str) {
+        let _ = fs::remove_file(self.meta_path(url));
+        let _ = fs::remove_file(self.body_path(url));
+    }
+}
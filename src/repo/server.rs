@@ -0,0 +1,447 @@
+use super::storage::sqlite::SQLiteStorage;
+use super::storage::StorageError;
+use super::{fever, Repository, RepositoryEvent};
+use crate::config::Config;
+use crate::feed::{Feed, Item};
+use crate::util;
+use anyhow::{bail, Context, Result};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot};
+
+/// The most a request's head and body may take up.
+const MAX_HEAD_SIZE: usize = 16 * 1024;
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// How long a client has to send its whole request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many items `GET /items` returns unless asked for a different number.
+const DEFAULT_ITEM_LIMIT: usize = 50;
+
+/// A request, as much of it as the API needs.
+struct Request {
+    method: String,
+    url: Url,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    /// The value of the header called `name`, in any case.
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A request read off a connection, along with the way back to it.
+type Pending = (Request, oneshot::Sender<(u16, Value)>);
+
+#[derive(Deserialize)]
+struct AddFeed {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct MarkRead {
+    ids: Vec<String>,
+}
+
+/// Serves the database over a small JSON API on `addr` until stopped, for
+/// tools that want to read and mark the same items as the app:
+///
+/// - `GET /feeds` lists feeds, with their unread and total item counts.
+/// - `GET /items` lists the newest items, of one feed with `?feed=<id>`, only
+///   unread ones with `?unread=true`, and at most `?limit=<n>` of them.
+/// - `POST /items/read` marks the items `{"ids": [...]}` read.
+/// - `POST /feeds` subscribes to the feed `{"url": "..."}`, fetching it in the
+///   background.
+///
+/// Each request must carry the `[server]` token from the config file as a
+/// bearer token, and send a JSON body as `application/json`. Requests naming
+/// a host or coming from a page other than this machine's are refused, so
+/// that websites open in a browser can't drive it.
+///
+/// With a `[fever]` API key set, it speaks the Fever API at `/fever/` too, so
+/// that apps which sync with Fever can read from it.
+///
+/// Feeds are refreshed on the refresh interval, as they are in the app.
+pub async fn serve(config: Config, addr: SocketAddr) -> Result<()> {
+    if config.server_token().is_none() {
+        bail!("set a token under [server] in moccasin.toml for clients of the API to send");
+    }
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("could not listen on {}", addr))?;
+    let addr = listener.local_addr()?;
+    let (tx, mut rx) = mpsc::unbounded_channel::<RepositoryEvent>();
+    let (requests_tx, mut requests) = mpsc::unbounded_channel::<Pending>();
+    let mut server = Server {
+        repo: Repository::init(&config, tx)?,
+        storage: SQLiteStorage::init(&config),
+        config,
+        addr,
+    };
+
+    println!("Serving the API on http://{}", addr);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                // Requests are read off their connections apart from the
+                // loop, so that a slow client holds up no one else.
+                Ok((stream, _)) => {
+                    tokio::spawn(respond(stream, requests_tx.clone()));
+                }
+                Err(err) => log::warn!("Failed to accept a connection: {}", err),
+            },
+            Some((request, reply)) = requests.recv() => {
+                let _ = reply.send(server.handle(&request));
+            }
+            _ = server.repo.process(&server.config) => {}
+            Some(event) = rx.recv() => match event {
+                RepositoryEvent::Failed(err) => log::warn!("{}", err),
+                RepositoryEvent::Errored => log::warn!("Failed to fetch a new feed"),
+                _ => {}
+            },
+        }
+    }
+}
+
+struct Server {
+    repo: Repository,
+    /// A connection of its own, so reads see what the app wrote meanwhile
+    /// rather than what the repository last cached.
+    storage: SQLiteStorage,
+    config: Config,
+    /// The address listened on, which clients may name as the host.
+    addr: SocketAddr,
+}
+
+impl Server {
+    fn handle(&mut self, request: &Request) -> (u16, Value) {
+        if !self.is_local(request) {
+            return (
+                403,
+                json!({ "error": "requests must come from this machine" }),
+            );
+        }
+
+        // Fever clients post even to read, so it sorts out writes itself.
+        if let ("/fever" | "/fever/", Some(api_key)) =
+            (request.url.path(), self.config.fever_api_key())
+        {
+            return match fever::respond(
+                &mut self.storage,
+                &self.config,
+                api_key,
+                &request.url,
+                &request.body,
+            ) {
+                Ok(response) => (200, response),
+                Err(_) => (500, json!({ "error": "could not read the database" })),
+            };
+        }
+
+        let token = request
+            .header("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if token.is_none() || token != self.config.server_token() {
+            return (401, json!({ "error": "missing or wrong token" }));
+        }
+
+        let writes = request.method == "POST";
+        if writes && self.config.read_only() {
+            return (403, json!({ "error": "moccasin is read-only" }));
+        }
+        let json = request.header("content-type").map_or(false, |value| {
+            value.split(';').next().map_or(false, |kind| {
+                kind.trim().eq_ignore_ascii_case("application/json")
+            })
+        });
+        if writes && !json {
+            return (415, json!({ "error": "expected an application/json body" }));
+        }
+
+        match (request.method.as_str(), request.url.path()) {
+            ("GET", "/feeds") => match self.storage.read_all(&self.config) {
+                Ok(feeds) => (200, Value::Array(feeds.iter().map(feed_json).collect())),
+                Err(_) => (500, json!({ "error": "could not read feeds" })),
+            },
+            ("GET", "/items") => self.list_items(&request.url),
+            ("POST", "/items/read") => match serde_json::from_slice::<MarkRead>(&request.body) {
+                Ok(MarkRead { ids }) => self.mark_read(&ids),
+                Err(err) => (400, json!({ "error": err.to_string() })),
+            },
+            ("POST", "/feeds") => match serde_json::from_slice::<AddFeed>(&request.body) {
+                Ok(AddFeed { url }) => self.add_feed(&url),
+                Err(err) => (400, json!({ "error": err.to_string() })),
+            },
+            (_, "/feeds" | "/items" | "/items/read") => {
+                (405, json!({ "error": "method not allowed" }))
+            }
+            _ => (404, json!({ "error": "not found" })),
+        }
+    }
+
+    /// Whether a request names this machine as its host, and, if it comes
+    /// from a web page, one served from this machine too. Browser extensions
+    /// run on this machine, so they may send requests as well.
+    fn is_local(&self, request: &Request) -> bool {
+        let local = |url: &Url| {
+            let host = url.host_str().unwrap_or_default();
+            match host.trim_start_matches('[').trim_end_matches(']').parse() {
+                Ok(ip) => self.is_local_ip(ip),
+                Err(_) => host.eq_ignore_ascii_case("localhost"),
+            }
+        };
+
+        let host = request
+            .header("host")
+            .and_then(|host| Url::parse(&format!("http://{}", host)).ok());
+        if !host.map_or(false, |host| local(&host)) {
+            return false;
+        }
+        match request.header("origin").map(Url::parse) {
+            None => true,
+            Some(Ok(origin))
+                if matches!(
+                    origin.scheme(),
+                    "chrome-extension" | "moz-extension" | "safari-web-extension"
+                ) =>
+            {
+                true
+            }
+            Some(Ok(origin)) => local(&origin),
+            Some(Err(_)) => false,
+        }
+    }
+
+    /// Whether `ip` is a loopback address or the one listened on, which is
+    /// any at all when listening on every interface.
+    fn is_local_ip(&self, ip: IpAddr) -> bool {
+        ip.is_loopback() || self.addr.ip().is_unspecified() || ip == self.addr.ip()
+    }
+
+    fn list_items(&mut self, url: &Url) -> (u16, Value) {
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        let unread = param("unread").map_or(false, |value| value == "true");
+        let Ok(limit) = param("limit").map_or(Ok(DEFAULT_ITEM_LIMIT), |limit| limit.parse()) else {
+            return (400, json!({ "error": "limit must be a number" }));
+        };
+
+        let mut items = match self.items(param("feed").as_deref()) {
+            Ok(items) => items,
+            Err(_) => return (500, json!({ "error": "could not read items" })),
+        };
+        items.retain(|item| !unread || !item.read());
+        // Newest first, with undated items last.
+        items.sort_by_cached_key(|item| {
+            std::cmp::Reverse(item.pub_date().and_then(util::parse_date))
+        });
+        items.truncate(limit);
+        (200, Value::Array(items.iter().map(item_json).collect()))
+    }
+
+    /// The items of one feed, or of every feed.
+    fn items(&mut self, feed_id: Option<&str>) -> Result<Vec<Item>, StorageError> {
+        let feed_ids = match feed_id {
+            Some(id) => vec![id.to_owned()],
+            None => self
+                .storage
+                .read_all(&self.config)?
+                .iter()
+                .map(|feed| feed.id().to_owned())
+                .collect(),
+        };
+        let mut items = vec![];
+        for id in feed_ids {
+            items.extend(self.storage.read_items_for_feed_id(&id)?);
+        }
+        Ok(items)
+    }
+
+    /// Marks items read, counting them in reading statistics as the app does,
+    /// and ignoring ids of items that don't exist.
+    fn mark_read(&mut self, ids: &[String]) -> (u16, Value) {
+        let Ok(items) = self.storage.read_items_with_ids(ids) else {
+            return (500, json!({ "error": "could not read items" }));
+        };
+        let mut marked = 0;
+        for item in &items {
+            if item.read() {
+                continue;
+            }
+            if self.storage.mark_item_read(item.id()).is_err() {
+                return (500, json!({ "error": "could not mark items read" }));
+            }
+            let _ = self.storage.record_item_event(item, "read");
+            marked += 1;
+        }
+        (200, json!({ "marked": marked }))
+    }
+
+    fn add_feed(&mut self, url: &str) -> (u16, Value) {
+        // Other sources run commands, which only the config file may set up.
+        if !util::is_feed_url(url, false) {
+            return (
+                400,
+                json!({ "error": format!("'{}' is not an http or https URL", url) }),
+            );
+        }
+        if self.config.feed_urls().contains(url) {
+            return (
+                409,
+                json!({ "error": format!("already subscribed to {}", url) }),
+            );
+        }
+        if let Err(err) = self.config.add_feed_url(url) {
+            return (
+                500,
+                json!({ "error": format!("could not write config: {}", err) }),
+            );
+        }
+        self.repo.add_feed_url(url, &self.config);
+        (202, json!({ "url": url }))
+    }
+}
+
+/// Reads a client's request, hands it to the server, and writes back its
+/// answer.
+async fn respond(mut stream: TcpStream, requests: mpsc::UnboundedSender<Pending>) {
+    let (status, body) =
+        match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+            Ok(Ok(request)) => {
+                let (reply, answer) = oneshot::channel();
+                let _ = requests.send((request, reply));
+                answer
+                    .await
+                    .unwrap_or_else(|_| (500, json!({ "error": "the server is stopping" })))
+            }
+            Ok(Err(err)) => (400, json!({ "error": err.to_string() })),
+            Err(_) => (408, json!({ "error": "request timed out" })),
+        };
+
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    );
+    if let Err(err) = stream.write_all(response.as_bytes()).await {
+        log::warn!("Failed to respond: {}", err);
+    }
+}
+
+/// Reads a request's head, and its body if it says how long that is.
+async fn read_request(stream: &mut TcpStream) -> Result<Request> {
+    let mut buf = Vec::new();
+    let head_end = loop {
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        if buf.len() > MAX_HEAD_SIZE {
+            bail!("request head too large");
+        }
+        let mut chunk = [0; 4096];
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            bail!("connection closed mid-request");
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        bail!("malformed request line");
+    };
+    let url = Url::parse("http://localhost")?
+        .join(target)
+        .context("malformed request target")?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_owned(), value.trim().to_owned()))
+        .collect::<Vec<_>>();
+    let length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .map(|(_, value)| value.parse::<usize>())
+        .transpose()
+        .context("malformed content length")?
+        .unwrap_or(0);
+    if length > MAX_BODY_SIZE {
+        bail!("request body too large");
+    }
+
+    let mut body = buf[head_end + 4..].to_vec();
+    while body.len() < length {
+        let mut chunk = [0; 4096];
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            bail!("connection closed mid-request");
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(length);
+
+    Ok(Request {
+        method: method.to_owned(),
+        url,
+        headers,
+        body,
+    })
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    }
+}
+
+fn feed_json(feed: &Feed) -> Value {
+    json!({
+        "id": feed.id(),
+        "title": feed.title(),
+        "url": feed.url(),
+        "link": feed.link(),
+        "unread": feed.unread_count(),
+        "items": feed.item_count(),
+    })
+}
+
+fn item_json(item: &Item) -> Value {
+    json!({
+        "id": item.id(),
+        "feed_id": item.feed_id(),
+        "title": item.title(),
+        "author": item.author(),
+        "link": item.link(),
+        "date": item.pub_date(),
+        "read": item.read(),
+    })
+}
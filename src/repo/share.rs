@@ -0,0 +1,284 @@
+use crate::config::ShareKind;
+use crate::feed::CalendarEvent;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// What's sent to a share target: the title, link and text of an item or page.
+#[derive(Debug, Clone, Default)]
+pub struct Shared {
+    pub title: String,
+    pub link: String,
+    pub text: String,
+}
+
+/// Where the event in an item is exported to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventExport {
+    File(PathBuf),
+    /// Imported into a calendar with `khal import`.
+    Khal,
+}
+
+/// Shares with a target reached through a command or an API. The clipboard
+/// and email are left to the app, which owns the terminal, and posts go to
+/// [`post`] once they're written.
+pub async fn share(client: &reqwest::Client, kind: &ShareKind, shared: &Shared) -> Result<()> {
+    match kind {
+        ShareKind::Command(command) => run_command(command, shared).await,
+        ShareKind::Wallabag {
+            url,
+            client_id,
+            client_secret,
+            username,
+            password,
+        } => {
+            let response = client
+                .post(format!("{}/oauth/v2/token", url))
+                .form(&[
+                    ("grant_type", "password"),
+                    ("client_id", client_id),
+                    ("client_secret", client_secret),
+                    ("username", username),
+                    ("password", password),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?;
+            let response: Value = serde_json::from_str(&response)?;
+            let token = response["access_token"]
+                .as_str()
+                .ok_or_else(|| anyhow!("no access token in response"))?;
+            client
+                .post(format!("{}/api/entries.json", url))
+                .bearer_auth(token)
+                .form(&[("url", &shared.link), ("title", &shared.title)])
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(())
+        }
+        _ => bail!("shared by the app"),
+    }
+}
+
+/// Posts `text` as a status to a Mastodon or Bluesky account, looking up its
+/// secret in the system keyring under the target's `name` if it isn't given.
+pub async fn post(
+    client: &reqwest::Client,
+    name: &str,
+    kind: &ShareKind,
+    text: &str,
+) -> Result<()> {
+    match kind {
+        ShareKind::Mastodon {
+            url,
+            token,
+            visibility,
+        } => {
+            let token = match token {
+                Some(token) => token.to_owned(),
+                None => keyring_secret(name).await?,
+            };
+            let mut form = vec![("status", text)];
+            if let Some(visibility) = visibility {
+                form.push(("visibility", visibility));
+            }
+            client
+                .post(format!("{}/api/v1/statuses", url))
+                .bearer_auth(token)
+                .form(&form)
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(())
+        }
+        ShareKind::Bluesky {
+            url,
+            handle,
+            password,
+        } => {
+            let password = match password {
+                Some(password) => password.to_owned(),
+                None => keyring_secret(name).await?,
+            };
+            let session = json!({ "identifier": handle, "password": password });
+            let response = client
+                .post(format!("{}/xrpc/com.atproto.server.createSession", url))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(session.to_string())
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?;
+            let session: Value = serde_json::from_str(&response)?;
+            let (Some(token), Some(did)) = (session["accessJwt"].as_str(), session["did"].as_str())
+            else {
+                bail!("no session in response");
+            };
+
+            let created_at = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+            let record = json!({
+                "repo": did,
+                "collection": "app.bsky.feed.post",
+                "record": {
+                    "$type": "app.bsky.feed.post",
+                    "text": text,
+                    "facets": link_facets(text),
+                    "createdAt": created_at,
+                },
+            });
+            client
+                .post(format!("{}/xrpc/com.atproto.repo.createRecord", url))
+                .bearer_auth(token)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(record.to_string())
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(())
+        }
+        _ => bail!("{} can't be posted to", name),
+    }
+}
+
+/// Marks up the links in a Bluesky post, which are only made clickable by
+/// facets giving their byte ranges.
+fn link_facets(text: &str) -> Vec<Value> {
+    let mut facets = vec![];
+    let mut start = 0;
+    for word in text.split_inclusive(char::is_whitespace) {
+        let link = word.trim_end();
+        if link.starts_with("https://") || link.starts_with("http://") {
+            facets.push(json!({
+                "index": { "byteStart": start, "byteEnd": start + link.len() },
+                "features": [{ "$type": "app.bsky.richtext.facet#link", "uri": link }],
+            }));
+        }
+        start += word.len();
+    }
+    facets
+}
+
+/// Looks up the secret of a share target in the system keyring, where it's
+/// kept under the service "moccasin" and the target's name.
+async fn keyring_secret(name: &str) -> Result<String> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("security");
+        command.args(["find-generic-password", "-s", "moccasin", "-a", name, "-w"]);
+        command
+    } else if cfg!(target_os = "linux") {
+        let mut command = Command::new("secret-tool");
+        command.args(["lookup", "service", "moccasin", "account", name]);
+        command
+    } else {
+        bail!("no keyring to look up {} in", name);
+    };
+
+    let output = command
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .context("could not read the keyring")?;
+    let secret = String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_owned();
+    if !output.status.success() || secret.is_empty() {
+        bail!("no secret for {} in the keyring", name);
+    }
+    Ok(secret)
+}
+
+/// Exports an event, downloading its calendar file first if it has one, and
+/// says where it went.
+pub async fn export_event(
+    client: &reqwest::Client,
+    event: &CalendarEvent,
+    to: &EventExport,
+) -> Result<String> {
+    let ics = match event {
+        CalendarEvent::Attached(url) => {
+            let ics = client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?;
+            if !ics.trim_start().starts_with("BEGIN:VCALENDAR") {
+                bail!("{} is not a calendar file", url);
+            }
+            ics
+        }
+        CalendarEvent::Mentioned(event) => event.to_ics(),
+    };
+
+    match to {
+        EventExport::File(path) => {
+            tokio::fs::write(path, ics)
+                .await
+                .with_context(|| format!("could not write {}", path.display()))?;
+            Ok(format!("exported event to {}", path.display()))
+        }
+        EventExport::Khal => {
+            let mut child = Command::new("khal")
+                .args(["import", "--batch", "-"])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .context("could not run khal")?;
+            let mut stdin = child.stdin.take().context("no stdin")?;
+            stdin.write_all(ics.as_bytes()).await?;
+            drop(stdin);
+
+            let status = child.wait().await?;
+            if !status.success() {
+                bail!("khal failed with {}", status);
+            }
+            Ok("imported event into khal".to_owned())
+        }
+    }
+}
+
+/// Pipes the text through a shell command, with the title and link in its
+/// environment rather than its command line, where they could run as code.
+async fn run_command(command: &str, shared: &Shared) -> Result<()> {
+    let mut shell = if cfg!(target_os = "windows") {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    let mut child = shell
+        .env("MOCCASIN_TITLE", &shared.title)
+        .env("MOCCASIN_LINK", &shared.link)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("could not run {}", command))?;
+
+    let mut stdin = child.stdin.take().context("no stdin")?;
+    let text = shared.text.clone();
+    tokio::spawn(async move {
+        let _ = stdin.write_all(text.as_bytes()).await;
+    });
+
+    let status = child.wait().await?;
+    if !status.success() {
+        bail!("{} failed with {}", command, status);
+    }
+    Ok(())
+}
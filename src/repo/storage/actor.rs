@@ -0,0 +1,34 @@
+use super::sqlite::SQLiteStorage;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+type Job = Box<dyn FnOnce(&mut SQLiteStorage) + Send>;
+
+/// Owns a connection to the database on a thread of its own, running the
+/// writes sent to it one after another, so that neither input handling nor
+/// rendering ever waits on them. The thread stops once the actor is dropped.
+pub struct StorageActor {
+    tx: Sender<Job>,
+}
+
+impl StorageActor {
+    pub fn spawn(mut storage: SQLiteStorage) -> Self {
+        let (tx, rx) = mpsc::channel::<Job>();
+        thread::Builder::new()
+            .name("storage".into())
+            .spawn(move || {
+                for job in rx {
+                    job(&mut storage);
+                }
+            })
+            .expect("Failed to start storage thread");
+        Self { tx }
+    }
+
+    /// Queues a job to run against the database after those sent before it.
+    pub fn run(&self, job: impl FnOnce(&mut SQLiteStorage) + Send + 'static) {
+        if self.tx.send(Box::new(job)).is_err() {
+            log::error!("Storage thread has stopped");
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use rusqlite::{params, Connection, Result, TransactionBehavior};
+
+/// Changes to the schema in the order they were made. Each is applied once,
+/// and recorded in `schema_migrations` by its place in this list, so new ones
+/// must only ever be added to the end.
+const MIGRATIONS: [(&str, &str); 5] = [
+    ("initial", include_str!("migrations/0001_initial.sql")),
+    (
+        "items_fts_update",
+        include_str!("migrations/0002_items_fts_update.sql"),
+    ),
+    (
+        "null_fields",
+        include_str!("migrations/0003_null_fields.sql"),
+    ),
+    (
+        "item_enclosures",
+        include_str!("migrations/0004_item_enclosures.sql"),
+    ),
+    (
+        "feed_language",
+        include_str!("migrations/0005_feed_language.sql"),
+    ),
+];
+
+/// Brings the database up to the latest schema, applying each migration it is
+/// missing in a transaction of its own. A migration that fails is rolled back,
+/// leaving the database as it was after the last one that succeeded.
+pub fn migrate(conn: &mut Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER NOT NULL PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )",
+    )?;
+
+    for (version, (name, sql)) in (1..).zip(MIGRATIONS) {
+        // Another connection may be migrating the same database, so the
+        // version is only trusted once the write lock is held.
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let current: usize = tx.query_row(
+            "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+            [],
+            |r| r.get(0),
+        )?;
+        if current > MIGRATIONS.len() {
+            log::warn!(
+                "Database schema is at version {}, newer than the {} known here",
+                current,
+                MIGRATIONS.len()
+            );
+            return Ok(());
+        }
+        if current >= version {
+            continue;
+        }
+
+        log::info!("Migrating database to version {} ({})", version, name);
+        tx.execute_batch(sql).map_err(|err| {
+            log::error!("Migration {} ({}) failed: {:?}", version, name, err);
+            err
+        })?;
+        tx.execute(
+            "INSERT INTO schema_migrations(version, name, applied_at)
+                VALUES(?1, ?2, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+            params![version, name],
+        )?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
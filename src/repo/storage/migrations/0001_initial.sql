@@ -0,0 +1,93 @@
+CREATE TABLE IF NOT EXISTS feeds (
+    id TEXT NOT NULL PRIMARY KEY,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL,
+    categories TEXT NOT NULL,
+    url TEXT NOT NULL,
+    link TEXT NOT NULL,
+    ttl TEXT,
+    pub_date TEXT,
+    last_fetched TEXT
+);
+CREATE TABLE IF NOT EXISTS items (
+    id TEXT NOT NULL PRIMARY KEY,
+    feed_id TEXT NOT NULL,
+    title TEXT,
+    author TEXT,
+    content TEXT,
+    description TEXT,
+    text_description TEXT,
+    categories TEXT,
+    link TEXT,
+    pub_date TEXT,
+    FOREIGN KEY(feed_id) REFERENCES feeds(id) ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS item_states (
+    item_id TEXT NOT NULL PRIMARY KEY,
+    read_at TEXT,
+    starred INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS feed_states (
+    feed_id TEXT NOT NULL PRIMARY KEY,
+    favorite INTEGER NOT NULL DEFAULT 0,
+    archived INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS item_events (
+    item_id TEXT NOT NULL,
+    feed_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS item_revisions (
+    item_id TEXT NOT NULL,
+    title TEXT,
+    text_description TEXT,
+    replaced_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS item_tags (
+    item_id TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY(item_id, tag)
+);
+CREATE TABLE IF NOT EXISTS item_categories (
+    item_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    domain TEXT,
+    PRIMARY KEY(item_id, name)
+);
+CREATE VIEW IF NOT EXISTS item_labels AS
+    SELECT item_id, tag FROM item_tags
+    UNION
+    SELECT item_id, name FROM item_categories;
+CREATE TABLE IF NOT EXISTS feed_failures (
+    url TEXT NOT NULL PRIMARY KEY,
+    status INTEGER NOT NULL,
+    count INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS backlog (
+    day TEXT NOT NULL PRIMARY KEY,
+    unread INTEGER NOT NULL
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS items_fts USING fts5(
+    item_id UNINDEXED,
+    title,
+    body
+);
+CREATE TRIGGER IF NOT EXISTS items_fts_insert AFTER INSERT ON items BEGIN
+    DELETE FROM items_fts WHERE item_id = new.id;
+    INSERT INTO items_fts(item_id, title, body)
+        VALUES(new.id, new.title, new.text_description);
+END;
+CREATE TRIGGER IF NOT EXISTS items_revision BEFORE INSERT ON items BEGIN
+    INSERT INTO item_revisions(item_id, title, text_description, replaced_at)
+        SELECT id, title, text_description, strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+        FROM items
+        WHERE id = new.id
+            AND (title IS NOT new.title OR text_description IS NOT new.text_description);
+END;
+CREATE TRIGGER IF NOT EXISTS items_fts_delete AFTER DELETE ON items BEGIN
+    DELETE FROM items_fts WHERE item_id = old.id;
+END;
+INSERT INTO items_fts(item_id, title, body)
+    SELECT id, title, text_description FROM items
+    WHERE id NOT IN (SELECT item_id FROM items_fts);
@@ -0,0 +1,5 @@
+CREATE TRIGGER IF NOT EXISTS items_fts_update AFTER UPDATE OF title, text_description ON items BEGIN
+    DELETE FROM items_fts WHERE item_id = old.id;
+    INSERT INTO items_fts(item_id, title, body)
+        VALUES(new.id, new.title, new.text_description);
+END;
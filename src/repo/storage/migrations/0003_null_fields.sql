@@ -0,0 +1,18 @@
+UPDATE feeds SET
+    ttl = NULLIF(ttl, 'NULL'),
+    pub_date = NULLIF(pub_date, 'NULL'),
+    last_fetched = NULLIF(last_fetched, 'NULL')
+WHERE 'NULL' IN (ttl, pub_date, last_fetched);
+UPDATE items SET
+    title = NULLIF(title, 'NULL'),
+    author = NULLIF(author, 'NULL'),
+    content = NULLIF(content, 'NULL'),
+    description = NULLIF(description, 'NULL'),
+    text_description = NULLIF(text_description, 'NULL'),
+    link = NULLIF(link, 'NULL'),
+    pub_date = NULLIF(pub_date, 'NULL')
+WHERE 'NULL' IN (title, author, content, description, text_description, link, pub_date);
+UPDATE item_revisions SET
+    title = NULLIF(title, 'NULL'),
+    text_description = NULLIF(text_description, 'NULL')
+WHERE 'NULL' IN (title, text_description);
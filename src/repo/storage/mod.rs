@@ -1,4 +1,8 @@
+pub mod actor;
+mod migrations;
 pub mod sqlite;
+#[cfg(test)]
+mod tests;
 
 pub enum StorageEvent {
     Insert,
@@ -7,4 +11,5 @@ pub enum StorageEvent {
     NoOp,
 }
 
+#[derive(Debug)]
 pub struct StorageError;
@@ -1,13 +1,171 @@
-use super::{StorageError, StorageEvent};
+use super::{migrations, StorageError, StorageEvent};
 use crate::config::Config;
 use crate::feed::{Feed, Item};
+use crate::repo::{ItemRevision, ItemState, ReadingStats, SearchResult, TagSummary};
 use crate::util;
-use rusqlite::{Connection, Result, Row, Transaction};
+use chrono::{Duration, Local, Utc};
+use rusqlite::backup::Progress;
+use rusqlite::{params, Connection, DatabaseName, OptionalExtension, Result, Row};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub struct SQLiteStorage {
     conn: Connection,
 }
 
+/// How long to wait for another connection to finish writing before giving up.
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Writes a fetched feed, updating only what came from the feed itself should
+/// it already be stored, so that columns holding the user's own state survive.
+const UPSERT_FEED: &str = "INSERT INTO feeds(
+        id,
+        title,
+        description,
+        categories,
+        url,
+        link,
+        ttl,
+        pub_date,
+        last_fetched,
+        language
+    ) VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+    ON CONFLICT(id) DO UPDATE SET
+        title = excluded.title,
+        description = excluded.description,
+        categories = excluded.categories,
+        url = excluded.url,
+        link = excluded.link,
+        ttl = excluded.ttl,
+        pub_date = excluded.pub_date,
+        last_fetched = excluded.last_fetched,
+        language = excluded.language";
+
+/// Writes a fetched item, updating only its content should it already be stored.
+const UPSERT_ITEM: &str = "INSERT INTO items(
+        id,
+        feed_id,
+        title,
+        author,
+        content,
+        description,
+        text_description,
+        categories,
+        link,
+        pub_date,
+        enclosure
+    ) VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+    ON CONFLICT(id) DO UPDATE SET
+        feed_id = excluded.feed_id,
+        title = excluded.title,
+        author = excluded.author,
+        content = excluded.content,
+        description = excluded.description,
+        text_description = excluded.text_description,
+        categories = excluded.categories,
+        link = excluded.link,
+        pub_date = excluded.pub_date,
+        enclosure = excluded.enclosure";
+
+/// Moves everything stored under one feed id (?1) over to another (?2),
+/// including item ids derived from the feed id when items have no guid.
+const REKEY_FEED_STATEMENTS: [&str; 11] = [
+    "UPDATE OR REPLACE feeds SET id = ?2 WHERE id = ?1",
+    "UPDATE OR REPLACE feed_states SET feed_id = ?2 WHERE feed_id = ?1",
+    "UPDATE items SET feed_id = ?2 WHERE feed_id = ?1",
+    "UPDATE item_events SET feed_id = ?2 WHERE feed_id = ?1",
+    "UPDATE OR REPLACE items SET id = ?2 || substr(id, length(?1) + 1)
+        WHERE substr(id, 1, length(?1) + 1) = ?1 || ':'",
+    "UPDATE OR REPLACE item_states SET item_id = ?2 || substr(item_id, length(?1) + 1)
+        WHERE substr(item_id, 1, length(?1) + 1) = ?1 || ':'",
+    "UPDATE item_events SET item_id = ?2 || substr(item_id, length(?1) + 1)
+        WHERE substr(item_id, 1, length(?1) + 1) = ?1 || ':'",
+    "UPDATE item_revisions SET item_id = ?2 || substr(item_id, length(?1) + 1)
+        WHERE substr(item_id, 1, length(?1) + 1) = ?1 || ':'",
+    "UPDATE OR REPLACE item_tags SET item_id = ?2 || substr(item_id, length(?1) + 1)
+        WHERE substr(item_id, 1, length(?1) + 1) = ?1 || ':'",
+    "UPDATE OR REPLACE item_categories SET item_id = ?2 || substr(item_id, length(?1) + 1)
+        WHERE substr(item_id, 1, length(?1) + 1) = ?1 || ':'",
+    "UPDATE items_fts SET item_id = ?2 || substr(item_id, length(?1) + 1)
+        WHERE substr(item_id, 1, length(?1) + 1) = ?1 || ':'",
+];
+
+/// Writes a feed and each of its items, leaving it to the caller to wrap them
+/// in a transaction so that none are written should any fail.
+fn write_feed_rows(conn: &Connection, feed: &Feed) -> Result<(), StorageError> {
+    let mut feed_stmt = conn.prepare_cached(UPSERT_FEED).map_err(|err| {
+        log::warn!("{:?}", err);
+        StorageError
+    })?;
+    let mut item_stmt = conn.prepare_cached(UPSERT_ITEM).map_err(|err| {
+        log::warn!("{:?}", err);
+        StorageError
+    })?;
+
+    if let Err(err) = feed_stmt.execute(params![
+        feed.id(),
+        feed.title(),
+        feed.description(),
+        "[]",
+        feed.url(),
+        feed.link(),
+        feed.ttl(),
+        feed.pub_date(),
+        feed.last_fetched(),
+        feed.language(),
+    ]) {
+        log::error!("{:?}", err);
+        return Err(StorageError);
+    }
+
+    for item in feed.items() {
+        if let Err(err) = item_stmt.execute(params![
+            item.id(),
+            item.feed_id(),
+            item.title(),
+            item.author(),
+            item.content(),
+            item.description(),
+            item.description(),
+            "[]",
+            item.link(),
+            item.pub_date(),
+            item.enclosure()
+                .and_then(|enclosure| serde_json::to_string(enclosure).ok()),
+        ]) {
+            log::error!("{:?}", err);
+            return Err(StorageError);
+        }
+        write_item_categories(conn, item)?;
+    }
+    Ok(())
+}
+
+/// Replaces the stored categories of an item with those it was fetched with.
+fn write_item_categories(conn: &Connection, item: &Item) -> Result<(), StorageError> {
+    let mut delete = conn
+        .prepare_cached("DELETE FROM item_categories WHERE item_id = ?1")
+        .map_err(|_| StorageError)?;
+    let mut insert = conn
+        .prepare_cached(
+            "INSERT OR IGNORE INTO item_categories(item_id, name, domain) VALUES(?1, ?2, ?3)",
+        )
+        .map_err(|_| StorageError)?;
+
+    if let Err(err) = delete.execute([item.id()]) {
+        log::error!("{:?}", err);
+        return Err(StorageError);
+    }
+    for category in item.categories() {
+        if let Err(err) = insert.execute(params![item.id(), category.name, category.domain]) {
+            log::error!("{:?}", err);
+            return Err(StorageError);
+        }
+    }
+    Ok(())
+}
+
 trait FromRow<'stmt> {
     fn from_row(row: &'stmt Row) -> Self;
 }
@@ -22,9 +180,13 @@ impl<'stmt> FromRow<'stmt> for Feed {
             url: row.get(4).unwrap(),
             link: row.get(5).unwrap(),
             ttl: row.get(6).ok(),
+            language: row.get(9).ok(),
             items: vec![],
             pub_date: row.get(7).ok(),
             last_fetched: row.get(8).ok(),
+            item_count: row.get(10).unwrap_or_default(),
+            unread_count: row.get(11).unwrap_or_default(),
+            prev_archive: None,
         }
     }
 }
@@ -39,93 +201,92 @@ impl<'stmt> Item {
             content: row.get(4).ok(),
             description: row.get(5).ok(),
             text_description: row.get(6).ok(),
-            categories: vec![], // FIXME
+            categories: row
+                .get::<_, String>(13)
+                .ok()
+                .and_then(|categories| serde_json::from_str(&categories).ok())
+                .unwrap_or_default(),
             link: row.get(8).ok(),
             pub_date: row.get(9).ok(),
+            enclosure: row
+                .get::<_, String>(10)
+                .ok()
+                .and_then(|enclosure| serde_json::from_str(&enclosure).ok()),
+            read: row.get(11).unwrap_or_default(),
+            updated: row.get(12).unwrap_or_default(),
         }
     }
 }
 
 impl SQLiteStorage {
-    pub fn write_feed_tx(
-        &self,
-        feed: &Feed,
-        tx: &Transaction,
-    ) -> Result<StorageEvent, StorageError> {
-        let stmt = "INSERT OR REPLACE INTO feeds(
-            id,
-            title,
-            description,
-            categories,
-            url,
-            link,
-            ttl,
-            pub_date,
-            last_fetched
-        ) VALUES(
-            IFNULL((SELECT id FROM feeds WHERE id = ?1), ?1),
-            ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9
-        )";
-
-        let mut stmt = tx.prepare_cached(stmt).map_err(|err| {
-            log::warn!("{:?}", err);
-            StorageError
-        })?;
+    pub fn init(config: &Config) -> Self {
+        if !config.should_cache() {
+            return Self::open_in_memory();
+        }
+        Self::prepare(Connection::open(config.db_path()).expect("Could not open database"))
+    }
 
-        match stmt.execute([
-            feed.id(),
-            feed.title(),
-            feed.description(),
-            "[]",
-            feed.url(),
-            feed.link(),
-            feed.ttl().unwrap_or("NULL"),
-            feed.pub_date().unwrap_or("NULL"),
-            feed.last_fetched().unwrap_or("NULL"),
-        ]) {
-            Ok(_) => {
-                for item in feed.items() {
-                    self.write_item(item)?;
-                }
+    /// Opens a database of its own in memory, gone as soon as it is dropped.
+    pub fn open_in_memory() -> Self {
+        Self::prepare(Connection::open_in_memory().expect("Could not open database"))
+    }
 
-                Ok(StorageEvent::Insert)
-            }
-            Err(err) => {
-                log::error!("{:?}", err);
-                Err(StorageError)
-            }
+    /// Opens two connections to the same database, one to read from and one to
+    /// write through. Without a database on disk, both share one in memory,
+    /// which is read without waiting for writes to be committed.
+    pub fn init_pair(config: &Config) -> (Self, Self) {
+        if config.should_cache() {
+            return (Self::init(config), Self::init(config));
         }
+
+        static NEXT_DB: AtomicUsize = AtomicUsize::new(0);
+        let uri = format!(
+            "file:moccasin-{}-{}?mode=memory&cache=shared",
+            std::process::id(),
+            NEXT_DB.fetch_add(1, Ordering::Relaxed)
+        );
+        let open = || Connection::open(&uri).expect("Could not open database");
+        let writer = Self::prepare(open());
+        let reader = Self::prepare(open());
+        reader
+            .conn
+            .pragma_update(None, "read_uncommitted", true)
+            .expect("Failed to set DB read_uncommitted");
+        (reader, writer)
     }
-}
 
-impl SQLiteStorage {
-    pub fn init(config: &Config) -> Self {
-        let conn = if config.should_cache() {
-            Connection::open(config.db_path()).expect("Could not open database")
-        } else {
-            Connection::open_in_memory().expect("Could not open database")
-        };
+    fn prepare(mut conn: Connection) -> Self {
+        // The write-ahead log lets the app read while a refresh is written by
+        // another connection, and only needs syncing at checkpoints. The page
+        // size only applies to a database yet to be created.
+        conn.busy_timeout(BUSY_TIMEOUT)
+            .expect("Failed to set DB busy timeout");
+        conn.pragma_update(None, "page_size", 4096)
+            .expect("Failed to set DB page size");
+        if let Err(err) =
+            conn.pragma_update_and_check(None, "journal_mode", "WAL", |r| r.get::<_, String>(0))
+        {
+            log::warn!("Could not use write-ahead log: {:?}", err);
+        }
+        conn.pragma_update(None, "synchronous", "NORMAL")
+            .expect("Failed to set DB synchronous mode");
 
-        conn.execute_batch(include_str!("schema.sql"))
-            .expect("Failed to initialize DB schema");
+        migrations::migrate(&mut conn).expect("Failed to migrate DB schema");
 
         Self { conn }
     }
 
+    /// Reads feed summaries, with item counts but without the items themselves.
     pub fn read_all(&mut self, config: &Config) -> Result<Vec<Feed>, StorageError> {
-        let stmt = "SELECT * FROM feeds";
+        let stmt = "SELECT feeds.*,
+            (SELECT COUNT(*) FROM items WHERE items.feed_id = feeds.id),
+            (SELECT COUNT(*) FROM items
+                LEFT JOIN item_states ON item_states.item_id = items.id
+                WHERE items.feed_id = feeds.id AND item_states.read_at IS NULL)
+            FROM feeds";
         let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
 
-        let feeds_iter = stmt.query_map([], |row| {
-            let mut feed = Feed::from_row(row);
-            match self.read_items_for_feed_id(feed.id()) {
-                Ok(items) => feed.items = items,
-                Err(_) => {
-                    log::error!("Failed to fetch items for feed {}", feed.id());
-                }
-            }
-            Ok(feed)
-        });
+        let feeds_iter = stmt.query_map([], |row| Ok(Feed::from_row(row)));
         let mut feeds = feeds_iter
             .expect("Could not unwrap feeds")
             .filter_map(|r| r.ok())
@@ -136,7 +297,13 @@ impl SQLiteStorage {
     }
 
     pub fn read_items_for_feed_id(&self, id: &str) -> Result<Vec<Item>, StorageError> {
-        let stmt = "SELECT * FROM items WHERE feed_id = ?1";
+        let stmt = "SELECT items.*, item_states.read_at IS NOT NULL,
+            EXISTS(SELECT 1 FROM item_revisions WHERE item_revisions.item_id = items.id),
+            (SELECT json_group_array(json_object('name', name, 'domain', domain))
+                FROM item_categories WHERE item_categories.item_id = items.id)
+            FROM items
+            LEFT JOIN item_states ON item_states.item_id = items.id
+            WHERE feed_id = ?1";
         let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
 
         let items_iter = stmt.query_map([id], |r| Ok(Item::from_row(r, id)));
@@ -148,54 +315,391 @@ impl SQLiteStorage {
         Ok(items)
     }
 
-    pub fn write_feed(
-        &self,
-        feed: &Feed,
-        tx: Option<&Transaction>,
+    /// Reads the items with a tag or category, from whichever feeds they belong to.
+    pub fn read_items_for_tag(&self, tag: &str) -> Result<Vec<Item>, StorageError> {
+        let stmt = "SELECT items.*, item_states.read_at IS NOT NULL,
+            EXISTS(SELECT 1 FROM item_revisions WHERE item_revisions.item_id = items.id),
+            (SELECT json_group_array(json_object('name', name, 'domain', domain))
+                FROM item_categories WHERE item_categories.item_id = items.id)
+            FROM item_labels
+            JOIN items ON items.id = item_labels.item_id
+            LEFT JOIN item_states ON item_states.item_id = items.id
+            WHERE item_labels.tag = ?1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let items = stmt
+            .query_map([tag], |r| Ok(Item::from_row(r, &r.get::<_, String>(1)?)))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<Vec<_>>();
+
+        Ok(items)
+    }
+
+    /// Reads the items credited to an author, from whichever feeds they belong to.
+    pub fn read_items_by_author(&self, author: &str) -> Result<Vec<Item>, StorageError> {
+        let stmt = "SELECT items.*, item_states.read_at IS NOT NULL,
+            EXISTS(SELECT 1 FROM item_revisions WHERE item_revisions.item_id = items.id),
+            (SELECT json_group_array(json_object('name', name, 'domain', domain))
+                FROM item_categories WHERE item_categories.item_id = items.id)
+            FROM items
+            LEFT JOIN item_states ON item_states.item_id = items.id
+            WHERE items.author = ?1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let items = stmt
+            .query_map([author], |r| Ok(Item::from_row(r, &r.get::<_, String>(1)?)))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<Vec<_>>();
+
+        Ok(items)
+    }
+
+    /// Reads the items with any of `ids`, skipping those that don't exist.
+    pub fn read_items_with_ids(&self, ids: &[String]) -> Result<Vec<Item>, StorageError> {
+        let stmt = "SELECT items.*, item_states.read_at IS NOT NULL,
+            EXISTS(SELECT 1 FROM item_revisions WHERE item_revisions.item_id = items.id),
+            (SELECT json_group_array(json_object('name', name, 'domain', domain))
+                FROM item_categories WHERE item_categories.item_id = items.id)
+            FROM items
+            LEFT JOIN item_states ON item_states.item_id = items.id
+            WHERE items.id IN (SELECT value FROM json_each(?1))";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+        let ids = serde_json::to_string(ids).map_err(|_| StorageError)?;
+
+        let items = stmt
+            .query_map([ids], |r| Ok(Item::from_row(r, &r.get::<_, String>(1)?)))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<Vec<_>>();
+
+        Ok(items)
+    }
+
+    /// Reads every author credited with a stored item, by name.
+    pub fn read_authors(&self) -> Result<Vec<TagSummary>, StorageError> {
+        let stmt = "SELECT items.author, COUNT(*),
+            SUM(item_states.read_at IS NULL)
+            FROM items
+            LEFT JOIN item_states ON item_states.item_id = items.id
+            WHERE items.author IS NOT NULL AND items.author != ''
+            GROUP BY items.author
+            ORDER BY items.author COLLATE NOCASE";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let authors = stmt
+            .query_map([], |r| {
+                Ok(TagSummary {
+                    name: r.get(0)?,
+                    item_count: r.get(1)?,
+                    unread_count: r.get(2)?,
+                })
+            })
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<Vec<_>>();
+
+        Ok(authors)
+    }
+
+    /// Reads every tag given to a stored item, and every category of one, by name.
+    pub fn read_tags(&self) -> Result<Vec<TagSummary>, StorageError> {
+        let stmt = "SELECT item_labels.tag, COUNT(*),
+            SUM(item_states.read_at IS NULL)
+            FROM item_labels
+            JOIN items ON items.id = item_labels.item_id
+            LEFT JOIN item_states ON item_states.item_id = items.id
+            GROUP BY item_labels.tag
+            ORDER BY item_labels.tag";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let tags = stmt
+            .query_map([], |r| {
+                Ok(TagSummary {
+                    name: r.get(0)?,
+                    item_count: r.get(1)?,
+                    unread_count: r.get(2)?,
+                })
+            })
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<Vec<_>>();
+
+        Ok(tags)
+    }
+
+    /// Replaces the tags of each item with those given for it.
+    pub fn write_item_tags(
+        &mut self,
+        tags: &[(String, Vec<String>)],
     ) -> Result<StorageEvent, StorageError> {
-        let stmt = "INSERT OR REPLACE INTO feeds(
-            id,
-            title,
-            description,
-            categories,
-            url,
-            link,
-            ttl,
-            pub_date,
-            last_fetched
-        ) VALUES(
-            IFNULL((SELECT id FROM feeds WHERE id = ?1), ?1),
-            ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9
-        )";
-
-        let mut stmt = (if let Some(tx) = tx {
-            tx.prepare_cached(stmt)
-        } else {
-            self.conn.prepare_cached(stmt)
+        let tx = self.conn.transaction().map_err(|_| StorageError)?;
+        {
+            let mut delete = tx
+                .prepare_cached("DELETE FROM item_tags WHERE item_id = ?1")
+                .map_err(|_| StorageError)?;
+            let mut insert = tx
+                .prepare_cached("INSERT OR IGNORE INTO item_tags(item_id, tag) VALUES(?1, ?2)")
+                .map_err(|_| StorageError)?;
+            for (item_id, item_tags) in tags {
+                if let Err(err) = delete.execute([item_id]) {
+                    log::error!("{:?}", err);
+                    return Err(StorageError);
+                }
+                for tag in item_tags {
+                    if let Err(err) = insert.execute([item_id, tag]) {
+                        log::error!("{:?}", err);
+                        return Err(StorageError);
+                    }
+                }
+            }
+        }
+        tx.commit().map_err(|_| StorageError)?;
+        Ok(StorageEvent::Update)
+    }
+
+    /// Writes a fetched feed along with its items, all or nothing.
+    pub fn write_feed(&mut self, feed: &Feed) -> Result<StorageEvent, StorageError> {
+        let tx = self.conn.transaction().map_err(|err| {
+            log::error!("{:?}", err);
+            StorageError
+        })?;
+        write_feed_rows(&tx, feed)?;
+        tx.commit().map_err(|err| {
+            log::error!("{:?}", err);
+            StorageError
+        })?;
+
+        Ok(StorageEvent::Insert)
+    }
+
+    pub fn write_feeds(&mut self, feeds: &Vec<Feed>) -> Result<Vec<StorageEvent>, StorageError> {
+        let tx = self.conn.transaction().map_err(|err| {
+            log::error!("{:?}", err);
+            StorageError
+        })?;
+
+        let mut events = vec![];
+        for feed in feeds {
+            write_feed_rows(&tx, feed)?;
+            events.push(StorageEvent::Insert);
+        }
+
+        tx.commit().map_err(|err| {
+            log::error!("{:?}", err);
+            StorageError
+        })?;
+
+        Ok(events)
+    }
+
+    /// The version of an item it was last updated from, if it ever was.
+    pub fn read_previous_revision(&self, id: &str) -> Result<Option<ItemRevision>, StorageError> {
+        let stmt = "SELECT title, text_description, replaced_at FROM item_revisions
+            WHERE item_id = ?1 ORDER BY rowid DESC LIMIT 1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        stmt.query_row([id], |row| {
+            Ok(ItemRevision {
+                title: row.get(0)?,
+                description: row.get(1)?,
+                replaced_at: row.get(2)?,
+            })
         })
+        .optional()
+        .map_err(|_| StorageError)
+    }
+
+    /// Returns the ids of feeds with any item whose title contains `query`.
+    pub fn search_item_feed_ids(&self, query: &str) -> Result<HashSet<String>, StorageError> {
+        let stmt = "SELECT DISTINCT feed_id FROM items WHERE title LIKE '%' || ?1 || '%'";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let ids = stmt
+            .query_map([query], |r| r.get::<_, String>(0))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<HashSet<_>>();
+
+        Ok(ids)
+    }
+
+    /// Full-text search over item titles and bodies, best matches first. Each
+    /// word of `query` is matched as a prefix, so results update while typing.
+    /// A `lang:` scope keeps to feeds in that language, and on its own lists
+    /// their newest items.
+    pub fn search_items(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>, StorageError> {
+        let (query, language) = util::split_language_scope(query);
+        let terms = query
+            .split_whitespace()
+            .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let row = |r: &Row| {
+            Ok(SearchResult {
+                feed_id: r.get(0)?,
+                item_id: Some(r.get(1)?),
+                title: r.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                feed_title: r.get(3)?,
+            })
+        };
+        let results = match (terms.is_empty(), language) {
+            (true, None) => return Ok(vec![]),
+            (true, Some(language)) => {
+                let stmt = "SELECT items.feed_id, items.id, items.title, feeds.title FROM items
+                    JOIN feeds ON feeds.id = items.feed_id
+                    WHERE lower(feeds.language) = lower(?1)
+                        OR lower(feeds.language) LIKE lower(?1) || '-%'
+                    ORDER BY items.rowid DESC
+                    LIMIT ?2";
+                let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+                let rows = stmt.query_map((language, limit), row);
+                rows.map(|rows| rows.filter_map(|r| r.ok()).collect::<Vec<_>>())
+            }
+            (false, language) => {
+                let stmt = "SELECT items.feed_id, items.id, items.title, feeds.title FROM items_fts
+                    JOIN items ON items.id = items_fts.item_id
+                    JOIN feeds ON feeds.id = items.feed_id
+                    WHERE items_fts MATCH ?1 AND (?3 IS NULL
+                        OR lower(feeds.language) = lower(?3)
+                        OR lower(feeds.language) LIKE lower(?3) || '-%')
+                    ORDER BY rank
+                    LIMIT ?2";
+                let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+                let rows = stmt.query_map((terms, limit, language), row);
+                rows.map(|rows| rows.filter_map(|r| r.ok()).collect::<Vec<_>>())
+            }
+        }
         .map_err(|err| {
-            log::warn!("{:?}", err);
+            log::error!("{:?}", err);
             StorageError
         })?;
 
-        match stmt.execute([
-            feed.id(),
-            feed.title(),
-            feed.description(),
-            "[]",
-            feed.url(),
-            feed.link(),
-            feed.ttl().unwrap_or("NULL"),
-            feed.pub_date().unwrap_or("NULL"),
-            feed.last_fetched().unwrap_or("NULL"),
-        ]) {
-            Ok(_) => {
-                for item in feed.items() {
-                    self.write_item(item)?;
+        Ok(results)
+    }
+
+    pub fn read_item_ids(&self) -> Result<HashSet<String>, StorageError> {
+        let stmt = "SELECT item_id FROM item_states WHERE read_at IS NOT NULL";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let ids = stmt
+            .query_map([], |r| r.get::<_, String>(0))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<HashSet<_>>();
+
+        Ok(ids)
+    }
+
+    pub fn read_item_ids_for_feed_id(&self, feed_id: &str) -> Result<HashSet<String>, StorageError> {
+        let stmt = "SELECT id FROM items WHERE feed_id = ?1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let ids = stmt
+            .query_map([feed_id], |r| r.get::<_, String>(0))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<HashSet<_>>();
+
+        Ok(ids)
+    }
+
+    /// Counts the items of a feed that aren't stored yet, and those whose
+    /// title or text changed since they were, as writing it would find them.
+    pub fn count_item_changes(&self, feed: &Feed) -> Result<(usize, usize), StorageError> {
+        let stmt = "SELECT title IS NOT ?2 OR text_description IS NOT ?3 FROM items WHERE id = ?1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let (mut new, mut updated) = (0, 0);
+        for item in feed.items() {
+            let changed = stmt
+                .query_row(params![item.id(), item.title(), item.description()], |r| {
+                    r.get::<_, bool>(0)
+                })
+                .optional()
+                .map_err(|_| StorageError)?;
+            match changed {
+                None => new += 1,
+                Some(true) => updated += 1,
+                Some(false) => {}
+            }
+        }
+
+        Ok((new, updated))
+    }
+
+    /// Reads the state of every item that has been read or starred.
+    pub fn read_item_states(&self) -> Result<Vec<ItemState>, StorageError> {
+        let stmt = "SELECT item_id, read_at, starred FROM item_states
+            WHERE read_at IS NOT NULL OR starred = 1
+            ORDER BY item_id";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let states = stmt
+            .query_map([], |r| {
+                Ok(ItemState {
+                    guid: r.get(0)?,
+                    read_at: r.get(1)?,
+                    starred: r.get(2)?,
+                })
+            })
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<Vec<_>>();
+
+        Ok(states)
+    }
+
+    /// Merges in item states from elsewhere, never unreading or unstarring an item.
+    pub fn merge_item_states(
+        &mut self,
+        states: &[ItemState],
+    ) -> Result<StorageEvent, StorageError> {
+        let tx = self.conn.transaction().map_err(|_| StorageError)?;
+        {
+            let stmt = "INSERT INTO item_states(item_id, read_at, starred) VALUES(?1, ?2, ?3)
+                ON CONFLICT(item_id) DO UPDATE SET
+                    read_at = IFNULL(read_at, excluded.read_at),
+                    starred = MAX(starred, excluded.starred)";
+            let mut stmt = tx.prepare_cached(stmt).map_err(|_| StorageError)?;
+            for state in states {
+                if let Err(err) = stmt.execute(params![state.guid, state.read_at, state.starred]) {
+                    log::error!("{:?}", err);
+                    return Err(StorageError);
                 }
+            }
+        }
+        tx.commit().map_err(|_| StorageError)?;
+        Ok(StorageEvent::Update)
+    }
 
-                Ok(StorageEvent::Insert)
+    pub fn star_item(&self, id: &str) -> Result<StorageEvent, StorageError> {
+        let stmt = "INSERT INTO item_states(item_id, starred) VALUES(?1, 1)
+            ON CONFLICT(item_id) DO UPDATE SET starred = 1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        match stmt.execute([id]) {
+            Ok(_) => Ok(StorageEvent::Update),
+            Err(err) => {
+                log::error!("{:?}", err);
+                Err(StorageError)
             }
+        }
+    }
+
+    pub fn mark_item_read(&self, id: &str) -> Result<StorageEvent, StorageError> {
+        let stmt = "INSERT INTO item_states(item_id, read_at) VALUES(?1, ?2)
+            ON CONFLICT(item_id) DO UPDATE SET read_at = IFNULL(read_at, excluded.read_at)";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        match stmt.execute([id, Utc::now().to_rfc3339().as_str()]) {
+            Ok(_) => Ok(StorageEvent::Update),
             Err(err) => {
                 log::error!("{:?}", err);
                 Err(StorageError)
@@ -203,128 +707,133 @@ impl SQLiteStorage {
         }
     }
 
-    pub fn write_feeds(&mut self, feeds: &Vec<Feed>) -> Result<Vec<StorageEvent>, StorageError> {
-        if let Ok(tx) = self.conn.transaction() {
-            let feed_stmt = "INSERT OR REPLACE INTO feeds(
-                    id,
-                    title,
-                    description,
-                    categories,
-                    url,
-                    link,
-                    ttl,
-                    pub_date,
-                    last_fetched
-                ) VALUES(
-                    IFNULL((SELECT id FROM feeds WHERE id = ?1), ?1),
-                    ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9
-                )";
-
-            let item_stmt = "INSERT OR REPLACE INTO items(
-                    id,
-                    feed_id,
-                    title,
-                    author,
-                    content,
-                    description,
-                    text_description,
-                    categories,
-                    link,
-                    pub_date
-                ) VALUES(
-                    IFNULL((SELECT id FROM items WHERE id = ?1), ?1),
-                    ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10
-                )";
-
-            let mut feed_stmt = tx.prepare_cached(feed_stmt).map_err(|err| {
-                log::warn!("{:?}", err);
-                StorageError
-            })?;
+    pub fn mark_item_unread(&self, id: &str) -> Result<StorageEvent, StorageError> {
+        let stmt = "UPDATE item_states SET read_at = NULL WHERE item_id = ?1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
 
-            let mut item_stmt = tx.prepare_cached(item_stmt).map_err(|err| {
-                log::warn!("{:?}", err);
-                StorageError
-            })?;
-
-            let mut events = vec![];
-
-            for feed in feeds {
-                if let Err(e) = feed_stmt.execute([
-                    feed.id(),
-                    feed.title(),
-                    feed.description(),
-                    "[]",
-                    feed.url(),
-                    feed.link(),
-                    feed.ttl().unwrap_or("NULL"),
-                    feed.pub_date().unwrap_or("NULL"),
-                    feed.last_fetched().unwrap_or("NULL"),
-                ]) {
-                    log::error!("{e:?}");
-                    return Err(StorageError);
-                }
+        match stmt.execute([id]) {
+            Ok(_) => Ok(StorageEvent::Update),
+            Err(err) => {
+                log::error!("{:?}", err);
+                Err(StorageError)
+            }
+        }
+    }
 
-                for item in feed.items() {
-                    if let Err(e) = item_stmt.execute([
-                        item.id(),
-                        item.feed_id(),
-                        item.title().unwrap_or("NULL"),
-                        item.author().unwrap_or("NULL"),
-                        item.content().unwrap_or("NULL"),
-                        item.description().unwrap_or("NULL"),
-                        item.description().unwrap_or("NULL"),
-                        "[]",
-                        item.link().unwrap_or("NULL"),
-                        item.pub_date().unwrap_or("NULL"),
-                    ]) {
-                        log::error!("{e:?}");
-                        return Err(StorageError);
-                    }
-                }
+    pub fn unstar_item(&self, id: &str) -> Result<StorageEvent, StorageError> {
+        let stmt = "UPDATE item_states SET starred = 0 WHERE item_id = ?1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
 
-                events.push(StorageEvent::Insert);
+        match stmt.execute([id]) {
+            Ok(_) => Ok(StorageEvent::Update),
+            Err(err) => {
+                log::error!("{:?}", err);
+                Err(StorageError)
             }
-            return Ok(events);
-        } else {
-            log::error!("");
-            Err(StorageError)
         }
     }
 
-    pub fn write_item(&self, item: &Item) -> Result<StorageEvent, StorageError> {
-        let stmt = "INSERT OR REPLACE INTO items(
-            id,
-            feed_id,
-            title,
-            author,
-            content,
-            description,
-            text_description,
-            categories,
-            link,
-            pub_date
-        ) VALUES(
-            IFNULL((SELECT id FROM items WHERE id = ?1), ?1),
-            ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10
-        )";
-
-        let mut stmt = self.conn.prepare_cached(stmt).map_err(|err| {
-            log::warn!("{:?}", err);
-            StorageError
-        })?;
+    /// Numbers every feed by its row, for clients that want integer ids. A
+    /// feed keeps its number for as long as it's stored.
+    pub fn read_feed_numbers(&self) -> Result<HashMap<String, i64>, StorageError> {
+        let stmt = "SELECT id, rowid FROM feeds";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
 
-        match stmt.execute([
-            item.id(),
-            item.feed_id(),
-            item.title().unwrap_or("NULL"),
-            item.author().unwrap_or("NULL"),
-            item.content().unwrap_or("NULL"),
-            item.description().unwrap_or("NULL"),
-            item.description().unwrap_or("NULL"),
-            "[]",
-            item.link().unwrap_or("NULL"),
-            item.pub_date().unwrap_or("NULL"),
-        ]) {
+        let numbers = stmt
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<HashMap<_, _>>();
+
+        Ok(numbers)
+    }
+
+    /// Numbers every item by its row, as [`Self::read_feed_numbers`] does feeds.
+    pub fn read_item_numbers(&self) -> Result<HashMap<String, i64>, StorageError> {
+        let stmt = "SELECT id, rowid FROM items";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let numbers = stmt
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<HashMap<_, _>>();
+
+        Ok(numbers)
+    }
+
+    /// The ids of every feed marked a favorite.
+    pub fn read_favorite_feeds(&self) -> Result<HashSet<String>, StorageError> {
+        let stmt = "SELECT feed_id FROM feed_states WHERE favorite = 1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let ids = stmt
+            .query_map([], |r| r.get(0))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<HashSet<_>>();
+
+        Ok(ids)
+    }
+
+    pub fn set_feed_favorite(
+        &self,
+        id: &str,
+        favorite: bool,
+    ) -> Result<StorageEvent, StorageError> {
+        let stmt = "INSERT INTO feed_states(feed_id, favorite) VALUES(?1, ?2)
+            ON CONFLICT(feed_id) DO UPDATE SET favorite = excluded.favorite";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        match stmt.execute(params![id, favorite]) {
+            Ok(_) => Ok(StorageEvent::Update),
+            Err(err) => {
+                log::error!("{:?}", err);
+                Err(StorageError)
+            }
+        }
+    }
+
+    /// The URLs of every archived feed.
+    pub fn read_archived_feeds(&self) -> Result<HashSet<String>, StorageError> {
+        let stmt = "SELECT url FROM feeds
+            JOIN feed_states ON feed_states.feed_id = feeds.id
+            WHERE archived = 1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        let urls = stmt
+            .query_map([], |r| r.get(0))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<HashSet<_>>();
+
+        Ok(urls)
+    }
+
+    pub fn set_feed_archived(
+        &self,
+        id: &str,
+        archived: bool,
+    ) -> Result<StorageEvent, StorageError> {
+        let stmt = "INSERT INTO feed_states(feed_id, archived) VALUES(?1, ?2)
+            ON CONFLICT(feed_id) DO UPDATE SET archived = excluded.archived";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        match stmt.execute(params![id, archived]) {
+            Ok(_) => Ok(StorageEvent::Update),
+            Err(err) => {
+                log::error!("{:?}", err);
+                Err(StorageError)
+            }
+        }
+    }
+
+    /// Records that an item was read or opened, for reading statistics.
+    pub fn record_item_event(&self, item: &Item, kind: &str) -> Result<StorageEvent, StorageError> {
+        let stmt = "INSERT INTO item_events(item_id, feed_id, kind, at) VALUES(?1, ?2, ?3, ?4)";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        match stmt.execute([item.id(), item.feed_id(), kind, &Utc::now().to_rfc3339()]) {
             Ok(_) => Ok(StorageEvent::Insert),
             Err(err) => {
                 log::error!("{:?}", err);
@@ -333,7 +842,190 @@ impl SQLiteStorage {
         }
     }
 
+    /// Records today's count of unread items, replacing any earlier count for today.
+    pub fn record_backlog(&self) -> Result<StorageEvent, StorageError> {
+        let stmt = "INSERT INTO backlog(day, unread) VALUES(
+                date('now', 'localtime'),
+                (SELECT COUNT(*) FROM items
+                    LEFT JOIN item_states ON item_states.item_id = items.id
+                    WHERE item_states.read_at IS NULL)
+            ) ON CONFLICT(day) DO UPDATE SET unread = excluded.unread";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        match stmt.execute([]) {
+            Ok(_) => Ok(StorageEvent::Update),
+            Err(err) => {
+                log::error!("{:?}", err);
+                Err(StorageError)
+            }
+        }
+    }
+
+    pub fn read_reading_stats(&self, days: usize) -> Result<ReadingStats, StorageError> {
+        let stmt = "SELECT date(at, 'localtime') AS day, COUNT(*) FROM item_events
+            WHERE kind = 'read' AND day > date('now', 'localtime', ?1)
+            GROUP BY day";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+        let reads = stmt
+            .query_map([format!("-{} days", days)], |r| {
+                Ok((r.get::<_, String>(0)?, r.get::<_, u64>(1)?))
+            })
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect::<HashMap<_, _>>();
+
+        // Days without any reads still get a bar.
+        let today = Local::now().date_naive();
+        let reads_per_day = (0..days as i64)
+            .rev()
+            .map(|ago| {
+                let day = today - Duration::days(ago);
+                let count = reads
+                    .get(&day.format("%Y-%m-%d").to_string())
+                    .copied()
+                    .unwrap_or_default();
+                (day.format("%d").to_string(), count)
+            })
+            .collect();
+
+        let stmt = "SELECT feeds.title, COUNT(*) FROM item_events
+            JOIN feeds ON feeds.id = item_events.feed_id
+            WHERE kind = 'read'
+            GROUP BY item_events.feed_id
+            ORDER BY COUNT(*) DESC
+            LIMIT 5";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+        let top_feeds = stmt
+            .query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, u64>(1)?)))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        // The backlog covers the same days as the reads.
+        let stmt = "SELECT unread FROM backlog
+            WHERE day > date('now', 'localtime', ?1)
+            ORDER BY day";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+        let backlog = stmt
+            .query_map([format!("-{} days", days)], |r| r.get::<_, u64>(0))
+            .map_err(|_| StorageError)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(ReadingStats {
+            reads_per_day,
+            top_feeds,
+            backlog,
+        })
+    }
+
+    pub fn update_feed_url(&self, id: &str, url: &str) -> Result<StorageEvent, StorageError> {
+        let stmt = "UPDATE feeds SET url = ?2 WHERE id = ?1";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        match stmt.execute([id, url]) {
+            Ok(update_count) if update_count > 0 => Ok(StorageEvent::Update),
+            Ok(_) => Ok(StorageEvent::NoOp),
+            Err(err) => {
+                log::error!("{:?}", err);
+                Err(StorageError)
+            }
+        }
+    }
+
+    /// Moves a feed's items and their read state to a new feed id.
+    pub fn rekey_feed(&mut self, old_id: &str, new_id: &str) -> Result<StorageEvent, StorageError> {
+        let tx = self.conn.transaction().map_err(|_| StorageError)?;
+        // Items still point at the old id while the feed is re-keyed, so
+        // their foreign keys are only checked once everything is moved over.
+        if let Err(err) = tx.execute_batch("PRAGMA defer_foreign_keys = ON") {
+            log::error!("{:?}", err);
+            return Err(StorageError);
+        }
+        for stmt in REKEY_FEED_STATEMENTS {
+            if let Err(err) = tx.execute(stmt, [old_id, new_id]) {
+                log::error!("{:?}", err);
+                return Err(StorageError);
+            }
+        }
+        tx.commit().map_err(|_| StorageError)?;
+        Ok(StorageEvent::Update)
+    }
+
+    /// Copies the whole database to a new file at `path`, page by page, so the
+    /// copy is consistent even while it's being written to.
+    pub fn backup_to(&self, path: &Path) -> Result<StorageEvent, StorageError> {
+        match self.conn.backup(DatabaseName::Main, path, None) {
+            Ok(_) => Ok(StorageEvent::NoOp),
+            Err(err) => {
+                log::error!("{:?}", err);
+                Err(StorageError)
+            }
+        }
+    }
+
+    /// Replaces the whole database with the one in the file at `path`, bringing
+    /// its schema up to date should it come from an older version.
+    pub fn restore_from(&mut self, path: &Path) -> Result<StorageEvent, StorageError> {
+        match self
+            .conn
+            .restore(DatabaseName::Main, path, None::<fn(Progress)>)
+            .and_then(|_| migrations::migrate(&mut self.conn))
+        {
+            Ok(_) => Ok(StorageEvent::Update),
+            Err(err) => {
+                log::error!("{:?}", err);
+                Err(StorageError)
+            }
+        }
+    }
+
+    /// Counts another fetch of the feed at `url` that found it gone, returning
+    /// how many there have been in a row.
+    pub fn record_feed_gone(&self, url: &str, status: u16) -> Result<u32, StorageError> {
+        let stmt = "INSERT INTO feed_failures(url, status, count) VALUES(?1, ?2, 1)
+            ON CONFLICT(url) DO UPDATE SET status = excluded.status, count = count + 1
+            RETURNING count";
+        let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
+
+        stmt.query_row(params![url, status], |r| r.get(0))
+            .map_err(|err| {
+                log::error!("{:?}", err);
+                StorageError
+            })
+    }
+
+    /// Forgets that the feeds at `urls` were ever gone, since they're back.
+    pub fn clear_feeds_gone(&mut self, urls: &[String]) -> Result<StorageEvent, StorageError> {
+        let tx = self.conn.transaction().map_err(|_| StorageError)?;
+        {
+            let stmt = "DELETE FROM feed_failures WHERE url = ?1";
+            let mut stmt = tx.prepare_cached(stmt).map_err(|_| StorageError)?;
+            for url in urls {
+                if let Err(err) = stmt.execute([url]) {
+                    log::error!("{:?}", err);
+                    return Err(StorageError);
+                }
+            }
+        }
+        tx.commit().map_err(|_| StorageError)?;
+        Ok(StorageEvent::Delete)
+    }
+
     pub fn delete_feed_with_url(&self, url: &str) -> Result<StorageEvent, StorageError> {
+        if let Err(err) = self
+            .conn
+            .execute("DELETE FROM feed_failures WHERE url = ?1", [url])
+        {
+            log::warn!("{:?}", err);
+        }
+        if let Err(err) = self.conn.execute(
+            "DELETE FROM feed_states WHERE feed_id IN (SELECT id FROM feeds WHERE url = ?1)",
+            [url],
+        ) {
+            log::warn!("{:?}", err);
+        }
+
         let stmt = "DELETE FROM feeds WHERE url = ?1";
         let mut stmt = self.conn.prepare_cached(stmt).map_err(|_| StorageError)?;
 
@@ -0,0 +1,377 @@
+use super::sqlite::SQLiteStorage;
+use crate::config::{Config, SortOrder};
+use crate::feed::{Category, Enclosure, Feed, Item};
+use proptest::prelude::*;
+use std::collections::HashSet;
+
+fn feed(id: &str, title: &str, items: Vec<Item>) -> Feed {
+    Feed {
+        id: id.into(),
+        title: title.into(),
+        description: String::new(),
+        categories: vec![],
+        url: format!("https://{id}.example/feed"),
+        link: format!("https://{id}.example"),
+        ttl: None,
+        language: None,
+        items,
+        pub_date: None,
+        last_fetched: None,
+        item_count: 0,
+        unread_count: 0,
+        prev_archive: None,
+    }
+}
+
+fn item(feed_id: &str, n: usize, title: &str, body: &str) -> Item {
+    Item {
+        id: format!("{feed_id}:{n}"),
+        feed_id: feed_id.into(),
+        title: Some(title.into()),
+        description: Some(body.into()),
+        text_description: Some(body.into()),
+        ..Default::default()
+    }
+}
+
+fn item_ids(storage: &SQLiteStorage, feed_id: &str) -> HashSet<String> {
+    storage.read_item_ids_for_feed_id(feed_id).unwrap()
+}
+
+#[test]
+fn refetch_updates_content_and_keeps_state() {
+    let mut storage = SQLiteStorage::open_in_memory();
+    storage
+        .write_feed(&feed("a", "A", vec![item("a", 1, "Old title", "body")]))
+        .unwrap();
+    storage.mark_item_read("a:1").unwrap();
+    storage.star_item("a:1").unwrap();
+
+    storage
+        .write_feed(&feed("a", "A", vec![item("a", 1, "New title", "body")]))
+        .unwrap();
+
+    let items = storage.read_items_for_feed_id("a").unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].title(), Some("New title"));
+    assert!(items[0].read());
+    assert!(items[0].updated());
+    let states = storage.read_item_states().unwrap();
+    assert!(states[0].starred);
+    let read_at = states[0].read_at.as_deref().unwrap();
+    assert!(read_at.ends_with("+00:00"), "{read_at} is not in UTC");
+    assert!(chrono::DateTime::parse_from_rfc3339(read_at).is_ok());
+    let revision = storage.read_previous_revision("a:1").unwrap().unwrap();
+    assert_eq!(revision.title.as_deref(), Some("Old title"));
+}
+
+#[test]
+fn reads_only_the_items_asked_for() {
+    let mut storage = SQLiteStorage::open_in_memory();
+    let items = vec![item("a", 1, "One", ""), item("a", 2, "Two", "")];
+    storage.write_feed(&feed("a", "A", items)).unwrap();
+
+    let ids = vec!["a:2".to_string(), "a:3".to_string()];
+    let items = storage.read_items_with_ids(&ids).unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].title(), Some("Two"));
+}
+
+#[test]
+fn rekeying_a_feed_keeps_its_items_and_read_state() {
+    let mut storage = SQLiteStorage::open_in_memory();
+    let items = vec![item("a", 1, "One", ""), item("a", 2, "Two", "")];
+    storage.write_feed(&feed("a", "A", items)).unwrap();
+    storage.mark_item_read("a:1").unwrap();
+
+    storage.rekey_feed("a", "b").unwrap();
+
+    assert!(item_ids(&storage, "a").is_empty());
+    assert_eq!(
+        item_ids(&storage, "b"),
+        HashSet::from(["b:1".to_string(), "b:2".to_string()])
+    );
+    let items = storage.read_items_for_feed_id("b").unwrap();
+    let one = items.iter().find(|item| item.id() == "b:1").unwrap();
+    assert!(one.read());
+    let feeds = storage.read_all(&Config::default()).unwrap();
+    assert_eq!(feeds.len(), 1);
+    assert_eq!(feeds[0].id(), "b");
+}
+
+#[test]
+fn read_state_survives_an_item_leaving_and_returning() {
+    let mut storage = SQLiteStorage::open_in_memory();
+    storage
+        .write_feed(&feed("a", "A", vec![item("a", 1, "One", "")]))
+        .unwrap();
+    storage.mark_item_read("a:1").unwrap();
+
+    storage.write_feed(&feed("a", "A", vec![])).unwrap();
+    storage
+        .write_feed(&feed("a", "A", vec![item("a", 1, "One", "")]))
+        .unwrap();
+
+    assert!(storage.read_items_for_feed_id("a").unwrap()[0].read());
+}
+
+#[test]
+fn missing_fields_read_back_as_none() {
+    let mut storage = SQLiteStorage::open_in_memory();
+    let bare = Item {
+        id: "a:1".into(),
+        feed_id: "a".into(),
+        ..Default::default()
+    };
+    storage.write_feed(&feed("a", "A", vec![bare])).unwrap();
+
+    let items = storage.read_items_for_feed_id("a").unwrap();
+    assert_eq!(items[0].title(), None);
+    assert_eq!(items[0].author(), None);
+    assert_eq!(items[0].link(), None);
+    assert!(storage.read_authors().unwrap().is_empty());
+    let feeds = storage.read_all(&Config::default()).unwrap();
+    assert_eq!(feeds[0].pub_date(), None);
+}
+
+#[test]
+fn item_numbers_survive_refetch_and_state_can_be_undone() {
+    let mut storage = SQLiteStorage::open_in_memory();
+    let items = vec![item("a", 1, "One", "body"), item("a", 2, "Two", "body")];
+    storage.write_feed(&feed("a", "A", items.clone())).unwrap();
+    let numbers = storage.read_item_numbers().unwrap();
+    storage.write_feed(&feed("a", "A", items)).unwrap();
+    assert_eq!(storage.read_item_numbers().unwrap(), numbers);
+    assert!(numbers["a:1"] < numbers["a:2"]);
+
+    storage.mark_item_read("a:1").unwrap();
+    storage.star_item("a:1").unwrap();
+    storage.mark_item_unread("a:1").unwrap();
+    storage.unstar_item("a:1").unwrap();
+
+    assert!(!storage.read_items_for_feed_id("a").unwrap()[0].read());
+    assert!(storage.read_item_states().unwrap().is_empty());
+}
+
+#[test]
+fn deleting_a_feed_removes_its_items_and_state() {
+    let mut storage = SQLiteStorage::open_in_memory();
+    let a = feed("a", "A", vec![item("a", 1, "Gardening", "soil")]);
+    let b = feed("b", "B", vec![item("b", 1, "Cooking", "soup")]);
+    storage.write_feeds(&vec![a.clone(), b]).unwrap();
+    storage.set_feed_favorite("a", true).unwrap();
+
+    storage.delete_feed_with_url(a.url()).unwrap();
+
+    let feeds = storage.read_all(&Config::default()).unwrap();
+    assert_eq!(feeds.len(), 1);
+    assert_eq!(feeds[0].id(), "b");
+    assert!(item_ids(&storage, "a").is_empty());
+    assert!(storage.read_favorite_feeds().unwrap().is_empty());
+    assert!(storage.search_items("gardening", 10).unwrap().is_empty());
+}
+
+#[test]
+fn read_all_sorts_feeds_and_counts_items() {
+    let mut storage = SQLiteStorage::open_in_memory();
+    let feeds = vec![
+        feed("c", "Cherry", vec![]),
+        feed(
+            "a",
+            "Apple",
+            vec![item("a", 1, "", ""), item("a", 2, "", "")],
+        ),
+        feed("b", "Banana", vec![item("b", 1, "", "")]),
+    ];
+    storage.write_feeds(&feeds).unwrap();
+    storage.mark_item_read("a:1").unwrap();
+
+    let feeds = storage.read_all(&Config::default()).unwrap();
+
+    let titles = feeds.iter().map(|f| f.title()).collect::<Vec<_>>();
+    assert_eq!(titles, ["Apple", "Banana", "Cherry"]);
+    assert_eq!(feeds[0].item_count(), 2);
+    assert_eq!(feeds[0].unread_count(), 1);
+}
+
+#[test]
+fn search_matches_title_and_body_prefixes() {
+    let mut storage = SQLiteStorage::open_in_memory();
+    let items = vec![
+        item("a", 1, "Rust release notes", "Borrow checker improvements"),
+        item("a", 2, "Gardening", "Tomatoes in spring"),
+    ];
+    storage.write_feed(&feed("a", "A", items)).unwrap();
+
+    let results = storage.search_items("rele", 10).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].item_id.as_deref(), Some("a:1"));
+    assert_eq!(results[0].feed_title, "A");
+
+    let results = storage.search_items("tomato", 10).unwrap();
+    assert_eq!(results[0].item_id.as_deref(), Some("a:2"));
+    assert!(storage.search_items("   ", 10).unwrap().is_empty());
+}
+
+#[test]
+fn search_follows_updated_items() {
+    let mut storage = SQLiteStorage::open_in_memory();
+    storage
+        .write_feed(&feed("a", "A", vec![item("a", 1, "Draft", "")]))
+        .unwrap();
+    storage
+        .write_feed(&feed("a", "A", vec![item("a", 1, "Published", "")]))
+        .unwrap();
+
+    assert!(storage.search_items("draft", 10).unwrap().is_empty());
+    assert_eq!(storage.search_items("published", 10).unwrap().len(), 1);
+}
+
+#[test]
+fn search_scopes_to_a_feed_language() {
+    let mut storage = SQLiteStorage::open_in_memory();
+    let mut english = feed("en", "English", vec![item("en", 1, "Release notes", "")]);
+    english.language = Some("en-US".into());
+    let mut german = feed("de", "German", vec![item("de", 1, "Release notes", "")]);
+    german.language = Some("de".into());
+    storage.write_feed(&english).unwrap();
+    storage.write_feed(&german).unwrap();
+
+    let feeds = storage.read_all(&Config::in_memory()).unwrap();
+    let english = feeds.iter().find(|feed| feed.id() == "en").unwrap();
+    assert_eq!(english.language(), Some("en-US"));
+
+    let results = storage.search_items("lang:en release", 10).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].item_id.as_deref(), Some("en:1"));
+    assert_eq!(storage.search_items("release", 10).unwrap().len(), 2);
+
+    let results = storage.search_items("lang:DE", 10).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].item_id.as_deref(), Some("de:1"));
+    assert!(storage.search_items("lang:fr", 10).unwrap().is_empty());
+}
+
+fn text() -> impl Strategy<Value = String> {
+    "\\PC{0,24}"
+}
+
+fn category() -> impl Strategy<Value = Category> {
+    (text(), proptest::option::of(text())).prop_map(|(name, domain)| Category { name, domain })
+}
+
+fn enclosure() -> impl Strategy<Value = Enclosure> {
+    (text(), text()).prop_map(|(url, mime_type)| Enclosure { url, mime_type })
+}
+
+fn arb_item(feed_id: String, n: usize) -> impl Strategy<Value = Item> {
+    (
+        proptest::option::of(text()),
+        proptest::option::of(text()),
+        proptest::option::of(text()),
+        proptest::option::of(text()),
+        proptest::collection::vec(category(), 0..4),
+        proptest::option::of(text()),
+        proptest::option::of(text()),
+        proptest::option::of(enclosure()),
+    )
+        .prop_map(
+            move |(
+                title,
+                author,
+                content,
+                description,
+                mut categories,
+                link,
+                pub_date,
+                enclosure,
+            )| {
+                // Categories are stored one per name.
+                let mut names = HashSet::new();
+                categories.retain(|c| names.insert(c.name.clone()));
+                Item {
+                    id: format!("{feed_id}:{n}"),
+                    feed_id: feed_id.clone(),
+                    title,
+                    author,
+                    content,
+                    text_description: description.clone(),
+                    description,
+                    categories,
+                    link,
+                    pub_date,
+                    enclosure,
+                    read: false,
+                    updated: false,
+                }
+            },
+        )
+}
+
+fn arb_feed() -> impl Strategy<Value = Feed> {
+    ("[a-z0-9]{1,12}", text(), 0..6usize)
+        .prop_flat_map(|(id, title, count)| {
+            let items = (0..count)
+                .map(|n| arb_item(id.clone(), n))
+                .collect::<Vec<_>>();
+            (
+                Just(id),
+                Just(title),
+                text(),
+                proptest::option::of(text()),
+                proptest::option::of(text()),
+                items,
+            )
+        })
+        .prop_map(|(id, title, description, ttl, pub_date, items)| Feed {
+            description,
+            ttl,
+            pub_date,
+            ..feed(&id, &title, items)
+        })
+}
+
+fn sorted(mut items: Vec<Item>) -> Vec<Item> {
+    for item in items.iter_mut() {
+        item.categories.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    items.sort_by(|a, b| a.id.cmp(&b.id));
+    items
+}
+
+proptest! {
+    #[test]
+    fn feeds_round_trip(feed in arb_feed()) {
+        let mut storage = SQLiteStorage::open_in_memory();
+        storage.write_feed(&feed).unwrap();
+
+        let stored = storage.read_all(&Config::default()).unwrap();
+        prop_assert_eq!(stored.len(), 1);
+        prop_assert_eq!(stored[0].id(), feed.id());
+        prop_assert_eq!(stored[0].title(), feed.title());
+        prop_assert_eq!(stored[0].description(), feed.description());
+        prop_assert_eq!(stored[0].url(), feed.url());
+        prop_assert_eq!(stored[0].ttl(), feed.ttl());
+        prop_assert_eq!(stored[0].pub_date(), feed.pub_date());
+        prop_assert_eq!(stored[0].item_count(), feed.items().len());
+
+        let items = storage.read_items_for_feed_id(feed.id()).unwrap();
+        prop_assert_eq!(sorted(items), sorted(feed.items().to_vec()));
+    }
+}
+
+#[test]
+fn feeds_sorted_by_unread_have_the_most_unread_first() {
+    let mut storage = SQLiteStorage::open_in_memory();
+    let items = |id| (1..=3).map(|n| item(id, n, "Title", "body")).collect();
+    storage.write_feed(&feed("a", "A", items("a"))).unwrap();
+    storage.write_feed(&feed("b", "B", items("b"))).unwrap();
+    storage.write_feed(&feed("c", "C", vec![])).unwrap();
+    storage.mark_item_read("a:1").unwrap();
+
+    let mut config = Config::default();
+    config.set_sort_order(SortOrder::Unread);
+    let feeds = storage.read_all(&config).unwrap();
+    let ids = feeds.iter().map(|feed| feed.id()).collect::<Vec<_>>();
+    assert_eq!(ids, ["b", "a", "c"]);
+}
@@ -0,0 +1,42 @@
+use super::translate::run_command;
+use crate::config::Summarizer;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+/// Summarizes `text` with the configured command or chat completions API.
+pub async fn summarize(
+    client: &reqwest::Client,
+    summarizer: &Summarizer,
+    text: &str,
+) -> Result<String> {
+    match summarizer {
+        Summarizer::Command(command) => run_command(command, text).await,
+        Summarizer::ChatCompletions {
+            url,
+            api_key,
+            model,
+            prompt,
+        } => {
+            let body = json!({
+                "model": model,
+                "messages": [
+                    { "role": "system", "content": prompt },
+                    { "role": "user", "content": text },
+                ],
+            });
+            let mut request = client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.to_string());
+            if let Some(key) = api_key {
+                request = request.bearer_auth(key);
+            }
+            let response = request.send().await?.error_for_status()?.text().await?;
+            let response: Value = serde_json::from_str(&response)?;
+            response["choices"][0]["message"]["content"]
+                .as_str()
+                .map(|summary| summary.trim().to_owned())
+                .ok_or_else(|| anyhow!("no summary in response"))
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::config::Translator;
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Translates `text` with the configured command or API.
+pub async fn translate(
+    client: &reqwest::Client,
+    translator: &Translator,
+    text: &str,
+) -> Result<String> {
+    match translator {
+        Translator::Command(command) => run_command(command, text).await,
+        Translator::LibreTranslate {
+            url,
+            api_key,
+            target,
+        } => {
+            let mut body = json!({
+                "q": text,
+                "source": "auto",
+                "target": target,
+                "format": "text",
+            });
+            if let Some(key) = api_key {
+                body["api_key"] = json!(key);
+            }
+            let response = client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.to_string())
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?;
+            let response: Value = serde_json::from_str(&response)?;
+            response["translatedText"]
+                .as_str()
+                .map(str::to_owned)
+                .ok_or_else(|| anyhow!("no translation in response"))
+        }
+        Translator::DeepL {
+            url,
+            api_key,
+            target,
+        } => {
+            let mut request = client
+                .post(url)
+                .form(&[("text", text), ("target_lang", &target.to_uppercase())]);
+            if let Some(key) = api_key {
+                request = request.header(
+                    reqwest::header::AUTHORIZATION,
+                    format!("DeepL-Auth-Key {}", key),
+                );
+            }
+            let response = request.send().await?.error_for_status()?.text().await?;
+            let response: Value = serde_json::from_str(&response)?;
+            response["translations"][0]["text"]
+                .as_str()
+                .map(str::to_owned)
+                .ok_or_else(|| anyhow!("no translation in response"))
+        }
+    }
+}
+
+/// Pipes `text` through a shell command, taking whatever it prints.
+pub(super) async fn run_command(command: &str, text: &str) -> Result<String> {
+    let mut shell = if cfg!(target_os = "windows") {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    let mut child = shell
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("could not run {}", command))?;
+
+    // Written alongside reading, since the command may print as it goes.
+    let mut stdin = child.stdin.take().context("no stdin")?;
+    let text = text.to_owned();
+    tokio::spawn(async move {
+        let _ = stdin.write_all(text.as_bytes()).await;
+    });
+
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        bail!("{} failed with {}", command, output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_owned())
+}
@@ -1,11 +1,14 @@
 use crate::app::{App, AppResult};
 use crate::event::EventHandler;
-use crate::ui;
+use crate::ui::{self, graphics};
+use crossterm::cursor;
 use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
-use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen, SetTitle};
+use std::collections::HashSet;
 use std::io;
 use std::panic;
 use tui::backend::Backend;
+use tui::layout::Rect;
 use tui::Terminal;
 
 /// Representation of a terminal user interface.
@@ -18,12 +21,27 @@ pub struct Tui<B: Backend> {
     terminal: Terminal<B>,
     /// Terminal event handler.
     pub events: EventHandler,
+    /// Last title written to the terminal, if any.
+    title: Option<String>,
+    /// Ids of the icon images the terminal already has.
+    transmitted_icons: HashSet<u32>,
+    /// The icons currently on screen, and where.
+    placed_icons: Vec<(u16, u16, u32)>,
+    /// The size of the terminal when icons were placed.
+    icons_size: Rect,
 }
 
 impl<B: Backend> Tui<B> {
     /// Constructs a new instance of [`Tui`].
     pub fn new(terminal: Terminal<B>, events: EventHandler) -> Self {
-        Self { terminal, events }
+        Self {
+            terminal,
+            events,
+            title: None,
+            transmitted_icons: HashSet::new(),
+            placed_icons: vec![],
+            icons_size: Rect::default(),
+        }
     }
 
     /// Initializes the terminal interface.
@@ -31,13 +49,20 @@ impl<B: Backend> Tui<B> {
     /// It enables the raw mode and sets terminal properties.
     pub fn init(&mut self) -> AppResult<()> {
         terminal::enable_raw_mode()?;
-        crossterm::execute!(io::stderr(), EnterAlternateScreen, EnableMouseCapture)?;
+        crossterm::execute!(io::stderr(), EnterAlternateScreen)?;
+        // Some consoles, like older Windows ones, can't report the mouse, which
+        // leaves the keyboard to get around with rather than failing to start.
+        if let Err(e) = crossterm::execute!(io::stderr(), EnableMouseCapture) {
+            log::warn!("Could not capture the mouse: {}", e);
+        }
 
         // Define a custom panic hook to reset the terminal properties.
         // This way, you won't have your terminal messed up if an unexpected error happens.
+        // Errors are ignored here, since panicking inside the hook would abort
+        // before the panic message is printed.
         let panic_hook = panic::take_hook();
         panic::set_hook(Box::new(move |panic| {
-            Self::reset().expect("failed to reset the terminal");
+            let _ = Self::reset();
             panic_hook(panic);
         }));
 
@@ -52,6 +77,31 @@ impl<B: Backend> Tui<B> {
     /// [`rendering`]: crate::ui:render
     pub fn draw(&mut self, app: &mut App) -> AppResult<()> {
         self.terminal.draw(|frame| ui::render(app, frame))?;
+        if app.graphics {
+            // Resizing clears the screen, taking any icons with it.
+            let size = self.terminal.size()?;
+            if self.icons_size != size {
+                self.placed_icons.clear();
+                self.icons_size = size;
+            }
+            graphics::draw_icons(app, &mut self.transmitted_icons, &mut self.placed_icons)?;
+        }
+        if app.config.should_set_terminal_title() {
+            let title = match app.config.profile() {
+                Some(profile) => format!("moccasin ({}) — {} unread", profile, app.unread_count()),
+                None => format!("moccasin — {} unread", app.unread_count()),
+            };
+            self.set_title(title)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the terminal title (OSC 0), so that e.g. tmux status lines can show it.
+    fn set_title(&mut self, title: String) -> AppResult<()> {
+        if self.title.as_ref() != Some(&title) {
+            crossterm::execute!(io::stderr(), SetTitle(&title))?;
+            self.title = Some(title);
+        }
         Ok(())
     }
 
@@ -61,7 +111,9 @@ impl<B: Backend> Tui<B> {
     /// the terminal properties if unexpected errors occur.
     fn reset() -> AppResult<()> {
         terminal::disable_raw_mode()?;
-        crossterm::execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+        // The mouse may never have been captured, so failing to let it go is fine.
+        let _ = crossterm::execute!(io::stderr(), DisableMouseCapture);
+        crossterm::execute!(io::stderr(), LeaveAlternateScreen, cursor::Show)?;
         Ok(())
     }
 
@@ -69,6 +121,12 @@ impl<B: Backend> Tui<B> {
     ///
     /// It disables the raw mode and reverts back the terminal properties.
     pub fn exit(&mut self) -> AppResult<()> {
+        if self.title.is_some() {
+            crossterm::execute!(io::stderr(), SetTitle(""))?;
+        }
+        if !self.placed_icons.is_empty() {
+            graphics::clear_icons()?;
+        }
         Self::reset()?;
         self.terminal.show_cursor()?;
         Ok(())
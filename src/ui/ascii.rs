@@ -0,0 +1,36 @@
+use tui::buffer::Buffer;
+
+/// The ASCII stand-in for a box-drawing, block or other symbol we draw, by
+/// what it looks like.
+fn ascii_symbol(symbol: &str) -> Option<&'static str> {
+    let ascii = match symbol {
+        "│" | "┃" | "║" => "|",
+        "─" | "━" | "═" => "-",
+        "┌" | "┐" | "└" | "┘" | "╭" | "╮" | "╰" | "╯" | "╔" | "╗" | "╚" | "╝" | "┏" | "┓" | "┗"
+        | "┛" | "├" | "┤" | "┬" | "┴" | "┼" => "+",
+        "█" | "▇" | "▆" | "▉" | "▊" | "▀" => "#",
+        "▅" | "▄" | "▋" | "▌" => "=",
+        "▃" | "▍" => "-",
+        "▂" | "▁" | "▎" | "▏" => "_",
+        "•" | "●" => "*",
+        "⚠" => "!",
+        "↻" => "~",
+        "…" => "~",
+        "—" => "-",
+        "·" => ".",
+        _ => return None,
+    };
+    Some(ascii)
+}
+
+/// Replaces every box-drawing, block or other symbol drawn with its closest
+/// ASCII look-alike, for terminals without Unicode such as serial consoles.
+/// The same few symbols in the text of items are replaced too, which does
+/// them no harm.
+pub fn replace_symbols(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if let Some(ascii) = ascii_symbol(&cell.symbol) {
+            cell.set_symbol(ascii);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::app::App;
+use tui::{
+    backend::Backend,
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph},
+    Frame,
+};
+
+/// What came of adding one of a batch of URLs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchOutcome {
+    /// Still being fetched.
+    Pending,
+    Added,
+    /// Already subscribed to, so left alone.
+    Subscribed,
+    /// Not a web or file URL.
+    Invalid,
+    Failed,
+    /// Gone, with the status it was gone with.
+    Gone(u16),
+}
+
+impl BatchOutcome {
+    fn label(&self) -> String {
+        match self {
+            Self::Pending => "fetching".into(),
+            Self::Added => "added".into(),
+            Self::Subscribed => "already subscribed".into(),
+            Self::Invalid => "not a URL".into(),
+            Self::Failed => "failed".into(),
+            Self::Gone(status) => format!("gone ({})", status),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Pending => Color::DarkGray,
+            Self::Added => Color::Green,
+            Self::Subscribed => Color::Blue,
+            Self::Invalid | Self::Failed | Self::Gone(_) => Color::Red,
+        }
+    }
+}
+
+/// One of a batch of URLs being added, as shown in the batch overlay.
+#[derive(Debug, Clone)]
+pub struct BatchEntry {
+    pub url: String,
+    pub outcome: BatchOutcome,
+}
+
+/// Lists each URL of a batch being added with what came of it, under a count
+/// of how many were added.
+pub fn render_batch_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let Some(entries) = &app.batch else {
+        return;
+    };
+
+    let count = |outcome: BatchOutcome| entries.iter().filter(|e| e.outcome == outcome).count();
+    let title = match count(BatchOutcome::Pending) {
+        0 => format!("Added {} of {}", count(BatchOutcome::Added), entries.len()),
+        pending => format!("Adding {} of {}", pending, entries.len()),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(super::borders(app, Borders::ALL))
+        .border_style(app.config.theme().overlay())
+        .border_type(BorderType::Plain)
+        .style(app.config.theme().overlay())
+        .padding(Padding::uniform(1));
+
+    let width = entries
+        .iter()
+        .map(|entry| entry.outcome.label().len())
+        .max()
+        .unwrap_or_default();
+    let lines: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<width$}  ", entry.outcome.label(), width = width),
+                    Style::default().fg(entry.outcome.color()),
+                ),
+                Span::raw(entry.url.clone()),
+            ])
+        })
+        .collect();
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
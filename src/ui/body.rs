@@ -0,0 +1,172 @@
+use crate::config::{Config, TextAlign};
+use crate::ui::code::CodeCache;
+use crate::ui::{prose, table};
+use std::cell::RefCell;
+use tui::style::Style;
+use tui::text::{Line, Span, Text};
+use unicode_width::UnicodeWidthChar;
+
+/// Lays out the text of an item or page for the detail pane. Tables written
+/// out as rows between pipes are drawn as grids that fit in `width`, and code
+/// fenced by backticks is highlighted in the language named after them.
+/// Paragraphs are set to `width` here too when they're to be justified or
+/// hyphenated, and otherwise left for the pane to wrap.
+pub fn layout_body<'a>(text: &'a str, width: u16, config: &Config, code: &CodeCache) -> Text<'a> {
+    let theme = config.theme();
+    let set_paragraphs = config.text_align() == TextAlign::Justified || config.hyphenate();
+    if !set_paragraphs
+        && !text.starts_with(['|', '`'])
+        && !text.contains("\n|")
+        && !text.contains("\n```")
+    {
+        return Text::raw(text);
+    }
+
+    let mut lines = vec![];
+    let mut rows = vec![];
+    let mut fence: Option<(&str, Vec<&str>)> = None;
+    for line in text.lines() {
+        if let Some((language, block)) = fence.as_mut() {
+            if line.trim_end() == "```" {
+                lines.extend(code.lines(language, &block.join("\n"), theme));
+                fence = None;
+            } else {
+                block.push(line);
+            }
+            continue;
+        }
+        if table::is_row(line) {
+            rows.push(line);
+            continue;
+        }
+        if !rows.is_empty() {
+            lines.extend(table::grid(&rows, width as usize));
+            rows.clear();
+        }
+        match line.strip_prefix("```") {
+            Some(language) => fence = Some((language.trim(), vec![])),
+            None if set_paragraphs => {
+                // Headings are never spread out to the full width.
+                let align = if line.starts_with('#') {
+                    TextAlign::Ragged
+                } else {
+                    config.text_align()
+                };
+                match prose::set_paragraph(line, width as usize, align, config.hyphenate()) {
+                    set if set.is_empty() => lines.push(Line::from("")),
+                    set => lines.extend(set),
+                }
+            }
+            None => lines.push(Line::from(line)),
+        }
+    }
+    if !rows.is_empty() {
+        lines.extend(table::grid(&rows, width as usize));
+    }
+    // A block left open runs to the end of the text.
+    if let Some((language, block)) = fence {
+        lines.extend(code.lines(language, &block.join("\n"), theme));
+    }
+    Text::from(lines)
+}
+
+/// Gathers characters back into spans, one for each run of a style.
+fn spans_of(chars: &[(char, Style)]) -> Vec<Span<'static>> {
+    let mut spans: Vec<Span<'static>> = vec![];
+    for &(c, style) in chars {
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push(c),
+            _ => spans.push(Span::styled(c.to_string(), style)),
+        }
+    }
+    spans
+}
+
+/// Wraps a line at spaces into rows no wider than `width`, keeping the styles
+/// of its spans, and cutting words too long for a row of their own. Rows after
+/// the first don't start with spaces.
+pub fn wrap_line(line: &Line<'_>, width: u16) -> Vec<Line<'static>> {
+    let width = width.max(1) as usize;
+    if line.width() <= width {
+        let spans = line
+            .spans
+            .iter()
+            .map(|span| Span::styled(span.content.to_string(), span.style))
+            .collect();
+        return vec![Line {
+            spans,
+            alignment: line.alignment,
+        }];
+    }
+
+    let chars = line
+        .spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+        .collect::<Vec<_>>();
+    let mut rows = vec![];
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = start;
+        let mut used = 0;
+        let mut space = None;
+        while end < chars.len() {
+            let w = chars[end].0.width().unwrap_or(0);
+            if chars[end].0 == ' ' {
+                space = Some(end);
+            }
+            if used + w > width {
+                break;
+            }
+            used += w;
+            end += 1;
+        }
+
+        let (cut, next) = match space {
+            _ if end == chars.len() => (end, end),
+            Some(at) if at > start => (at, at + 1),
+            _ => (end.max(start + 1), end.max(start + 1)),
+        };
+        rows.push(Line {
+            spans: spans_of(&chars[start..cut]),
+            alignment: line.alignment,
+        });
+        start = next;
+        while start < chars.len() && chars[start].0 == ' ' {
+            start += 1;
+        }
+    }
+    rows
+}
+
+/// The text in the Detail panel, laid out and wrapped to rows, kept for as
+/// long as the same text is shown at the same width. Only the rows in view
+/// are drawn from it, so long articles don't slow every frame down.
+#[derive(Debug, Default)]
+pub struct BodyCache {
+    rows: RefCell<Option<(String, u16, Vec<Line<'static>>)>>,
+}
+
+impl BodyCache {
+    /// Hands the rows of the text known by `key` to `view`, laying them out
+    /// with `build` first if they're of other text or another width.
+    pub fn with<R>(
+        &self,
+        key: &str,
+        width: u16,
+        build: impl FnOnce() -> Vec<Line<'static>>,
+        view: impl FnOnce(&[Line<'static>]) -> R,
+    ) -> R {
+        let mut cached = self.rows.borrow_mut();
+        match cached.as_ref() {
+            Some((cached_key, cached_width, _)) if cached_key == key && *cached_width == width => {}
+            _ => *cached = Some((key.to_owned(), width, build())),
+        }
+        let (_, _, rows) = cached.as_ref().unwrap();
+        view(rows)
+    }
+
+    pub fn invalidate(&self) {
+        self.rows.borrow_mut().take();
+    }
+}
@@ -1,33 +1,79 @@
-use crate::app::{App, View};
+use crate::app::{App, FeedRow, Status, Tab, View};
+use crate::config::{Column, ColumnKind, Config, PaneLayout, RowTemplate, Theme};
+use crate::feed::{Feed, Item};
+use crate::ui::graphics::IconSlot;
+use crate::ui::{body, find};
+use crate::util::{self, DiffLine};
 use tui::{
     backend::Backend,
     layout::Alignment,
     prelude::*,
     style::{Color, Modifier, Style},
     widgets::{
-        scrollbar, Block, BorderType, Borders, List, ListItem, Padding, Paragraph, Scrollbar, Wrap,
+        scrollbar, Block, BorderType, Borders, List, ListItem, ListState, Padding, Paragraph,
+        Scrollbar, Wrap,
     },
     Frame,
 };
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The most rows the selected item's preview takes up.
+const PREVIEW_ROWS: usize = 2;
+
+/// What preview rows start with, to set them apart from the item's own row.
+const PREVIEW_INDENT: &str = "  ";
 
 pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(
-            [
+    // In the two-pane layout the feeds list is a full-width picker of its own,
+    // and is hidden once a feed is open.
+    let two_pane = app.config.layout() == PaneLayout::TwoPane;
+    let chunks = match app.config.layout() {
+        PaneLayout::ThreePane => Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
                 Constraint::Max(40),
                 Constraint::Min(60),
                 Constraint::Min(60),
-            ]
-            .as_ref(),
-        )
-        .split(area);
+            ])
+            .split(area)
+            .to_vec(),
+        PaneLayout::TwoPane => Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(0),
+                Constraint::Percentage(40),
+                Constraint::Min(60),
+            ])
+            .split(area)
+            .to_vec(),
+        PaneLayout::Horizontal => {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Max(40), Constraint::Min(60)])
+                .split(area);
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(40), Constraint::Min(10)])
+                .split(columns[1]);
+            vec![columns[0], rows[0], rows[1]]
+        }
+    };
 
+    // The Tags and Authors tabs list tags or authors in place of feeds, and the
+    // items tagged with one, or by one, in place of a feed's.
+    let labels_tab = app.active_tab.lists_labels();
     let left = Block::default()
-        .title("Feeds")
+        .title(match (&app.active_tab, app.feed_filter) {
+            (Tab::Tags, _) => "Tags".to_owned(),
+            (Tab::Authors, _) => "Authors".to_owned(),
+            (Tab::Favorites, Some(filter)) => format!("Favorites ({})", filter),
+            (Tab::Favorites, None) => "Favorites".to_owned(),
+            (_, Some(filter)) => format!("Feeds ({})", filter),
+            _ => "Feeds".to_owned(),
+        })
         .title_alignment(Alignment::Left)
         .title_style(Style::default().bg(Color::White).fg(Color::Red))
-        .padding(if app.should_render_feeds_scroll() {
+        .padding(if !labels_tab && app.should_render_feeds_scroll() {
             Padding {
                 top: 1,
                 bottom: 1,
@@ -37,7 +83,7 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
         } else {
             Padding::uniform(1)
         })
-        .borders(Borders::ALL)
+        .borders(super::borders(app, Borders::ALL))
         .border_style(if app.active_view == View::MainList {
             app.config.theme().active_border()
         } else {
@@ -45,30 +91,132 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
         })
         .border_type(BorderType::Plain);
 
-    let feeds_list = List::new(
-        app.feeds
+    let screen_reader = app.config.screen_reader();
+    let feed_count = app.feeds.items().len();
+    let label_count = app.labels.items().len();
+    let label_kind = match app.active_tab {
+        Tab::Authors => "Author",
+        _ => "Tag",
+    };
+    let rows = if labels_tab {
+        app.labels
             .items()
             .iter()
-            .map(|feed| ListItem::new(format!("{} ({})", feed.title(), feed.items().len())))
-            .collect::<Vec<_>>(),
-    )
-    .block(left)
-    .style(app.config.theme().base())
-    .highlight_style(if app.active_view == View::MainList {
-        app.config.theme().active_selection()
+            .enumerate()
+            .map(|(i, label)| {
+                ListItem::new(if screen_reader {
+                    format!(
+                        "{} {} of {}: {}, {} unread of {}",
+                        label_kind,
+                        i + 1,
+                        label_count,
+                        label.name,
+                        label.unread_count,
+                        label.item_count
+                    )
+                } else {
+                    format!(
+                        "{} ({}/{})",
+                        label.name, label.unread_count, label.item_count
+                    )
+                })
+            })
+            .collect::<Vec<_>>()
     } else {
-        app.config.theme().selection()
-    });
+        app.feed_rows()
+            .into_iter()
+            .map(|row| {
+                let (i, feed) = match row {
+                    FeedRow::Feed(i, feed) => (i, feed),
+                    FeedRow::Header(header) if screen_reader => {
+                        return ListItem::new(format!("Section: {}", header));
+                    }
+                    FeedRow::Header(header) => {
+                        return ListItem::new(header)
+                            .style(app.config.theme().border().add_modifier(Modifier::BOLD));
+                    }
+                };
+                // Flag feeds fetched without verifying their certificate.
+                let insecure = app
+                    .config
+                    .feed_rule(feed.url())
+                    .map_or(false, |rule| rule.is_insecure());
+                if screen_reader {
+                    return ListItem::new(format!(
+                        "Feed {} of {}: {}{}, {} unread of {}{}",
+                        i + 1,
+                        feed_count,
+                        feed.title(),
+                        if app.is_favorite(feed) {
+                            ", favorite"
+                        } else {
+                            ""
+                        },
+                        feed.unread_count(),
+                        feed.item_count(),
+                        if insecure {
+                            ", certificate not verified"
+                        } else {
+                            ""
+                        }
+                    ));
+                }
 
-    let current_feed = app
-        .feeds
-        .state
-        .selected()
-        .and_then(|i| app.feeds.items().get(i));
+                // Icons are drawn over the blank cells after rendering.
+                let mut spans = if app.graphics && app.icons.contains_key(feed.id()) {
+                    vec![Span::raw("   ")]
+                } else {
+                    vec![feed_badge(app.config.theme(), feed.title()), Span::raw(" ")]
+                };
+                if insecure {
+                    spans.push(Span::styled("⚠ ", app.config.theme().highlight()));
+                }
+                if app.is_favorite(feed) {
+                    let star = if app.config.ascii() { "* " } else { "★ " };
+                    spans.push(Span::styled(star, app.config.theme().highlight()));
+                }
+                spans.push(Span::raw(feed_row(feed, app.config.feed_template())));
+                ListItem::new(Line::from(spans))
+            })
+            .collect::<Vec<_>>()
+    };
+    let main_list = List::new(rows)
+        .block(left.clone())
+        .style(app.config.theme().base())
+        .highlight_style(if app.active_view == View::MainList {
+            app.config.theme().active_selection()
+        } else {
+            app.config.theme().selection()
+        });
 
-    if let Some(feed) = current_feed {
+    // The title of the items' list, the key its rows are cached under, and
+    // how to highlight unread items.
+    let current_list = if labels_tab {
+        let sigil = match app.active_tab {
+            Tab::Authors => '@',
+            _ => '#',
+        };
+        app.current_label()
+            .map(|label| (label.name.clone(), format!("{}{}", sigil, label.name), None))
+    } else {
+        app.feeds
+            .state
+            .selected()
+            .and_then(|i| app.feeds.items().get(i))
+            .map(|feed| {
+                let highlight = app
+                    .config
+                    .feed_rule(feed.url())
+                    .filter(|rule| rule.is_high_priority())
+                    .map(|_| app.config.theme().highlight());
+                (feed.title().to_owned(), feed.id().to_owned(), highlight)
+            })
+    }
+    .filter(|_| !(two_pane && app.active_view == View::MainList));
+
+    if let Some((title, key, highlight)) = current_list {
         let block = Block::default()
-            .title(feed.title())
+            .title(title)
             .title_alignment(Alignment::Left)
             .padding(if app.should_render_items_scroll() {
                 Padding {
@@ -80,7 +228,7 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
             } else {
                 Padding::uniform(1)
             })
-            .borders(Borders::ALL)
+            .borders(super::borders(app, Borders::ALL))
             .border_style(if app.active_view == View::SubList {
                 app.config.theme().active_border()
             } else {
@@ -88,70 +236,185 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
             })
             .border_type(BorderType::Plain);
 
-        let items_list = List::new(
-            feed.items()
+        let items_area = if app.current_item().is_some() || app.current_page().is_some() {
+            chunks[1]
+        } else {
+            chunks[1].union(chunks[2])
+        };
+
+        // Only the visible window of rows is handed to the List, so huge feeds
+        // don't cost a fresh row per item on every frame.
+        let selected = app.items.state.selected();
+        let items_inner = block.inner(items_area);
+        let window =
+            app.item_rows
+                .window(app.items.items().len(), selected, items_inner.height as usize);
+        let rows = app.item_rows.rows(&key, items_inner.width, || {
+            // The feed column is only worth its room when items come from more
+            // than one feed.
+            let aggregated = app.items.items().iter().any(|item| item.feed_id() != key);
+            let columns = app.config.item_columns().layout(items_inner.width, |kind| {
+                kind != ColumnKind::Feed || aggregated
+            });
+            let count = app.items.items().len();
+            app.items
+                .items()
                 .iter()
-                .map(|item| {
-                    let title = item.title().clone().unwrap_or("default".into());
-                    ListItem::new(title)
+                .enumerate()
+                .map(|(i, item)| {
+                    let feed_title = match aggregated {
+                        true => app
+                            .feeds
+                            .items()
+                            .iter()
+                            .find(|feed| feed.id() == item.feed_id())
+                            .map_or("", |feed| feed.title()),
+                        false => "",
+                    };
+                    if screen_reader {
+                        item_context(item, i, count, feed_title).into()
+                    } else if let Some(template) = app.config.item_template() {
+                        let row = templated_item_row(item, feed_title, template);
+                        fit(&row, items_inner.width as usize).into()
+                    } else {
+                        item_row(&app.config, item, feed_title, &columns).into()
+                    }
                 })
-                .collect::<Vec<_>>(),
-        )
-        .block(block)
-        .style(app.config.theme().base())
-        .highlight_style(if app.active_view == View::SubList {
-            app.config.theme().active_selection()
-        } else {
-            app.config.theme().selection()
+                .collect()
         });
+        let rows = window
+            .clone()
+            .map(|i| {
+                let item = &app.items.items()[i];
+                let mut text = rows[i].clone();
+                if app.config.item_preview() && selected == Some(i) {
+                    text.extend(item_preview(&app.config, item, items_inner.width));
+                }
+                let row = ListItem::new(text);
+                match highlight {
+                    Some(style) if !item.read() => row.style(style),
+                    _ => row,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut window_state = ListState::default();
+        window_state.select(selected.map(|i| i - window.start));
 
-        if app.current_item().is_some() {
-            frame.render_stateful_widget(items_list, chunks[1], &mut app.items.state);
-            if app.should_render_items_scroll() {
-                frame.render_stateful_widget(
-                    Scrollbar::default()
-                        .begin_symbol(None)
-                        .end_symbol(None)
-                        .track_symbol(scrollbar::VERTICAL.thumb)
-                        .track_style(app.config.theme().scrollbar_track())
-                        .thumb_style(app.config.theme().scrollbar_thumb()),
-                    chunks[1].inner(&Margin {
-                        vertical: 1,
-                        horizontal: 1,
-                    }),
-                    &mut app.items_scroll,
-                );
-            }
-        } else {
+        let items_list = List::new(rows)
+            .block(block)
+            .style(app.config.theme().base())
+            .highlight_style(if app.active_view == View::SubList {
+                app.config.theme().active_selection()
+            } else {
+                app.config.theme().selection()
+            });
+
+        frame.render_stateful_widget(items_list, items_area, &mut window_state);
+        follow_with_cursor(app, frame, items_inner, &window_state, View::SubList);
+        if app.items.items().is_empty() {
+            render_empty_hint(app, frame, items_inner, "No items yet — press 'r' to refresh");
+        }
+        if app.should_render_items_scroll() {
             frame.render_stateful_widget(
-                items_list,
-                chunks[1].union(chunks[2]),
-                &mut app.items.state,
+                scrollbar(app),
+                items_area.inner(&Margin {
+                    vertical: 1,
+                    horizontal: 1,
+                }),
+                &mut app.items_scroll,
             );
-            if app.should_render_items_scroll() {
-                frame.render_stateful_widget(
-                    Scrollbar::default()
-                        .begin_symbol(None)
-                        .end_symbol(None)
-                        .track_symbol(scrollbar::VERTICAL.thumb)
-                        .track_style(app.config.theme().scrollbar_track())
-                        .thumb_style(app.config.theme().scrollbar_thumb()),
-                    chunks[1].union(chunks[2]).inner(&Margin {
-                        vertical: 1,
-                        horizontal: 1,
-                    }),
-                    &mut app.items_scroll,
-                );
-            }
         }
 
-        if let Some(detail) = &app.current_item() {
+        // A page followed with the internal browser takes the place of the item.
+        // Its tables are drawn to fit the text's width, inside the pane's
+        // margin and padding.
+        let right_padding = if app.should_render_detail_scroll() {
+            2
+        } else {
+            1
+        };
+        let text_width = chunks[2].width.saturating_sub(4 + 1 + right_padding);
+        let text_width = match app.config.max_content_width() {
+            Some(max) => text_width.min(max),
+            None => text_width,
+        };
+        let translation = app.current_translation();
+        let summary = app.current_summary();
+        let detail = match app.current_page() {
+            Some(page) => Some((
+                page.title().unwrap_or("[no title]"),
+                page.url(),
+                match translation {
+                    Some(_) => "translated, :translate for original".to_owned(),
+                    None => String::new(),
+                },
+                match translation {
+                    Some(_) => format!("{}:translated", page.url()),
+                    None => format!("{}:original", page.url()),
+                },
+                Body::Text(translation.unwrap_or(page.text())),
+            )),
+            None => app.current_item().map(|item| {
+                let date = item.pub_date().unwrap_or("[no date]");
+                let (date, variant, body) = match (app.current_revision(), translation) {
+                    (Some(revision), _) => (
+                        format!("changes since {}", revision.replaced_at),
+                        "diff",
+                        Body::Diff(
+                            format!(
+                                "{}\n\n{}",
+                                revision.title.as_deref().unwrap_or_default(),
+                                revision.description.as_deref().unwrap_or_default()
+                            ),
+                            format!(
+                                "{}\n\n{}",
+                                item.title().unwrap_or_default(),
+                                item.description().unwrap_or_default()
+                            ),
+                        ),
+                    ),
+                    (None, Some(translation)) => (
+                        format!("{} · translated, :translate for original", date),
+                        "translated",
+                        Body::Text(translation),
+                    ),
+                    (None, None) if item.updated() => (
+                        format!("{} · updated, D to compare", date),
+                        "original",
+                        Body::Text(item.description().unwrap_or("[no content]")),
+                    ),
+                    (None, None) if item.event().is_some() => (
+                        format!("{} · event, :ical to export", date),
+                        "original",
+                        Body::Text(item.description().unwrap_or("[no content]")),
+                    ),
+                    (None, None) => (
+                        date.to_owned(),
+                        "original",
+                        Body::Text(item.description().unwrap_or("[no content]")),
+                    ),
+                };
+                (
+                    item.title().unwrap_or("[no title]"),
+                    item.author().unwrap_or("[anonymous]"),
+                    date,
+                    format!("{}:{}", item.id(), variant),
+                    body,
+                )
+            }),
+        };
+
+        if let Some((title, author, date, key, body)) = detail {
             let block = Block::default()
-                .title("Detail")
+                .title(if app.current_page().is_some() {
+                    "Browsing"
+                } else {
+                    "Detail"
+                })
                 .title_alignment(Alignment::Left)
                 .padding(Padding::uniform(1))
                 .style(app.config.theme().base())
-                .borders(Borders::ALL)
+                .borders(super::borders(app, Borders::ALL))
                 .border_style(if app.active_view == View::Detail {
                     app.config.theme().active_border()
                 } else {
@@ -172,90 +435,508 @@ pub fn render_browse_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, a
                 .margin(2)
                 .split(chunks[2]);
 
-            let title = Paragraph::new(detail.title().unwrap_or("[no title]"))
+            // Text kept narrower than the panel is centered in it.
+            let body_area = content_chunks[4];
+            let body_width = (text_width + 1 + right_padding).min(body_area.width);
+            let body_area = Rect {
+                x: body_area.x + (body_area.width - body_width) / 2,
+                width: body_width,
+                ..body_area
+            };
+
+            // The text is laid out and wrapped once for each item and width,
+            // and only the rows in view are drawn. Its key changes along with
+            // its length, so an item updated on refresh is laid out again.
+            let key = format!("{}:{}:{}", key, body.len(), summary.map_or(0, str::len));
+            let mut scroll = app.detail_scroll_index as usize;
+            let (rows, total, matches) = app.body_rows.with(
+                &key,
+                text_width,
+                || {
+                    let mut text = Text::default();
+                    // A summary goes above the text it's of, set apart from it.
+                    if let Some(summary) = summary {
+                        text.extend([Line::styled(
+                            "Summary",
+                            Style::default().add_modifier(Modifier::BOLD),
+                        )]);
+                        text.extend(Text::styled(
+                            summary,
+                            Style::default().add_modifier(Modifier::ITALIC),
+                        ));
+                        text.extend([Line::from("")]);
+                    }
+                    text.extend(match &body {
+                        Body::Text(body) => {
+                            body::layout_body(body, text_width, &app.config, &app.code_blocks)
+                        }
+                        Body::Diff(old, new) => revision_diff(app, old, new),
+                    });
+                    text.lines
+                        .iter()
+                        .flat_map(|line| body::wrap_line(line, text_width))
+                        .collect()
+                },
+                |rows| {
+                    // Matches of a search in the panel are found in all of the
+                    // text, and the one jumped to is scrolled into view, a
+                    // couple of rows below the top.
+                    let matches = app.find.as_ref().map(|search| {
+                        let matches = find::match_rows(rows, &search.query);
+                        if search.follow {
+                            if let Some(row) = matches.get(search.current) {
+                                scroll = row.saturating_sub(2);
+                            }
+                        }
+                        matches
+                    });
+                    scroll = scroll.min(rows.len().saturating_sub(1));
+                    let visible = rows
+                        .iter()
+                        .skip(scroll)
+                        .take(body_area.height as usize)
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    (visible, rows.len(), matches)
+                },
+            );
+            let rows = match (&app.find, &matches) {
+                (Some(search), Some(matches)) => find::mark_matches(
+                    rows,
+                    &search.query,
+                    matches.partition_point(|&row| row < scroll),
+                    search.current,
+                    app.config.theme(),
+                ),
+                _ => rows,
+            };
+
+            let title = Paragraph::new(title)
                 .style(Style::default().add_modifier(Modifier::ITALIC))
                 .wrap(Wrap { trim: true })
                 .alignment(Alignment::Center);
 
-            let author = Paragraph::new(detail.author().unwrap_or("[anonymous]"))
-                .alignment(Alignment::Center);
+            let author = Paragraph::new(author).alignment(Alignment::Center);
 
-            let date = Paragraph::new(detail.pub_date().unwrap_or("[no date]"))
-                .alignment(Alignment::Center);
+            let date = Paragraph::new(date).alignment(Alignment::Center);
 
-            let body = Paragraph::new(detail.description().unwrap_or("[no content]"))
-                .wrap(Wrap { trim: true })
-                .block(Block::default().padding(Padding {
-                    top: 0,
-                    bottom: 0,
-                    left: 1,
-                    right: if app.should_render_detail_scroll() {
-                        2
-                    } else {
-                        1
-                    },
-                }))
-                .scroll((app.detail_scroll_index, 0));
+            let body = Paragraph::new(rows).block(Block::default().padding(Padding {
+                top: 0,
+                bottom: 0,
+                left: 1,
+                right: right_padding,
+            }));
 
             frame.render_widget(title, content_chunks[0]);
             frame.render_widget(author, content_chunks[1]);
             frame.render_widget(date, content_chunks[2]);
             frame.render_widget(
                 Block::default()
-                    .borders(Borders::TOP)
+                    .borders(super::borders(app, Borders::TOP))
                     .border_style(app.config.theme().border())
                     .padding(Padding::vertical(1)),
                 content_chunks[3],
             );
-            frame.render_widget(body, content_chunks[4]);
+            frame.render_widget(body, body_area);
+            if screen_reader && app.active_view == View::Detail {
+                frame.set_cursor(content_chunks[0].x, content_chunks[0].y);
+            }
+
+            if let (Some(matches), Some(search)) = (matches, app.find.as_mut()) {
+                if search.follow {
+                    search.follow = false;
+                    app.status = match matches.len() {
+                        0 => Status::Errored(format!("no matches for {}", search.query)),
+                        count => Status::Info(format!(
+                            "match {} of {} for {}",
+                            search.current.min(count - 1) + 1,
+                            count,
+                            search.query
+                        )),
+                    };
+                }
+                search.current = search.current.min(matches.len().saturating_sub(1));
+                search.rows = matches;
+            }
 
-            app.detail_scroll = app.detail_scroll.content_length(48);
+            app.detail_scroll_index = scroll as u16;
+            app.detail_scroll = app
+                .detail_scroll
+                .content_length(total as u16)
+                .position(scroll as u16);
             if app.should_render_detail_scroll() {
                 frame.render_stateful_widget(
-                    Scrollbar::default()
-                        .begin_symbol(None)
-                        .end_symbol(None)
-                        .track_symbol(scrollbar::VERTICAL.thumb)
-                        .track_style(app.config.theme().scrollbar_track())
-                        .thumb_style(app.config.theme().scrollbar_thumb()),
+                    scrollbar(app),
                     content_chunks[4],
                     &mut app.detail_scroll,
                 );
             }
         }
 
-        frame.render_stateful_widget(feeds_list, chunks[0], &mut app.feeds.state);
-        if app.should_render_feeds_scroll() {
-            frame.render_stateful_widget(
-                Scrollbar::default()
-                    .begin_symbol(None)
-                    .end_symbol(None)
-                    .track_symbol(scrollbar::VERTICAL.thumb)
-                    .track_style(app.config.theme().scrollbar_track())
-                    .thumb_style(app.config.theme().scrollbar_thumb()),
-                chunks[0].inner(&Margin {
-                    vertical: 1,
-                    horizontal: 1,
-                }),
-                &mut app.feeds_scroll,
-            );
+        if two_pane {
+            return;
         }
+
+        render_main_list(app, frame, main_list, &left, chunks[0]);
     } else {
-        frame.render_stateful_widget(feeds_list, area, &mut app.feeds.state);
-        if app.should_render_feeds_scroll() {
-            frame.render_stateful_widget(
-                Scrollbar::default()
-                    .begin_symbol(None)
-                    .end_symbol(None)
-                    .track_symbol(scrollbar::VERTICAL.thumb)
-                    .track_style(app.config.theme().scrollbar_track())
-                    .thumb_style(app.config.theme().scrollbar_thumb()),
-                area.inner(&Margin {
-                    vertical: 1,
-                    horizontal: 1,
-                }),
-                &mut app.feeds_scroll,
-            );
+        render_main_list(app, frame, main_list, &left, area);
+    }
+}
+
+/// Draws the list of feeds, or of tags or authors in the Tags and Authors
+/// tabs, that items are picked from.
+fn render_main_list<B: Backend>(
+    app: &mut App,
+    frame: &mut Frame<'_, B>,
+    list: List,
+    block: &Block,
+    area: Rect,
+) {
+    let inner = block.inner(area);
+    if app.active_tab.lists_labels() {
+        frame.render_stateful_widget(list, area, &mut app.labels.state);
+        follow_with_cursor(app, frame, inner, &app.labels.state, View::MainList);
+        if app.labels.items().is_empty() {
+            let hint = match app.active_tab {
+                Tab::Authors => "No authors yet — items are listed here by who wrote them, once their feeds name them",
+                _ => "No tags yet — add rules under [tags] in moccasin.toml to tag items as they're fetched",
+            };
+            render_empty_hint(app, frame, inner, hint);
+        }
+        return;
+    }
+
+    // The list is drawn by rows, which count section headers, rather than by
+    // feeds.
+    let selected_row = app.feeds.state.selected().and_then(|selected| {
+        app.feed_rows()
+            .iter()
+            .position(|row| matches!(row, FeedRow::Feed(i, _) if *i == selected))
+    });
+    app.feed_rows_state.select(selected_row);
+    frame.render_stateful_widget(list, area, &mut app.feed_rows_state);
+    follow_with_cursor(app, frame, inner, &app.feed_rows_state, View::MainList);
+    record_icon_slots(app, inner);
+    if app.feeds.items().is_empty() {
+        let hint = if app.feed_filter.is_some() {
+            "No feeds match — press the filter's key again to show them all"
+        } else if app.active_tab == Tab::Favorites {
+            "No favorite feeds yet — press 's' on a feed in the Browse tab to add one"
+        } else {
+            "No feeds yet — press 'a' to add one by URL, or list them under [sources] in moccasin.toml"
+        };
+        render_empty_hint(app, frame, inner, hint);
+    }
+    if app.should_render_feeds_scroll() {
+        frame.render_stateful_widget(
+            scrollbar(app),
+            area.inner(&Margin {
+                vertical: 1,
+                horizontal: 1,
+            }),
+            &mut app.feeds_scroll,
+        );
+    }
+}
+
+/// A scrollbar without arrows, its track and thumb told apart by style, or by
+/// symbol where only ASCII can be drawn.
+fn scrollbar(app: &App) -> Scrollbar<'static> {
+    let bar = Scrollbar::default()
+        .begin_symbol(None)
+        .end_symbol(None)
+        .track_style(app.config.theme().scrollbar_track())
+        .thumb_style(app.config.theme().scrollbar_thumb());
+    if app.config.ascii() {
+        bar.track_symbol("|").thumb_symbol("#")
+    } else {
+        bar.track_symbol(scrollbar::VERTICAL.thumb)
+    }
+}
+
+/// The start of an item's description, shown under it while it's selected:
+/// as much as fits in two dimmed rows, indented past the unread marker.
+fn item_preview(config: &Config, item: &Item, width: u16) -> Vec<Line<'static>> {
+    let text = item
+        .description()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.is_empty() {
+        return vec![];
+    }
+    let width = width.saturating_sub(PREVIEW_INDENT.len() as u16);
+    let mut rows = body::wrap_line(&Line::from(text), width)
+        .into_iter()
+        .map(|row| {
+            row.spans
+                .into_iter()
+                .map(|span| span.content)
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>();
+    if rows.len() > PREVIEW_ROWS {
+        let rest = rows.split_off(PREVIEW_ROWS - 1).join(" ");
+        rows.push(fit(&rest, width as usize).trim_end().to_owned());
+    }
+    let style = config.theme().base().add_modifier(Modifier::DIM);
+    rows.into_iter()
+        .map(|row| Line::styled(format!("{}{}", PREVIEW_INDENT, row), style))
+        .collect()
+}
+
+/// Lays an item out in the given columns, each cut short or padded to its
+/// width and separated by a space.
+fn item_row(config: &Config, item: &Item, feed_title: &str, columns: &[Column]) -> Line<'static> {
+    let mut spans = vec![];
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(" "));
+        }
+        let text = match column.kind {
+            ColumnKind::Unread => if item.read() { "" } else { "•" }.to_owned(),
+            ColumnKind::Date => item.pub_date().map(short_date).unwrap_or_default(),
+            ColumnKind::Author => item.author().unwrap_or_default().to_owned(),
+            ColumnKind::Title if item.updated() => {
+                format!("↻ {}", item.title().unwrap_or("default"))
+            }
+            ColumnKind::Title => item.title().unwrap_or("default").to_owned(),
+            ColumnKind::Feed => feed_title.to_owned(),
+            ColumnKind::Tag => {
+                spans.extend(tag_badge(config, item, column.width as usize));
+                continue;
+            }
+        };
+        spans.push(Span::raw(fit(&text, column.width as usize)));
+    }
+    Line::from(spans)
+}
+
+/// An item's first category, or failing that its first tag, as a badge cut
+/// short to `width` and padded out with plain spaces.
+fn tag_badge(config: &Config, item: &Item, width: usize) -> Vec<Span<'static>> {
+    let tag = match item.categories().first() {
+        Some(category) => Some(category.name.trim().to_owned()),
+        None => config.item_tags(item).into_iter().next(),
+    };
+    let Some(tag) = tag.filter(|tag| !tag.is_empty()) else {
+        return vec![Span::raw(" ".repeat(width))];
+    };
+    let label = fit(&tag, width);
+    let label = label.trim_end();
+    vec![
+        Span::styled(label.to_owned(), config.theme().badge(&tag)),
+        Span::raw(" ".repeat(width - label.width())),
+    ]
+}
+
+/// An item filled into a row template. The flag is `•` for unread items and
+/// `↻` for updated ones, and dates default to `YYYY-MM-DD`.
+fn templated_item_row(item: &Item, feed_title: &str, template: &RowTemplate) -> String {
+    template.render(|field, format| match field {
+        "date" => item
+            .pub_date()
+            .and_then(util::parse_date)
+            .map(|date| date.format(format.unwrap_or("%Y-%m-%d")).to_string())
+            .unwrap_or_default(),
+        "flag" if item.updated() => "↻".to_owned(),
+        "flag" if !item.read() => "•".to_owned(),
+        "flag" => " ".to_owned(),
+        "title" => item.title().unwrap_or("default").to_owned(),
+        "author" => item.author().unwrap_or_default().to_owned(),
+        "feed" => feed_title.to_owned(),
+        "link" => item.link().unwrap_or_default().to_owned(),
+        _ => String::new(),
+    })
+}
+
+/// A feed filled into the feeds list's row template.
+fn feed_row(feed: &Feed, template: &RowTemplate) -> String {
+    template.render(|field, format| match field {
+        "title" => feed.title().to_owned(),
+        "unread" => feed.unread_count().to_string(),
+        "count" => feed.item_count().to_string(),
+        "url" => feed.url().to_owned(),
+        "date" => feed
+            .pub_date()
+            .and_then(util::parse_date)
+            .map(|date| date.format(format.unwrap_or("%Y-%m-%d")).to_string())
+            .unwrap_or_default(),
+        _ => String::new(),
+    })
+}
+
+/// An item told in words for screen readers, starting with where it is in the
+/// list, such as `"Item 2 of 10: Title, unread, 2023-11-02, by Author"`.
+fn item_context(item: &Item, index: usize, count: usize, feed_title: &str) -> String {
+    let mut parts = vec![format!(
+        "Item {} of {}: {}",
+        index + 1,
+        count,
+        item.title().unwrap_or("[no title]")
+    )];
+    if !item.read() {
+        parts.push("unread".to_owned());
+    }
+    if item.updated() {
+        parts.push("updated".to_owned());
+    }
+    let date = item.pub_date().map(short_date).unwrap_or_default();
+    if !date.is_empty() {
+        parts.push(date);
+    }
+    if let Some(author) = item.author() {
+        parts.push(format!("by {}", author));
+    }
+    if !feed_title.is_empty() {
+        parts.push(format!("from {}", feed_title));
+    }
+    parts.join(", ")
+}
+
+/// An item's date as `YYYY-MM-DD`, whether it's given in RSS or Atom format.
+fn short_date(date: &str) -> String {
+    util::parse_date(date)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Pads `text` with spaces to `width` cells, or cuts it short with an ellipsis
+/// if it's wider.
+fn fit(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return format!("{}{}", text, " ".repeat(width - text.width()));
+    }
+    let mut fitted = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
         }
+        fitted.push(c);
+        used += w;
+    }
+    if width > 0 {
+        fitted.push('…');
+        used += 1;
     }
+    fitted + &" ".repeat(width - used)
+}
+
+/// What the Detail panel shows: the text of an item or page, or the changes
+/// between two versions of an item.
+enum Body<'a> {
+    Text(&'a str),
+    Diff(String, String),
+}
+
+impl Body<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Body::Text(text) => text.len(),
+            Body::Diff(old, new) => old.len() + new.len(),
+        }
+    }
+}
+
+/// The changes between two versions of an item's title and text, with removed
+/// lines in red and added ones in green.
+fn revision_diff(app: &App, old: &str, new: &str) -> Text<'static> {
+    let lines: Vec<_> = util::diff_lines(old, new)
+        .into_iter()
+        .map(|line| match line {
+            DiffLine::Same(line) => Line::styled(format!("  {}", line), app.config.theme().base()),
+            DiffLine::Removed(line) => {
+                Line::styled(format!("- {}", line), Style::default().fg(Color::Red))
+            }
+            DiffLine::Added(line) => {
+                Line::styled(format!("+ {}", line), Style::default().fg(Color::Green))
+            }
+        })
+        .collect();
+    Text::from(lines)
+}
+
+/// A colored two-letter badge standing in for a feed's icon, made of the
+/// initials of its first two words, or the first two letters of a single one.
+fn feed_badge(theme: &Theme, title: &str) -> Span<'static> {
+    let words: Vec<_> = title.split_whitespace().collect();
+    let initials: String = match words.as_slice() {
+        [] => "··".to_owned(),
+        [word] => word.chars().take(2).collect(),
+        [first, second, ..] => first
+            .chars()
+            .take(1)
+            .chain(second.chars().take(1))
+            .collect(),
+    };
+    Span::styled(
+        format!(
+            "{:<2}",
+            initials.to_uppercase().chars().take(2).collect::<String>()
+        ),
+        theme.badge(title),
+    )
+}
+
+/// Notes where the icons of the visible feeds go, for drawing them once the
+/// frame is on screen.
+fn record_icon_slots(app: &mut App, area: Rect) {
+    if !app.graphics {
+        return;
+    }
+    let offset = app.feed_rows_state.offset();
+    let slots = app
+        .feed_rows()
+        .into_iter()
+        .skip(offset)
+        .take(area.height as usize)
+        .enumerate()
+        .filter_map(|(row, feed)| match feed {
+            FeedRow::Feed(_, feed) if app.icons.contains_key(feed.id()) => Some(IconSlot {
+                x: area.x,
+                y: area.y + row as u16,
+                feed_id: feed.id().to_owned(),
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    app.icon_slots.extend(slots);
+}
+
+/// Puts the terminal cursor on the selected row of a list while it's active,
+/// since that's where screen readers look for what to read.
+fn follow_with_cursor<B: Backend>(
+    app: &App,
+    frame: &mut Frame<'_, B>,
+    area: Rect,
+    state: &ListState,
+    view: View,
+) {
+    if !app.config.screen_reader() || app.active_view != view {
+        return;
+    }
+    if let Some(selected) = state.selected() {
+        let row = selected.saturating_sub(state.offset()) as u16;
+        if row < area.height {
+            frame.set_cursor(area.x, area.y + row);
+        }
+    }
+}
+
+/// Centers a hint about what to do next in an empty list.
+fn render_empty_hint<B: Backend>(app: &App, frame: &mut Frame<'_, B>, area: Rect, hint: &str) {
+    let hint = Paragraph::new(hint)
+        .style(app.config.theme().base().add_modifier(Modifier::DIM))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(Block::default().padding(Padding {
+            top: area.height.saturating_sub(1) / 2,
+            bottom: 0,
+            left: 1,
+            right: 1,
+        }));
+    frame.render_widget(hint, area);
 }
@@ -0,0 +1,152 @@
+use crate::config::Theme;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use syntect::easy::ScopeRegionIterator;
+use syntect::parsing::{ParseState, ScopeStack, SyntaxSet};
+use syntect::util::LinesWithEndings;
+use tui::style::{Modifier, Style};
+use tui::text::{Line, Span};
+
+/// How many highlighted blocks are kept before starting over.
+const MAX_CACHED_BLOCKS: usize = 64;
+
+/// The syntaxes bundled with syntect, which take a while to load so are only
+/// loaded once the first code block is shown.
+static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+
+/// The kinds of code told apart, each drawn in a style taken from the theme.
+enum Token {
+    Comment,
+    Keyword,
+    String,
+    Constant,
+    Plain,
+}
+
+impl Token {
+    /// The kind of the innermost scope that has one, as in `comment.line` or
+    /// `storage.type`.
+    fn of(stack: &ScopeStack) -> Self {
+        stack
+            .as_slice()
+            .iter()
+            .rev()
+            .find_map(|scope| {
+                let name = scope.build_string();
+                let kind = name.split('.').next().unwrap_or_default();
+                match kind {
+                    "comment" => Some(Self::Comment),
+                    "keyword" | "storage" => Some(Self::Keyword),
+                    "string" => Some(Self::String),
+                    "constant" => Some(Self::Constant),
+                    _ => None,
+                }
+            })
+            .unwrap_or(Self::Plain)
+    }
+
+    /// Colors are taken from the theme's borders, selection and overlay, so
+    /// code matches the rest of the app, with bold and italics to tell kinds
+    /// apart in themes without many.
+    fn style(&self, theme: &Theme) -> Style {
+        let fg = |style: Style| Style {
+            fg: style.fg,
+            ..Style::default()
+        };
+        let bg_as_fg = |style: Style| Style {
+            fg: style.bg,
+            ..Style::default()
+        };
+        match self {
+            Self::Comment => fg(theme.border()).add_modifier(Modifier::ITALIC),
+            Self::Keyword => fg(theme.active_border()).add_modifier(Modifier::BOLD),
+            Self::String => bg_as_fg(theme.active_selection()),
+            Self::Constant => bg_as_fg(theme.overlay()),
+            Self::Plain => Style::default(),
+        }
+    }
+}
+
+/// Swaps the indentation of a line of code for non-breaking spaces, which the
+/// detail pane doesn't trim away.
+fn keep_indent(line: &str) -> String {
+    let text = line.trim_start_matches([' ', '\t']);
+    let indent = line[..line.len() - text.len()].replace('\t', "    ");
+    format!("{}{}", "\u{a0}".repeat(indent.len()), text)
+}
+
+fn plain(code: &str) -> Vec<Line<'static>> {
+    code.lines()
+        .map(|line| Line::from(keep_indent(line)))
+        .collect()
+}
+
+/// Highlights code in a language syntect knows by name or file extension,
+/// or leaves it plain.
+fn highlight(language: &str, code: &str, theme: &Theme) -> Vec<Line<'static>> {
+    if language.is_empty() {
+        return plain(code);
+    }
+    let syntaxes = SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines);
+    let Some(syntax) = syntaxes.find_syntax_by_token(language) else {
+        return plain(code);
+    };
+
+    let mut state = ParseState::new(syntax);
+    let mut stack = ScopeStack::new();
+    let mut lines = vec![];
+    for line in LinesWithEndings::from(code) {
+        let Ok(ops) = state.parse_line(line, syntaxes) else {
+            return plain(code);
+        };
+        let mut spans = vec![];
+        let mut indented = false;
+        for (text, op) in ScopeRegionIterator::new(&ops, line) {
+            if stack.apply(op).is_err() {
+                return plain(code);
+            }
+            let text = text.trim_end_matches(['\n', '\r']);
+            if text.is_empty() {
+                continue;
+            }
+            let text = if indented {
+                text.to_owned()
+            } else {
+                indented = true;
+                keep_indent(text)
+            };
+            spans.push(Span::styled(text, Token::of(&stack).style(theme)));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+/// Code blocks already highlighted, by language and code, since it's too slow
+/// to do on every frame. Cleared when the theme changes.
+#[derive(Debug, Default)]
+pub struct CodeCache {
+    blocks: RefCell<HashMap<(String, String), Vec<Line<'static>>>>,
+}
+
+impl CodeCache {
+    pub fn lines(&self, language: &str, code: &str, theme: &Theme) -> Vec<Line<'static>> {
+        let key = (language.to_owned(), code.to_owned());
+        let mut blocks = self.blocks.borrow_mut();
+        if let Some(lines) = blocks.get(&key) {
+            return lines.clone();
+        }
+
+        if blocks.len() >= MAX_CACHED_BLOCKS {
+            blocks.clear();
+        }
+        let lines = highlight(language, code, theme);
+        blocks.insert(key, lines.clone());
+        lines
+    }
+
+    pub fn invalidate(&self) {
+        self.blocks.borrow_mut().clear();
+    }
+}
@@ -0,0 +1,94 @@
+use crate::config::Theme;
+use tui::style::Modifier;
+use tui::text::{Line, Span};
+
+/// The byte ranges of a line matching `query`, ignoring case. Only ASCII is
+/// folded, so the ranges line up with the original.
+fn match_ranges(line: &str, query: &str) -> Vec<(usize, usize)> {
+    let haystack = line.to_ascii_lowercase();
+    let needle = query.to_ascii_lowercase();
+    haystack
+        .match_indices(&needle)
+        .map(|(start, found)| (start, start + found.len()))
+        .collect()
+}
+
+fn line_text(line: &Line<'_>) -> String {
+    line.spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect::<String>()
+}
+
+/// The row each match of `query` is on, in text already wrapped to rows.
+pub fn match_rows(rows: &[Line<'_>], query: &str) -> Vec<usize> {
+    rows.iter()
+        .enumerate()
+        .flat_map(|(row, line)| {
+            let count = match_ranges(&line_text(line), query).len();
+            vec![row; count]
+        })
+        .collect()
+}
+
+/// Styles each match of `query` in some rows, the `current` one most of all,
+/// where `first` is the number of matches in the rows above them.
+pub fn mark_matches<'a>(
+    rows: Vec<Line<'a>>,
+    query: &str,
+    first: usize,
+    current: usize,
+    theme: &Theme,
+) -> Vec<Line<'a>> {
+    let mut index = first;
+    let mut lines = vec![];
+
+    for line in rows {
+        let content = line_text(&line);
+        let ranges = match_ranges(&content, query);
+        if ranges.is_empty() {
+            lines.push(line);
+            continue;
+        }
+
+        let mut spans = vec![];
+        let mut offset = 0;
+        for span in &line.spans {
+            let end = offset + span.content.len();
+            let mut at = offset;
+            for (i, &(start, stop)) in ranges.iter().enumerate() {
+                let (start, stop) = (start.max(at), stop.min(end));
+                if start >= stop {
+                    continue;
+                }
+                if start > at {
+                    spans.push(Span::styled(content[at..start].to_owned(), span.style));
+                }
+                let style = if index + i == current {
+                    theme
+                        .active_selection()
+                        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+                } else {
+                    theme.selection()
+                };
+                spans.push(Span::styled(
+                    content[start..stop].to_owned(),
+                    span.style.patch(style),
+                ));
+                at = stop;
+            }
+            if at < end {
+                spans.push(Span::styled(content[at..end].to_owned(), span.style));
+            }
+            offset = end;
+        }
+
+        index += ranges.len();
+        lines.push(Line {
+            spans,
+            alignment: line.alignment,
+        });
+    }
+
+    lines
+}
@@ -0,0 +1,109 @@
+use crate::app::App;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use crossterm::cursor::{MoveTo, RestorePosition, SavePosition};
+use std::collections::HashSet;
+use std::env;
+use std::io::{self, Write};
+
+/// Largest chunk of base64 data the kitty graphics protocol accepts at once.
+const CHUNK_SIZE: usize = 4096;
+
+/// A site icon, ready to be sent to the terminal.
+#[derive(Debug, Clone)]
+pub struct Icon {
+    /// Derived from the image itself, so that the terminal's copy can be
+    /// reused for as long as the icon doesn't change.
+    id: u32,
+    png: Vec<u8>,
+}
+
+impl Icon {
+    pub fn new(png: Vec<u8>) -> Self {
+        let hash = png.iter().fold(0x811c9dc5u32, |hash, b| {
+            (hash ^ *b as u32).wrapping_mul(0x01000193)
+        });
+        Self {
+            id: hash.max(1),
+            png,
+        }
+    }
+}
+
+/// Where a feed's icon should be drawn on screen, as found while rendering.
+#[derive(Debug, Clone)]
+pub struct IconSlot {
+    pub x: u16,
+    pub y: u16,
+    pub feed_id: String,
+}
+
+/// Whether the terminal can display images with the kitty graphics protocol.
+/// There's no reliable way to ask, so known terminals are recognized by the
+/// environment they set.
+pub fn supports_images() -> bool {
+    env::var_os("KITTY_WINDOW_ID").is_some()
+        || env::var("TERM").map_or(false, |term| term == "xterm-kitty")
+        || env::var("TERM_PROGRAM").map_or(false, |program| {
+            matches!(program.as_str(), "WezTerm" | "ghostty")
+        })
+}
+
+/// Draws the icons of the feeds the last render left room for, sending each
+/// image to the terminal the first time it's shown. Icons are only redrawn
+/// when they or their places change, since placing them again makes them
+/// flicker.
+pub fn draw_icons(
+    app: &App,
+    transmitted: &mut HashSet<u32>,
+    placed: &mut Vec<(u16, u16, u32)>,
+) -> io::Result<()> {
+    let icons: Vec<_> = app
+        .icon_slots
+        .iter()
+        .filter_map(|slot| Some((slot.x, slot.y, app.icons.get(&slot.feed_id)?)))
+        .collect();
+    let placements: Vec<_> = icons.iter().map(|(x, y, icon)| (*x, *y, icon.id)).collect();
+    if *placed == placements {
+        return Ok(());
+    }
+
+    let mut out = io::stderr().lock();
+    write!(out, "\x1b_Ga=d,d=a,q=2\x1b\\")?;
+    crossterm::queue!(out, SavePosition)?;
+    for (x, y, icon) in icons {
+        if transmitted.insert(icon.id) {
+            transmit(&mut out, icon)?;
+        }
+        crossterm::queue!(out, MoveTo(x, y))?;
+        write!(out, "\x1b_Ga=p,i={},c=2,r=1,C=1,q=2\x1b\\", icon.id)?;
+    }
+    crossterm::queue!(out, RestorePosition)?;
+    out.flush()?;
+
+    *placed = placements;
+    Ok(())
+}
+
+/// Removes any icons from the screen, leaving the images with the terminal.
+pub fn clear_icons() -> io::Result<()> {
+    let mut out = io::stderr().lock();
+    write!(out, "\x1b_Ga=d,d=a,q=2\x1b\\")?;
+    out.flush()
+}
+
+fn transmit(out: &mut impl Write, icon: &Icon) -> io::Result<()> {
+    let data = STANDARD.encode(&icon.png);
+    let chunks: Vec<_> = data.as_bytes().chunks(CHUNK_SIZE).collect();
+    for (n, chunk) in chunks.iter().enumerate() {
+        let more = (n + 1 < chunks.len()) as u8;
+        if n == 0 {
+            write!(out, "\x1b_Ga=t,f=100,i={},q=2,m={};", icon.id, more)?;
+        } else {
+            write!(out, "\x1b_Gm={};", more)?;
+        }
+        out.write_all(chunk)?;
+        write!(out, "\x1b\\")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::app::App;
+use tui::{
+    backend::Backend,
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph, Wrap},
+    Frame,
+};
+
+/// Shows what's known about the current feed, including where it's fetched from.
+pub fn render_inspector_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let Some(feed) = app.current_feed() else {
+        return;
+    };
+
+    let label = |name: &str| Span::styled(format!("{:<14}", name), Style::default().bold());
+    let mut lines = vec![
+        Line::from(vec![label("Title"), Span::raw(feed.title())]),
+        Line::from(vec![label("URL"), Span::raw(feed.url())]),
+    ];
+    if let Some(bridged) = app.config.bridge_url(feed.url()) {
+        lines.push(Line::from(vec![label("Fetched via"), Span::raw(bridged)]));
+    }
+    lines.push(Line::from(vec![label("Site"), Span::raw(feed.link())]));
+    if let Some(language) = feed.language() {
+        lines.push(Line::from(vec![label("Language"), Span::raw(language)]));
+    }
+    lines.extend([
+        Line::from(vec![
+            label("Items"),
+            Span::raw(format!("{} ({} unread)", feed.item_count(), feed.unread_count())),
+        ]),
+        Line::from(vec![
+            label("Last fetched"),
+            Span::raw(feed.last_fetched().unwrap_or("never")),
+        ]),
+    ]);
+    if let Some(rule) = app.config.feed_rule(feed.url()) {
+        let priority = if rule.is_high_priority() {
+            "high"
+        } else {
+            "normal"
+        };
+        lines.push(Line::from(vec![label("Priority"), Span::raw(priority)]));
+        if let Some(timeout) = rule.refresh_timeout() {
+            lines.push(Line::from(vec![label("Timeout"), Span::raw(format!("{}s", timeout))]));
+        }
+        if rule.is_insecure() {
+            lines.push(Line::from(vec![
+                label("TLS"),
+                Span::styled("certificate not verified", app.config.theme().highlight()),
+            ]));
+        }
+    }
+    if !feed.description().is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(feed.description()));
+    }
+
+    let block = Block::default()
+        .title("Feed")
+        .borders(super::borders(app, Borders::ALL))
+        .border_style(app.config.theme().overlay())
+        .border_type(BorderType::Plain)
+        .style(app.config.theme().overlay())
+        .padding(Padding::uniform(1));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .block(block),
+        area,
+    );
+}
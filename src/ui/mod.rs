@@ -1,48 +1,182 @@
 use crate::app::{App, Status, Tab};
+use crate::config::{ColorMode, RenderMode, StatusSegment};
 use tui::{
     backend::Backend,
     layout::Alignment,
     prelude::*,
-    widgets::{Block, BorderType, Borders, Clear, Gauge, Padding, Paragraph, Tabs},
+    widgets::{
+        block::{Position, Title},
+        Block, BorderType, Borders, Clear, Gauge, List, ListItem, Padding, Paragraph, Tabs, Wrap,
+    },
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
+pub mod ascii;
+pub mod batch;
+pub mod body;
 pub mod browse;
+pub mod code;
 pub mod detail;
+pub mod find;
+pub mod graphics;
+pub mod inspector;
+pub mod monitor;
+pub mod preview;
+pub mod prose;
+pub mod stats;
+pub mod table;
 pub mod themed;
+#[cfg(test)]
+mod tests;
 
 /// Renders the user interface widgets.
 pub fn render<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
     let wrapper = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(2),
+            // Dashboards stay on the one tab, so leave out the tabs bar.
+            Constraint::Length(if app.config.dashboard_interval().is_some() {
+                0
+            } else {
+                2
+            }),
             Constraint::Min(10),
             Constraint::Length(2),
         ])
         .split(frame.size());
 
+    app.icon_slots.clear();
     render_tabs_bar(app, frame, wrapper[0]);
 
     match app.active_tab {
-        Tab::Browse => {
+        Tab::Browse | Tab::Favorites | Tab::Tags | Tab::Authors => {
             browse::render_browse_area(app, frame, wrapper[1]);
         }
-        _ => {}
     }
 
     render_status_bar(app, frame, wrapper[2]);
 
+    if app.should_render_console() && app.has_search_results() {
+        render_search_overlay(app, frame, wrapper[1]);
+    }
+
+    if app.stats.is_some() {
+        stats::render_stats_overlay(app, frame, centered_rect_ratio((7, 9), (7, 9), frame.size()));
+    }
+
+    if app.monitor.is_some() {
+        monitor::render_monitor_overlay(
+            app,
+            frame,
+            centered_rect_ratio((7, 9), (5, 9), frame.size()),
+        );
+    }
+
+    if app.show_inspector {
+        inspector::render_inspector_overlay(
+            app,
+            frame,
+            centered_rect_ratio((5, 9), (5, 9), frame.size()),
+        );
+    }
+
+    if app.batch.is_some() {
+        batch::render_batch_overlay(
+            app,
+            frame,
+            centered_rect_ratio((7, 9), (5, 9), frame.size()),
+        );
+    }
+
+    if app.preview.is_some() {
+        preview::render_preview_overlay(
+            app,
+            frame,
+            centered_rect_ratio((5, 9), (7, 9), frame.size()),
+        );
+    }
+
+    if app.log_lines.is_some() {
+        render_log_overlay(app, frame, centered_rect_ratio((7, 9), (7, 9), frame.size()));
+    }
+
+    if app.share_menu.is_some() {
+        render_share_overlay(app, frame, frame.size());
+    }
+
+    if app.compose.is_some() {
+        render_compose_overlay(
+            app,
+            frame,
+            centered_rect_ratio((5, 9), (3, 9), frame.size()),
+        );
+    }
+
     if app.show_keybinds {
         render_keybinds_overlay(app, frame, frame.size());
     }
+
+    // Images are drawn over everything, so would show through overlays.
+    if app.show_keybinds
+        || app.show_inspector
+        || app.stats.is_some()
+        || app.monitor.is_some()
+        || app.batch.is_some()
+        || app.preview.is_some()
+        || app.log_lines.is_some()
+        || app.share_menu.is_some()
+        || app.compose.is_some()
+        || (app.should_render_console() && app.has_search_results())
+    {
+        app.icon_slots.clear();
+    }
+
+    downgrade_colors(frame, app.config.color());
+    if draws_ascii(app) {
+        ascii::replace_symbols(frame.buffer_mut());
+    }
+}
+
+/// Whether to draw borders and symbols in ASCII, which terminals without
+/// Unicode need, and which take fewer bytes to send over slow connections.
+fn draws_ascii(app: &App) -> bool {
+    app.config.ascii() || app.config.render_mode() == RenderMode::Minimal
+}
+
+/// Brings every color drawn down to the nearest the terminal can show,
+/// including those that aren't part of the theme, like feed badges.
+fn downgrade_colors<B: Backend>(frame: &mut Frame<'_, B>, color: ColorMode) {
+    if color == ColorMode::TrueColor {
+        return;
+    }
+    for cell in frame.buffer_mut().content.iter_mut() {
+        cell.fg = color.downgrade(cell.fg);
+        cell.bg = color.downgrade(cell.bg);
+    }
+}
+
+/// The borders a block is drawn with, or none for screen readers, which would
+/// only read them out as rows of box-drawing characters.
+fn borders(app: &App, borders: Borders) -> Borders {
+    if app.config.screen_reader() {
+        Borders::NONE
+    } else {
+        borders
+    }
 }
 
 fn render_tabs_bar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
     let browse = Tab::Browse.to_string().clone();
     let (b, rowse) = browse.split_at(1);
     let b = b.underlined().to_owned();
-    let browse = Line::from(vec![b, rowse.into()]);
+    let mut browse = Line::from(vec![b, rowse.into()]);
+    // Counted from each feed's unread count, which is kept up to date as items
+    // are read.
+    let unread = app.unread_count();
+    if unread > 0 {
+        browse.spans.push(format!(" ({})", unread).into());
+    }
 
     let favorites = Tab::Favorites.to_string().clone();
     let (f, avorites) = favorites.split_at(1);
@@ -54,13 +188,30 @@ fn render_tabs_bar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Re
     let t = t.underlined().to_owned();
     let tags = Line::from(vec![t, ags.into()]);
 
-    let tabs = Tabs::new(vec![browse, favorites, tags])
-        .block(
-            Block::default()
-                .style(app.config.theme().status())
-                .borders(Borders::BOTTOM)
-                .border_style(app.config.theme().active_border()),
-        )
+    let authors = Tab::Authors.to_string().clone();
+    let (a, uthors) = authors.split_at(1);
+    let a = a.underlined().to_owned();
+    let authors = Line::from(vec![a, uthors.into()]);
+
+    let mut block = Block::default()
+        .style(app.config.theme().status())
+        .borders(borders(app, Borders::BOTTOM))
+        .border_style(app.config.theme().active_border());
+    let read_only = app.config.read_only().then_some("read-only");
+    let labels = [app.config.profile(), read_only]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if !labels.is_empty() {
+        block = block.title(
+            Title::from(format!(" {} ", labels.join(", ")))
+                .alignment(Alignment::Right)
+                .position(Position::Bottom),
+        );
+    }
+
+    let tabs = Tabs::new(vec![browse, favorites, tags, authors])
+        .block(block)
         .select(app.active_tab.index_of())
         .highlight_style(app.config.theme().selection());
     frame.render_widget(tabs, area);
@@ -70,7 +221,7 @@ fn render_keybinds_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>,
     let area = centered_rect_ratio((5, 9), (5, 9), area);
 
     let block = Block::default()
-        .borders(Borders::ALL)
+        .borders(borders(app, Borders::ALL))
         .border_style(app.config.theme().overlay())
         .border_type(BorderType::Plain)
         .style(app.config.theme().overlay())
@@ -81,8 +232,13 @@ fn render_keybinds_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>,
             right: 2,
         });
 
+    // Side by side, a screen reader would read both halves as one line.
     let layout = Layout::default()
-        .direction(Direction::Horizontal)
+        .direction(if app.config.screen_reader() {
+            Direction::Vertical
+        } else {
+            Direction::Horizontal
+        })
         .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
         .split(area);
 
@@ -93,10 +249,26 @@ fn render_keybinds_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>,
         Line::from("Esc    deselect current"),
         Line::from("Tab    cycle tabs"),
         Line::from("b/f/t  go to Browse/Favorites/Tags tab"),
+        Line::from("A      go to Authors tab"),
         Line::from(":      console mode"),
+        Line::from("F      find feed, or only favorites in Feeds"),
+        Line::from("u/e    only unread/failing feeds in Feeds"),
+        Line::from("x      only archived feeds in Feeds"),
+        Line::from("s      favorite/unfavorite feed"),
+        Line::from("s      share item from Detail"),
+        Line::from("/      search, or find in Detail panel"),
+        Line::from("n/N    next/previous match in Detail"),
+        Line::from("J/K    next/previous item from Detail"),
+        Line::from("i      inspect feed"),
+        Line::from("D      compare updated item"),
+        Line::from("M      monitor status feeds"),
+        Line::from("^o/^i  jump back/forward between feeds"),
+        Line::from("m/'    set/jump to mark by letter"),
         Line::from("r      refresh all feeds"),
+        Line::from("y/n    unsubscribe from/keep dead feed"),
         Line::from("q      quit"),
         Line::from("o      open feed/item in browser"),
+        Line::from("O      open feed/item in background"),
         Line::from(",      open config file"),
         Line::from("?      toggle this help dialog"),
     ];
@@ -104,8 +276,25 @@ fn render_keybinds_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>,
 
     let console = vec![
         Line::from(":add <URL>      scroll down/up"),
+        Line::from(":add-many       add feeds from file or clipboard"),
         Line::from(":delete <URL>   focus previous/next panel"),
-        Line::from(":search <TERM>  filter feeds"),
+        Line::from(":search <TERM>  search items as you type"),
+        Line::from("Up/Down         pick a search result"),
+        Line::from("Ent             jump to search result"),
+        Line::from(":edit-url <URL> change feed URL"),
+        Line::from(":feed <NAME>    find a feed by name"),
+        Line::from(":find <TEXT>    find text in Detail panel"),
+        Line::from(":goto <N>       select nth feed or item"),
+        Line::from(":jump <MARK>    jump to mark, back or forward"),
+        Line::from(":layout <NAME>  switch layout"),
+        Line::from(":open <N|URL>   open nth link of item, or URL"),
+        Line::from(":log            show recent log lines"),
+        Line::from(":profile <NAME> switch profile"),
+        Line::from(":stats reading  show reading statistics"),
+        Line::from(":theme <NAME>   switch color scheme"),
+        Line::from(":summarize      summarize item, or hide it"),
+        Line::from(":translate      translate item, or undo"),
+        Line::from("Tab             complete command/argument"),
         Line::from("Esc             exit console mode"),
     ];
     let console_keybinds = Paragraph::new(console).block(block.title("Console"));
@@ -115,28 +304,206 @@ fn render_keybinds_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>,
     frame.render_widget(console_keybinds, layout[1]);
 }
 
+/// Lists the share targets, numbered for picking one with a key.
+fn render_share_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let targets = app.config.share_targets();
+    let width = targets
+        .iter()
+        .map(|target| target.name().width() as u16)
+        .max()
+        .unwrap_or_default()
+        .max(12)
+        + 8;
+    let height = targets.len() as u16 + 2;
+    let area = centered_rect_sized(width.min(area.width), height.min(area.height), area);
+
+    let list = List::new(
+        targets
+            .iter()
+            .enumerate()
+            .map(|(i, target)| match i {
+                0..=8 => ListItem::new(format!("{}  {}", i + 1, target.name())),
+                _ => ListItem::new(format!("   {}", target.name())),
+            })
+            .collect::<Vec<_>>(),
+    )
+    .block(
+        Block::default()
+            .title("Share")
+            .borders(borders(app, Borders::ALL))
+            .border_style(app.config.theme().overlay())
+            .border_type(BorderType::Plain)
+            .style(app.config.theme().overlay()),
+    )
+    .highlight_style(app.config.theme().active_selection());
+
+    frame.render_widget(Clear, area);
+    if let Some(menu) = app.share_menu.as_mut() {
+        frame.render_stateful_widget(list, area, menu);
+    }
+}
+
+/// Shows the post being written, with the cell under the cursor reversed, as
+/// the text wraps in ways the terminal cursor can't follow.
+fn render_compose_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let Some(compose) = &app.compose else {
+        return;
+    };
+    let Some(target) = app.config.share_targets().get(compose.target) else {
+        return;
+    };
+
+    let count = match target.kind().post_limit() {
+        Some(limit) => format!("{}/{}", compose.char_count(), limit),
+        None => compose.char_count().to_string(),
+    };
+    let block = Block::default()
+        .title(format!("Post to {} ({})", target.name(), count))
+        .title(
+            Title::from(" Enter send · Alt+Enter new line · Esc cancel ")
+                .alignment(Alignment::Right)
+                .position(Position::Bottom),
+        )
+        .borders(borders(app, Borders::ALL))
+        .border_style(app.config.theme().overlay())
+        .border_type(BorderType::Plain)
+        .style(app.config.theme().overlay())
+        .padding(Padding::horizontal(1));
+
+    let at = compose
+        .text
+        .char_indices()
+        .nth(compose.cursor)
+        .map_or(compose.text.len(), |(i, _)| i);
+    let (before, rest) = compose.text.split_at(at);
+    let mut rest = rest.chars();
+    let under = rest.next();
+
+    let mut lines = vec![Line::default()];
+    push_lines(&mut lines, before);
+    let cursor = Style::default().add_modifier(Modifier::REVERSED);
+    let last = lines.last_mut().expect("at least one line");
+    match under {
+        Some('\n') => {
+            last.spans.push(Span::styled(" ", cursor));
+            lines.push(Line::default());
+        }
+        Some(c) => last.spans.push(Span::styled(c.to_string(), cursor)),
+        None => last.spans.push(Span::styled(" ", cursor)),
+    }
+    push_lines(&mut lines, rest.as_str());
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Adds text to the end of the last line, starting a new line at each newline.
+fn push_lines<'a>(lines: &mut Vec<Line<'a>>, text: &'a str) {
+    for (i, part) in text.split('\n').enumerate() {
+        if i > 0 {
+            lines.push(Line::default());
+        }
+        if !part.is_empty() {
+            let last = lines.last_mut().expect("at least one line");
+            last.spans.push(Span::raw(part));
+        }
+    }
+}
+
+/// Shows the most recent log lines that fit, newest at the bottom.
+fn render_log_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let Some(lines) = &app.log_lines else {
+        return;
+    };
+
+    let block = Block::default()
+        .title(format!("Log ({})", app.config.log_path().display()))
+        .borders(borders(app, Borders::ALL))
+        .border_style(app.config.theme().overlay())
+        .border_type(BorderType::Plain)
+        .style(app.config.theme().overlay())
+        .padding(Padding::horizontal(1));
+
+    let height = block.inner(area).height as usize;
+    let text = if lines.is_empty() {
+        vec![Line::from("Nothing logged yet")]
+    } else {
+        lines[lines.len().saturating_sub(height)..]
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect()
+    };
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn render_search_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let height = (app.search.results.items().len() as u16 + 2).min(area.height);
+    let area = Rect {
+        y: area.bottom() - height,
+        height,
+        ..area
+    };
+
+    let results = List::new(
+        app.search
+            .results
+            .items()
+            .iter()
+            .map(|result| match result.item_id {
+                Some(_) => ListItem::new(format!("{}  ({})", result.title, result.feed_title)),
+                None => ListItem::new(result.title.clone()),
+            })
+            .collect::<Vec<_>>(),
+    )
+    .block(
+        Block::default()
+            .title("Search")
+            .borders(borders(app, Borders::ALL))
+            .border_style(app.config.theme().overlay())
+            .border_type(BorderType::Plain)
+            .style(app.config.theme().overlay()),
+    )
+    .highlight_style(app.config.theme().active_selection());
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(results, area, &mut app.search.results.state);
+}
+
 fn render_console_area<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
     let block = Block::default()
         .style(app.config.theme().status())
-        .borders(Borders::TOP)
+        .borders(borders(app, Borders::TOP))
         .border_style(app.config.theme().active_border());
 
-    let input_field = Paragraph::new(app.command_state.input.as_str()).block(block);
+    let mut input = vec![Span::raw(app.command_state.input.as_str())];
+    let hint = app.completion_hint();
+    if let Some(hint) = &hint {
+        input.push(Span::styled(hint.as_str(), Style::default().dim()));
+    }
+    let inner = block.inner(area);
+    let input_field = Paragraph::new(Line::from(input)).block(block);
 
     frame.render_widget(input_field, area);
     frame.set_cursor(
         // Draw the cursor at the current position in the input field.
         // This position is can be controlled via the left and right arrow key
-        area.x + app.command_state.cursor_position as u16,
-        // Move one line down, from the border to the input line
-        area.y + 1,
+        inner.x + app.command_state.cursor_position as u16,
+        // Below the border, if there is one
+        inner.y,
     )
 }
 
 fn render_status_bar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
     let block = Block::default()
         .style(app.config.theme().status())
-        .borders(Borders::TOP)
+        .borders(borders(app, Borders::TOP))
         .border_style(app.config.theme().active_border());
 
     if app.should_render_console() {
@@ -150,21 +517,22 @@ fn render_status_bar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area:
                             .block(block)
                             .ratio(*n as f64 / *count as f64)
                             .label(format!("Loading {}/{}", n, count))
-                            .use_unicode(true)
+                            .use_unicode(!draws_ascii(app))
                             .gauge_style(app.config.theme().status()),
                         area,
                     );
                 }
             }
             Status::Done => {
-                let text = match app.current_feed().cloned() {
-                    Some(feed) => {
-                        let mut message = String::from("Last fetched: ");
-                        let date = feed.last_fetched().unwrap_or("never").into();
-                        message.push_str(date);
-                        message
+                // A dead feed waits on an answer, which is asked for first. Run in
+                // a degraded mode with a config problem in view until it's fixed.
+                let text = match (app.dead_feed_prompt(), app.config.problems()) {
+                    (Some(prompt), _) => prompt,
+                    (None, []) => status_segments_text(app),
+                    (None, [problem]) => format!("CONFIG: {}", problem),
+                    (None, [problem, rest @ ..]) => {
+                        format!("CONFIG: {} (and {} more)", problem, rest.len())
                     }
-                    _ => "[no selection]".to_string(),
                 };
                 frame.render_widget(
                     Paragraph::new(text)
@@ -173,6 +541,14 @@ fn render_status_bar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area:
                     area,
                 );
             }
+            Status::Info(s) => {
+                frame.render_widget(
+                    Paragraph::new(s.as_str())
+                        .alignment(Alignment::Center)
+                        .block(block),
+                    area,
+                );
+            }
             Status::Errored(s) => {
                 frame.render_widget(
                     Paragraph::new(format!("ERROR: {}", s))
@@ -185,6 +561,45 @@ fn render_status_bar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area:
     }
 }
 
+fn status_segments_text(app: &App) -> String {
+    app.config
+        .status_bar()
+        .segments()
+        .iter()
+        .map(|segment| match segment {
+            StatusSegment::Text(text) => text.clone(),
+            StatusSegment::LastFetched => match app.current_feed() {
+                Some(feed) => format!(
+                    "Last fetched: {}",
+                    feed.last_fetched().unwrap_or("never")
+                ),
+                None => "[no selection]".into(),
+            },
+            StatusSegment::Unread => format!("{} unread", app.unread_count()),
+            StatusSegment::Filter => match &app.filter {
+                Some(filter) => format!("/{}", filter),
+                None => String::new(),
+            },
+            StatusSegment::Offline => {
+                if app.offline {
+                    "[offline]".into()
+                } else {
+                    String::new()
+                }
+            }
+            StatusSegment::Countdown => match app.repo.time_until_refresh() {
+                Some(remaining) => {
+                    let secs = remaining.as_secs();
+                    format!("Next refresh: {}:{:02}", secs / 60, secs % 60)
+                }
+                None => String::new(),
+            },
+        })
+        .collect::<String>()
+        .trim()
+        .to_owned()
+}
+
 fn centered_rect_ratio(ratio_x: (u32, u32), ratio_y: (u32, u32), r: Rect) -> Rect {
     let each_x = (ratio_x.1 - ratio_x.0) / 2;
     let each_y = (ratio_y.1 - ratio_y.0) / 2;
@@ -0,0 +1,135 @@
+use crate::app::App;
+use crate::util;
+use tui::{
+    backend::Backend,
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph},
+    Frame,
+};
+
+/// How bad the latest update of a status page is, as told by its title, from
+/// least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Resolved,
+    Unknown,
+    Maintenance,
+    Minor,
+    Major,
+}
+
+impl Severity {
+    /// Guesses the severity from the words status pages commonly use. A
+    /// resolved incident is resolved however bad it was.
+    pub fn from_title(title: &str) -> Self {
+        let title = title.to_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|word| title.contains(word));
+
+        if mentions(&["resolved", "operational", "recovered", "completed"]) {
+            Self::Resolved
+        } else if mentions(&["maintenance", "scheduled"]) {
+            Self::Maintenance
+        } else if mentions(&["major", "outage", "critical", "down"]) {
+            Self::Major
+        } else if mentions(&[
+            "degraded",
+            "partial",
+            "minor",
+            "elevated",
+            "investigating",
+            "identified",
+            "monitoring",
+            "incident",
+        ]) {
+            Self::Minor
+        } else {
+            Self::Unknown
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Resolved => "resolved",
+            Self::Maintenance => "maintenance",
+            Self::Minor => "minor",
+            Self::Major => "major",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Resolved => Color::Green,
+            Self::Maintenance => Color::Blue,
+            Self::Minor => Color::Yellow,
+            Self::Major => Color::Red,
+            Self::Unknown => Color::DarkGray,
+        }
+    }
+}
+
+/// The latest update of a status page feed, as shown in the monitor view.
+#[derive(Debug, Clone)]
+pub struct MonitorEntry {
+    pub feed_title: String,
+    pub title: Option<String>,
+    pub date: Option<String>,
+    pub severity: Severity,
+}
+
+/// Lists the newest item of each status page feed, one per line, worst first.
+pub fn render_monitor_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let Some(entries) = &app.monitor else {
+        return;
+    };
+
+    let block = Block::default()
+        .title("Monitor")
+        .borders(super::borders(app, Borders::ALL))
+        .border_style(app.config.theme().overlay())
+        .border_type(BorderType::Plain)
+        .style(app.config.theme().overlay())
+        .padding(Padding::uniform(1));
+
+    let feed_width = entries
+        .iter()
+        .map(|entry| entry.feed_title.chars().count())
+        .max()
+        .unwrap_or_default()
+        .min(30);
+    let lines: Vec<_> = if entries.is_empty() {
+        vec![Line::from(
+            "No status feeds — mark them with monitor = true under [sources.rules]",
+        )]
+    } else {
+        entries
+            .iter()
+            .map(|entry| {
+                let date = entry
+                    .date
+                    .as_deref()
+                    .and_then(util::parse_date)
+                    .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                // The dot's color is lost on a screen reader, so say it instead.
+                let marker = if app.config.screen_reader() {
+                    format!("{}: ", entry.severity.label())
+                } else {
+                    "● ".to_owned()
+                };
+                Line::from(vec![
+                    Span::styled(marker, Style::default().fg(entry.severity.color())),
+                    Span::styled(
+                        format!("{:<width$.width$}  ", entry.feed_title, width = feed_width),
+                        Style::default().bold(),
+                    ),
+                    Span::raw(format!("{:<16}  ", date)),
+                    Span::raw(entry.title.clone().unwrap_or("no updates".to_owned())),
+                ])
+            })
+            .collect()
+    };
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
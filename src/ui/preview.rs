@@ -0,0 +1,104 @@
+use crate::app::App;
+use crate::feed::Item;
+use crate::util;
+use tui::{
+    backend::Backend,
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph, Wrap},
+    Frame,
+};
+
+/// How many of a previewed feed's items are listed.
+const PREVIEW_ITEMS: usize = 5;
+
+/// Shows a feed fetched with `:add` before it's subscribed to: its title,
+/// description, latest items and how often it posts, so that the right one of
+/// a site's feeds can be picked.
+pub fn render_preview_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let Some(feed) = &app.preview else {
+        return;
+    };
+
+    let block = Block::default()
+        .title("Subscribe?")
+        .borders(super::borders(app, Borders::ALL))
+        .border_style(app.config.theme().overlay())
+        .border_type(BorderType::Plain)
+        .style(app.config.theme().overlay())
+        .padding(Padding::uniform(1));
+
+    let mut lines = vec![
+        Line::styled(feed.title().to_owned(), Style::default().bold()),
+        Line::styled(feed.url().to_owned(), Style::default().dim()),
+        Line::default(),
+    ];
+    let description = feed.description().split_whitespace().collect::<Vec<_>>();
+    if !description.is_empty() {
+        lines.push(Line::from(description.join(" ")));
+        lines.push(Line::default());
+    }
+
+    let mut items = feed.items().iter().collect::<Vec<_>>();
+    // Feeds mostly list their newest items first, but not all of them do.
+    items.sort_by_key(|item| std::cmp::Reverse(item.pub_date().and_then(util::parse_date)));
+    if items.is_empty() {
+        lines.push(Line::from("No items yet"));
+    }
+    for item in items.iter().take(PREVIEW_ITEMS) {
+        let date = item
+            .pub_date()
+            .and_then(util::parse_date)
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<10}  ", date), Style::default().dim()),
+            Span::raw(item.title().unwrap_or("Untitled").to_owned()),
+        ]));
+    }
+    lines.push(Line::default());
+    lines.push(Line::from(posting_rate(feed.items())));
+    lines.push(Line::default());
+    lines.push(Line::styled(
+        "Enter/y subscribe   Esc/n cancel",
+        Style::default().dim(),
+    ));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(block).wrap(Wrap { trim: true }),
+        area,
+    );
+}
+
+/// Roughly how often a feed posts, going by the dates of its items, like
+/// "About 3 items a week".
+fn posting_rate(items: &[Item]) -> String {
+    let dates = items
+        .iter()
+        .filter_map(|item| item.pub_date().and_then(util::parse_date))
+        .collect::<Vec<_>>();
+    let days = match (dates.iter().min(), dates.iter().max()) {
+        (Some(first), Some(last)) if dates.len() > 1 => {
+            (*last - *first).num_seconds() as f64 / 86_400.0
+        }
+        _ => 0.0,
+    };
+    if days <= 0.0 {
+        return "Not enough dated items to tell how often it posts".to_owned();
+    }
+
+    let per_day = (dates.len() - 1) as f64 / days;
+    let (count, period) = if per_day >= 1.0 {
+        (per_day, "day")
+    } else if per_day * 7.0 >= 1.0 {
+        (per_day * 7.0, "week")
+    } else if per_day * 30.0 >= 1.0 {
+        (per_day * 30.0, "month")
+    } else {
+        return "Less than one item a month".to_owned();
+    };
+    match count.round() as usize {
+        1 => format!("About 1 item a {}", period),
+        count => format!("About {} items a {}", count, period),
+    }
+}
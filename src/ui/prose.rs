@@ -0,0 +1,118 @@
+use crate::config::TextAlign;
+use hyphenation::{Hyphenator, Language, Load, Standard};
+use std::mem;
+use std::sync::OnceLock;
+use tui::text::Line;
+use unicode_width::UnicodeWidthStr;
+
+/// The shortest a word is for it to be worth hyphenating.
+const MIN_HYPHENATED_LEN: usize = 5;
+
+/// English hyphenation patterns, embedded in the binary and only loaded once
+/// the first word needs hyphenating.
+static PATTERNS: OnceLock<Option<Standard>> = OnceLock::new();
+
+/// Splits a word where the patterns allow, as late as leaves its first part
+/// and a hyphen within `room` columns. Words with anything but letters, like
+/// links and numbers, are left whole, though trailing punctuation is fine.
+fn split_word(word: &str, room: usize) -> Option<(&str, &str)> {
+    let letters = word.trim_end_matches(|c: char| !c.is_alphabetic());
+    if letters.chars().count() < MIN_HYPHENATED_LEN || !letters.chars().all(char::is_alphabetic) {
+        return None;
+    }
+    // Folding case mustn't move the breaks, so only words that keep their
+    // length are hyphenated.
+    let folded = letters.to_lowercase();
+    if folded.len() != letters.len() {
+        return None;
+    }
+
+    let patterns = PATTERNS
+        .get_or_init(|| Standard::from_embedded(Language::EnglishUS).ok())
+        .as_ref()?;
+    let breaks = patterns.hyphenate(&folded).breaks;
+    breaks
+        .into_iter()
+        .rev()
+        .find(|&at| word.is_char_boundary(at) && word[..at].width() < room)
+        .map(|at| word.split_at(at))
+}
+
+/// Spreads the words of a line out to `width`, widening the gaps on the left
+/// first.
+fn justify(words: &[String], width: usize) -> String {
+    let gaps = words.len().saturating_sub(1);
+    let spare = width.saturating_sub(words.iter().map(|word| word.width()).sum());
+
+    let mut line = String::new();
+    for (i, word) in words.iter().enumerate() {
+        line.push_str(word);
+        if i < gaps {
+            let spaces = spare / gaps + usize::from(i < spare % gaps);
+            line.push_str(&" ".repeat(spaces.max(1)));
+        }
+    }
+    line
+}
+
+/// Sets a paragraph in lines no wider than `width`, ragged or justified, and
+/// with long words hyphenated rather than moved down whole if `hyphenate` is
+/// set. The last line of a paragraph is never justified.
+pub fn set_paragraph(
+    text: &str,
+    width: usize,
+    align: TextAlign,
+    hyphenate: bool,
+) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    let mut line: Vec<String> = vec![];
+    let mut used = 0;
+
+    for mut word in text.split(' ').filter(|word| !word.is_empty()) {
+        loop {
+            let gap = usize::from(!line.is_empty());
+            if used + gap + word.width() <= width {
+                line.push(word.to_owned());
+                used += gap + word.width();
+                break;
+            }
+
+            let split = if hyphenate {
+                split_word(word, width.saturating_sub(used + gap))
+            } else {
+                None
+            };
+            match split {
+                Some((head, tail)) => {
+                    line.push(format!("{}-", head));
+                    lines.push(mem::take(&mut line));
+                    used = 0;
+                    word = tail;
+                }
+                // A word too long for a line of its own is left to be cut.
+                None if line.is_empty() => {
+                    line.push(word.to_owned());
+                    used = word.width();
+                    break;
+                }
+                None => {
+                    lines.push(mem::take(&mut line));
+                    used = 0;
+                }
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    let last = lines.len().saturating_sub(1);
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, words)| match align {
+            TextAlign::Justified if i < last => Line::from(justify(&words, width)),
+            _ => Line::from(words.join(" ")),
+        })
+        .collect()
+}
@@ -0,0 +1,119 @@
+use crate::app::App;
+use tui::{
+    backend::Backend,
+    prelude::*,
+    widgets::{BarChart, Block, BorderType, Borders, Clear, Padding, Paragraph, Sparkline},
+    Frame,
+};
+
+/// Widest a top feeds bar may grow, in cells.
+const MAX_BAR_WIDTH: u64 = 30;
+
+pub fn render_stats_overlay<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let Some(stats) = &app.stats else {
+        return;
+    };
+
+    let block = Block::default()
+        .title("Reading")
+        .borders(super::borders(app, Borders::ALL))
+        .border_style(app.config.theme().overlay())
+        .border_type(BorderType::Plain)
+        .style(app.config.theme().overlay())
+        .padding(Padding::uniform(1));
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(8),
+            Constraint::Length(stats.top_feeds.len() as u16 + 2),
+            Constraint::Length(6),
+        ])
+        .split(block.inner(area));
+
+    frame.render_widget(Clear, area);
+
+    // Charts make no sense read out cell by cell, so give the numbers instead.
+    if app.config.screen_reader() {
+        let mut lines = vec![Line::from("Items read per day")];
+        lines.extend(
+            stats
+                .reads_per_day
+                .iter()
+                .map(|(day, count)| Line::from(format!("{}: {}", day, count))),
+        );
+        lines.push(Line::from(""));
+        lines.push(Line::from("Top feeds by reads"));
+        lines.extend(
+            stats
+                .top_feeds
+                .iter()
+                .map(|(title, count)| Line::from(format!("{}: {}", title, count))),
+        );
+        if let (Some(first), Some(last)) = (stats.backlog.first(), stats.backlog.last()) {
+            lines.push(Line::from(""));
+            lines.push(Line::from(format!(
+                "Unread backlog: {} then, {} now",
+                first, last
+            )));
+        }
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+        return;
+    }
+
+    frame.render_widget(block, area);
+
+    let reads = stats
+        .reads_per_day
+        .iter()
+        .map(|(day, count)| (day.as_str(), *count))
+        .collect::<Vec<_>>();
+    frame.render_widget(
+        BarChart::default()
+            .block(Block::default().title("Items read per day"))
+            .data(&reads[..])
+            .bar_width(3)
+            .bar_gap(1)
+            .bar_style(app.config.theme().overlay())
+            .value_style(app.config.theme().overlay().reversed()),
+        chunks[0],
+    );
+
+    let most = stats
+        .top_feeds
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or_default()
+        .max(1);
+    let title_width = stats
+        .top_feeds
+        .iter()
+        .map(|(title, _)| title.chars().count())
+        .max()
+        .unwrap_or_default();
+    let top_feeds = if stats.top_feeds.is_empty() {
+        vec![Line::from("Nothing read yet")]
+    } else {
+        stats
+            .top_feeds
+            .iter()
+            .map(|(title, count)| {
+                let bar = "█".repeat((count * MAX_BAR_WIDTH / most).max(1) as usize);
+                Line::from(format!("{:title_width$}  {} {}", title, bar, count))
+            })
+            .collect()
+    };
+    frame.render_widget(
+        Paragraph::new(top_feeds).block(Block::default().title("Top feeds by reads")),
+        chunks[1],
+    );
+
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::default().title("Unread backlog"))
+            .data(&stats.backlog)
+            .style(app.config.theme().overlay()),
+        chunks[2],
+    );
+}
@@ -0,0 +1,96 @@
+use tui::text::Line;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The narrowest a column is squeezed to before a table is left to wrap.
+const MIN_COLUMN_WIDTH: usize = 4;
+
+/// Whether a line is a row of a table, written out between pipes.
+pub fn is_row(line: &str) -> bool {
+    line.len() >= 2 && line.starts_with('|') && line.ends_with('|')
+}
+
+/// The cells of a row, or `None` for the rule under a header row.
+fn row_cells(line: &str) -> Option<Vec<&str>> {
+    let cells = line[1..line.len() - 1]
+        .split('|')
+        .map(str::trim)
+        .collect::<Vec<_>>();
+    if cells
+        .iter()
+        .all(|cell| !cell.is_empty() && cell.chars().all(|c| c == '-'))
+    {
+        None
+    } else {
+        Some(cells)
+    }
+}
+
+/// Pads a cell out to `width` columns, or cuts it short with an ellipsis.
+fn fit_cell(cell: &str, width: usize) -> String {
+    if cell.width() <= width {
+        return format!("{}{}", cell, " ".repeat(width - cell.width()));
+    }
+
+    let mut fitted = String::new();
+    let mut used = 0;
+    for c in cell.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w >= width {
+            break;
+        }
+        fitted.push(c);
+        used += w;
+    }
+    fitted.push('…');
+    used += 1;
+    fitted.push_str(&" ".repeat(width.saturating_sub(used)));
+    fitted
+}
+
+fn rule(widths: &[usize], left: &str, middle: &str, right: &str) -> String {
+    let segments = widths.iter().map(|w| "─".repeat(w + 2)).collect::<Vec<_>>();
+    format!("{}{}{}", left, segments.join(middle), right)
+}
+
+/// Lays out the rows of a table as a grid no wider than `width`, taking
+/// columns in from the widest first until it fits.
+pub fn grid(rows: &[&str], width: usize) -> Vec<Line<'static>> {
+    let rows = rows.iter().map(|row| row_cells(row)).collect::<Vec<_>>();
+    let columns = rows.iter().flatten().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return vec![];
+    }
+
+    let mut widths = vec![0; columns];
+    for cells in rows.iter().flatten() {
+        for (i, cell) in cells.iter().enumerate() {
+            widths[i] = widths[i].max(cell.width());
+        }
+    }
+    let budget = width.saturating_sub(3 * columns + 1);
+    while widths.iter().sum::<usize>() > budget {
+        let (widest, &most) = widths.iter().enumerate().max_by_key(|(_, w)| **w).unwrap();
+        if most <= MIN_COLUMN_WIDTH {
+            break;
+        }
+        widths[widest] = most - 1;
+    }
+
+    let mut lines = vec![Line::from(rule(&widths, "┌", "┬", "┐"))];
+    for cells in &rows {
+        let line = match cells {
+            Some(cells) => {
+                let cells = widths
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| fit_cell(cells.get(i).copied().unwrap_or_default(), *w))
+                    .collect::<Vec<_>>();
+                format!("│ {} │", cells.join(" │ "))
+            }
+            None => rule(&widths, "├", "┼", "┤"),
+        };
+        lines.push(Line::from(line));
+    }
+    lines.push(Line::from(rule(&widths, "└", "┴", "┘")));
+    lines
+}
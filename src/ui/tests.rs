@@ -0,0 +1,219 @@
+use crate::app::{App, Status, View};
+use crate::config::{Config, FeedSections, BUILTIN_THEMES};
+use crate::feed::{Category, Feed, Item};
+use crate::golden;
+use tui::{backend::TestBackend, buffer::Buffer, Terminal};
+use unicode_width::UnicodeWidthStr;
+
+const WIDE: (u16, u16) = (160, 30);
+const NARROW: (u16, u16) = (60, 20);
+
+fn item(n: usize, title: &str, body: &str) -> Item {
+    Item {
+        id: format!("moccasin:{n}"),
+        feed_id: "moccasin".into(),
+        title: Some(title.into()),
+        author: Some("Tobias".into()),
+        link: Some(format!("https://moccasin.example/posts/{n}")),
+        pub_date: Some("Mon, 02 Oct 2023 09:00:00 +0000".into()),
+        description: Some(body.into()),
+        text_description: Some(body.into()),
+        ..Default::default()
+    }
+}
+
+fn feed() -> Feed {
+    Feed {
+        id: "moccasin".into(),
+        title: "Moccasin Blog".into(),
+        description: "Notes on a terminal feed reader".into(),
+        categories: vec![],
+        url: "https://moccasin.example/feed".into(),
+        link: "https://moccasin.example".into(),
+        ttl: None,
+        language: None,
+        items: vec![
+            item(1, "Release 0.1", "The first release reads RSS feeds."),
+            item(2, "Themes", "Eight themes ship built in."),
+        ],
+        pub_date: Some("Mon, 02 Oct 2023 09:00:00 +0000".into()),
+        last_fetched: None,
+        item_count: 2,
+        unread_count: 2,
+        prev_archive: None,
+    }
+}
+
+/// An app with one feed open and its first item selected, storing nothing on
+/// disk.
+fn app(theme: &str, (width, height): (u16, u16)) -> App {
+    let mut config = Config::in_memory();
+    config.set_theme(theme).unwrap();
+    let mut app = App::with_config(config, (width, height)).unwrap();
+    let feed = feed();
+    app.items.items = feed.items().to_vec();
+    app.items.state.select(Some(0));
+    app.feeds.items = vec![feed];
+    app.feeds.state.select(Some(0));
+    app.active_view = View::SubList;
+    app
+}
+
+/// The text of a rendered buffer, one line per row, skipping the cells that
+/// wide characters spill into.
+fn text(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for y in 0..buffer.area.height {
+        let mut line = String::new();
+        let mut skip = 0;
+        for x in 0..buffer.area.width {
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let symbol = buffer.get(x, y).symbol.as_str();
+            skip = symbol.width().saturating_sub(1);
+            line.push_str(symbol);
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Renders the app as set up by `setup` under every built-in theme, checking
+/// each against the one snapshot, as themes change colors and not layout.
+fn assert_snapshot(name: &str, size: (u16, u16), setup: impl Fn(&mut App)) {
+    let path = golden::fixtures("snapshots").join(format!("{name}.txt"));
+    let failures = BUILTIN_THEMES
+        .iter()
+        .filter_map(|theme| {
+            let mut app = app(theme, size);
+            setup(&mut app);
+            let mut terminal = Terminal::new(TestBackend::new(size.0, size.1)).unwrap();
+            terminal
+                .draw(|frame| super::render(&mut app, frame))
+                .unwrap();
+            golden::check(&path, &text(terminal.backend().buffer()))
+                .map(|diff| format!("theme {theme}: {diff}"))
+        })
+        .collect();
+    golden::assert_all(failures);
+}
+
+#[test]
+fn three_pane_layout() {
+    assert_snapshot("three_pane", WIDE, |_| {});
+}
+
+#[test]
+fn narrow_layout() {
+    assert_snapshot("narrow", NARROW, |_| {});
+}
+
+#[test]
+fn keybinds_overlay() {
+    assert_snapshot("keybinds", WIDE, |app| app.show_keybinds = true);
+}
+
+#[test]
+fn share_menu() {
+    assert_snapshot("share", WIDE, |app| {
+        app.active_view = View::Detail;
+        app.open_share_menu();
+    });
+}
+
+#[test]
+fn console() {
+    assert_snapshot("console", WIDE, |app| {
+        app.toggle_console(Some("add https://moccasin.example/feed"))
+    });
+}
+
+#[test]
+fn loading_gauge() {
+    assert_snapshot("loading", WIDE, |app| app.status = Status::Loading(3, 10));
+}
+
+#[test]
+fn error_status() {
+    assert_snapshot("error", WIDE, |app| {
+        app.status = Status::Errored("Failed to fetch https://moccasin.example/feed".into())
+    });
+}
+
+#[test]
+fn read_only_label() {
+    assert_snapshot("read_only", NARROW, |app| app.config.set_read_only(true));
+}
+
+#[test]
+fn feed_sections() {
+    assert_snapshot("feed_sections", WIDE, |app| {
+        app.config.set_feed_sections(FeedSections::Unread);
+        let mut quiet = feed();
+        quiet.id = "quiet".into();
+        quiet.title = "Quiet Blog".into();
+        quiet.unread_count = 0;
+        app.feeds.items.push(quiet);
+    });
+}
+
+#[test]
+fn tag_badges() {
+    assert_snapshot("tag_badges", WIDE, |app| {
+        app.config
+            .set_item_columns("unread tag title".parse().unwrap());
+        app.items.items[0].categories = vec![Category {
+            name: "Announcements".into(),
+            domain: None,
+        }];
+    });
+}
+
+#[test]
+fn item_preview() {
+    assert_snapshot("item_preview", NARROW, |app| {
+        app.config.set_item_preview(true);
+        app.items.items[0].text_description = Some(
+            "The first release reads RSS feeds, marks what you've read, and opens \
+             links in your browser. Themes, search and favorites come next."
+                .into(),
+        );
+    });
+}
+
+#[test]
+fn feed_preview() {
+    assert_snapshot("feed_preview", WIDE, |app| {
+        let mut preview = feed();
+        preview
+            .items
+            .push(item(3, "Search", "Items can be searched."));
+        let dates = [
+            "Mon, 16 Oct 2023 09:00:00 +0000",
+            "Mon, 09 Oct 2023 09:00:00 +0000",
+            "Mon, 02 Oct 2023 09:00:00 +0000",
+        ];
+        for (item, date) in preview.items.iter_mut().rev().zip(dates) {
+            item.pub_date = Some(date.into());
+        }
+        app.preview = Some(preview);
+    });
+}
+
+#[test]
+fn table_fits_detail_pane() {
+    assert_snapshot("table", WIDE, |app| {
+        app.active_view = View::Detail;
+        app.items.items[0].text_description = Some(
+            "Changes by release:\n\
+             | Version | Date | Notes |\n\
+             |---|---|---|\n\
+             | 0.1.3 | 2023-10-02 | Tables are drawn as grids that fit the pane |\n\
+             | 0.1.2 | 2023-09-18 | Fixes |"
+                .into(),
+        );
+    });
+}
@@ -1,5 +1,14 @@
 use crate::config::{Config, SortOrder};
 use crate::feed::Feed;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, FixedOffset};
+use std::ffi::OsStr;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+#[cfg(test)]
+mod tests;
 
 pub fn sort_feeds(feeds: &mut Vec<Feed>, config: &Config) {
     match config.sort_order() {
@@ -17,14 +26,277 @@ pub fn sort_feeds(feeds: &mut Vec<Feed>, config: &Config) {
                 a_index.cmp(&b_index)
             })
         }
-        SortOrder::Unread => {
-            unimplemented!()
-        }
+        SortOrder::Unread => feeds.sort_by(|a, b| {
+            b.unread_count()
+                .cmp(&a.unread_count())
+                .then_with(|| a.title().cmp(b.title()))
+        }),
         SortOrder::Newest => feeds.sort_by(|a, b| a.last_fetched().cmp(&b.last_fetched())),
         SortOrder::Oldest => feeds.sort_by(|a, b| b.last_fetched().cmp(&a.last_fetched())),
     }
 }
 
+/// Scores how well `needle` matches `haystack` as a case-insensitive subsequence,
+/// favoring contiguous and early matches, or `None` if it doesn't match at all.
+pub fn fuzzy_score(needle: &str, haystack: &str) -> Option<i64> {
+    let haystack = haystack.to_lowercase().chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last = None;
+
+    for c in needle.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = pos + haystack[pos..].iter().position(|&h| h == c)?;
+        score += match last {
+            Some(last) if found == last + 1 => 3,
+            _ => 1,
+        };
+        if found == 0 {
+            score += 2;
+        }
+        last = Some(found);
+        pos = found + 1;
+    }
+
+    Some(score * 100 - haystack.len() as i64)
+}
+
+/// Parses a date as feeds give them, in either RSS or Atom format.
+pub fn parse_date(date: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc2822(date)
+        .or_else(|_| DateTime::parse_from_rfc3339(date))
+        .ok()
+}
+
+/// A line of a diff between two texts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Compares two texts line by line, keeping the longest run of lines they have
+/// in common and marking the rest as removed from `old` or added in `new`.
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+
+    // common[i][j] is the length of the longest common subsequence of
+    // old[i..] and new[j..].
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    lines.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+    lines
+}
+
+/// Shows a desktop notification, on platforms with a standard way to do so.
+pub fn notify(summary: &str, body: &str) {
+    let Some(mut command) = notify_command(summary, body) else {
+        return;
+    };
+    crate::report!(
+        command.stdout(Stdio::null()).stderr(Stdio::null()).spawn(),
+        "Failed to send notification"
+    );
+}
+
+fn notify_command(summary: &str, body: &str) -> Option<Command> {
+    if cfg!(target_os = "macos") {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {:?} with title {:?}",
+            body, summary
+        ));
+        Some(command)
+    } else if cfg!(target_os = "linux") {
+        let mut command = Command::new("notify-send");
+        // Feed titles starting with `-` would otherwise be read as options.
+        command.args(["--app-name=moccasin", "--", summary, body]);
+        Some(command)
+    } else {
+        None
+    }
+}
+
+/// Copies text to the clipboard with an OSC 52 escape sequence, which the
+/// terminal carries out itself, so that it works over SSH too.
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()
+}
+
+/// Whether a feed's `language`, like `en-US`, is the `wanted` one, which may
+/// leave out the region to match them all, as `en` does.
+pub fn language_matches(language: &str, wanted: &str) -> bool {
+    let (language, wanted) = (language.to_lowercase(), wanted.trim().to_lowercase());
+    language == wanted
+        || language
+            .strip_prefix(&wanted)
+            .map_or(false, |rest| rest.starts_with('-'))
+}
+
+/// Takes a `lang:` scope out of a search query, as in `lang:de rust`,
+/// giving back the rest of the query and the language it's scoped to.
+pub fn split_language_scope(query: &str) -> (String, Option<&str>) {
+    let mut language = None;
+    let terms = query
+        .split_whitespace()
+        .filter(|term| match term.strip_prefix("lang:") {
+            Some(code) if !code.is_empty() => {
+                language = Some(code);
+                false
+            }
+            _ => true,
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    (terms, language)
+}
+
+/// Reads text from the clipboard with the platform's clipboard tool, since
+/// unlike writing to it, reading it isn't something terminals all allow.
+pub fn read_clipboard() -> io::Result<String> {
+    let tools: &[&[&str]] = if cfg!(target_os = "macos") {
+        &[&["pbpaste"]]
+    } else if cfg!(windows) {
+        &[&["powershell", "-NoProfile", "-Command", "Get-Clipboard"]]
+    } else {
+        &[
+            &["wl-paste", "--no-newline"],
+            &["xclip", "-selection", "clipboard", "-o"],
+            &["xsel", "--clipboard", "--output"],
+        ]
+    };
+
+    let mut last_err = io::Error::new(io::ErrorKind::NotFound, "no clipboard tool found");
+    for tool in tools {
+        match Command::new(tool[0])
+            .args(&tool[1..])
+            .stderr(Stdio::null())
+            .output()
+        {
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+            }
+            Ok(output) => {
+                last_err = io::Error::other(format!("{} exited with {}", tool[0], output.status));
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => last_err = err,
+        }
+    }
+    Err(last_err)
+}
+
+/// Whether `url` is a web URL, or with `files` a file URL too, rather than a
+/// source like `exec:` that runs a command.
+pub fn is_feed_url(url: &str, files: bool) -> bool {
+    reqwest::Url::parse(url).map_or(false, |url| match url.scheme() {
+        "http" | "https" => true,
+        "file" => files,
+        _ => false,
+    })
+}
+
+/// The command that opens `link` in the default app for it, if this platform
+/// has one. On Windows only web and mail links are opened, since `explorer`
+/// would just as well run a program given its path, and links come from feeds.
+pub fn system_open_command(link: &OsStr) -> Option<Command> {
+    let mut command = if cfg!(target_os = "windows") {
+        let web = link.to_str().map_or(false, |link| {
+            is_feed_url(link, false) || link.starts_with("mailto:")
+        });
+        if !web {
+            return None;
+        }
+        // Unlike `rundll32 url.dll,FileProtocolHandler`, `explorer` copes with
+        // any URL, handing it to the default app for its scheme.
+        Command::new("explorer")
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "linux") {
+        Command::new("xdg-open")
+    } else {
+        return None;
+    };
+    command.arg(link);
+    Some(command)
+}
+
+/// Percent-encodes everything but unreserved characters, for a part of a URL
+/// like a `mailto:` subject, where `+` would not be read as a space.
+pub fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Takes the query parameters matching any of `params` out of a link, where a
+/// trailing `*` matches any parameter starting with what comes before it. The
+/// rest of the link is left as it was, down to how it's encoded.
+pub fn clean_url(url: &str, params: &[String]) -> String {
+    let (rest, fragment) = match url.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (url, None),
+    };
+    let Some((base, query)) = rest.split_once('?') else {
+        return url.to_owned();
+    };
+    let is_tracking = |pair: &str| {
+        let name = pair.split('=').next().unwrap_or_default();
+        params.iter().any(|param| match param.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == param,
+        })
+    };
+    let kept = query
+        .split('&')
+        .filter(|pair| !pair.is_empty() && !is_tracking(pair))
+        .collect::<Vec<_>>();
+
+    let mut cleaned = base.to_owned();
+    if !kept.is_empty() {
+        cleaned.push('?');
+        cleaned.push_str(&kept.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        cleaned.push('#');
+        cleaned.push_str(fragment);
+    }
+    cleaned
+}
+
 #[macro_export]
 macro_rules! report {
     ($fallible:expr, $message:literal) => {
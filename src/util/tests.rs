@@ -0,0 +1,120 @@
+use super::*;
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+#[test]
+fn links_and_paths_are_opened_whole() {
+    for link in [
+        "https://example.com/a b?x=1&y=2",
+        "/home/me/.config/moccasin/config.toml",
+    ] {
+        let command = system_open_command(OsStr::new(link)).unwrap();
+        assert_eq!(command.get_args().collect::<Vec<_>>(), [link]);
+    }
+}
+
+#[cfg(windows)]
+#[test]
+fn explorer_is_only_given_web_and_mail_links() {
+    for link in [
+        "https://example.com/a%20post?x=1",
+        "http://example.com",
+        "mailto:someone@example.com?subject=Hi",
+    ] {
+        let command = system_open_command(OsStr::new(link))
+            .unwrap_or_else(|| panic!("{} was not opened", link));
+        assert_eq!(command.get_program(), "explorer");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), [link]);
+    }
+
+    for link in [
+        r"C:\Windows\System32\calc.exe",
+        "file:///C:/Windows/System32/calc.exe",
+        r"\\server\share\run.bat",
+        "search-ms:query=calc",
+        "ms-settings:",
+    ] {
+        assert!(
+            system_open_command(OsStr::new(link)).is_none(),
+            "{} was opened",
+            link
+        );
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn notifications_are_never_read_as_options() {
+    let command = notify_command("--icon=x.png", "-u low").unwrap();
+    assert_eq!(
+        command.get_args().collect::<Vec<_>>(),
+        ["--app-name=moccasin", "--", "--icon=x.png", "-u low"]
+    );
+}
+
+#[test]
+fn clean_url_takes_out_tracking_params_only() {
+    let params = ["utm_*".to_owned(), "fbclid".to_owned()];
+    let cases = [
+        // Nothing to take out.
+        ("https://example.com/post", "https://example.com/post"),
+        (
+            "https://example.com/post?id=1",
+            "https://example.com/post?id=1",
+        ),
+        // Wildcards match by prefix, and plain names only whole.
+        (
+            "https://example.com/post?utm_source=rss&utm_medium=feed&id=1",
+            "https://example.com/post?id=1",
+        ),
+        (
+            "https://example.com/post?fbclid=abc&fbclid_x=1",
+            "https://example.com/post?fbclid_x=1",
+        ),
+        (
+            "https://example.com/post?utm=1",
+            "https://example.com/post?utm=1",
+        ),
+        // Nothing left drops the `?`.
+        (
+            "https://example.com/post?utm_source=rss",
+            "https://example.com/post",
+        ),
+        // Fragments are kept, even when they look like a query.
+        (
+            "https://example.com/post?utm_source=rss#comments",
+            "https://example.com/post#comments",
+        ),
+        (
+            "https://example.com/post#section?utm_source=rss",
+            "https://example.com/post#section?utm_source=rss",
+        ),
+        // Empty pairs go, as does a name with no value.
+        (
+            "https://example.com/post?&id=1&&utm_source&",
+            "https://example.com/post?id=1",
+        ),
+        // The rest is left as it was encoded.
+        (
+            "https://example.com/a%20b?q=caf%C3%A9+au+lait&tag=a%26b&utm_campaign=x",
+            "https://example.com/a%20b?q=caf%C3%A9+au+lait&tag=a%26b",
+        ),
+        (
+            "https://example.com/?q=%7e&fbclid=1",
+            "https://example.com/?q=%7e",
+        ),
+    ];
+    let failures = cases
+        .iter()
+        .filter_map(|(url, expected)| {
+            let actual = clean_url(url, &params);
+            (actual != *expected)
+                .then(|| format!("{url}\n--- expected\n{expected}\n--- actual\n{actual}"))
+        })
+        .collect();
+    crate::golden::assert_all(failures);
+
+    assert_eq!(
+        clean_url("https://example.com/?utm_source=rss", &[]),
+        "https://example.com/?utm_source=rss"
+    );
+}
@@ -0,0 +1,409 @@
+mod harness;
+
+use crossterm::event::KeyCode;
+use harness::{rss, Harness};
+use moccasin::app::{Status, View};
+use moccasin::ui::batch::BatchOutcome;
+use std::time::{Duration, SystemTime};
+
+/// Adds the feed served at `/feed` and waits for it to be listed.
+async fn add_feed(harness: &mut Harness, items: &[(&str, &str)]) {
+    harness.serve("/feed", rss("Example", items)).await;
+    let url = harness.url("/feed");
+    harness.subscribe(&url).await;
+}
+
+#[tokio::test]
+async fn add_fetches_and_lists_a_feed() {
+    let mut harness = Harness::start().await;
+    assert!(harness.app.feeds.items().is_empty());
+
+    add_feed(&mut harness, &[("first", "Hello"), ("second", "World")]).await;
+
+    let feed = &harness.app.feeds.items()[0];
+    assert_eq!(feed.title(), "Example");
+    assert_eq!(feed.url(), harness.url("/feed"));
+    assert_eq!(feed.item_count(), 2);
+    assert!(matches!(harness.app.status, Status::Done));
+    assert!(!harness.app.should_render_console());
+}
+
+#[tokio::test]
+async fn add_reports_a_feed_that_cannot_be_fetched() {
+    let mut harness = Harness::start().await;
+    harness.serve_status("/feed", 404, String::new()).await;
+
+    let url = harness.url("/feed");
+    harness.command(&format!("add {}", url));
+    harness
+        .run_until("the fetch fails", |app| {
+            matches!(app.status, Status::Errored(_))
+        })
+        .await;
+
+    assert!(harness.app.feeds.items().is_empty());
+}
+
+#[tokio::test]
+async fn add_previews_a_feed_before_subscribing() {
+    let mut harness = Harness::start().await;
+    harness
+        .serve("/feed", rss("Example", &[("first", "Hello")]))
+        .await;
+    let url = harness.url("/feed");
+
+    harness.command(&format!("add {}", url));
+    harness
+        .run_until("the feed is previewed", |app| app.preview.is_some())
+        .await;
+    assert_eq!(harness.app.preview.as_ref().unwrap().title(), "Example");
+    assert!(harness.app.feeds.items().is_empty());
+
+    harness.press(KeyCode::Esc);
+    assert!(harness.app.preview.is_none());
+    assert!(!harness.app.config.feed_urls().contains(&url));
+    harness.run_for(Duration::from_millis(200)).await;
+    assert!(harness.app.feeds.items().is_empty());
+}
+
+#[tokio::test]
+async fn delete_removes_the_selected_feed() {
+    let mut harness = Harness::start().await;
+    add_feed(&mut harness, &[("first", "Hello")]).await;
+
+    harness.press(KeyCode::Char('j'));
+    harness.command("delete");
+
+    assert!(harness.app.feeds.items().is_empty());
+    assert!(harness.app.items.items().is_empty());
+    assert_eq!(harness.app.active_view, View::MainList);
+}
+
+#[tokio::test]
+async fn read_only_mode_keeps_subscriptions() {
+    let mut harness = Harness::start().await;
+    add_feed(&mut harness, &[("first", "Hello")]).await;
+    harness.app.config.set_read_only(true);
+
+    harness.press(KeyCode::Char('j'));
+    harness.command("delete");
+
+    assert_eq!(harness.app.feeds.items().len(), 1);
+    assert!(matches!(harness.app.status, Status::Errored(_)));
+
+    harness.press(KeyCode::Char('a'));
+    assert!(!harness.app.should_render_console());
+
+    harness.press(KeyCode::Char('s'));
+    assert!(!harness.app.is_favorite(&harness.app.feeds.items()[0]));
+}
+
+#[tokio::test]
+async fn search_finds_items_as_they_are_typed() {
+    let mut harness = Harness::start().await;
+    add_feed(
+        &mut harness,
+        &[("gardening", "Tomatoes"), ("cooking", "Soup")],
+    )
+    .await;
+
+    harness.press(KeyCode::Char('/'));
+    harness.type_text("tomat");
+    harness
+        .run_until("search results come in", |app| app.has_search_results())
+        .await;
+
+    let results = harness.app.search.results.items();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].feed_title, "Example");
+
+    harness.press(KeyCode::Enter);
+    assert!(!harness.app.should_render_console());
+    let selected = harness.app.items.state.selected().unwrap();
+    assert_eq!(
+        harness.app.items.items()[selected].title(),
+        Some("gardening")
+    );
+}
+
+#[tokio::test]
+async fn refresh_picks_up_new_items() {
+    let mut harness = Harness::start().await;
+    add_feed(&mut harness, &[("first", "Hello")]).await;
+
+    harness
+        .serve(
+            "/feed",
+            rss("Example", &[("first", "Hello"), ("second", "Again")]),
+        )
+        .await;
+    harness.press(KeyCode::Char('r'));
+    harness
+        .run_until("the refresh finishes", |app| {
+            app.feeds
+                .items()
+                .first()
+                .map_or(false, |feed| feed.item_count() == 2)
+        })
+        .await;
+    harness
+        .run_until(
+            "the refresh is summed up",
+            |app| matches!(&app.status, Status::Info(message) if message == "1 new item"),
+        )
+        .await;
+}
+
+#[tokio::test]
+async fn refresh_only_reloads_while_another_app_holds_the_database() {
+    let mut harness = Harness::start().await;
+    add_feed(&mut harness, &[("first", "Hello")]).await;
+    harness.app.repo.hold_off();
+
+    harness
+        .serve(
+            "/feed",
+            rss("Example", &[("first", "Hello"), ("second", "Again")]),
+        )
+        .await;
+    harness.press(KeyCode::Char('r'));
+    harness.run_for(Duration::from_millis(500)).await;
+
+    assert_eq!(harness.app.feeds.items()[0].item_count(), 1);
+    assert!(matches!(harness.app.status, Status::Done));
+}
+
+#[tokio::test]
+async fn refresh_counts_failed_feeds() {
+    let mut harness = Harness::start().await;
+    add_feed(&mut harness, &[("first", "Hello")]).await;
+
+    harness.serve_status("/feed", 500, String::new()).await;
+    harness.press(KeyCode::Char('r'));
+    harness
+        .run_until("the refresh is summed up", |app| {
+            matches!(&app.status, Status::Info(message) if message == "no new items (1 feed failed)")
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn add_reads_a_local_feed_file() {
+    let mut harness = Harness::start().await;
+    let path = std::env::temp_dir().join(format!("moccasin-{}.xml", std::process::id()));
+    std::fs::write(&path, rss("Local", &[("note", "Kept on disk")])).unwrap();
+
+    harness
+        .subscribe(&format!("file://{}", path.display()))
+        .await;
+    std::fs::remove_file(&path).unwrap();
+
+    let feed = &harness.app.feeds.items()[0];
+    assert_eq!(feed.title(), "Local");
+    assert_eq!(feed.item_count(), 1);
+}
+
+#[tokio::test]
+async fn local_feed_files_are_refetched_when_they_change() {
+    let mut harness = Harness::start().await;
+    let path = std::env::temp_dir().join(format!("moccasin-watched-{}.xml", std::process::id()));
+    std::fs::write(&path, rss("Local", &[("first", "One")])).unwrap();
+    harness
+        .subscribe(&format!("file://{}", path.display()))
+        .await;
+    // Waits out one check, which notes when the file was last changed.
+    harness.run_for(Duration::from_secs(3)).await;
+
+    std::fs::write(&path, rss("Local", &[("first", "One"), ("second", "Two")])).unwrap();
+    let file = std::fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+    harness
+        .run_until("the change is picked up", |app| {
+            app.feeds.items()[0].item_count() == 2
+        })
+        .await;
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn exec_sources_read_the_feed_a_command_prints() {
+    let mut harness = Harness::start().await;
+    let path = std::env::temp_dir().join(format!("moccasin-exec-{}.xml", std::process::id()));
+    std::fs::write(&path, rss("Scraped", &[("post", "From a script")])).unwrap();
+
+    harness
+        .subscribe(&format!("exec:cat {}", path.display()))
+        .await;
+    std::fs::remove_file(&path).unwrap();
+
+    let feed = &harness.app.feeds.items()[0];
+    assert_eq!(feed.title(), "Scraped");
+    assert_eq!(feed.url(), format!("exec:cat {}", path.display()));
+}
+
+#[tokio::test]
+async fn archive_links_never_run_commands() {
+    let mut harness = Harness::start().await;
+    harness.app.config.set_archive_pages(2);
+    let marker = std::env::temp_dir().join(format!("moccasin-archive-{}", std::process::id()));
+    let feed = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom">
+  <channel>
+    <title>Paged</title>
+    <link>https://example.com</link>
+    <description>Paged</description>
+    <atom:link rel="prev-archive" href="exec:touch {}"/>
+    <item><title>first</title><link>https://example.com/first</link></item>
+  </channel>
+</rss>"#,
+        marker.display()
+    );
+    harness.serve("/feed", feed).await;
+
+    let url = harness.url("/feed");
+    harness.subscribe(&url).await;
+
+    assert!(!marker.exists());
+}
+
+#[tokio::test]
+async fn filter_sources_pipe_the_feed_through_a_script() {
+    let mut harness = Harness::start().await;
+    harness
+        .serve("/feed", rss("Example", &[("first", "Hello")]))
+        .await;
+
+    let url = harness.url("/feed");
+    harness
+        .subscribe(&format!("filter:sed s/Example/Filtered/:{}", url))
+        .await;
+
+    let feed = &harness.app.feeds.items()[0];
+    assert_eq!(feed.title(), "Filtered");
+    assert_eq!(feed.item_count(), 1);
+}
+
+#[tokio::test]
+async fn jumps_go_back_and_forth_between_feeds_read() {
+    let mut harness = Harness::start().await;
+    for (path, title) in [("/first", "First"), ("/second", "Second")] {
+        // Feeds are told apart by their links.
+        let body = rss(title, &[(title, "Hello")]).replace(
+            "<link>https://example.com</link>",
+            &format!("<link>https://example.com{}</link>", path),
+        );
+        harness.serve(path, body).await;
+        let url = harness.url(path);
+        harness.subscribe(&url).await;
+    }
+    let current = |harness: &Harness| harness.app.current_feed().unwrap().title().to_owned();
+
+    harness.command("feed First");
+    harness.type_text("ma");
+    harness.command("feed Second");
+    harness.command("jump back");
+    assert_eq!(current(&harness), "First");
+    assert_eq!(harness.app.active_view, View::SubList);
+
+    harness.command("jump forward");
+    assert_eq!(current(&harness), "Second");
+
+    harness.type_text("'a");
+    assert_eq!(current(&harness), "First");
+    harness.type_text("'b");
+    assert!(
+        matches!(&harness.app.status, Status::Errored(message) if message == "mark b is not set")
+    );
+}
+
+#[tokio::test]
+async fn add_many_reports_how_each_url_went() {
+    let mut harness = Harness::start().await;
+    harness
+        .serve("/feed", rss("Example", &[("first", "Hello")]))
+        .await;
+    // Nothing is served at /missing.
+    let path = std::env::temp_dir().join(format!("moccasin-{}.txt", std::process::id()));
+    let list = format!(
+        "# feeds to try\n{}\n\nnot a url\n{}\n{}\n",
+        harness.url("/feed"),
+        harness.url("/missing"),
+        harness.url("/feed"),
+    );
+    std::fs::write(&path, list).unwrap();
+
+    harness.command(&format!("add-many {}", path.display()));
+    harness
+        .run_until("the batch is fetched", |app| {
+            app.batch.as_ref().map_or(false, |batch| {
+                batch
+                    .iter()
+                    .all(|entry| entry.outcome != BatchOutcome::Pending)
+            }) && !app.feeds.items().is_empty()
+        })
+        .await;
+    std::fs::remove_file(&path).unwrap();
+
+    let outcomes: Vec<_> = harness
+        .app
+        .batch
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|entry| entry.outcome)
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            BatchOutcome::Added,
+            BatchOutcome::Invalid,
+            BatchOutcome::Gone(404)
+        ]
+    );
+    assert_eq!(harness.app.feeds.items().len(), 1);
+    assert_eq!(harness.app.feeds.items()[0].title(), "Example");
+
+    harness.press(KeyCode::Esc);
+    assert!(harness.app.batch.is_none());
+}
+
+#[tokio::test]
+async fn tab_completes_categories_with_spaces() {
+    let mut harness = Harness::start().await;
+    let feed = rss("Example", &[("first", "Hello")]).replace(
+        "<description>Hello",
+        "<category>Home Garden</category><description>Hello",
+    );
+    harness.serve("/feed", feed).await;
+    let url = harness.url("/feed");
+    harness.subscribe(&url).await;
+
+    harness.press(KeyCode::Char(':'));
+    harness.type_text("tag Home G");
+    harness
+        .run_until("the category is stored", |app| {
+            app.completion_hint().as_deref() == Some("arden")
+        })
+        .await;
+
+    harness.press(KeyCode::Tab);
+    assert_eq!(harness.app.command_state.input, ":tag Home Garden");
+}
+
+#[cfg(windows)]
+#[tokio::test]
+async fn key_releases_are_ignored_on_windows() {
+    use crossterm::event::{KeyEvent, KeyEventKind, KeyModifiers};
+    use moccasin::handler::handle_key_events;
+
+    let mut harness = Harness::start().await;
+    let mut release = KeyEvent::new(KeyCode::Char(':'), KeyModifiers::NONE);
+    release.kind = KeyEventKind::Release;
+    handle_key_events(release, &mut harness.app).unwrap();
+    assert!(!harness.app.should_render_console());
+
+    harness.press(KeyCode::Char(':'));
+    assert!(harness.app.should_render_console());
+}
@@ -0,0 +1,125 @@
+//! Drives an [`App`] without a terminal, against in-memory storage and a mock
+//! server standing in for the sites its feeds are fetched from.
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use moccasin::app::App;
+use moccasin::config::Config;
+use moccasin::handler::handle_key_events;
+use std::time::Duration;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// How long to wait on the app before a test is failed as stuck.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Harness {
+    pub app: App,
+    pub server: MockServer,
+}
+
+impl Harness {
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+        let app = App::with_config(Config::in_memory(), (160, 40)).unwrap();
+        Self { app, server }
+    }
+
+    /// The URL of `path` on the mock server.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.server.uri(), path)
+    }
+
+    /// Serves `body` as an RSS feed at `path`, in place of anything served
+    /// there before.
+    pub async fn serve(&self, at: &str, body: String) {
+        self.serve_status(at, 200, body).await;
+    }
+
+    pub async fn serve_status(&self, at: &str, status: u16, body: String) {
+        self.server.reset().await;
+        Mock::given(method("GET"))
+            .and(path(at))
+            .respond_with(
+                ResponseTemplate::new(status)
+                    .insert_header("content-type", "application/rss+xml")
+                    .set_body_string(body),
+            )
+            .mount(&self.server)
+            .await;
+    }
+
+    pub fn press(&mut self, code: KeyCode) {
+        handle_key_events(KeyEvent::new(code, KeyModifiers::NONE), &mut self.app).unwrap();
+    }
+
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.press(KeyCode::Char(c));
+        }
+    }
+
+    /// Opens the console, types `command` into it and submits it, as in
+    /// `harness.command("add https://example.com/feed")`.
+    pub fn command(&mut self, command: &str) {
+        self.press(KeyCode::Char(':'));
+        self.type_text(command);
+        self.press(KeyCode::Enter);
+    }
+
+    /// Adds the feed at `url` with `:add`, subscribes to it from its preview,
+    /// and waits for it to be listed.
+    pub async fn subscribe(&mut self, url: &str) {
+        self.command(&format!("add {}", url));
+        self.run_until("the feed is previewed", |app| app.preview.is_some())
+            .await;
+        self.press(KeyCode::Enter);
+        self.run_until("the feed is added", |app| {
+            app.feeds.items().iter().any(|feed| feed.url() == url)
+        })
+        .await;
+    }
+
+    /// Lets the app take in repository events and run scheduled work until
+    /// `done` holds, failing the test if it doesn't in time.
+    pub async fn run_until(&mut self, what: &str, done: impl Fn(&App) -> bool) {
+        let wait = async {
+            while !done(&self.app) {
+                self.app.update().await;
+            }
+        };
+        if tokio::time::timeout(TIMEOUT, wait).await.is_err() {
+            panic!("timed out waiting until {}", what);
+        }
+    }
+
+    /// Lets the app run for `duration`, as it would between keypresses.
+    pub async fn run_for(&mut self, duration: Duration) {
+        let run = async {
+            loop {
+                self.app.update().await;
+            }
+        };
+        let _ = tokio::time::timeout(duration, run).await;
+    }
+}
+
+/// An RSS feed of items given as `(title, description)`, each linked under
+/// `https://example.com`.
+pub fn rss(title: &str, items: &[(&str, &str)]) -> String {
+    let items = items
+        .iter()
+        .map(|(title, description)| {
+            format!(
+                "<item><title>{0}</title><link>https://example.com/{0}</link>\
+                 <guid>https://example.com/{0}</guid>\
+                 <description>{1}</description></item>",
+                title, description
+            )
+        })
+        .collect::<String>();
+    format!(
+        "<?xml version=\"1.0\"?><rss version=\"2.0\"><channel>\
+         <title>{}</title><link>https://example.com</link>\
+         <description>A feed served in tests</description>{}</channel></rss>",
+        title, items
+    )
+}